@@ -0,0 +1,209 @@
+//! # Math Fuzz Target
+//! 
+//! Drives the Q64.64 helpers, tick conversions and swap steps in
+//! `src/math.rs` with arbitrary inputs and checks their documented
+//! invariants. The module is compiled for the host straight from the
+//! contract sources, so nothing here ships in the WASM.
+//! 
+//! Run with `cargo fuzz run math` from the repository root.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use soroban_sdk::Env;
+
+#[allow(dead_code)]
+#[path = "../../src/constants.rs"]
+mod constants;
+
+#[allow(dead_code)]
+#[path = "../../src/error.rs"]
+mod error;
+
+#[allow(dead_code)]
+#[path = "../../src/math.rs"]
+mod math;
+
+use constants::{MAX_TICK, MIN_TICK, Q64};
+use math::{
+    compute_swap_step, compute_swap_step_with_target, get_sqrt_ratio_at_tick, get_tick_at_sqrt_ratio,
+    mul_div, mul_div_rounding, mul_q64, MAX_SQRT_RATIO, MIN_SQRT_RATIO, MIN_STRICT_TICK,
+};
+
+#[derive(Arbitrary, Debug)]
+enum Case {
+    MulQ64 { a: u128, b: u128 },
+    MulDiv { a: u128, b: u128, denominator: u128 },
+    Tick { tick: i32 },
+    SqrtPrice { sqrt_price_x64: u128 },
+    SwapStep { sqrt_price_x64: u128, liquidity: i128, amount_a: i128, amount_b: i128, zero_for_one: bool },
+    SwapStepWithTarget {
+        sqrt_price_x64: u128,
+        sqrt_price_target: u128,
+        liquidity: i128,
+        amount_specified: i128,
+        zero_for_one: bool,
+    },
+}
+
+thread_local! {
+    static ENV: Env = Env::default();
+}
+
+/// Map an arbitrary value into [MIN_SQRT_RATIO, MAX_SQRT_RATIO]
+fn sqrt_price_in_range(raw: u128) -> u128 {
+    MIN_SQRT_RATIO + raw % (MAX_SQRT_RATIO - MIN_SQRT_RATIO + 1)
+}
+
+/// Map an arbitrary value into [MIN_TICK, MAX_TICK]
+fn tick_in_range(raw: i32) -> i32 {
+    MIN_TICK + (raw as i64 - i32::MIN as i64).rem_euclid((MAX_TICK - MIN_TICK + 1) as i64) as i32
+}
+
+// ============================================================
+// Q64.64 ARITHMETIC
+// ============================================================
+
+/// mul_q64 is floor(a * b / 2^64) whenever the result fits in u128
+fn check_mul_q64(env: &Env, a: u128, b: u128) {
+    // Keep the high half of the product below 2^64 so the result fits
+    if (a >> 64).checked_mul(b >> 64).is_none_or(|hh| hh >> 64 != 0) {
+        return;
+    }
+    let exact = mul_div_rounding(env, a, b, Q64, false);
+    if exact == u128::MAX {
+        return;
+    }
+
+    assert_eq!(mul_q64(env, a, b), exact, "mul_q64({a}, {b})");
+    assert_eq!(mul_q64(env, a, b), mul_q64(env, b, a), "mul_q64 not commutative");
+    assert_eq!(mul_q64(env, a, Q64), a, "mul_q64 by one");
+}
+
+/// U256 mul_div and the integer-only mul_div_rounding agree, and rounding
+/// up adds at most one
+fn check_mul_div(env: &Env, a: u128, b: u128, denominator: u128) {
+    if denominator == 0 {
+        return;
+    }
+
+    let down = mul_div_rounding(env, a, b, denominator, false);
+    let up = mul_div_rounding(env, a, b, denominator, true);
+
+    assert_eq!(mul_div(env, a, b, denominator), down, "mul_div({a}, {b}, {denominator})");
+    assert!(up >= down && up - down <= 1, "round-up drift: {down} -> {up}");
+    if down != u128::MAX && denominator == 1 {
+        assert_eq!(up, down, "exact division rounded up");
+    }
+}
+
+// ============================================================
+// TICK CONVERSIONS
+// ============================================================
+
+/// Forward conversion is monotonic and round-trips where strictly increasing
+fn check_tick(env: &Env, tick: i32) {
+    let sqrt_price = get_sqrt_ratio_at_tick(env, tick);
+    assert!((MIN_SQRT_RATIO..=MAX_SQRT_RATIO).contains(&sqrt_price), "sqrt price out of bounds at {tick}");
+
+    if tick < MAX_TICK {
+        let next = get_sqrt_ratio_at_tick(env, tick + 1);
+        if tick >= MIN_STRICT_TICK {
+            assert!(next > sqrt_price, "not strictly increasing at {tick}");
+        } else {
+            assert!(next >= sqrt_price, "decreasing at {tick}");
+        }
+    }
+
+    if tick >= MIN_STRICT_TICK {
+        assert_eq!(get_tick_at_sqrt_ratio(env, sqrt_price), tick, "round trip at {tick}");
+    }
+}
+
+/// Reverse conversion returns the greatest tick whose price is <= the input
+fn check_sqrt_price(env: &Env, sqrt_price_x64: u128) {
+    let tick = get_tick_at_sqrt_ratio(env, sqrt_price_x64);
+
+    assert!(get_sqrt_ratio_at_tick(env, tick) <= sqrt_price_x64, "tick {tick} above {sqrt_price_x64}");
+    if tick < MAX_TICK {
+        assert!(get_sqrt_ratio_at_tick(env, tick + 1) > sqrt_price_x64, "tick {tick} not greatest");
+    }
+}
+
+// ============================================================
+// SWAP STEPS
+// ============================================================
+
+/// Never charges more than the input and moves the price further for
+/// larger inputs, in the swap direction
+fn check_swap_step(env: &Env, sqrt_price_x64: u128, liquidity: i128, amount_a: i128, amount_b: i128, zero_for_one: bool) {
+    let (small, large) = if amount_a <= amount_b { (amount_a, amount_b) } else { (amount_b, amount_a) };
+
+    let (price_small, in_small, _) = compute_swap_step(env, sqrt_price_x64, liquidity, small, zero_for_one);
+    let (price_large, in_large, _) = compute_swap_step(env, sqrt_price_x64, liquidity, large, zero_for_one);
+
+    assert!(in_small <= small.max(0) && in_large <= large.max(0), "charged more than input");
+
+    if zero_for_one {
+        assert!(price_small <= sqrt_price_x64, "price rose on zero_for_one");
+        assert!(price_large <= price_small, "larger input moved price less");
+    } else {
+        assert!(price_small >= sqrt_price_x64, "price fell on one_for_zero");
+        assert!(price_large >= price_small, "larger input moved price less");
+    }
+}
+
+/// Stops at the target, stays between current and target, never delivers
+/// more than the output owed, and only exceeds the remaining input when the
+/// target is reached
+fn check_swap_step_with_target(
+    env: &Env,
+    sqrt_price_x64: u128,
+    sqrt_price_target: u128,
+    liquidity: i128,
+    amount_specified: i128,
+    zero_for_one: bool,
+) {
+    let (next, amount_in, amount_out) = compute_swap_step_with_target(
+        env, sqrt_price_x64, liquidity, amount_specified, zero_for_one, sqrt_price_target,
+    );
+
+    if zero_for_one {
+        assert!(next <= sqrt_price_x64 && next >= sqrt_price_target, "price left [target, current]");
+    } else {
+        assert!(next >= sqrt_price_x64 && next <= sqrt_price_target, "price left [current, target]");
+    }
+    assert!(amount_in >= 0 && amount_out >= 0, "negative step amounts");
+
+    let remaining = amount_specified.unsigned_abs();
+    if amount_specified < 0 {
+        assert!(amount_out as u128 <= remaining, "exact output overpaid");
+    } else if next != sqrt_price_target {
+        assert!(amount_in as u128 <= remaining, "exact input overspent");
+    }
+}
+
+fuzz_target!(|case: Case| {
+    ENV.with(|env| {
+        // Host budget accumulates across runs on the shared Env
+        env.budget().reset_unlimited();
+
+        match case {
+            Case::MulQ64 { a, b } => check_mul_q64(env, a, b),
+            Case::MulDiv { a, b, denominator } => check_mul_div(env, a, b, denominator),
+            Case::Tick { tick } => check_tick(env, tick_in_range(tick)),
+            Case::SqrtPrice { sqrt_price_x64 } => check_sqrt_price(env, sqrt_price_in_range(sqrt_price_x64)),
+            Case::SwapStep { sqrt_price_x64, liquidity, amount_a, amount_b, zero_for_one } => check_swap_step(
+                env, sqrt_price_in_range(sqrt_price_x64), liquidity, amount_a, amount_b, zero_for_one,
+            ),
+            Case::SwapStepWithTarget { sqrt_price_x64, sqrt_price_target, liquidity, amount_specified, zero_for_one } => {
+                let current = sqrt_price_in_range(sqrt_price_x64);
+                let target = sqrt_price_in_range(sqrt_price_target);
+                // The engine always passes a target on the swap's side of the price
+                let target = if zero_for_one { target.min(current) } else { target.max(current) };
+                check_swap_step_with_target(env, current, target, liquidity.max(0), amount_specified, zero_for_one);
+            }
+        }
+    })
+});
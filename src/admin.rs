@@ -0,0 +1,27 @@
+use soroban_sdk::{panic_with_error, Address, Env};
+
+use crate::error::PoolError;
+use crate::storage::read_pool_config;
+use crate::types::PoolConfig;
+
+// ============================================================
+// ADMIN AUTHORIZATION
+// ============================================================
+
+/// Verify that `admin` is the pool admin and has authorized the call
+/// 
+/// # Returns
+/// The current pool configuration
+/// 
+/// # Panics
+/// If `admin` does not match `PoolConfig.admin`
+pub fn require_admin(env: &Env, admin: &Address) -> PoolConfig {
+    let config = read_pool_config(env);
+
+    if *admin != config.admin {
+        panic_with_error!(env, PoolError::Unauthorized);
+    }
+    admin.require_auth();
+
+    config
+}
@@ -0,0 +1,120 @@
+//! # Range Analytics
+//! 
+//! Read-only estimates for LPs choosing a range. Nothing here touches
+//! storage; inputs are sqrt prices in Q64.64.
+//! 
+//! ## Units
+//! Values are for one unit of liquidity, in token1, Q64.64, so they carry
+//! the same scale as `fee_growth_global_1`. They are computed in 256-bit
+//! intermediates rather than with the saturating amount helpers, which
+//! lose precision near the extreme ticks.
+
+use soroban_sdk::{Env, U256};
+
+use crate::constants::Q64;
+use crate::math::{get_sqrt_price_for_ratio, mul_div};
+
+// ============================================================
+// IMPERMANENT LOSS
+// ============================================================
+
+/// Value in token1 at `sqrt_eval` of the tokens one unit of liquidity in
+/// `[sqrt_lower, sqrt_upper]` holds at `sqrt_price`
+/// 
+/// amount0 = (su - s) / (s * su), amount1 = s - sl, and amount0 is worth
+/// amount0 * sqrt_eval^2 in token1.
+fn unit_value_x64(
+    env: &Env,
+    sqrt_lower: u128,
+    sqrt_upper: u128,
+    sqrt_price: u128,
+    sqrt_eval: u128,
+) -> u128 {
+    let sp = sqrt_price.clamp(sqrt_lower, sqrt_upper);
+
+    let value0 = mul_div(env, mul_div(env, sqrt_upper - sp, sqrt_eval, sqrt_upper), sqrt_eval, sp);
+    value0.saturating_add(sp - sqrt_lower)
+}
+
+/// Loss of one unit of liquidity versus holding its tokens when the price
+/// moves from `sqrt_price_x64` to `sqrt_price_after_x64`
+/// 
+/// Valued in token1 at the new price; zero if the LP is worth more.
+pub fn impermanent_loss_x64(
+    env: &Env,
+    sqrt_lower: u128,
+    sqrt_upper: u128,
+    sqrt_price_x64: u128,
+    sqrt_price_after_x64: u128,
+) -> u128 {
+    let held = unit_value_x64(env, sqrt_lower, sqrt_upper, sqrt_price_x64, sqrt_price_after_x64);
+    let lp = unit_value_x64(env, sqrt_lower, sqrt_upper, sqrt_price_after_x64, sqrt_price_after_x64);
+
+    held.saturating_sub(lp)
+}
+
+/// LP value minus held value for `liquidity` when the price moves from
+/// `sqrt_price_x64` to `sqrt_price_after_x64`
+/// 
+/// Held value is the tokens the position holds at `sqrt_price_x64`; both
+/// are valued at the new price. In token1 units (not Q64.64), saturating;
+/// negative is a loss versus holding.
+pub fn impermanent_delta(
+    env: &Env,
+    sqrt_lower: u128,
+    sqrt_upper: u128,
+    sqrt_price_x64: u128,
+    sqrt_price_after_x64: u128,
+    liquidity: i128,
+) -> i128 {
+    if liquidity <= 0 {
+        return 0;
+    }
+
+    let held = unit_value_x64(env, sqrt_lower, sqrt_upper, sqrt_price_x64, sqrt_price_after_x64);
+    let lp = unit_value_x64(env, sqrt_lower, sqrt_upper, sqrt_price_after_x64, sqrt_price_after_x64);
+
+    let magnitude = mul_div(env, held.abs_diff(lp), liquidity as u128, Q64).min(i128::MAX as u128) as i128;
+    if lp >= held { magnitude } else { -magnitude }
+}
+
+// ============================================================
+// BREAK-EVEN FEES
+// ============================================================
+
+/// Fee yield needed to offset impermanent loss for a `volatility_bps` move
+/// 
+/// Averages the loss of a move up by `(1 + v)` and down by `1 / (1 + v)`
+/// from `sqrt_price_x64`, and divides it by the position's value at that
+/// price. The result is a Q64.64 fraction of the deposit (2^64 = 100%),
+/// so ranges of different widths can be compared directly: a narrower
+/// range holds less capital per unit of liquidity and needs more.
+pub fn break_even_fees_x64(
+    env: &Env,
+    sqrt_lower: u128,
+    sqrt_upper: u128,
+    sqrt_price_x64: u128,
+    volatility_bps: u32,
+) -> u128 {
+    let deposit_value = unit_value_x64(env, sqrt_lower, sqrt_upper, sqrt_price_x64, sqrt_price_x64);
+
+    if deposit_value == 0 || volatility_bps == 0 {
+        return 0;
+    }
+
+    // sqrt(1 + v) in Q64.64
+    let move_x64 = get_sqrt_price_for_ratio(
+        env,
+        &U256::from_u32(env, 10_000 + volatility_bps),
+        &U256::from_u32(env, 10_000),
+    );
+
+    let sqrt_up = mul_div(env, sqrt_price_x64, move_x64, Q64);
+    let sqrt_down = mul_div(env, sqrt_price_x64, Q64, move_x64);
+
+    let loss_up = impermanent_loss_x64(env, sqrt_lower, sqrt_upper, sqrt_price_x64, sqrt_up);
+    let loss_down = impermanent_loss_x64(env, sqrt_lower, sqrt_upper, sqrt_price_x64, sqrt_down);
+    let expected_loss = loss_up / 2 + loss_down / 2 + (loss_up % 2 + loss_down % 2).div_ceil(2);
+
+    mul_div(env, expected_loss, Q64, deposit_value)
+}
@@ -0,0 +1,92 @@
+//! # Basis Point Math
+//! 
+//! Fee arithmetic on i128 token amounts, shared by the swap engine and
+//! the liquidity/protocol fee paths.
+//! 
+//! ## Overflow
+//! `amount * bps` does not fit in i128 for amounts near `i128::MAX`, so
+//! every helper splits the amount into quotient and remainder of the
+//! divisor first: `a * n / d == (a / d) * n + (a % d) * n / d`. This gives
+//! the exact result of the full-width product without a 256-bit type; the
+//! tests check every helper against a 256-bit reference up to `i128::MAX`.
+//! 
+//! ## Policies
+//! - `amount` must be non-negative (`AmountNegative`) and `bps` at most
+//!   10000 (`InvalidFee`): a negative fee base is always a caller bug
+//! - `apply_bps`, `apply_bps_ceil` and `deduct_bps` never exceed `amount`
+//!   and therefore cannot overflow
+//! - `gross_up` can exceed i128 and fails with `Overflow` instead of
+//!   saturating
+
+use soroban_sdk::{panic_with_error, Env};
+
+use crate::error::{PoolError, PoolErrorExt};
+
+/// Basis point denominator (100%)
+pub const BPS_DENOMINATOR: i128 = 10000;
+
+/// Exact `amount * numerator / denominator` rounded down
+/// Requires `amount >= 0`, `0 <= numerator <= denominator`, `denominator > 0`
+#[inline]
+fn mul_div_floor(amount: i128, numerator: i128, denominator: i128) -> i128 {
+    let q = amount / denominator;
+    let r = amount % denominator;
+    q * numerator + (r * numerator) / denominator
+}
+
+#[inline]
+fn check_inputs(env: &Env, amount: i128, bps: u32) {
+    if amount < 0 {
+        panic_with_error!(env, PoolError::AmountNegative);
+    }
+    if bps as i128 > BPS_DENOMINATOR {
+        panic_with_error!(env, PoolError::InvalidFee);
+    }
+}
+
+/// `amount * bps / 10000`, rounded down
+pub fn apply_bps(env: &Env, amount: i128, bps: u32) -> i128 {
+    check_inputs(env, amount, bps);
+    mul_div_floor(amount, bps as i128, BPS_DENOMINATOR)
+}
+
+/// `amount * bps / 10000`, rounded up
+pub fn apply_bps_ceil(env: &Env, amount: i128, bps: u32) -> i128 {
+    check_inputs(env, amount, bps);
+    let bps = bps as i128;
+    let floor = mul_div_floor(amount, bps, BPS_DENOMINATOR);
+    if (amount % BPS_DENOMINATOR) * bps % BPS_DENOMINATOR != 0 {
+        floor + 1
+    } else {
+        floor
+    }
+}
+
+/// `amount` less its `bps` share, i.e. `amount * (10000 - bps) / 10000`
+/// rounded down (the deducted share is rounded up)
+pub fn deduct_bps(env: &Env, amount: i128, bps: u32) -> i128 {
+    amount - apply_bps_ceil(env, amount, bps)
+}
+
+/// Smallest gross amount whose `bps` share leaves at least `net`,
+/// i.e. `net * 10000 / (10000 - bps)` rounded up
+/// 
+/// # Panics
+/// `InvalidFee` if `bps == 10000` (nothing is left after the fee),
+/// `Overflow` if the result does not fit in i128
+pub fn gross_up(env: &Env, net: i128, bps: u32) -> i128 {
+    check_inputs(env, net, bps);
+    let denominator = BPS_DENOMINATOR - bps as i128;
+    if denominator == 0 {
+        panic_with_error!(env, PoolError::InvalidFee);
+    }
+
+    let q = net / denominator;
+    let r = net % denominator;
+    let rem_scaled = r * BPS_DENOMINATOR;
+    let rem_part = rem_scaled / denominator + if rem_scaled % denominator != 0 { 1 } else { 0 };
+
+    q.checked_mul(BPS_DENOMINATOR)
+        .and_then(|v| v.checked_add(rem_part))
+        .unwrap_or_else(|| panic_with_error!(env, PoolErrorExt::Overflow))
+}
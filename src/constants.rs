@@ -1,3 +1,17 @@
+// ============================================================
+// INTERFACE CONSTANTS
+// ============================================================
+
+/// Marker symbol returned by `belugaswap_pool_marker`
+pub const POOL_MARKER: &str = "belugaswap_pool";
+
+/// Pool interface version, bumped on breaking entrypoint changes
+pub const POOL_INTERFACE_VERSION: u32 = 1;
+
+/// Code version reported by `version`, bumped with every release so
+/// tooling can tell which WASM is live after an `upgrade`
+pub const CONTRACT_VERSION: u32 = 1;
+
 // ============================================================
 // TICK CONSTANTS
 // ============================================================
@@ -11,17 +25,14 @@ pub const MIN_TICK: i32 = -887272;
 pub const MAX_TICK: i32 = 887272;
 
 // ============================================================
-// SQRT PRICE CONSTANTS (Q64.64 format)
+// PRICE CONSTANTS
 // ============================================================
+//
+// The Q64.64 sqrt price bounds are `math::MIN_SQRT_RATIO` and
+// `math::MAX_SQRT_RATIO`, the sqrt prices at MIN_TICK and MAX_TICK.
 
-/// Minimum sqrt price (at MIN_TICK)
-/// sqrt(1.0001^-887272) * 2^64
-#[allow(dead_code)]
-pub const MIN_SQRT_PRICE: u128 = 4295128739;
-
-/// Maximum sqrt price (at MAX_TICK) - using u128::MAX as safe upper bound
-#[allow(dead_code)]
-pub const MAX_SQRT_PRICE: u128 = u128::MAX;
+/// Largest token decimals accepted by price helpers (10^38 fits in u128)
+pub const MAX_TOKEN_DECIMALS: u32 = 38;
 
 /// sqrt price for 1:1 price ratio (2^64)
 #[allow(dead_code)]
@@ -48,14 +59,42 @@ pub const MIN_SWAP_AMOUNT: i128 = 1;
 /// Minimum output amount (dust threshold)
 pub const MIN_OUTPUT_AMOUNT: i128 = 1;
 
+/// Tick half-widths of the `preset_range` presets, indexed by preset:
+/// narrow (±5%), medium (±15%), wide (±50%)
+/// 
+/// ln(1 + pct) / ln(1.0001), rounded to the nearest tick.
+pub const RANGE_PRESET_TICK_DELTAS: [i32; 3] = [488, 1398, 4055];
+
+/// Largest token amount accepted at the contract boundary; leaves headroom
+/// for fee gross-ups and sums of two amounts in i128
+pub const MAX_AMOUNT: i128 = 1 << 120;
+
 /// Maximum slippage in basis points (50% = 5000 bps)
 pub const MAX_SLIPPAGE_BPS: i128 = 5000;
 
-/// Maximum iterations in swap loop (prevents infinite loops)
+/// Maximum steps in the swap loop; a swap that needs more panics rather
+/// than filling partially
 pub const MAX_SWAP_ITERATIONS: u32 = 1024;
 
-/// Maximum steps when searching for next initialized tick
-pub const MAX_TICK_SEARCH_STEPS: i32 = 2000;
+/// Most pools `swap_exact_in_multi` routes through in one call
+pub const MAX_ROUTE_HOPS: u32 = 4;
+
+/// Tick bitmap words returned by `get_swap_context` on each side of the
+/// word holding the current tick
+pub const SWAP_CONTEXT_WORDS: i32 = 2;
+
+/// Most bitmap words `get_populated_words` scans per call
+pub const MAX_BITMAP_WORDS_PER_QUERY: u32 = 256;
+
+/// Most ticks `get_ticks_in_range` returns per call
+pub const MAX_TICKS_PER_QUERY: u32 = 100;
+
+/// Most positions `get_positions_page` returns per call
+pub const MAX_POSITIONS_PER_QUERY: u32 = 50;
+
+/// Bisection steps `swap_and_add` spends sizing its swap (each step is one
+/// simulated swap)
+pub const ZAP_SEARCH_ITERATIONS: u32 = 32;
 
 // ============================================================
 // FEE CONSTANTS
@@ -64,9 +103,17 @@ pub const MAX_TICK_SEARCH_STEPS: i32 = 2000;
 /// Maximum fee in basis points (100%)
 pub const MAX_FEE_BPS: u32 = 10000;
 
+/// Maximum swap fee `set_fee_bps` accepts in basis points (10%)
+/// 
+/// Well below `MAX_FEE_BPS`, which the swap engine itself rejects.
+pub const MAX_SWAP_FEE_BPS: u32 = 1000;
+
 /// Maximum protocol fee in basis points (100% of LP fees)
 pub const MAX_PROTOCOL_FEE_BPS: u32 = 10000;
 
+/// Maximum deposit/withdrawal fee in basis points (10%)
+pub const MAX_LIQUIDITY_FEE_BPS: u32 = 1000;
+
 /// Default fee (0.3%)
 #[allow(dead_code)]
 pub const DEFAULT_FEE_BPS: u32 = 30;
@@ -91,13 +138,24 @@ pub const Q128: u128 = 1u128 << 64 << 64;
 pub const MAX_REASONABLE_FEE_DELTA: u128 = 1u128 << 96;
 
 // ============================================================
-// TWAP CONSTANTS (Reserved for future use)
+// STORAGE TTL CONSTANTS
+// ============================================================
+
+/// Ledgers per day at a 5 second close time
+const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// Persistent entries are extended once their TTL drops below this
+pub const PERSISTENT_TTL_THRESHOLD: u32 = 30 * DAY_IN_LEDGERS;
+
+/// TTL persistent entries are extended to
+pub const PERSISTENT_TTL_EXTEND_TO: u32 = 60 * DAY_IN_LEDGERS;
+
+// ============================================================
+// TWAP CONSTANTS
 // ============================================================
 
 /// Maximum TWAP observations stored
-#[allow(dead_code)]
 pub const MAX_TWAP_OBSERVATIONS: u32 = 100;
 
 /// Minimum time between TWAP observations (in seconds)
-#[allow(dead_code)]
 pub const MIN_TWAP_OBSERVATION_INTERVAL: u64 = 1;
\ No newline at end of file
@@ -1,4 +1,4 @@
-use soroban_sdk::{symbol_short, Symbol};
+use soroban_sdk::{contracterror, symbol_short, Symbol};
 
 // ============================================================
 // ERROR SYMBOLS (For PreviewResult and swap validation)
@@ -42,26 +42,176 @@ impl ErrorSymbol {
     pub fn same_token() -> Symbol {
         symbol_short!("SAME_TKN")
     }
+    
+    /// Price limit on the wrong side of the current price or out of range
+    pub fn bad_limit() -> Symbol {
+        symbol_short!("BAD_LIM")
+    }
 }
 
 // ============================================================
-// ERROR MESSAGES (For panic messages)
+// CONTRACT ERRORS
 // ============================================================
 
-/// Human-readable error messages for panics
-pub struct ErrorMsg;
+/// Contract errors raised by every entrypoint failure
+/// 
+/// Codes are not grouped by origin: the validators in `validate.rs` return
+/// codes from across the range (e.g. `InvalidTick`, `DeadlineTooFar`), and
+/// the same codes are also raised directly where a failing condition is
+/// detected outside a validator. Codes are stable: new variants
+/// are only ever appended. The contract spec allows at most 50 variants,
+/// all of which are taken, so later failures live in `PoolErrorExt`.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum PoolError {
+    /// Amount is zero or negative where a positive one is required
+    AmountNotPositive = 1,
+    /// Amount is negative where zero or more is required
+    AmountNegative = 2,
+    /// Amount exceeds `MAX_AMOUNT`
+    AmountAboveCap = 3,
+    /// Ticks out of range or lower not below upper
+    InvalidTickRange = 4,
+    /// Sqrt price outside [MIN_SQRT_RATIO, MAX_SQRT_RATIO)
+    SqrtPriceOutOfBounds = 5,
+    /// Address is the pool contract itself
+    SelfAddress = 6,
+    /// Deadline further in the future than the configured window allows
+    DeadlineTooFar = 7,
+    /// A boundary tick's liquidity does not cover the position being burned
+    TickAccountingMismatch = 8,
+    /// Pool has not been initialized
+    NotInitialized = 9,
+    /// Pool already initialized
+    AlreadyInitialized = 10,
+    /// Pool already initialized with different parameters
+    InitializationMismatch = 11,
+    /// Swap fee outside 1-10000 bps at `initialize`, outside 1-1000 bps
+    /// (`MAX_SWAP_FEE_BPS`) at `set_fee_bps`, or a bps value above 10000
+    InvalidFee = 12,
+    /// Protocol fee share outside 0-10000 bps
+    InvalidProtocolFee = 13,
+    /// Deposit or withdrawal fee outside 0-1000 bps
+    InvalidLiquidityFee = 14,
+    /// Tick spacing not positive
+    InvalidTickSpacing = 15,
+    /// Price numerator or denominator not positive
+    InvalidPrice = 16,
+    /// Token decimals outside 0-38
+    InvalidDecimals = 17,
+    /// Tick out of range or not aligned to the spacing
+    InvalidTick = 18,
+    /// Range preset not 0 (narrow), 1 (medium) or 2 (wide)
+    InvalidRangePreset = 19,
+    /// Token is not one of the pool's tokens, or not the reward token
+    /// already being streamed
+    InvalidToken = 20,
+    /// Input and output tokens are the same, both pool tokens are, or the
+    /// reward token is a pool token
+    SameToken = 21,
+    /// Amounts fall short of the caller's minimums
+    SlippageExceeded = 22,
+    /// Price limit not between the current price and the bound for the
+    /// swap direction
+    InvalidPriceLimit = 23,
+    /// Liquidity amount too low
+    LiquidityTooLow = 24,
+    /// Deposit rounds to zero for this liquidity
+    UnfundedPosition = 25,
+    /// Position holds less liquidity than requested
+    InsufficientLiquidity = 26,
+    /// Deadline has passed
+    DeadlineExpired = 27,
+    /// Caller is not the pool admin
+    Unauthorized = 28,
+    /// No vested protocol fees to release
+    NothingToRelease = 29,
+    /// Withdrawal queue is disabled
+    WithdrawalQueueDisabled = 30,
+    /// Removal would exceed the per-interval queue threshold: use
+    /// `request_withdrawal`
+    WithdrawalAboveThreshold = 31,
+    /// No queued withdrawal for this position
+    NoWithdrawalRequest = 32,
+    /// Next withdrawal tranche not yet releasable
+    WithdrawalNotReady = 33,
+    /// Lookback exceeds the available observation history
+    ObservationTooOld = 34,
+    /// Flash loan not paid back by the callback
+    FlashNotFunded = 35,
+    /// Reserve tracking is disabled
+    ReserveTrackingDisabled = 36,
+    /// No position with this id
+    PositionNotFound = 37,
+    /// Caller does not own this position
+    NotPositionOwner = 38,
+    /// No admin transfer pending
+    NoPendingAdmin = 39,
+    /// Swaps, liquidity additions and flash loans are paused
+    PoolPaused = 40,
+    /// Post-only liquidity must supply exactly one token
+    PostOnlyNotSingleSided = 41,
+    /// Post-only range is at or across the current price
+    PostOnlyWouldFill = 42,
+    /// Swap needs more than `MAX_SWAP_ITERATIONS` steps to fill
+    SwapTooManyIterations = 43,
+    /// Swap amount below `MIN_SWAP_AMOUNT`
+    SwapAmountTooSmall = 44,
+    /// No liquidity in the swap direction, even across empty gaps
+    NoLiquidity = 45,
+    /// Swap output below `MIN_OUTPUT_AMOUNT`
+    OutputTooSmall = 46,
+    /// Swap slippage above `MAX_SLIPPAGE_BPS`
+    MaxSlippageExceeded = 47,
+    /// Swap step fee outside [0, amount_in], or a fee too large to credit
+    /// as fee growth
+    InvalidFeeCalculation = 48,
+    /// Multi-hop route is empty or longer than `MAX_ROUTE_HOPS`
+    InvalidRoute = 49,
+    /// Fee and tick spacing are not a canonical fee tier's pair, or the tier
+    /// does not match the pool's tick spacing
+    InvalidFeeTier = 50,
+}
 
-impl ErrorMsg {
-    pub const ALREADY_INITIALIZED: &'static str = "pool already initialized";
-    pub const INVALID_FEE: &'static str = "invalid fee: must be 1-10000 bps";
-    pub const INVALID_PROTOCOL_FEE: &'static str = "invalid protocol fee: must be 0-10000 bps";
-    pub const INVALID_TICK_SPACING: &'static str = "invalid tick spacing: must be positive";
-    pub const INVALID_TICK_RANGE: &'static str = "invalid tick range: lower must be < upper";
-    pub const INVALID_TOKEN: &'static str = "invalid token for this pool";
-    pub const SAME_TOKEN: &'static str = "input and output tokens are the same";
-    pub const SLIPPAGE_EXCEEDED: &'static str = "slippage tolerance exceeded";
-    pub const LIQUIDITY_TOO_LOW: &'static str = "liquidity amount too low";
-    pub const INSUFFICIENT_LIQUIDITY: &'static str = "insufficient liquidity in position";
-    pub const INVALID_LIQUIDITY_AMOUNT: &'static str = "liquidity amount must be positive";
-    pub const SWAP_VALIDATION_FAILED: &'static str = "swap validation failed";
-}
\ No newline at end of file
+/// Contract errors past the 50-variant spec limit of `PoolError`
+/// 
+/// Codes continue from 51 so every failure keeps a unique number.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum PoolErrorExt {
+    /// Arithmetic result does not fit its type
+    Overflow = 51,
+    /// Division by a zero denominator
+    DivisionByZero = 52,
+    /// Limit order's range not yet fully crossed by the price
+    LimitOrderNotCrossed = 53,
+    /// Liquidity ran out before the price limit on a swap that does not
+    /// allow partial fills
+    IncompleteSwap = 54,
+}
+
+impl PoolError {
+    /// Contract error for a swap validation failure reported as an
+    /// `ErrorSymbol`
+    pub fn from_swap_symbol(symbol: &Symbol) -> PoolError {
+        if *symbol == ErrorSymbol::amt_low() {
+            PoolError::SwapAmountTooSmall
+        } else if *symbol == ErrorSymbol::no_liq() {
+            PoolError::NoLiquidity
+        } else if *symbol == ErrorSymbol::slip_hi() {
+            PoolError::SlippageExceeded
+        } else if *symbol == ErrorSymbol::out_dust() {
+            PoolError::OutputTooSmall
+        } else if *symbol == ErrorSymbol::slip_max() {
+            PoolError::MaxSlippageExceeded
+        } else if *symbol == ErrorSymbol::bad_limit() {
+            PoolError::InvalidPriceLimit
+        } else if *symbol == ErrorSymbol::same_token() {
+            PoolError::SameToken
+        } else {
+            PoolError::InvalidToken
+        }
+    }
+}
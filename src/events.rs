@@ -1,4 +1,6 @@
-use soroban_sdk::{Env, Symbol};
+use soroban_sdk::{Address, BytesN, Env, Symbol};
+
+use crate::types::{CollectEvent, LiquidityChange, LiquidityEvent, SwapEvent};
 
 // ============================================================
 // EVENT NAMES
@@ -32,8 +34,96 @@ impl EventName {
         Symbol::new(env, "collect")
     }
     
-    pub fn sync_tick(env: &Env) -> Symbol {
-        Symbol::new(env, "synctk")
+    pub fn liquidity(env: &Env) -> Symbol {
+        Symbol::new(env, "liq")
+    }
+    
+    pub fn fee_zero_growth(env: &Env) -> Symbol {
+        Symbol::new(env, "fee0")
+    }
+    
+    pub fn liquidity_fees_set(env: &Env) -> Symbol {
+        Symbol::new(env, "liq_fees_set")
+    }
+    
+    pub fn protocol_fee_set(env: &Env) -> Symbol {
+        Symbol::new(env, "proto_fee_set")
+    }
+
+    pub fn swap_fee_set(env: &Env) -> Symbol {
+        Symbol::new(env, "fee_set")
+    }
+
+    pub fn rewards_funded(env: &Env) -> Symbol {
+        Symbol::new(env, "rewards_fund")
+    }
+
+    pub fn collect_rewards(env: &Env) -> Symbol {
+        Symbol::new(env, "collect_rew")
+    }
+    
+    pub fn collect_protocol(env: &Env) -> Symbol {
+        Symbol::new(env, "collect_proto")
+    }
+    
+    pub fn vesting_set(env: &Env) -> Symbol {
+        Symbol::new(env, "vesting_set")
+    }
+    
+    pub fn vested_release(env: &Env) -> Symbol {
+        Symbol::new(env, "vest_release")
+    }
+    
+    pub fn withdrawal_queue_set(env: &Env) -> Symbol {
+        Symbol::new(env, "wd_queue_set")
+    }
+    
+    pub fn withdrawal_requested(env: &Env) -> Symbol {
+        Symbol::new(env, "wd_request")
+    }
+    
+    pub fn withdrawal_cancelled(env: &Env) -> Symbol {
+        Symbol::new(env, "wd_cancel")
+    }
+    
+    pub fn flash(env: &Env) -> Symbol {
+        Symbol::new(env, "flash")
+    }
+    
+    pub fn reserve_tracking_set(env: &Env) -> Symbol {
+        Symbol::new(env, "reserve_track")
+    }
+    
+    pub fn reserve_sync(env: &Env) -> Symbol {
+        Symbol::new(env, "reserve_sync")
+    }
+    
+    pub fn pause(env: &Env) -> Symbol {
+        Symbol::new(env, "pause")
+    }
+    
+    pub fn unpause(env: &Env) -> Symbol {
+        Symbol::new(env, "unpause")
+    }
+    
+    pub fn deadline_window_set(env: &Env) -> Symbol {
+        Symbol::new(env, "deadline_window")
+    }
+    
+    pub fn tick_audit_set(env: &Env) -> Symbol {
+        Symbol::new(env, "tick_audit")
+    }
+    
+    pub fn admin_proposed(env: &Env) -> Symbol {
+        Symbol::new(env, "admin_propose")
+    }
+    
+    pub fn admin_changed(env: &Env) -> Symbol {
+        Symbol::new(env, "admin_changed")
+    }
+    
+    pub fn upgrade(env: &Env) -> Symbol {
+        Symbol::new(env, "upgrade")
     }
 }
 
@@ -58,41 +148,262 @@ pub fn emit_pool_init(env: &Env, sqrt_price_x64: u128, current_tick: i32, tick_s
 }
 
 /// Emit add liquidity event
-pub fn emit_add_liquidity(env: &Env, liquidity: i128, amount0: i128, amount1: i128) {
+/// Topics: ("add_liq", owner, lower, upper); data: `LiquidityEvent`
+pub fn emit_add_liquidity(
+    env: &Env,
+    owner: &Address,
+    lower: i32,
+    upper: i32,
+    liquidity: i128,
+    amount0: i128,
+    amount1: i128,
+) {
     env.events().publish(
-        (EventName::add_liquidity(env),),
-        (liquidity, amount0, amount1),
+        (EventName::add_liquidity(env), owner.clone(), lower, upper),
+        LiquidityEvent { liquidity, amount0, amount1 },
     );
 }
 
 /// Emit remove liquidity event
-pub fn emit_remove_liquidity(env: &Env, liquidity: i128, amount0: i128, amount1: i128) {
+/// Topics: ("remove_liq", owner, lower, upper); data: `LiquidityEvent`
+pub fn emit_remove_liquidity(
+    env: &Env,
+    owner: &Address,
+    lower: i32,
+    upper: i32,
+    liquidity: i128,
+    amount0: i128,
+    amount1: i128,
+) {
     env.events().publish(
-        (EventName::remove_liquidity(env),),
-        (liquidity, amount0, amount1),
+        (EventName::remove_liquidity(env), owner.clone(), lower, upper),
+        LiquidityEvent { liquidity, amount0, amount1 },
     );
 }
 
 /// Emit swap event
-pub fn emit_swap(env: &Env, amount_in: i128, amount_out: i128, zero_for_one: bool) {
+/// Topics: ("swap", caller, zero_for_one); data: `SwapEvent` with the price
+/// and tick the pool was left at
+pub fn emit_swap(
+    env: &Env,
+    caller: &Address,
+    zero_for_one: bool,
+    amount_in: i128,
+    amount_out: i128,
+    fee: i128,
+    ticks_crossed: u32,
+    amount_remaining: i128,
+    sqrt_price_x64: u128,
+    tick: i32,
+) {
+    env.events().publish(
+        (EventName::swap(env), caller.clone(), zero_for_one),
+        SwapEvent { amount_in, amount_out, fee, ticks_crossed, amount_remaining, sqrt_price_x64, tick },
+    );
+}
+
+/// Emit active liquidity change event
+pub fn emit_liquidity_change(env: &Env, old_liquidity: i128, new_liquidity: i128, cause: LiquidityChange) {
     env.events().publish(
-        (EventName::swap(env),),
-        (amount_in, amount_out, zero_for_one),
+        (EventName::liquidity(env),),
+        (old_liquidity, new_liquidity, cause),
     );
 }
 
 /// Emit collect fees event
-pub fn emit_collect(env: &Env, amount0: u128, amount1: u128) {
+/// Topics: ("collect", owner, lower, upper); data: `CollectEvent`
+pub fn emit_collect(
+    env: &Env,
+    owner: &Address,
+    lower: i32,
+    upper: i32,
+    recipient: &Address,
+    amount0: u128,
+    amount1: u128,
+) {
+    env.events().publish(
+        (EventName::collect(env), owner.clone(), lower, upper),
+        CollectEvent { recipient: recipient.clone(), amount0, amount1 },
+    );
+}
+
+/// Emit liquidity mining funding event
+/// `end` is when the combined reward stream runs out
+pub fn emit_rewards_funded(env: &Env, token: &Address, amount: i128, end: u64) {
+    env.events().publish(
+        (EventName::rewards_funded(env),),
+        (token.clone(), amount, end),
+    );
+}
+
+/// Emit liquidity mining reward claim event
+/// Topics: ("collect_rew", owner, lower, upper); data: amount
+pub fn emit_collect_rewards(env: &Env, owner: &Address, lower: i32, upper: i32, amount: u128) {
+    env.events().publish(
+        (EventName::collect_rewards(env), owner.clone(), lower, upper),
+        amount,
+    );
+}
+
+/// Emit warning that an LP fee was too small to move fee growth
+/// `zero_for_one` tells which token the fee was paid in (true = token0)
+pub fn emit_fee_zero_growth(env: &Env, zero_for_one: bool, fee: u128, liquidity: u128) {
     env.events().publish(
-        (EventName::collect(env),),
+        (EventName::fee_zero_growth(env),),
+        (zero_for_one, fee, liquidity),
+    );
+}
+
+/// Emit deposit/withdrawal fee change event
+pub fn emit_liquidity_fees_set(env: &Env, deposit_fee_bps: u32, withdraw_fee_bps: u32) {
+    env.events().publish(
+        (EventName::liquidity_fees_set(env),),
+        (deposit_fee_bps, withdraw_fee_bps),
+    );
+}
+
+/// Emit protocol fee share change event
+pub fn emit_protocol_fee_set(env: &Env, protocol_fee_bps: u32) {
+    env.events().publish(
+        (EventName::protocol_fee_set(env),),
+        protocol_fee_bps,
+    );
+}
+
+/// Emit swap fee change event
+pub fn emit_swap_fee_set(env: &Env, old_fee_bps: u32, new_fee_bps: u32) {
+    env.events().publish(
+        (EventName::swap_fee_set(env),),
+        (old_fee_bps, new_fee_bps),
+    );
+}
+
+/// Emit protocol fee collection event
+/// `vested` is true when the fees were moved into the vesting schedule
+pub fn emit_collect_protocol(env: &Env, amount0: u128, amount1: u128, vested: bool) {
+    env.events().publish(
+        (EventName::collect_protocol(env),),
+        (amount0, amount1, vested),
+    );
+}
+
+/// Emit protocol fee vesting duration change event
+pub fn emit_vesting_set(env: &Env, duration: u64) {
+    env.events().publish(
+        (EventName::vesting_set(env),),
+        duration,
+    );
+}
+
+/// Emit vested protocol fee release event
+pub fn emit_vested_release(env: &Env, amount0: u128, amount1: u128) {
+    env.events().publish(
+        (EventName::vested_release(env),),
         (amount0, amount1),
     );
 }
 
-/// Emit tick sync event (for debugging/indexing)
-pub fn emit_sync_tick(env: &Env, tick: i32, sqrt_price_x64: u128) {
+/// Emit withdrawal queue settings change event
+pub fn emit_withdrawal_queue_set(env: &Env, threshold: i128, interval: u64) {
+    env.events().publish(
+        (EventName::withdrawal_queue_set(env),),
+        (threshold, interval),
+    );
+}
+
+/// Emit queued withdrawal event
+pub fn emit_withdrawal_requested(env: &Env, lower: i32, upper: i32, liquidity: i128) {
+    env.events().publish(
+        (EventName::withdrawal_requested(env),),
+        (lower, upper, liquidity),
+    );
+}
+
+/// Emit cancelled withdrawal event (`remaining` = liquidity left unqueued)
+pub fn emit_withdrawal_cancelled(env: &Env, lower: i32, upper: i32, remaining: i128) {
+    env.events().publish(
+        (EventName::withdrawal_cancelled(env),),
+        (lower, upper, remaining),
+    );
+}
+
+/// Emit flash loan event
+/// Topics: ("flash", caller, receiver); data: (amount0, amount1, paid0, paid1)
+/// where `paid` is what the pool received on top of the loan
+pub fn emit_flash(
+    env: &Env,
+    caller: &Address,
+    receiver: &Address,
+    amount0: i128,
+    amount1: i128,
+    paid0: i128,
+    paid1: i128,
+) {
+    env.events().publish(
+        (EventName::flash(env), caller.clone(), receiver.clone()),
+        (amount0, amount1, paid0, paid1),
+    );
+}
+
+/// Emit reserve tracking toggle event
+pub fn emit_reserve_tracking_set(env: &Env, enabled: bool) {
+    env.events().publish(
+        (EventName::reserve_tracking_set(env),),
+        enabled,
+    );
+}
+
+/// Emit pause or unpause event (data = admin that toggled it)
+pub fn emit_paused_set(env: &Env, admin: &Address, paused: bool) {
+    let name = if paused { EventName::pause(env) } else { EventName::unpause(env) };
+    env.events().publish((name,), admin.clone());
+}
+
+/// Emit tick accounting audit toggle event
+pub fn emit_tick_audit_set(env: &Env, enabled: bool) {
+    env.events().publish(
+        (EventName::tick_audit_set(env),),
+        enabled,
+    );
+}
+
+/// Emit deadline ceiling change event (0 = ceiling disabled)
+pub fn emit_deadline_window_set(env: &Env, window_secs: u64) {
+    env.events().publish(
+        (EventName::deadline_window_set(env),),
+        window_secs,
+    );
+}
+
+/// Emit admin transfer proposal event
+pub fn emit_admin_proposed(env: &Env, current: &Address, pending: &Address) {
+    env.events().publish(
+        (EventName::admin_proposed(env),),
+        (current.clone(), pending.clone()),
+    );
+}
+
+/// Emit admin transfer completion event
+pub fn emit_admin_changed(env: &Env, previous: &Address, admin: &Address) {
+    env.events().publish(
+        (EventName::admin_changed(env),),
+        (previous.clone(), admin.clone()),
+    );
+}
+
+/// Emit contract upgrade event (old hash is None before the first upgrade)
+pub fn emit_upgrade(env: &Env, old_wasm_hash: &Option<BytesN<32>>, new_wasm_hash: &BytesN<32>) {
+    env.events().publish(
+        (EventName::upgrade(env),),
+        (old_wasm_hash.clone(), new_wasm_hash.clone()),
+    );
+}
+
+/// Emit reserve sync event (delta = actual balance - tracked reserve;
+/// negative values are an unresolved shortfall)
+pub fn emit_reserve_sync(env: &Env, delta0: i128, delta1: i128) {
     env.events().publish(
-        (EventName::sync_tick(env),),
-        (tick, sqrt_price_x64),
+        (EventName::reserve_sync(env),),
+        (delta0, delta1),
     );
 }
@@ -0,0 +1,23 @@
+use soroban_sdk::{contractclient, Address, Bytes, Env};
+
+// ============================================================
+// FLASH LOAN CALLBACK
+// ============================================================
+
+/// Interface a contract implements to receive `flash` callbacks
+///
+/// `amount0`/`amount1` have already been sent to the receiver when
+/// `on_flash_loan` runs; before it returns, the receiver must transfer
+/// them back to `pool` plus `fee0`/`fee1`.
+#[contractclient(name = "FlashLoanReceiverClient")]
+pub trait FlashLoanReceiver {
+    fn on_flash_loan(
+        env: Env,
+        pool: Address,
+        amount0: i128,
+        amount1: i128,
+        fee0: i128,
+        fee1: i128,
+        data: Bytes,
+    );
+}
@@ -1,41 +1,106 @@
 #![no_std]
+#![allow(clippy::too_many_arguments)]
 
-use soroban_sdk::{contract, contractimpl, token, Address, Env};
+use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, Bytes, BytesN, Env, Symbol, Vec, U256};
 
 // ============================================================
 // MODULE DECLARATIONS
 // ============================================================
 
+mod admin;
+mod analytics;
+mod bps;
 mod constants;
 mod error;
 mod events;
+mod flash;
 mod math;
+mod oracle;
 mod position;
+mod reserves;
+mod rewards;
 mod storage;
 mod swap;
 mod tick;
+mod tick_bitmap;
+#[cfg(feature = "testutils")]
+mod testutils;
+#[cfg(test)]
+mod tests;
 mod types;
+mod validate;
+mod vesting;
+mod zap;
 
 // ============================================================
 // IMPORTS
 // ============================================================
 
-use constants::{MAX_FEE_BPS, MAX_PROTOCOL_FEE_BPS};
-use error::{ErrorMsg, ErrorSymbol};
-use events::{emit_initialized, emit_pool_init, emit_add_liquidity, emit_remove_liquidity, emit_swap, emit_collect};
-use math::{get_amounts_for_liquidity, get_liquidity_for_amounts, snap_tick_to_spacing, MIN_LIQUIDITY, get_sqrt_ratio_at_tick};
-use position::{read_position, write_position, update_position, modify_position, calculate_pending_fees, has_liquidity};
+use admin::require_admin;
+use analytics::{break_even_fees_x64, impermanent_delta};
+use bps::{apply_bps, apply_bps_ceil, deduct_bps};
+use constants::{
+    MAX_FEE_BPS, MAX_SWAP_FEE_BPS, MAX_PROTOCOL_FEE_BPS, MAX_LIQUIDITY_FEE_BPS, MAX_TOKEN_DECIMALS, POOL_MARKER, POOL_INTERFACE_VERSION,
+    CONTRACT_VERSION,
+    RANGE_PRESET_TICK_DELTAS, SWAP_CONTEXT_WORDS, MAX_BITMAP_WORDS_PER_QUERY, MAX_POSITIONS_PER_QUERY,
+    MAX_TICKS_PER_QUERY, MAX_ROUTE_HOPS, Q64,
+};
+use error::ErrorSymbol;
+use events::{
+    emit_initialized, emit_pool_init, emit_add_liquidity, emit_remove_liquidity, emit_swap, emit_collect,
+    emit_collect_protocol, emit_vesting_set, emit_vested_release, emit_liquidity_fees_set,
+    emit_protocol_fee_set, emit_swap_fee_set, emit_withdrawal_queue_set, emit_withdrawal_requested, emit_withdrawal_cancelled,
+    emit_flash, emit_reserve_tracking_set, emit_reserve_sync, emit_liquidity_change,
+    emit_admin_proposed, emit_admin_changed, emit_deadline_window_set,
+    emit_paused_set, emit_upgrade, emit_tick_audit_set, emit_rewards_funded, emit_collect_rewards,
+};
+use math::{
+    get_amounts_for_liquidity, get_amounts_for_liquidity_rounding, get_liquidity_for_amounts,
+    MIN_LIQUIDITY, get_sqrt_ratio_at_tick, get_sqrt_price_for_ratio, get_ratio_for_sqrt_price,
+    get_tick_at_sqrt_ratio, mul_div_checked,
+};
+use oracle::{fees_earned_between, observe_at, write_observation};
+use position::{
+    read_position, write_position, has_position, remove_position, update_position, modify_position,
+    calculate_pending_fees, calculate_pending_rewards, has_liquidity, is_empty,
+};
 use storage::{
     is_initialized, set_initialized,
     read_pool_config, write_pool_config,
-    read_pool_state, write_pool_state, init_pool_state,
+    read_pool_state, try_read_pool_state, write_pool_state, init_pool_state,
+    read_vesting_duration, write_vesting_duration,
+    read_vesting_schedule, write_vesting_schedule, read_reward_schedule,
+    read_withdrawal_queue_config, write_withdrawal_queue_config,
+    read_withdrawal_request, write_withdrawal_request, remove_withdrawal_request,
+    read_withdrawal_window, write_withdrawal_window, remove_withdrawal_window,
+    read_tracked_reserves, write_tracked_reserves, remove_tracked_reserves, read_tick_bitmap_word,
+    read_position_record, write_position_record, read_owner_positions,
+    read_limit_order, write_limit_order, remove_limit_order,
+    read_pending_admin, write_pending_admin, remove_pending_admin,
+    read_max_deadline_window, write_max_deadline_window, is_paused, write_paused,
+    read_wasm_hash, write_wasm_hash, is_tick_accounting_checked, write_tick_accounting_checks,
+    extend_if_present, extend_pool_entries,
 };
-use swap::{engine_swap, validate_and_preview_swap};
-use tick::{get_fee_growth_inside, update_tick, is_valid_tick};
-use types::{PoolConfig, PoolState, PositionInfo, SwapResult, PreviewResult, TickInfo};
+use reserves::track_reserves;
+use rewards::{accrue_rewards, current_reward_growth, fund_schedule, pay_rewards};
+use swap::{engine_swap, is_fully_filled, simulate_swap, validate_and_preview_swap};
+use tick::{get_fee_growth_inside, get_reward_growth_inside, update_tick, is_valid_tick, nearest_valid_ticks, preset_range, sync_tick_bitmap};
+use tick_bitmap::{set_tick_initialized, ticks_in_word, word_position, word_start_tick};
+use types::{
+    LimitOrder, LiquidityChange, PoolConfig, PoolInfo, PoolState, Position, PositionInfo, PositionRecord,
+    SwapAndAddResult, SwapContext, SwapResult, PreviewResult, RewardSchedule, TickInfo, TrackedReserves, VestingSchedule, WithdrawalQueueConfig, WithdrawalRequest, WithdrawalWindow,
+};
+use validate::ensure;
+use vesting::{release_vested_fees, restack_schedule};
+use zap::optimal_swap_amount;
 
 // Re-export for external use
-pub use storage::read_tick_info;
+pub use error::{PoolError, PoolErrorExt};
+pub use flash::{
+    FlashLoanReceiver, FlashLoanReceiverClient,
+};
+pub use storage::{read_tick_info, DataKey};
+pub use types::{CollectEvent, FeeTier, LiquidityEvent, MintParams, SwapEvent, SwapParams};
 
 // ============================================================
 // CONTRACT DEFINITION
@@ -51,6 +116,12 @@ impl BelugaSwap {
     // ========================================================
 
     /// Initialize the pool with configuration
+    /// 
+    /// `fee_bps` and `tick_spacing` must be the pair of a canonical
+    /// `FeeTier`; any other combination fails with `InvalidFeeTier`.
+    /// `current_tick` must be the tick `sqrt_price_x64` lies in, as given by
+    /// `get_tick_at_sqrt_ratio`, else `InvalidTick`.
+    /// Prefer `initialize_with_tier`.
     pub fn initialize(
         env: Env,
         admin: Address,
@@ -62,50 +133,113 @@ impl BelugaSwap {
         current_tick: i32,
         tick_spacing: i32,
     ) {
-        admin.require_auth();
+        init_pool(
+            &env, admin, token_a, token_b, FeeTier::from_params(fee_bps, tick_spacing),
+            fee_bps, protocol_fee_bps, sqrt_price_x64, current_tick, tick_spacing,
+        );
+    }
 
-        if is_initialized(&env) {
-            panic!("{}", ErrorMsg::ALREADY_INITIALIZED);
-        }
+    /// Initialize the pool with the fee and tick spacing of a canonical tier
+    /// 
+    /// Fails with `InvalidFeeTier` for `Custom`, and with `InvalidTick` as
+    /// `initialize` does.
+    pub fn initialize_with_tier(
+        env: Env,
+        admin: Address,
+        token_a: Address,
+        token_b: Address,
+        fee_tier: FeeTier,
+        protocol_fee_bps: u32,
+        sqrt_price_x64: u128,
+        current_tick: i32,
+    ) {
+        let (fee_bps, tick_spacing) = fee_tier
+            .params()
+            .unwrap_or_else(|| panic_with_error!(env, PoolError::InvalidFeeTier));
 
-        if fee_bps == 0 || fee_bps > MAX_FEE_BPS {
-            panic!("{}", ErrorMsg::INVALID_FEE);
-        }
+        init_pool(
+            &env, admin, token_a, token_b, fee_tier,
+            fee_bps, protocol_fee_bps, sqrt_price_x64, current_tick, tick_spacing,
+        );
+    }
 
-        if protocol_fee_bps > MAX_PROTOCOL_FEE_BPS {
-            panic!("{}", ErrorMsg::INVALID_PROTOCOL_FEE);
-        }
+    /// Initialize the pool at the price implied by reserve amounts
+    /// 
+    /// Same as `initialize`, with the starting price `amount_b / amount_a`
+    /// in smallest units, where `amount_a` is of `token_a` and `amount_b`
+    /// of `token_b` (either order). The sqrt price and tick are derived with
+    /// `sqrt_price_from_reserves`.
+    pub fn initialize_from_reserves(
+        env: Env,
+        admin: Address,
+        token_a: Address,
+        token_b: Address,
+        fee_bps: u32,
+        protocol_fee_bps: u32,
+        amount_a: i128,
+        amount_b: i128,
+        tick_spacing: i32,
+    ) {
+        // The pool stores the sorted pair, so the price is token1 per token0
+        let (amount0, amount1) = if token_a < token_b {
+            (amount_a, amount_b)
+        } else {
+            (amount_b, amount_a)
+        };
 
-        if tick_spacing <= 0 {
-            panic!("{}", ErrorMsg::INVALID_TICK_SPACING);
-        }
+        let sqrt_price_x64 = Self::sqrt_price_from_reserves(env.clone(), amount0, amount1);
+
+        init_pool(
+            &env, admin, token_a, token_b, FeeTier::from_params(fee_bps, tick_spacing),
+            fee_bps, protocol_fee_bps, sqrt_price_x64, get_tick_at_sqrt_ratio(&env, sqrt_price_x64),
+            tick_spacing,
+        );
+    }
 
-        // Validate tick is in valid range
-        if !is_valid_tick(current_tick) {
-            panic!("invalid initial tick");
+    /// Initialize the pool, or return its config if already initialized
+    /// with the same parameters
+    /// 
+    /// Safe for deployment scripts to retry. An existing pool matches if
+    /// admin, tokens (in either order), fee and tick spacing are identical
+    /// and its current tick is within one tick of the tick for
+    /// `sqrt_price_x64`. Anything else fails with `InitializationMismatch`
+    /// and changes nothing.
+    pub fn initialize_or_get(
+        env: Env,
+        admin: Address,
+        token_a: Address,
+        token_b: Address,
+        fee_bps: u32,
+        protocol_fee_bps: u32,
+        sqrt_price_x64: u128,
+        current_tick: i32,
+        tick_spacing: i32,
+    ) -> PoolConfig {
+        ensure(&env, validate::sqrt_price_in_bounds(sqrt_price_x64));
+
+        if !is_initialized(&env) {
+            Self::initialize(
+                env.clone(), admin, token_a, token_b, fee_bps, protocol_fee_bps,
+                sqrt_price_x64, current_tick, tick_spacing,
+            );
+            return read_pool_config(&env);
         }
 
-        // Sort tokens for consistent ordering
-        let (token0, token1) = if token_a < token_b {
-            (token_a.clone(), token_b.clone())
-        } else {
-            (token_b.clone(), token_a.clone())
-        };
+        let config = read_pool_config(&env);
+        let pool = read_pool_state(&env);
 
-        let config = PoolConfig {
-            admin,
-            token_a,
-            token_b,
-            fee_bps,
-            protocol_fee_bps,
-        };
-        write_pool_config(&env, &config);
+        let same_params = config.admin == admin
+            && (config.token_a.clone(), config.token_b.clone()) == sort_tokens(token_a, token_b)
+            && config.fee_bps == fee_bps
+            && pool.tick_spacing == tick_spacing;
+
+        let same_price = (get_tick_at_sqrt_ratio(&env, sqrt_price_x64) - pool.current_tick).abs() <= 1;
 
-        init_pool_state(&env, sqrt_price_x64, current_tick, tick_spacing, token0, token1);
-        set_initialized(&env);
+        if !same_params || !same_price {
+            panic_with_error!(env, PoolError::InitializationMismatch);
+        }
 
-        emit_pool_init(&env, sqrt_price_x64, current_tick, tick_spacing);
-        emit_initialized(&env, fee_bps, tick_spacing);
+        config
     }
 
     // ========================================================
@@ -117,6 +251,167 @@ impl BelugaSwap {
         read_pool_state(&env)
     }
 
+    /// Get pool configuration (admin, tokens, fees and fee tier)
+    pub fn get_pool_config(env: Env) -> PoolConfig {
+        read_pool_config(&env)
+    }
+
+    /// Whether `initialize` has been called on this contract (never panics)
+    pub fn is_initialized(env: Env) -> bool {
+        is_initialized(&env)
+    }
+
+    /// Get pool state, or None if the pool has not been initialized
+    /// 
+    /// Not named `try_get_pool_state`: the generated client already uses that
+    /// name for the fallible variant of `get_pool_state`.
+    pub fn get_pool_state_opt(env: Env) -> Option<PoolState> {
+        try_read_pool_state(&env)
+    }
+
+    /// Marker identifying this contract as a BelugaSwap pool
+    /// 
+    /// Returns a fixed symbol and the interface version. Works before
+    /// `initialize`, so integrators can probe any address cheaply: a contract
+    /// without this entrypoint fails the call.
+    pub fn belugaswap_pool_marker(env: Env) -> (Symbol, u32) {
+        (Symbol::new(&env, POOL_MARKER), POOL_INTERFACE_VERSION)
+    }
+
+    /// Get pool marker, tokens, fee and tick spacing
+    pub fn get_info(env: Env) -> PoolInfo {
+        let config = read_pool_config(&env);
+        let pool = read_pool_state(&env);
+        let (marker, interface_version) = Self::belugaswap_pool_marker(env);
+
+        PoolInfo {
+            marker,
+            interface_version,
+            token0: pool.token0,
+            token1: pool.token1,
+            fee_bps: config.fee_bps,
+            tick_spacing: pool.tick_spacing,
+        }
+    }
+
+    /// Get the pool state the swap engine reads, in one call
+    /// 
+    /// Covers `SWAP_CONTEXT_WORDS` tick bitmap words on each side of the
+    /// current tick, with the liquidity_net of every initialized tick in
+    /// them. An off-chain engine seeded from this replicates `engine_swap`
+    /// exactly for swaps that do not leave those words.
+    pub fn get_swap_context(env: Env) -> SwapContext {
+        let config = read_pool_config(&env);
+        let pool = read_pool_state(&env);
+
+        let center = word_position(pool.current_tick, pool.tick_spacing);
+        let mut bitmap_words = Vec::new(&env);
+        let mut ticks = Vec::new(&env);
+
+        for word_pos in (center - SWAP_CONTEXT_WORDS)..=(center + SWAP_CONTEXT_WORDS) {
+            let word = read_tick_bitmap_word(&env, word_pos);
+            bitmap_words.push_back((word_pos, word));
+
+            for tick in ticks_in_word(&env, word_pos, word, pool.tick_spacing).iter() {
+                ticks.push_back((tick, storage::read_tick_info(&env, tick).liquidity_net));
+            }
+        }
+
+        SwapContext {
+            sqrt_price_x64: pool.sqrt_price_x64,
+            current_tick: pool.current_tick,
+            liquidity: pool.liquidity,
+            tick_spacing: pool.tick_spacing,
+            fee_bps: config.fee_bps,
+            protocol_fee_bps: config.protocol_fee_bps,
+            bitmap_words,
+            ticks,
+        }
+    }
+
+    /// Get a raw tick bitmap word
+    /// 
+    /// Bit `b` of word `w` marks tick `(w * 128 + b) * tick_spacing` as
+    /// initialized. Word indices stay within -5386..=6931 (spacing 1),
+    /// which would fit an `i16`, but contract arguments have no 16-bit
+    /// integer type, so they are `i32` like the ticks they derive from and
+    /// the `TickBitmap` storage key.
+    pub fn get_bitmap_word(env: Env, word_index: i32) -> u128 {
+        read_tick_bitmap_word(&env, word_index)
+    }
+
+    /// Get the nonzero bitmap words among `count` words from `start`
+    /// 
+    /// Lets an indexer find every initialized tick without probing ticks
+    /// one by one. Scans at most `MAX_BITMAP_WORDS_PER_QUERY` words; page
+    /// through the full range with successive `start` values. Word indices
+    /// are `i32`, as in `get_bitmap_word`.
+    pub fn get_populated_words(env: Env, start: i32, count: u32) -> Vec<(i32, u128)> {
+        let end = start.saturating_add(count.min(MAX_BITMAP_WORDS_PER_QUERY) as i32);
+
+        let mut words = Vec::new(&env);
+        for word_index in start..end {
+            let word = read_tick_bitmap_word(&env, word_index);
+            if word != 0 {
+                words.push_back((word_index, word));
+            }
+        }
+
+        words
+    }
+
+    /// Initialized ticks in `[start_tick, end_tick]`, ascending, for
+    /// liquidity charts
+    /// 
+    /// Walks the tick bitmap, so every position's boundary ticks are
+    /// included. Stops after `max_results` ticks (capped at
+    /// `MAX_TICKS_PER_QUERY`) or `MAX_BITMAP_WORDS_PER_QUERY` bitmap words
+    /// and returns a cursor; call again with the cursor as `start_tick`
+    /// until it is None.
+    /// 
+    /// # Returns
+    /// ((tick, info) pairs, cursor)
+    pub fn get_ticks_in_range(
+        env: Env,
+        start_tick: i32,
+        end_tick: i32,
+        max_results: u32,
+    ) -> (Vec<(i32, TickInfo)>, Option<i32>) {
+        if start_tick > end_tick {
+            panic_with_error!(env, PoolError::InvalidTickRange);
+        }
+
+        let tick_spacing = read_pool_state(&env).tick_spacing;
+        let limit = max_results.min(MAX_TICKS_PER_QUERY);
+
+        let mut ticks = Vec::new(&env);
+        let last_word = word_position(end_tick, tick_spacing);
+        let mut word_pos = word_position(start_tick, tick_spacing);
+        let mut words_scanned = 0;
+
+        while word_pos <= last_word {
+            if words_scanned == MAX_BITMAP_WORDS_PER_QUERY {
+                return (ticks, Some(word_start_tick(word_pos, tick_spacing)));
+            }
+            words_scanned += 1;
+
+            let word = read_tick_bitmap_word(&env, word_pos);
+            for tick in ticks_in_word(&env, word_pos, word, tick_spacing).iter() {
+                if tick < start_tick || tick > end_tick {
+                    continue;
+                }
+                if ticks.len() == limit {
+                    return (ticks, Some(tick));
+                }
+                ticks.push_back((tick, storage::read_tick_info(&env, tick)));
+            }
+
+            word_pos += 1;
+        }
+
+        (ticks, None)
+    }
+
     /// Get tick info
     pub fn get_tick_info(env: Env, tick: i32) -> TickInfo {
         storage::read_tick_info(&env, tick)
@@ -124,40 +419,429 @@ impl BelugaSwap {
 
     /// Get position info with pending fees
     pub fn get_position(env: Env, owner: Address, lower: i32, upper: i32) -> PositionInfo {
+        ensure(&env, validate::tick_range(lower, upper));
+
+        let pos = read_position(&env, &owner, lower, upper);
+
+        position_info(&env, &read_pool_state(&env), &pos, lower, upper)
+    }
+
+    /// Get an id-based position with pending fees
+    pub fn get_position_by_id(env: Env, position_id: u64) -> PositionInfo {
+        let record = load_position_record(&env, position_id);
+
+        position_info(
+            &env, &read_pool_state(&env), &record.position, record.lower_tick, record.upper_tick,
+        )
+    }
+
+    /// Get the owner, range and raw state of an id-based position
+    pub fn get_position_record(env: Env, position_id: u64) -> Option<PositionRecord> {
+        read_position_record(&env, position_id)
+    }
+
+    /// Ranges of every non-empty range-keyed position of `owner`, oldest
+    /// first
+    /// 
+    /// Same order as `get_positions`, without reading the positions.
+    pub fn list_positions(env: Env, owner: Address) -> Vec<(i32, i32)> {
+        read_owner_positions(&env, &owner)
+    }
+
+    /// Every non-empty range-keyed position of `owner`, oldest first
+    /// 
+    /// Returns (lower, upper, info) per position, with info as from
+    /// `get_position`. Positions leave the list once they hold no liquidity
+    /// and no owed fees. Positions untouched since this index was added are
+    /// listed after their next write. Owners with many positions should use
+    /// `get_positions_page`.
+    pub fn get_positions(env: Env, owner: Address) -> Vec<(i32, i32, PositionInfo)> {
+        let ranges = read_owner_positions(&env, &owner);
+        owner_positions(&env, &owner, &ranges)
+    }
+
+    /// Up to `limit` of `owner`'s positions starting at index `start` of
+    /// `get_positions`
+    /// 
+    /// `limit` is capped at `MAX_POSITIONS_PER_QUERY`; a page shorter than
+    /// the limit is the last one.
+    pub fn get_positions_page(
+        env: Env,
+        owner: Address,
+        start: u32,
+        limit: u32,
+    ) -> Vec<(i32, i32, PositionInfo)> {
+        let ranges = read_owner_positions(&env, &owner);
+        let start = start.min(ranges.len());
+        let end = start.saturating_add(limit.min(MAX_POSITIONS_PER_QUERY)).min(ranges.len());
+
+        owner_positions(&env, &owner, &ranges.slice(start..end))
+    }
+
+    /// Principal a position would hold if the price moved to `hypothetical_tick`
+    /// 
+    /// Returns (amount0, amount1) at that tick's sqrt price; owed fees are
+    /// not included and nothing is written.
+    pub fn get_position_value_at_tick(
+        env: Env,
+        owner: Address,
+        lower: i32,
+        upper: i32,
+        hypothetical_tick: i32,
+    ) -> (i128, i128) {
+        ensure(&env, validate::tick_range(lower, upper));
+
+        if !is_valid_tick(hypothetical_tick) {
+            panic_with_error!(env, PoolError::InvalidTick);
+        }
+
+        let pos = read_position(&env, &owner, lower, upper);
+
+        if !has_liquidity(&pos) {
+            return (0, 0);
+        }
+
+        get_amounts_for_liquidity(
+            &env, pos.liquidity, get_sqrt_ratio_at_tick(&env, lower), get_sqrt_ratio_at_tick(&env, upper),
+            get_sqrt_ratio_at_tick(&env, hypothetical_tick),
+        )
+    }
+
+    /// Sqrt price the pool must reach for `[lower, upper]` to start earning
+    /// 
+    /// Below the range this is the lower boundary and the price must rise;
+    /// at or above the upper tick it is the upper boundary and the price
+    /// must fall. A range that is already active returns the current sqrt
+    /// price with `false`.
+    /// 
+    /// # Returns
+    /// (target_sqrt_price_x64, price_must_rise)
+    pub fn price_to_activate(env: Env, lower: i32, upper: i32) -> (u128, bool) {
+        ensure(&env, validate::tick_range(lower, upper));
+
+        let pool = read_pool_state(&env);
+
+        if pool.current_tick < lower {
+            (get_sqrt_ratio_at_tick(&env, lower), true)
+        } else if pool.current_tick >= upper {
+            (get_sqrt_ratio_at_tick(&env, upper), false)
+        } else {
+            (pool.sqrt_price_x64, false)
+        }
+    }
+
+    /// Token amounts a deposit of `liquidity` into `[lower, upper]` costs at
+    /// the current price
+    /// 
+    /// Ticks must be aligned to the spacing and amounts are rounded up,
+    /// exactly as `add_liquidity_advanced` charges them. The deposit fee, if
+    /// any, is charged on top.
+    /// 
+    /// # Returns
+    /// (amount0, amount1)
+    pub fn calc_amounts_for_liquidity(env: Env, liquidity: i128, lower: i32, upper: i32) -> (i128, i128) {
+        ensure(&env, validate::tick_range(lower, upper));
+
+        let pool = read_pool_state(&env);
+        let (lower, upper) = aligned_range(&env, lower, upper, pool.tick_spacing);
+
+        get_amounts_for_liquidity_rounding(
+            &env, liquidity, get_sqrt_ratio_at_tick(&env, lower), get_sqrt_ratio_at_tick(&env, upper),
+            pool.sqrt_price_x64, true,
+        )
+    }
+
+    /// Liquidity `add_liquidity_advanced` would credit for the desired
+    /// token0/token1 amounts at the current price
+    /// 
+    /// Accounts for the deposit fee reserved out of the desired amounts. A result below `MIN_LIQUIDITY` would be rejected
+    /// by the deposit.
+    pub fn calc_liquidity_for_amounts(
+        env: Env,
+        amount0_desired: i128,
+        amount1_desired: i128,
+        lower: i32,
+        upper: i32,
+    ) -> i128 {
+        ensure(&env, validate::tick_range(lower, upper));
+        ensure(&env, validate::amount_non_negative(amount0_desired));
+        ensure(&env, validate::amount_non_negative(amount1_desired));
+        ensure(&env, validate::amount_below_cap(amount0_desired));
+        ensure(&env, validate::amount_below_cap(amount1_desired));
+
+        let config = read_pool_config(&env);
+        let pool = read_pool_state(&env);
+        let (lower, upper) = aligned_range(&env, lower, upper, pool.tick_spacing);
+
+        liquidity_for_deposit(
+            &env, &config, &pool, get_sqrt_ratio_at_tick(&env, lower), get_sqrt_ratio_at_tick(&env, upper),
+            amount0_desired, amount1_desired,
+        )
+    }
+
+    /// Value of a position versus holding its tokens if the price moved to
+    /// `hypothetical_tick`
+    /// 
+    /// Positions do not record their original deposit, so "holding" means
+    /// keeping the tokens the position holds at the current price. Both
+    /// sides are valued in token1 at the hypothetical price; negative is
+    /// impermanent loss. Fees are not included.
+    pub fn get_impermanent_delta(
+        env: Env,
+        owner: Address,
+        lower: i32,
+        upper: i32,
+        hypothetical_tick: i32,
+    ) -> i128 {
+        ensure(&env, validate::tick_range(lower, upper));
+
+        if !is_valid_tick(hypothetical_tick) {
+            panic_with_error!(env, PoolError::InvalidTick);
+        }
+
+        let pos = read_position(&env, &owner, lower, upper);
+        let pool = read_pool_state(&env);
+
+        impermanent_delta(
+            &env,
+            get_sqrt_ratio_at_tick(&env, lower),
+            get_sqrt_ratio_at_tick(&env, upper),
+            pool.sqrt_price_x64,
+            get_sqrt_ratio_at_tick(&env, hypothetical_tick),
+            pos.liquidity,
+        )
+    }
+
+    /// Tick cumulatives as of each of `seconds_agos` seconds ago
+    /// 
+    /// Interpolated between observations (and extrapolated past the newest
+    /// one) like Uniswap V3's `observe`, so the arithmetic-mean tick over a
+    /// window is `(cumulatives[i] - cumulatives[j]) / (agos[j] - agos[i])`.
+    /// Fails with `ObservationTooOld` if any point predates the oracle
+    /// history.
+    pub fn observe(env: Env, seconds_agos: Vec<u32>) -> Vec<i64> {
+        let pool = read_pool_state(&env);
+        let now = env.ledger().timestamp();
+
+        let mut cumulatives = Vec::new(&env);
+        for seconds_ago in seconds_agos.iter() {
+            let target = now
+                .checked_sub(seconds_ago as u64)
+                .unwrap_or_else(|| panic_with_error!(env, PoolError::ObservationTooOld));
+
+            // Wraps like the i56 cumulatives of Uniswap V3; only differences
+            // between two values are meaningful
+            cumulatives.push_back(observe_at(&env, &pool, target).tick_cumulative as i64);
+        }
+
+        cumulatives
+    }
+
+    /// Arithmetic-mean tick over the last `seconds_ago` seconds
+    /// 
+    /// Derived from the tick cumulative, so a single-ledger price spike only
+    /// moves it in proportion to how long it lasted. Rounds toward negative
+    /// infinity. `seconds_ago = 0` returns the current tick. Fails with
+    /// `ObservationTooOld` if the window predates the oracle history.
+    pub fn get_mean_tick(env: Env, seconds_ago: u64) -> i32 {
+        let pool = read_pool_state(&env);
+
+        if seconds_ago == 0 {
+            return pool.current_tick;
+        }
+
+        let now = env.ledger().timestamp();
+        let then = now
+            .checked_sub(seconds_ago)
+            .unwrap_or_else(|| panic_with_error!(env, PoolError::ObservationTooOld));
+
+        let delta = observe_at(&env, &pool, now).tick_cumulative
+            .wrapping_sub(observe_at(&env, &pool, then).tick_cumulative);
+
+        delta.div_euclid(seconds_ago as i128) as i32
+    }
+
+    /// Estimate a position's value `seconds_ago` from the oracle history
+    /// 
+    /// Returns (amount0, amount1): principal at the historical sqrt price plus
+    /// fees accrued by then. Assumes the position's liquidity has not changed
+    /// since; the fee part subtracts global fee growth since then from what
+    /// is owed now, so it is only an estimate (exact while the position
+    /// stayed in range and nothing was collected in between).
+    pub fn get_position_value_at(
+        env: Env,
+        owner: Address,
+        lower: i32,
+        upper: i32,
+        seconds_ago: u64,
+    ) -> (i128, i128) {
+        ensure(&env, validate::tick_range(lower, upper));
+
         let pos = read_position(&env, &owner, lower, upper);
         let pool = read_pool_state(&env);
 
+        let target = env.ledger().timestamp()
+            .checked_sub(seconds_ago)
+            .unwrap_or_else(|| panic_with_error!(env, PoolError::ObservationTooOld));
+        let obs = observe_at(&env, &pool, target);
+
         if !has_liquidity(&pos) {
-            return PositionInfo {
-                liquidity: 0,
-                amount0: 0,
-                amount1: 0,
-                fees_owed_0: pos.tokens_owed_0,
-                fees_owed_1: pos.tokens_owed_1,
-            };
+            return (0, 0);
         }
 
-        let sqrt_lower = get_sqrt_ratio_at_tick(lower);
-        let sqrt_upper = get_sqrt_ratio_at_tick(upper);
+        let sqrt_lower = get_sqrt_ratio_at_tick(&env, lower);
+        let sqrt_upper = get_sqrt_ratio_at_tick(&env, upper);
 
         let (amount0, amount1) = get_amounts_for_liquidity(
-            &env, pos.liquidity, sqrt_lower, sqrt_upper, pool.sqrt_price_x64,
+            &env, pos.liquidity, sqrt_lower, sqrt_upper, obs.sqrt_price_x64,
         );
 
         let (inside_0, inside_1) = get_fee_growth_inside(
             &env, lower, upper, pool.current_tick,
             pool.fee_growth_global_0, pool.fee_growth_global_1,
         );
-
         let (pending_0, pending_1) = calculate_pending_fees(&pos, inside_0, inside_1);
 
-        PositionInfo {
-            liquidity: pos.liquidity,
-            amount0,
-            amount1,
-            fees_owed_0: pos.tokens_owed_0.saturating_add(pending_0),
-            fees_owed_1: pos.tokens_owed_1.saturating_add(pending_1),
+        let fees_now_0 = pos.tokens_owed_0.saturating_add(pending_0);
+        let fees_now_1 = pos.tokens_owed_1.saturating_add(pending_1);
+
+        let fees_0 = fees_now_0.saturating_sub(fees_earned_between(
+            pos.liquidity, obs.fee_growth_global_0, pool.fee_growth_global_0,
+        ));
+        let fees_1 = fees_now_1.saturating_sub(fees_earned_between(
+            pos.liquidity, obs.fee_growth_global_1, pool.fee_growth_global_1,
+        ));
+
+        (
+            amount0.saturating_add(fees_0.min(i128::MAX as u128) as i128),
+            amount1.saturating_add(fees_1.min(i128::MAX as u128) as i128),
+        )
+    }
+
+    /// Fee yield needed to break even against impermanent loss
+    /// 
+    /// Estimates the loss of a range `[lower, upper)` at the current price
+    /// for a price move of `volatility_bps` in either direction, as a Q64.64
+    /// fraction of the deposit's value (2^64 = 100%). Compare against
+    /// expected fee income over the same horizon; narrower ranges need more.
+    pub fn break_even_fees(env: Env, lower: i32, upper: i32, volatility_bps: u32) -> u128 {
+        ensure(&env, validate::tick_range(lower, upper));
+
+        let pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower, upper, pool.tick_spacing);
+
+        break_even_fees_x64(
+            &env,
+            get_sqrt_ratio_at_tick(&env, lower),
+            get_sqrt_ratio_at_tick(&env, upper),
+            pool.sqrt_price_x64,
+            volatility_bps,
+        )
+    }
+
+    /// Tick for a human-readable price, accounting for token decimals
+    /// 
+    /// `human_price_num / human_price_den` is the price of one whole token0
+    /// in whole token1, where token0/token1 are the pool's sorted tokens
+    /// (lower address first) and `decimals_a`/`decimals_b` their decimals.
+    /// Returns the greatest tick at or below that price. Use it (and
+    /// `get_sqrt_ratio_at_tick` of the result) to pick `initialize` inputs;
+    /// the raw tick differs from the human one by 10^(decimals_b - decimals_a).
+    pub fn tick_for_price(
+        env: Env,
+        human_price_num: u128,
+        human_price_den: u128,
+        decimals_a: u32,
+        decimals_b: u32,
+    ) -> i32 {
+        if human_price_num == 0 || human_price_den == 0 {
+            panic_with_error!(env, PoolError::InvalidPrice);
+        }
+
+        if decimals_a > MAX_TOKEN_DECIMALS || decimals_b > MAX_TOKEN_DECIMALS {
+            panic_with_error!(env, PoolError::InvalidDecimals);
+        }
+
+        // Raw price in smallest units: human * 10^decimals_b / 10^decimals_a
+        let ten = U256::from_u32(&env, 10);
+        let raw_num = U256::from_u128(&env, human_price_num).mul(&ten.pow(decimals_b));
+        let raw_den = U256::from_u128(&env, human_price_den).mul(&ten.pow(decimals_a));
+
+        get_tick_at_sqrt_ratio(&env, get_sqrt_price_for_ratio(&env, &raw_num, &raw_den))
+    }
+
+    /// Q64.64 sqrt price for reserves of `amount0` token0 and `amount1`
+    /// token1, i.e. sqrt(amount1 / amount0) * 2^64 rounded down
+    /// 
+    /// Token0/token1 are the pool's sorted tokens (lower address first) and
+    /// the amounts are in smallest units. Fails with `SqrtPriceOutOfBounds`
+    /// if the ratio is outside the tick range.
+    pub fn sqrt_price_from_reserves(env: Env, amount0: i128, amount1: i128) -> u128 {
+        ensure(&env, validate::amount_positive(amount0));
+        ensure(&env, validate::amount_positive(amount1));
+
+        let sqrt_price_x64 = get_sqrt_price_for_ratio(
+            &env,
+            &U256::from_u128(&env, amount1 as u128),
+            &U256::from_u128(&env, amount0 as u128),
+        );
+        ensure(&env, validate::sqrt_price_in_bounds(sqrt_price_x64));
+
+        sqrt_price_x64
+    }
+
+    /// Human-readable price for a sqrt price, accounting for token decimals
+    /// 
+    /// Inverse of `tick_for_price`: returns `(numerator, denominator)` of the
+    /// price of one whole token0 in whole token1, with `decimals_a` and
+    /// `decimals_b` the decimals of the pool's sorted token0 and token1.
+    /// Both terms fit in i128 and are not reduced; divide off-chain. For a
+    /// tick, pass `get_sqrt_ratio_at_tick` of it.
+    pub fn price_from_sqrt(
+        env: Env,
+        sqrt_price_x64: u128,
+        decimals_a: u32,
+        decimals_b: u32,
+    ) -> (i128, i128) {
+        ensure(&env, validate::sqrt_price_in_bounds(sqrt_price_x64));
+
+        if decimals_a > MAX_TOKEN_DECIMALS || decimals_b > MAX_TOKEN_DECIMALS {
+            panic_with_error!(env, PoolError::InvalidDecimals);
         }
+
+        // Human price in whole units: raw * 10^decimals_a / 10^decimals_b
+        let (scale_num, scale_den) = if decimals_a >= decimals_b {
+            (10u128.pow(decimals_a - decimals_b), 1)
+        } else {
+            (1, 10u128.pow(decimals_b - decimals_a))
+        };
+
+        get_ratio_for_sqrt_price(&env, sqrt_price_x64, scale_num, scale_den)
+    }
+
+    /// Get the spacing-aligned ticks just below and above a sqrt price
+    /// Returns (tick, tick) when the price is exactly on a valid tick
+    pub fn nearest_valid_ticks(env: Env, sqrt_price_x64: u128) -> (i32, i32) {
+        ensure(&env, validate::sqrt_price_in_bounds(sqrt_price_x64));
+
+        let pool = read_pool_state(&env);
+        nearest_valid_ticks(&env, sqrt_price_x64, pool.tick_spacing)
+    }
+
+    /// Spacing-aligned tick range for a named preset around the current tick
+    /// 
+    /// Presets: 0 = narrow (±5%), 1 = medium (±15%), 2 = wide (±50%) of
+    /// price. The range is symmetric around the aligned tick holding the
+    /// current price and always brackets it.
+    pub fn preset_range(env: Env, preset: u32) -> (i32, i32) {
+        let tick_delta = match RANGE_PRESET_TICK_DELTAS.get(preset as usize) {
+            Some(delta) => *delta,
+            None => panic_with_error!(env, PoolError::InvalidRangePreset),
+        };
+
+        let pool = read_pool_state(&env);
+        preset_range(&env, pool.current_tick, pool.tick_spacing, tick_delta)
     }
 
     /// Get swap direction for a given input token
@@ -171,6 +855,10 @@ impl BelugaSwap {
     // ========================================================
 
     /// Swap tokens with automatic direction detection
+    /// 
+    /// `deadline` is a ledger timestamp after which the call reverts (0 = no deadline).
+    /// Reverts with `IncompleteSwap` if liquidity runs out before the amount is
+    /// filled; use `swap_with_params` to accept partial fills.
     pub fn swap(
         env: Env,
         caller: Address,
@@ -179,22 +867,23 @@ impl BelugaSwap {
         amount_in: i128,
         min_amount_out: i128,
         sqrt_price_limit_x64: u128,
+        deadline: u64,
     ) -> SwapResult {
         let pool = read_pool_state(&env);
 
         if token_in != pool.token0 && token_in != pool.token1 {
-            panic!("{}", ErrorMsg::INVALID_TOKEN);
+            panic_with_error!(env, PoolError::InvalidToken);
         }
         if token_out != pool.token0 && token_out != pool.token1 {
-            panic!("{}", ErrorMsg::INVALID_TOKEN);
+            panic_with_error!(env, PoolError::InvalidToken);
         }
         if token_in == token_out {
-            panic!("{}", ErrorMsg::SAME_TOKEN);
+            panic_with_error!(env, PoolError::SameToken);
         }
 
         let zero_for_one = token_in == pool.token0;
 
-        Self::swap_advanced(env, caller, amount_in, min_amount_out, zero_for_one, sqrt_price_limit_x64)
+        Self::swap_advanced(env, caller, amount_in, min_amount_out, 0, zero_for_one, sqrt_price_limit_x64, deadline)
     }
 
     /// Preview swap with automatic direction detection
@@ -235,56 +924,225 @@ impl BelugaSwap {
     }
 
     /// Swap with manual direction control
+    /// 
+    /// A positive `amount_specified` is an exact-input swap. A negative one
+    /// requests exactly `-amount_specified` of the output token and charges
+    /// whatever input is needed; `min_amount_out` still guards the delivered
+    /// amount when the price limit stops the swap early. A positive
+    /// `max_amount_in` reverts with `SlippageExceeded` if the input charged,
+    /// fee included, would exceed it (0 = no limit). Running out of
+    /// liquidity before the price limit reverts with `IncompleteSwap`.
     pub fn swap_advanced(
         env: Env,
         caller: Address,
         amount_specified: i128,
         min_amount_out: i128,
+        max_amount_in: i128,
         zero_for_one: bool,
         sqrt_price_limit_x64: u128,
+        deadline: u64,
     ) -> SwapResult {
-        caller.require_auth();
+        let params = SwapParams::new(amount_specified, zero_for_one)
+            .with_min_amount_out(min_amount_out)
+            .with_max_amount_in(max_amount_in)
+            .with_sqrt_price_limit(sqrt_price_limit_x64)
+            .with_deadline(deadline);
 
-        let config = read_pool_config(&env);
-        let mut pool = read_pool_state(&env);
+        Self::swap_with_params(env, caller, params)
+    }
 
-        let fee_bps = config.fee_bps as i128;
-        let protocol_fee_bps = config.protocol_fee_bps as i128;
+    /// Swap described by a `SwapParams` struct
+    /// 
+    /// `caller` pays the input; the output goes to `params.recipient`
+    /// (defaults to `caller`). Zeroed optional fields behave exactly like
+    /// `swap_advanced` with the same values. With `params.allow_partial`, a
+    /// swap that runs out of liquidity fills what it can and reports the
+    /// rest as `amount_remaining`.
+    pub fn swap_with_params(env: Env, caller: Address, params: SwapParams) -> SwapResult {
+        let SwapParams {
+            amount_specified,
+            zero_for_one,
+            min_amount_out,
+            max_amount_in,
+            sqrt_price_limit_x64,
+            deadline,
+            recipient,
+            allow_partial,
+        } = params;
+        let recipient = recipient.unwrap_or_else(|| caller.clone());
+
+        ensure(&env, validate::amount_below_cap(amount_specified));
+        ensure(&env, validate::amount_non_negative(min_amount_out));
+        ensure(&env, validate::amount_non_negative(max_amount_in));
+        ensure(&env, validate::address_not_self(&env, &caller));
+        ensure(&env, validate::address_not_self(&env, &recipient));
 
-        let validation = validate_and_preview_swap(
-            &env, &pool, amount_specified, min_amount_out,
-            zero_for_one, sqrt_price_limit_x64, fee_bps,
-        );
+        caller.require_auth();
+        check_deadline(&env, deadline);
 
-        if let Err(e) = validation {
-            panic!("{}: {:?}", ErrorMsg::SWAP_VALIDATION_FAILED, e);
+        execute_swap(
+            &env, &caller, &recipient, amount_specified, zero_for_one,
+            min_amount_out, max_amount_in, sqrt_price_limit_x64, allow_partial,
+        )
+    }
+
+    /// Exact-input swap routed through a chain of BelugaSwap pools
+    /// 
+    /// Each pool in `pools` swaps the previous leg's output, starting from
+    /// `amount_in` of `token_in`; the direction of every leg follows from
+    /// the token coming in. Intermediate outputs go to `caller` and are
+    /// spent by the next leg within the same call, so `caller` only needs
+    /// to authorize this invocation. `min_amount_out` applies to the final
+    /// leg only. This pool may appear in the route; other pools are called
+    /// through their `swap`.
+    /// 
+    /// # Returns
+    /// One `SwapResult` per leg, in route order
+    pub fn swap_exact_in_multi(
+        env: Env,
+        caller: Address,
+        pools: Vec<Address>,
+        token_in: Address,
+        amount_in: i128,
+        min_amount_out: i128,
+        deadline: u64,
+    ) -> Vec<SwapResult> {
+        if pools.is_empty() || pools.len() > MAX_ROUTE_HOPS {
+            panic_with_error!(env, PoolError::InvalidRoute);
         }
+        ensure(&env, validate::amount_positive(amount_in));
+        ensure(&env, validate::amount_below_cap(amount_in));
+        ensure(&env, validate::amount_non_negative(min_amount_out));
+        ensure(&env, validate::address_not_self(&env, &caller));
 
-        let (amount_in_total, amount_out_total) = engine_swap(
-            &env, &mut pool, amount_specified, zero_for_one,
-            sqrt_price_limit_x64, fee_bps, protocol_fee_bps,
-        );
+        caller.require_auth();
+        check_deadline(&env, deadline);
 
-        write_pool_state(&env, &pool);
+        let this = env.current_contract_address();
+        let mut token = token_in;
+        let mut amount = amount_in;
+        let mut results = Vec::new(&env);
 
-        let pool_addr = env.current_contract_address();
+        for (i, pool_addr) in pools.iter().enumerate() {
+            let leg_min_out = if i as u32 + 1 == pools.len() { min_amount_out } else { 0 };
 
-        // Transfer tokens
-        if zero_for_one {
-            token::Client::new(&env, &pool.token0).transfer(&caller, &pool_addr, &amount_in_total);
-            token::Client::new(&env, &pool.token1).transfer(&pool_addr, &caller, &amount_out_total);
-        } else {
-            token::Client::new(&env, &pool.token1).transfer(&caller, &pool_addr, &amount_in_total);
-            token::Client::new(&env, &pool.token0).transfer(&pool_addr, &caller, &amount_out_total);
+            let result = if pool_addr == this {
+                let pool = read_pool_state(&env);
+                let (zero_for_one, token_out) = route_leg(&env, &pool, &token);
+                token = token_out;
+                execute_swap(&env, &caller, &caller, amount, zero_for_one, leg_min_out, 0, 0, false)
+            } else {
+                let client = BelugaSwapClient::new(&env, &pool_addr);
+                let (_, token_out) = route_leg(&env, &client.get_pool_state(), &token);
+                let result = client.swap(&caller, &token, &token_out, &amount, &leg_min_out, &0, &deadline);
+                token = token_out;
+                result
+            };
+
+            amount = result.amount_out;
+            results.push_back(result);
         }
 
-        emit_swap(&env, amount_in_total, amount_out_total, zero_for_one);
+        results
+    }
+
+    /// Enter a position with a single token
+    /// 
+    /// Swaps the share of `amount_in` that balances the remainder against
+    /// the swap output for `[lower_tick, upper_tick]` at the post-swap
+    /// price, then mints a position for `owner` with both. `amount_min_out`
+    /// applies to the swap leg. Whatever the mint cannot use (rounding and
+    /// the bisection tolerance) never leaves the owner and is reported as
+    /// the refund.
+    pub fn swap_and_add(
+        env: Env,
+        owner: Address,
+        lower_tick: i32,
+        upper_tick: i32,
+        token_in_is_zero: bool,
+        amount_in: i128,
+        amount_min_out: i128,
+        deadline: u64,
+    ) -> SwapAndAddResult {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+        ensure(&env, validate::amount_positive(amount_in));
+        ensure(&env, validate::amount_below_cap(amount_in));
+        ensure(&env, validate::amount_non_negative(amount_min_out));
+        ensure(&env, validate::address_not_self(&env, &owner));
+
+        owner.require_auth();
+        check_deadline(&env, deadline);
+
+        let config = read_pool_config(&env);
+        let pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        let swap_amount = optimal_swap_amount(
+            &env, &pool, get_sqrt_ratio_at_tick(&env, lower), get_sqrt_ratio_at_tick(&env, upper),
+            token_in_is_zero, amount_in, config.fee_bps, config.protocol_fee_bps,
+        );
+
+        let (swapped_in, swapped_out) = if swap_amount > 0 {
+            let result = execute_swap(
+                &env, &owner, &owner, swap_amount, token_in_is_zero, amount_min_out, 0, 0, true,
+            );
+            (result.amount_in, result.amount_out)
+        } else {
+            (0, 0)
+        };
+
+        let (available0, available1) = if token_in_is_zero {
+            (amount_in - swapped_in, swapped_out)
+        } else {
+            (swapped_out, amount_in - swapped_in)
+        };
+
+        let mut pos = read_position(&env, &owner, lower, upper);
+        let (liquidity, amount0, amount1) = deposit_liquidity(
+            &env, &owner, &mut pos, lower, upper, available0, available1, 0, 0,
+        );
+        write_position(&env, &owner, lower, upper, &pos);
+
+        SwapAndAddResult {
+            liquidity,
+            amount0,
+            amount1,
+            refund0: available0 - amount0 - apply_bps_ceil(&env, amount0, config.deposit_fee_bps),
+            refund1: available1 - amount1 - apply_bps_ceil(&env, amount1, config.deposit_fee_bps),
+        }
+    }
+
+    /// Quote a swap without executing it
+    /// 
+    /// Runs the swap engine against a copy of the pool, so the result matches
+    /// `swap_advanced` with the same inputs exactly. No auth, no transfers and
+    /// no state writes.
+    pub fn quote_swap(
+        env: Env,
+        amount_specified: i128,
+        zero_for_one: bool,
+        sqrt_price_limit_x64: u128,
+    ) -> SwapResult {
+        ensure(&env, validate::amount_below_cap(amount_specified));
+
+        let config = read_pool_config(&env);
+        let pool = read_pool_state(&env);
+
+        let (outcome, sim_pool) = simulate_swap(
+            &env, &pool, amount_specified, zero_for_one,
+            sqrt_price_limit_x64, config.fee_bps, config.protocol_fee_bps,
+        );
 
         SwapResult {
-            amount_in: amount_in_total,
-            amount_out: amount_out_total,
-            current_tick: pool.current_tick,
-            sqrt_price_x64: pool.sqrt_price_x64,
+            amount_in: outcome.amount_in,
+            amount_out: outcome.amount_out,
+            fee_amount: outcome.fee,
+            ticks_crossed: outcome.ticks_crossed,
+            amount_remaining: outcome.amount_remaining,
+            current_tick: sim_pool.current_tick,
+            sqrt_price_x64: sim_pool.sqrt_price_x64,
+            fully_filled: is_fully_filled(amount_specified, outcome.amount_in, outcome.amount_out),
         }
     }
 
@@ -298,10 +1156,10 @@ impl BelugaSwap {
     ) -> PreviewResult {
         let config = read_pool_config(&env);
         let pool = read_pool_state(&env);
-        let fee_bps = config.fee_bps as i128;
+        let fee_bps = config.fee_bps;
 
         let validation = validate_and_preview_swap(
-            &env, &pool, amount_specified, min_amount_out,
+            &env, &pool, amount_specified, min_amount_out, 0,
             zero_for_one, sqrt_price_limit_x64, fee_bps,
         );
 
@@ -359,6 +1217,12 @@ impl BelugaSwap {
     // ========================================================
 
     /// Add liquidity with automatic token ordering
+    /// 
+    /// Ticks must be aligned to the spacing, as for `add_liquidity_advanced`.
+    /// 
+    /// # Returns
+    /// (liquidity, amount_a_used, amount_b_used) with amounts in the order
+    /// `token_a`/`token_b` were passed
     pub fn add_liquidity(
         env: Env,
         owner: Address,
@@ -370,27 +1234,62 @@ impl BelugaSwap {
         amount_b_min: i128,
         lower_tick: i32,
         upper_tick: i32,
+        deadline: u64,
     ) -> (i128, i128, i128) {
         let pool = read_pool_state(&env);
 
         if (token_a != pool.token0 && token_a != pool.token1) || 
            (token_b != pool.token0 && token_b != pool.token1) {
-            panic!("{}", ErrorMsg::INVALID_TOKEN);
+            panic_with_error!(env, PoolError::InvalidToken);
         }
         if token_a == token_b {
-            panic!("{}", ErrorMsg::SAME_TOKEN);
+            panic_with_error!(env, PoolError::SameToken);
         }
 
+        let a_is_token0 = token_a == pool.token0;
         let (amount0_desired, amount1_desired, amount0_min, amount1_min) = 
-            if token_a == pool.token0 {
+            if a_is_token0 {
                 (amount_a_desired, amount_b_desired, amount_a_min, amount_b_min)
             } else {
                 (amount_b_desired, amount_a_desired, amount_b_min, amount_a_min)
             };
 
+        let (liquidity, amount0, amount1) = Self::add_liquidity_advanced(
+            env, owner, lower_tick, upper_tick,
+            amount0_desired, amount1_desired, amount0_min, amount1_min, deadline,
+        );
+
+        if a_is_token0 {
+            (liquidity, amount0, amount1)
+        } else {
+            (liquidity, amount1, amount0)
+        }
+    }
+
+    /// Add liquidity from desired amounts of the pool's token_a/token_b
+    /// 
+    /// `token_a`/`token_b` are the pool's sorted tokens, so amounts are in
+    /// token0/token1 order whatever order `initialize` was given. Liquidity
+    /// is the largest amount both sides can fund at the current price; only
+    /// the amounts it requires are transferred, and the `_min` bounds guard
+    /// against the price moving before execution.
+    /// 
+    /// # Returns
+    /// (liquidity, amount_a_used, amount_b_used)
+    pub fn add_liquidity_from_amounts(
+        env: Env,
+        owner: Address,
+        lower_tick: i32,
+        upper_tick: i32,
+        amount_a_desired: i128,
+        amount_b_desired: i128,
+        amount_a_min: i128,
+        amount_b_min: i128,
+        deadline: u64,
+    ) -> (i128, i128, i128) {
         Self::add_liquidity_advanced(
             env, owner, lower_tick, upper_tick,
-            amount0_desired, amount1_desired, amount0_min, amount1_min,
+            amount_a_desired, amount_b_desired, amount_a_min, amount_b_min, deadline,
         )
     }
 
@@ -404,73 +1303,114 @@ impl BelugaSwap {
         amount1_desired: i128,
         amount0_min: i128,
         amount1_min: i128,
+        deadline: u64,
     ) -> (i128, i128, i128) {
-        owner.require_auth();
+        let params = MintParams::new(lower_tick, upper_tick, amount0_desired, amount1_desired)
+            .with_min_amounts(amount0_min, amount1_min)
+            .with_deadline(deadline);
 
-        let mut pool = read_pool_state(&env);
-        let pool_addr = env.current_contract_address();
+        Self::mint_with_params(env, owner, params)
+    }
 
-        let lower = snap_tick_to_spacing(lower_tick, pool.tick_spacing);
-        let upper = snap_tick_to_spacing(upper_tick, pool.tick_spacing);
+    /// Add liquidity described by a `MintParams` struct
+    /// 
+    /// `owner` pays the tokens; the position is credited to
+    /// `params.recipient` (defaults to `owner`). Zeroed optional fields
+    /// behave exactly like `add_liquidity_advanced` with the same values.
+    pub fn mint_with_params(env: Env, owner: Address, params: MintParams) -> (i128, i128, i128) {
+        let MintParams {
+            lower_tick,
+            upper_tick,
+            amount0_desired,
+            amount1_desired,
+            amount0_min,
+            amount1_min,
+            deadline,
+            recipient,
+        } = params;
+        let recipient = recipient.unwrap_or_else(|| owner.clone());
+
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+        ensure(&env, validate::amount_non_negative(amount0_desired));
+        ensure(&env, validate::amount_non_negative(amount1_desired));
+        ensure(&env, validate::amount_below_cap(amount0_desired));
+        ensure(&env, validate::amount_below_cap(amount1_desired));
+        ensure(&env, validate::amount_non_negative(amount0_min));
+        ensure(&env, validate::amount_non_negative(amount1_min));
+        ensure(&env, validate::address_not_self(&env, &owner));
+        ensure(&env, validate::address_not_self(&env, &recipient));
 
-        if lower >= upper {
-            panic!("{}", ErrorMsg::INVALID_TICK_RANGE);
-        }
+        owner.require_auth();
+        check_deadline(&env, deadline);
 
-        let sqrt_lower = get_sqrt_ratio_at_tick(lower);
-        let sqrt_upper = get_sqrt_ratio_at_tick(upper);
+        let pool = read_pool_state(&env);
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
 
-        let liquidity = get_liquidity_for_amounts(
-            &env, amount0_desired, amount1_desired,
-            sqrt_lower, sqrt_upper, pool.sqrt_price_x64,
+        let mut pos = read_position(&env, &recipient, lower, upper);
+        let result = deposit_liquidity(
+            &env, &owner, &mut pos, lower, upper,
+            amount0_desired, amount1_desired, amount0_min, amount1_min,
         );
+        write_position(&env, &recipient, lower, upper, &pos);
 
-        if liquidity < MIN_LIQUIDITY {
-            panic!("{}", ErrorMsg::LIQUIDITY_TOO_LOW);
-        }
+        result
+    }
 
-        let (amount0_actual, amount1_actual) = get_amounts_for_liquidity(
-            &env, liquidity, sqrt_lower, sqrt_upper, pool.sqrt_price_x64,
-        );
+    /// Add single-sided liquidity that must rest outside the current price
+    /// 
+    /// Exactly one of `amount0_desired` / `amount1_desired` may be non-zero.
+    /// Token0 quotes above the price, so the range must still sit at or
+    /// above it (`sqrt_price <= sqrt(lower)`); token1 quotes below, so the
+    /// range must sit at or below it (`sqrt_price >= sqrt(upper)`). If the
+    /// price has moved into or past the range by execution time, the call
+    /// reverts instead of depositing liquidity that would fill at once.
+    pub fn add_liquidity_post_only(
+        env: Env,
+        owner: Address,
+        lower_tick: i32,
+        upper_tick: i32,
+        amount0_desired: i128,
+        amount1_desired: i128,
+        amount0_min: i128,
+        amount1_min: i128,
+        deadline: u64,
+    ) -> (i128, i128, i128) {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+        ensure(&env, validate::amount_non_negative(amount0_desired));
+        ensure(&env, validate::amount_non_negative(amount1_desired));
+        ensure(&env, validate::amount_below_cap(amount0_desired));
+        ensure(&env, validate::amount_below_cap(amount1_desired));
+        ensure(&env, validate::amount_non_negative(amount0_min));
+        ensure(&env, validate::amount_non_negative(amount1_min));
+        ensure(&env, validate::address_not_self(&env, &owner));
 
-        if amount0_actual < amount0_min || amount1_actual < amount1_min {
-            panic!("{}", ErrorMsg::SLIPPAGE_EXCEEDED);
+        owner.require_auth();
+        check_deadline(&env, deadline);
+
+        if (amount0_desired > 0) == (amount1_desired > 0) {
+            panic_with_error!(env, PoolError::PostOnlyNotSingleSided);
         }
 
-        // Update ticks FIRST to initialize fee_growth_outside properly
-        update_tick(&env, lower, pool.current_tick, liquidity,
-            pool.fee_growth_global_0, pool.fee_growth_global_1, false);
-        update_tick(&env, upper, pool.current_tick, liquidity,
-            pool.fee_growth_global_0, pool.fee_growth_global_1, true);
+        let pool = read_pool_state(&env);
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
 
-        // Get fee growth inside AFTER ticks are initialized
-        let (inside_0, inside_1) = get_fee_growth_inside(
-            &env, lower, upper, pool.current_tick,
-            pool.fee_growth_global_0, pool.fee_growth_global_1,
-        );
+        let resting = if amount0_desired > 0 {
+            pool.sqrt_price_x64 <= get_sqrt_ratio_at_tick(&env, lower)
+        } else {
+            pool.sqrt_price_x64 >= get_sqrt_ratio_at_tick(&env, upper)
+        };
+        if !resting {
+            panic_with_error!(env, PoolError::PostOnlyWouldFill);
+        }
 
-        // Update position
         let mut pos = read_position(&env, &owner, lower, upper);
-        modify_position(&mut pos, liquidity, inside_0, inside_1);
+        let result = deposit_liquidity(
+            &env, &owner, &mut pos, lower, upper,
+            amount0_desired, amount1_desired, amount0_min, amount1_min,
+        );
         write_position(&env, &owner, lower, upper, &pos);
 
-        // Update pool liquidity if position is in range
-        if pool.current_tick >= lower && pool.current_tick < upper {
-            pool.liquidity = pool.liquidity.saturating_add(liquidity);
-        }
-        write_pool_state(&env, &pool);
-
-        // Transfer tokens
-        if amount0_actual > 0 {
-            token::Client::new(&env, &pool.token0).transfer(&owner, &pool_addr, &amount0_actual);
-        }
-        if amount1_actual > 0 {
-            token::Client::new(&env, &pool.token1).transfer(&owner, &pool_addr, &amount1_actual);
-        }
-
-        emit_add_liquidity(&env, liquidity, amount0_actual, amount1_actual);
-
-        (liquidity, amount0_actual, amount1_actual)
+        result
     }
 
     /// Remove liquidity from a position
@@ -480,114 +1420,2003 @@ impl BelugaSwap {
         lower_tick: i32,
         upper_tick: i32,
         liquidity_delta: i128,
+        deadline: u64,
     ) -> (i128, i128) {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+        ensure(&env, validate::amount_positive(liquidity_delta));
+        ensure(&env, validate::amount_below_cap(liquidity_delta));
+
         owner.require_auth();
+        check_deadline(&env, deadline);
 
-        let mut pool = read_pool_state(&env);
-        let pool_addr = env.current_contract_address();
+        let pool = read_pool_state(&env);
 
-        let lower = snap_tick_to_spacing(lower_tick, pool.tick_spacing);
-        let upper = snap_tick_to_spacing(upper_tick, pool.tick_spacing);
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
 
-        if liquidity_delta <= 0 {
-            panic!("{}", ErrorMsg::INVALID_LIQUIDITY_AMOUNT);
-        }
+        let mut pos = read_position(&env, &owner, lower, upper);
+        let window = DataKey::WithdrawalWindow(owner.clone(), lower, upper);
+        let (amount0, amount1) = withdraw_liquidity(&env, &mut pos, lower, upper, liquidity_delta, Some(&window));
+        write_position(&env, &owner, lower, upper, &pos);
 
-        let (inside_0, inside_1) = get_fee_growth_inside(
-            &env, lower, upper, pool.current_tick,
-            pool.fee_growth_global_0, pool.fee_growth_global_1,
-        );
+        pay_withdrawal(&env, &owner, lower, upper, liquidity_delta, amount0, amount1)
+    }
 
-        let mut pos = read_position(&env, &owner, lower, upper);
+    /// Remove liquidity and collect all owed fees in one call
+    /// 
+    /// Same as `remove_liquidity` followed by `collect` for `u128::MAX`:
+    /// fees are checkpointed before the principal is burned, so the payout
+    /// includes everything earned up to this ledger.
+    /// 
+    /// # Returns
+    /// (principal0, principal1, fees0, fees1) transferred to the owner
+    pub fn decrease_liquidity_and_collect(
+        env: Env,
+        owner: Address,
+        lower_tick: i32,
+        upper_tick: i32,
+        liquidity_delta: i128,
+        deadline: u64,
+    ) -> (i128, i128, u128, u128) {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+        ensure(&env, validate::amount_positive(liquidity_delta));
+        ensure(&env, validate::amount_below_cap(liquidity_delta));
 
-        if liquidity_delta > pos.liquidity {
-            panic!("{}", ErrorMsg::INSUFFICIENT_LIQUIDITY);
-        }
+        owner.require_auth();
+        check_deadline(&env, deadline);
+
+        let pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        let mut pos = read_position(&env, &owner, lower, upper);
+        let window = DataKey::WithdrawalWindow(owner.clone(), lower, upper);
+        let (amount0, amount1) = withdraw_liquidity(&env, &mut pos, lower, upper, liquidity_delta, Some(&window));
+        pay_withdrawal(&env, &owner, lower, upper, liquidity_delta, amount0, amount1);
 
-        modify_position(&mut pos, -liquidity_delta, inside_0, inside_1);
+        // Fees are capped to the balance left after the principal went out
+        let pool = read_pool_state(&env);
+        let (fees0, fees1) = take_tokens_owed(&env, &pool, &mut pos, u128::MAX, u128::MAX);
         write_position(&env, &owner, lower, upper, &pos);
 
-        update_tick(&env, lower, pool.current_tick, -liquidity_delta,
-            pool.fee_growth_global_0, pool.fee_growth_global_1, false);
-        update_tick(&env, upper, pool.current_tick, -liquidity_delta,
-            pool.fee_growth_global_0, pool.fee_growth_global_1, true);
+        pay_tokens_owed(&env, &pool, &owner, lower, upper, &owner, fees0, fees1);
 
-        if pool.current_tick >= lower && pool.current_tick < upper {
-            pool.liquidity = pool.liquidity.saturating_sub(liquidity_delta);
-        }
-        write_pool_state(&env, &pool);
+        (amount0, amount1, fees0, fees1)
+    }
 
-        let sqrt_lower = get_sqrt_ratio_at_tick(lower);
-        let sqrt_upper = get_sqrt_ratio_at_tick(upper);
+    /// Fully exit a position: burn all its liquidity, collect everything it
+    /// is owed and delete it
+    /// 
+    /// Unclaimed liquidity mining rewards are paid out as well. Fails with
+    /// `PositionNotFound` if nothing is stored for the range. If
+    /// the pool balance cannot cover all owed fees, the shortfall stays
+    /// owed and the position is kept so it can be collected later. A queued
+    /// withdrawal for the range is dropped with the position.
+    /// 
+    /// # Returns
+    /// (principal0, principal1, fees0, fees1) transferred to the owner
+    pub fn close_position(
+        env: Env,
+        owner: Address,
+        lower_tick: i32,
+        upper_tick: i32,
+        deadline: u64,
+    ) -> (i128, i128, u128, u128) {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
 
-        let (amount0, amount1) = get_amounts_for_liquidity(
-            &env, liquidity_delta, sqrt_lower, sqrt_upper, pool.sqrt_price_x64,
-        );
+        owner.require_auth();
+        check_deadline(&env, deadline);
 
-        if amount0 > 0 {
-            token::Client::new(&env, &pool.token0).transfer(&pool_addr, &owner, &amount0);
+        let pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        if !has_position(&env, &owner, lower, upper) {
+            panic_with_error!(env, PoolError::PositionNotFound);
         }
-        if amount1 > 0 {
-            token::Client::new(&env, &pool.token1).transfer(&pool_addr, &owner, &amount1);
+
+        let mut pos = read_position(&env, &owner, lower, upper);
+        let liquidity = pos.liquidity;
+        let window = DataKey::WithdrawalWindow(owner.clone(), lower, upper);
+
+        let (amount0, amount1) = if liquidity > 0 {
+            let (amount0, amount1) = withdraw_liquidity(&env, &mut pos, lower, upper, liquidity, Some(&window));
+            pay_withdrawal(&env, &owner, lower, upper, liquidity, amount0, amount1)
+        } else {
+            (0, 0)
+        };
+
+        let pool = read_pool_state(&env);
+        let rewards = take_rewards_owed(&env, &pool, &mut pos, lower, upper);
+        let (fees0, fees1) = take_tokens_owed(&env, &pool, &mut pos, u128::MAX, u128::MAX);
+        if is_empty(&pos) {
+            remove_position(&env, &owner, lower, upper);
+            remove_withdrawal_request(&env, &owner, lower, upper);
+            remove_withdrawal_window(&env, &window);
+        } else {
+            write_position(&env, &owner, lower, upper, &pos);
         }
 
-        emit_remove_liquidity(&env, liquidity_delta, amount0, amount1);
+        pay_tokens_owed(&env, &pool, &owner, lower, upper, &owner, fees0, fees1);
 
-        (amount0, amount1)
+        if rewards > 0 {
+            pay_rewards(&env, &owner, rewards);
+            emit_collect_rewards(&env, &owner, lower, upper, rewards);
+        }
+
+        (amount0, amount1, fees0, fees1)
     }
 
     // ========================================================
-    // FEE COLLECTION
+    // POSITIONS BY ID
     // ========================================================
 
-    /// Collect accumulated fees from a position
-    pub fn collect(
+    /// Open an empty position on a range and return its id
+    /// 
+    /// Unlike `(owner, lower, upper)` positions, any number of id-based
+    /// positions can share an owner and range, each with its own fee
+    /// checkpoints (e.g. one per user of a vault). Ticks must be aligned to
+    /// the spacing; fund the position with `add_liquidity_by_id`.
+    pub fn open_position(env: Env, owner: Address, lower_tick: i32, upper_tick: i32) -> u64 {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+        ensure(&env, validate::address_not_self(&env, &owner));
+
+        owner.require_auth();
+
+        let mut pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        let position_id = pool.next_position_id;
+        pool.next_position_id += 1;
+        write_pool_state(&env, &pool);
+
+        let record = PositionRecord {
+            owner,
+            lower_tick: lower,
+            upper_tick: upper,
+            position: Position::default(),
+        };
+        write_position_record(&env, position_id, &record);
+
+        position_id
+    }
+
+    /// Add liquidity to an id-based position
+    /// 
+    /// Same sizing, fees and slippage checks as `add_liquidity_advanced`;
+    /// the position's owner pays.
+    pub fn add_liquidity_by_id(
         env: Env,
         owner: Address,
-        lower_tick: i32,
-        upper_tick: i32,
-    ) -> (u128, u128) {
+        position_id: u64,
+        amount0_desired: i128,
+        amount1_desired: i128,
+        amount0_min: i128,
+        amount1_min: i128,
+        deadline: u64,
+    ) -> (i128, i128, i128) {
+        ensure(&env, validate::amount_non_negative(amount0_desired));
+        ensure(&env, validate::amount_non_negative(amount1_desired));
+        ensure(&env, validate::amount_below_cap(amount0_desired));
+        ensure(&env, validate::amount_below_cap(amount1_desired));
+        ensure(&env, validate::amount_non_negative(amount0_min));
+        ensure(&env, validate::amount_non_negative(amount1_min));
+
         owner.require_auth();
+        check_deadline(&env, deadline);
 
-        let pool = read_pool_state(&env);
-        let pool_addr = env.current_contract_address();
+        let mut record = load_owned_position(&env, &owner, position_id);
+        let result = deposit_liquidity(
+            &env, &owner, &mut record.position, record.lower_tick, record.upper_tick,
+            amount0_desired, amount1_desired, amount0_min, amount1_min,
+        );
+        write_position_record(&env, position_id, &record);
 
-        let lower = snap_tick_to_spacing(lower_tick, pool.tick_spacing);
-        let upper = snap_tick_to_spacing(upper_tick, pool.tick_spacing);
+        result
+    }
 
-        let mut pos = read_position(&env, &owner, lower, upper);
+    /// Remove liquidity from an id-based position
+    /// 
+    /// Removals beyond the queue threshold within one interval are
+    /// rejected; the withdrawal queue only serves `(owner, lower, upper)`
+    /// positions, so larger exits are spread over several intervals.
+    pub fn remove_liquidity_by_id(
+        env: Env,
+        owner: Address,
+        position_id: u64,
+        liquidity_delta: i128,
+        deadline: u64,
+    ) -> (i128, i128) {
+        ensure(&env, validate::amount_positive(liquidity_delta));
+        ensure(&env, validate::amount_below_cap(liquidity_delta));
+
+        owner.require_auth();
+        check_deadline(&env, deadline);
+
+        let mut record = load_owned_position(&env, &owner, position_id);
+        let window = DataKey::WithdrawalWindowById(position_id);
+        let (amount0, amount1) = withdraw_liquidity(
+            &env, &mut record.position, record.lower_tick, record.upper_tick, liquidity_delta, Some(&window),
+        );
+        write_position_record(&env, position_id, &record);
+
+        pay_withdrawal(&env, &owner, record.lower_tick, record.upper_tick, liquidity_delta, amount0, amount1)
+    }
+
+    /// Collect accumulated fees from an id-based position
+    /// 
+    /// Same as `collect`: pays at most the requested amounts and leaves the
+    /// rest owed.
+    /// 
+    /// # Returns
+    /// (amount0, amount1) actually collected
+    pub fn collect_by_id(
+        env: Env,
+        owner: Address,
+        position_id: u64,
+        amount0_requested: u128,
+        amount1_requested: u128,
+        deadline: u64,
+    ) -> (u128, u128) {
+        owner.require_auth();
+        check_deadline(&env, deadline);
+
+        let pool = read_pool_state(&env);
+        let mut record = load_owned_position(&env, &owner, position_id);
 
         let (inside_0, inside_1) = get_fee_growth_inside(
-            &env, lower, upper, pool.current_tick,
+            &env, record.lower_tick, record.upper_tick, pool.current_tick,
             pool.fee_growth_global_0, pool.fee_growth_global_1,
         );
+        let reward_inside = get_reward_growth_inside(
+            &env, record.lower_tick, record.upper_tick, pool.current_tick, pool.reward_growth_global,
+        );
 
-        update_position(&mut pos, inside_0, inside_1);
+        update_position(&env, &mut record.position, inside_0, inside_1, reward_inside);
 
-        let amount0 = pos.tokens_owed_0;
-        let amount1 = pos.tokens_owed_1;
+        let (amount0, amount1) = take_tokens_owed(
+            &env, &pool, &mut record.position, amount0_requested, amount1_requested,
+        );
+        write_position_record(&env, position_id, &record);
 
-        // Cap fees to available balance
-        let pool_balance_0 = token::Client::new(&env, &pool.token0).balance(&pool_addr) as u128;
-        let pool_balance_1 = token::Client::new(&env, &pool.token1).balance(&pool_addr) as u128;
+        pay_tokens_owed(&env, &pool, &owner, record.lower_tick, record.upper_tick, &owner, amount0, amount1)
+    }
 
-        let amount0_capped = amount0.min(pool_balance_0);
-        let amount1_capped = amount1.min(pool_balance_1);
+    // ========================================================
+    // LIMIT ORDERS
+    // ========================================================
 
-        pos.tokens_owed_0 = pos.tokens_owed_0.saturating_sub(amount0_capped);
-        pos.tokens_owed_1 = pos.tokens_owed_1.saturating_sub(amount1_capped);
+    /// Place a limit order: `amount` of one token on `[tick, tick + spacing)`
+    /// 
+    /// Selling token0 (`zero_for_one`) rests above the price, selling token1
+    /// below it; like `add_liquidity_post_only`, placement reverts with
+    /// `PostOnlyWouldFill` if the range is not fully on that side. The order
+    /// occupies a new id-based position whose id is returned. Once the price
+    /// has crossed the whole range, `settle_limit_order` pays it out in the
+    /// other token; until then it can be cancelled with
+    /// `remove_liquidity_by_id` and `collect_by_id`.
+    pub fn place_limit_order(
+        env: Env,
+        owner: Address,
+        tick: i32,
+        zero_for_one: bool,
+        amount: i128,
+        deadline: u64,
+    ) -> u64 {
+        ensure(&env, validate::amount_positive(amount));
+        ensure(&env, validate::amount_below_cap(amount));
+        ensure(&env, validate::address_not_self(&env, &owner));
 
-        write_position(&env, &owner, lower, upper, &pos);
+        owner.require_auth();
+        check_deadline(&env, deadline);
+
+        let mut pool = read_pool_state(&env);
+        let upper_tick = tick.saturating_add(pool.tick_spacing);
+        ensure(&env, validate::tick_range(tick, upper_tick));
+
+        let (lower, upper) = aligned_range(&env, tick, upper_tick, pool.tick_spacing);
+
+        let resting = if zero_for_one {
+            pool.sqrt_price_x64 <= get_sqrt_ratio_at_tick(&env, lower)
+        } else {
+            pool.sqrt_price_x64 >= get_sqrt_ratio_at_tick(&env, upper)
+        };
+        if !resting {
+            panic_with_error!(env, PoolError::PostOnlyWouldFill);
+        }
+
+        let order_id = pool.next_position_id;
+        pool.next_position_id += 1;
+        write_pool_state(&env, &pool);
+
+        let mut record = PositionRecord {
+            owner: owner.clone(),
+            lower_tick: lower,
+            upper_tick: upper,
+            position: Position::default(),
+        };
+
+        let (amount0, amount1) = if zero_for_one { (amount, 0) } else { (0, amount) };
+        deposit_liquidity(&env, &owner, &mut record.position, lower, upper, amount0, amount1, 0, 0);
+
+        write_position_record(&env, order_id, &record);
+        write_limit_order(&env, order_id, &LimitOrder { zero_for_one });
+
+        order_id
+    }
 
-        if amount0_capped > 0 {
-            token::Client::new(&env, &pool.token0).transfer(&pool_addr, &owner, &(amount0_capped as i128));
+    /// Settle a filled limit order
+    /// 
+    /// Reverts with `LimitOrderNotCrossed` unless the price has crossed the
+    /// whole range, so a partly filled order, or one the price has come
+    /// back into, cannot be settled. Burns all of the order's liquidity
+    /// and pays it out, entirely in the token bought, together with its
+    /// fees and any liquidity mining rewards. Settlement is exempt from the
+    /// withdrawal queue: a filled order is always paid out in full.
+    /// 
+    /// # Returns
+    /// (principal0, principal1, fees0, fees1) transferred to the owner
+    pub fn settle_limit_order(env: Env, owner: Address, order_id: u64) -> (i128, i128, u128, u128) {
+        owner.require_auth();
+
+        let mut record = load_owned_position(&env, &owner, order_id);
+        let order = read_limit_order(&env, order_id)
+            .unwrap_or_else(|| panic_with_error!(env, PoolError::PositionNotFound));
+        let (lower, upper) = (record.lower_tick, record.upper_tick);
+
+        let pool = read_pool_state(&env);
+        let filled = if order.zero_for_one {
+            pool.sqrt_price_x64 >= get_sqrt_ratio_at_tick(&env, upper)
+        } else {
+            pool.sqrt_price_x64 <= get_sqrt_ratio_at_tick(&env, lower)
+        };
+        if !filled {
+            panic_with_error!(env, PoolErrorExt::LimitOrderNotCrossed);
+        }
+
+        let liquidity = record.position.liquidity;
+        let (amount0, amount1) = if liquidity > 0 {
+            let (amount0, amount1) = withdraw_liquidity(&env, &mut record.position, lower, upper, liquidity, None);
+            pay_withdrawal(&env, &owner, lower, upper, liquidity, amount0, amount1)
+        } else {
+            (0, 0)
+        };
+
+        let pool = read_pool_state(&env);
+        let rewards = take_rewards_owed(&env, &pool, &mut record.position, lower, upper);
+        let (fees0, fees1) = take_tokens_owed(&env, &pool, &mut record.position, u128::MAX, u128::MAX);
+        write_position_record(&env, order_id, &record);
+        remove_limit_order(&env, order_id);
+
+        pay_tokens_owed(&env, &pool, &owner, lower, upper, &owner, fees0, fees1);
+
+        if rewards > 0 {
+            pay_rewards(&env, &owner, rewards);
+            emit_collect_rewards(&env, &owner, lower, upper, rewards);
         }
-        if amount1_capped > 0 {
-            token::Client::new(&env, &pool.token1).transfer(&pool_addr, &owner, &(amount1_capped as i128));
+
+        (amount0, amount1, fees0, fees1)
+    }
+
+    /// Limit order resting on position `order_id` (None once settled)
+    pub fn get_limit_order(env: Env, order_id: u64) -> Option<LimitOrder> {
+        read_limit_order(&env, order_id)
+    }
+
+    // ========================================================
+    // FLASH LOANS
+    // ========================================================
+
+    /// Lend pool reserves for the duration of a single callback
+    /// 
+    /// Sends `amount0`/`amount1` to `receiver`, then calls
+    /// `on_flash_loan` on it with the fee owed on each (`fee_bps` of the
+    /// amount, rounded up). The loan plus fee must be back in the pool when
+    /// the callback returns. Everything paid on top of the loan is split
+    /// like a swap fee: the protocol share by `protocol_fee_bps`, the rest
+    /// to in-range LPs through fee growth (to the protocol if there is no
+    /// active liquidity). Soroban does not allow re-entering the pool from
+    /// the callback, so `swap` and liquidity changes cannot run against the
+    /// lent-out reserves.
+    /// 
+    /// # Returns
+    /// (paid0, paid1) received on top of the loan
+    pub fn flash(
+        env: Env,
+        caller: Address,
+        receiver: Address,
+        amount0: i128,
+        amount1: i128,
+        data: Bytes,
+    ) -> (i128, i128) {
+        ensure(&env, validate::amount_non_negative(amount0));
+        ensure(&env, validate::amount_non_negative(amount1));
+        ensure(&env, validate::amount_below_cap(amount0));
+        ensure(&env, validate::amount_below_cap(amount1));
+        ensure(&env, validate::address_not_self(&env, &receiver));
+
+        if amount0 == 0 && amount1 == 0 {
+            panic_with_error!(env, PoolError::AmountNotPositive);
         }
 
-        emit_collect(&env, amount0_capped, amount1_capped);
+        caller.require_auth();
+        check_not_paused(&env);
+
+        let config = read_pool_config(&env);
+        let mut pool = read_pool_state(&env);
+        let pool_addr = env.current_contract_address();
+
+        let fee0 = apply_bps_ceil(&env, amount0, config.fee_bps);
+        let fee1 = apply_bps_ceil(&env, amount1, config.fee_bps);
+
+        let token0 = token::Client::new(&env, &pool.token0);
+        let token1 = token::Client::new(&env, &pool.token1);
+        let balance0_before = token0.balance(&pool_addr);
+        let balance1_before = token1.balance(&pool_addr);
 
-        (amount0_capped, amount1_capped)
+        if amount0 > 0 {
+            token0.transfer(&pool_addr, &receiver, &amount0);
+        }
+        if amount1 > 0 {
+            token1.transfer(&pool_addr, &receiver, &amount1);
+        }
+
+        FlashLoanReceiverClient::new(&env, &receiver).on_flash_loan(
+            &pool_addr, &amount0, &amount1, &fee0, &fee1, &data,
+        );
+
+        let paid0 = token0.balance(&pool_addr) - balance0_before;
+        let paid1 = token1.balance(&pool_addr) - balance1_before;
+
+        if paid0 < fee0 || paid1 < fee1 {
+            panic_with_error!(env, PoolError::FlashNotFunded);
+        }
+
+        write_observation(&env, &mut pool);
+
+        let (growth0, protocol0) = split_flash_fee(&env, &pool, paid0, config.protocol_fee_bps);
+        let (growth1, protocol1) = split_flash_fee(&env, &pool, paid1, config.protocol_fee_bps);
+        pool.fee_growth_global_0 = pool.fee_growth_global_0.wrapping_add(growth0);
+        pool.fee_growth_global_1 = pool.fee_growth_global_1.wrapping_add(growth1);
+        pool.protocol_fees_0 = pool.protocol_fees_0.saturating_add(protocol0 as u128);
+        pool.protocol_fees_1 = pool.protocol_fees_1.saturating_add(protocol1 as u128);
+
+        write_pool_state(&env, &pool);
+        track_reserves(&env, paid0, paid1);
+
+        emit_flash(&env, &caller, &receiver, amount0, amount1, paid0, paid1);
+
+        (paid0, paid1)
+    }
+
+    // ========================================================
+    // TICK BITMAP MIGRATION
+    // ========================================================
+
+    /// Set tick bitmap bits for ticks initialized before the bitmap existed
+    /// 
+    /// Permissionless and idempotent: bits are derived from stored tick data,
+    /// so callers can only make the bitmap match storage. Ticks must be
+    /// spacing-aligned.
+    pub fn sync_tick_bitmap(env: Env, ticks: Vec<i32>) {
+        let pool = read_pool_state(&env);
+
+        for tick in ticks.iter() {
+            if !is_valid_tick(tick) || tick % pool.tick_spacing != 0 {
+                panic_with_error!(env, PoolError::InvalidTick);
+            }
+        }
+
+        sync_tick_bitmap(&env, &ticks, pool.tick_spacing);
+    }
+
+    // ========================================================
+    // STORAGE TTL
+    // ========================================================
+
+    /// Keep persistent entries and the contract instance from being archived
+    /// 
+    /// Permissionless, for keepers: extends each listed key that exists to
+    /// `PERSISTENT_TTL_EXTEND_TO` ledgers once its TTL is below
+    /// `PERSISTENT_TTL_THRESHOLD`, and the contract instance and pool-wide
+    /// entries (state, config, settings, oracle) likewise. Entries are also
+    /// extended whenever they are read or written, so this is only needed
+    /// for rarely touched positions and ticks, or a pool that sits idle.
+    /// 
+    /// # Returns
+    /// Number of listed keys that exist and were checked
+    pub fn extend_entries(env: Env, keys: Vec<DataKey>) -> u32 {
+        extend_pool_entries(&env);
+
+        let mut extended = 0;
+        for key in keys.iter() {
+            if extend_if_present(&env, &key) {
+                extended += 1;
+            }
+        }
+        extended
+    }
+
+    // ========================================================
+    // WITHDRAWAL QUEUE
+    // ========================================================
+
+    /// Queue a withdrawal too large for `remove_liquidity`
+    /// 
+    /// The liquidity stays in the position (and keeps earning fees) until
+    /// `process_withdrawal` releases it, at most `threshold` per `interval`.
+    /// Replaces any existing request for the same position.
+    pub fn request_withdrawal(
+        env: Env,
+        owner: Address,
+        lower_tick: i32,
+        upper_tick: i32,
+        liquidity: i128,
+        deadline: u64,
+    ) -> WithdrawalRequest {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+        ensure(&env, validate::amount_positive(liquidity));
+        ensure(&env, validate::amount_below_cap(liquidity));
+
+        owner.require_auth();
+        check_deadline(&env, deadline);
+
+        let queue = read_withdrawal_queue_config(&env);
+        if queue.threshold == 0 {
+            panic_with_error!(env, PoolError::WithdrawalQueueDisabled);
+        }
+
+        let pool = read_pool_state(&env);
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        let pos = read_position(&env, &owner, lower, upper);
+        if liquidity > pos.liquidity {
+            panic_with_error!(env, PoolError::InsufficientLiquidity);
+        }
+
+        let request = WithdrawalRequest {
+            remaining: liquidity,
+            next_release: env.ledger().timestamp(),
+        };
+        write_withdrawal_request(&env, &owner, lower, upper, &request);
+
+        emit_withdrawal_requested(&env, lower, upper, liquidity);
+
+        request
+    }
+
+    /// Release the next tranche of a queued withdrawal to its owner
+    /// 
+    /// Permissionless: funds always go to the position owner, so keepers can
+    /// drive the queue. Each call burns whatever is left of the position's
+    /// `threshold` allowance for the current interval (everything left if
+    /// the queue has since been disabled); fails with `WithdrawalNotReady`
+    /// if direct removals have already used it up.
+    pub fn process_withdrawal(
+        env: Env,
+        owner: Address,
+        lower_tick: i32,
+        upper_tick: i32,
+    ) -> (i128, i128) {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+
+        let config = read_pool_config(&env);
+        let queue = read_withdrawal_queue_config(&env);
+        let mut pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        let mut request = read_withdrawal_request(&env, &owner, lower, upper)
+            .unwrap_or_else(|| panic_with_error!(env, PoolError::NoWithdrawalRequest));
+
+        let now = env.ledger().timestamp();
+        if now < request.next_release {
+            panic_with_error!(env, PoolError::WithdrawalNotReady);
+        }
+
+        let mut pos = read_position(&env, &owner, lower, upper);
+
+        // The owner may have removed liquidity directly in the meantime
+        let mut tranche = request.remaining.min(pos.liquidity);
+        if tranche <= 0 {
+            remove_withdrawal_request(&env, &owner, lower, upper);
+            return (0, 0);
+        }
+
+        // Direct removals count against the same per-interval allowance
+        if queue.threshold > 0 {
+            let key = DataKey::WithdrawalWindow(owner.clone(), lower, upper);
+            let mut window = current_withdrawal_window(&env, &queue, &key);
+            let allowance = queue.threshold - window.removed;
+            if allowance <= 0 {
+                panic_with_error!(env, PoolError::WithdrawalNotReady);
+            }
+            tranche = tranche.min(allowance);
+            window.removed += tranche;
+            write_withdrawal_window(&env, &key, &window);
+        }
+
+        write_observation(&env, &mut pool);
+
+        let (amount0, amount1) = burn_liquidity(
+            &env, &config, &mut pool, &mut pos, lower, upper, tranche,
+        );
+
+        write_position(&env, &owner, lower, upper, &pos);
+        write_pool_state(&env, &pool);
+
+        request.remaining -= tranche;
+        if request.remaining == 0 || pos.liquidity == 0 {
+            remove_withdrawal_request(&env, &owner, lower, upper);
+        } else {
+            request.next_release = now.saturating_add(queue.interval);
+            write_withdrawal_request(&env, &owner, lower, upper, &request);
+        }
+
+        transfer_out(&env, &pool, &owner, amount0, amount1);
+
+        emit_remove_liquidity(&env, &owner, lower, upper, tranche, amount0, amount1);
+
+        (amount0, amount1)
+    }
+
+    /// Cancel a queued withdrawal; already processed tranches are not undone
+    pub fn cancel_withdrawal(env: Env, owner: Address, lower_tick: i32, upper_tick: i32) {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+
+        owner.require_auth();
+
+        let pool = read_pool_state(&env);
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        let request = read_withdrawal_request(&env, &owner, lower, upper)
+            .unwrap_or_else(|| panic_with_error!(env, PoolError::NoWithdrawalRequest));
+
+        remove_withdrawal_request(&env, &owner, lower, upper);
+
+        emit_withdrawal_cancelled(&env, lower, upper, request.remaining);
+    }
+
+    /// Get the queued withdrawal for a position, if any
+    pub fn get_withdrawal_request(env: Env, owner: Address, lower: i32, upper: i32) -> Option<WithdrawalRequest> {
+        ensure(&env, validate::tick_range(lower, upper));
+
+        read_withdrawal_request(&env, &owner, lower, upper)
+    }
+
+    // ========================================================
+    // FEE COLLECTION
+    // ========================================================
+
+    /// Collect accumulated fees from a position
+    /// 
+    /// Pays at most `amount0_requested` / `amount1_requested` of the owed
+    /// fees (`u128::MAX` collects everything); whatever is not paid stays
+    /// owed to the position.
+    /// 
+    /// # Returns
+    /// (amount0, amount1) actually collected
+    pub fn collect(
+        env: Env,
+        owner: Address,
+        lower_tick: i32,
+        upper_tick: i32,
+        amount0_requested: u128,
+        amount1_requested: u128,
+        deadline: u64,
+    ) -> (u128, u128) {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+
+        owner.require_auth();
+        check_deadline(&env, deadline);
+
+        let pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        let mut pos = read_position(&env, &owner, lower, upper);
+
+        let (inside_0, inside_1) = get_fee_growth_inside(
+            &env, lower, upper, pool.current_tick,
+            pool.fee_growth_global_0, pool.fee_growth_global_1,
+        );
+        let reward_inside = get_reward_growth_inside(
+            &env, lower, upper, pool.current_tick, pool.reward_growth_global,
+        );
+
+        update_position(&env, &mut pos, inside_0, inside_1, reward_inside);
+
+        let (amount0, amount1) = take_tokens_owed(&env, &pool, &mut pos, amount0_requested, amount1_requested);
+        write_position(&env, &owner, lower, upper, &pos);
+
+        pay_tokens_owed(&env, &pool, &owner, lower, upper, &owner, amount0, amount1)
+    }
+
+    /// Collect fees from a position to a different recipient
+    /// 
+    /// Same as `collect`, but the owner authorizes and the tokens go to
+    /// `recipient` (e.g. a vault treasury). At most `amount0_max` /
+    /// `amount1_max` is paid; the rest stays owed to the position.
+    /// 
+    /// # Returns
+    /// (amount0, amount1) actually collected
+    pub fn collect_to(
+        env: Env,
+        owner: Address,
+        lower_tick: i32,
+        upper_tick: i32,
+        recipient: Address,
+        amount0_max: u128,
+        amount1_max: u128,
+    ) -> (u128, u128) {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+        ensure(&env, validate::address_not_self(&env, &recipient));
+
+        owner.require_auth();
+
+        let pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        let mut pos = read_position(&env, &owner, lower, upper);
+
+        let (inside_0, inside_1) = get_fee_growth_inside(
+            &env, lower, upper, pool.current_tick,
+            pool.fee_growth_global_0, pool.fee_growth_global_1,
+        );
+        let reward_inside = get_reward_growth_inside(
+            &env, lower, upper, pool.current_tick, pool.reward_growth_global,
+        );
+
+        update_position(&env, &mut pos, inside_0, inside_1, reward_inside);
+
+        let (amount0, amount1) = take_tokens_owed(&env, &pool, &mut pos, amount0_max, amount1_max);
+        write_position(&env, &owner, lower, upper, &pos);
+
+        pay_tokens_owed(&env, &pool, &owner, lower, upper, &recipient, amount0, amount1)
+    }
+
+    /// Collect only the fees already credited to a position
+    /// 
+    /// Pays out `tokens_owed` as recorded (credited by earlier burns and
+    /// collects) without recomputing fee growth, so previously earned fees
+    /// stay claimable even if the live fee-growth update ever starts to
+    /// fail. A panic reverts the whole call on Soroban, which is why this is
+    /// a separate entrypoint rather than a reordering of `collect`. Fees
+    /// accrued since the last update stay in the position.
+    pub fn collect_owed(
+        env: Env,
+        owner: Address,
+        lower_tick: i32,
+        upper_tick: i32,
+        deadline: u64,
+    ) -> (u128, u128) {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+
+        owner.require_auth();
+        check_deadline(&env, deadline);
+
+        let pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        let mut pos = read_position(&env, &owner, lower, upper);
+
+        let (amount0, amount1) = take_tokens_owed(&env, &pool, &mut pos, u128::MAX, u128::MAX);
+        write_position(&env, &owner, lower, upper, &pos);
+
+        pay_tokens_owed(&env, &pool, &owner, lower, upper, &owner, amount0, amount1)
+    }
+
+    // ========================================================
+    // LIQUIDITY MINING
+    // ========================================================
+
+    /// Stream `amount` of an external reward token to in-range liquidity
+    /// over the next `duration_secs` seconds
+    /// 
+    /// Transfers `amount` from the admin. Rewards accrue per second to the
+    /// active liquidity, so out-of-range positions earn nothing; seconds
+    /// with no in-range liquidity push the end of the stream back instead of
+    /// being lost. Funding again rolls whatever has not streamed yet into the
+    /// new window. The token cannot be one of the pool's tokens and is fixed
+    /// by the first funding.
+    pub fn fund_rewards(env: Env, admin: Address, token: Address, amount: i128, duration_secs: u64) {
+        ensure(&env, validate::amount_positive(amount));
+        ensure(&env, validate::amount_below_cap(amount));
+        ensure(&env, validate::address_not_self(&env, &token));
+
+        require_admin(&env, &admin);
+
+        if duration_secs == 0 {
+            panic_with_error!(env, PoolError::AmountNotPositive);
+        }
+
+        let mut pool = read_pool_state(&env);
+        if token == pool.token0 || token == pool.token1 {
+            panic_with_error!(env, PoolError::SameToken);
+        }
+
+        accrue_rewards(&env, &mut pool);
+        let schedule = fund_schedule(&env, token.clone(), amount, duration_secs);
+        write_pool_state(&env, &pool);
+
+        token::Client::new(&env, &token).transfer(&admin, &env.current_contract_address(), &amount);
+
+        emit_rewards_funded(&env, &token, amount, schedule.end);
+    }
+
+    /// Claim a position's liquidity mining rewards
+    /// 
+    /// Also credits the position's fees to `tokens_owed`, as `collect` does.
+    /// 
+    /// # Returns
+    /// Reward tokens paid to `owner`
+    pub fn collect_rewards(env: Env, owner: Address, lower_tick: i32, upper_tick: i32) -> u128 {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+
+        owner.require_auth();
+
+        let mut pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+
+        if !has_position(&env, &owner, lower, upper) {
+            panic_with_error!(env, PoolError::PositionNotFound);
+        }
+
+        accrue_rewards(&env, &mut pool);
+
+        let mut pos = read_position(&env, &owner, lower, upper);
+        let amount = take_rewards_owed(&env, &pool, &mut pos, lower, upper);
+
+        write_position(&env, &owner, lower, upper, &pos);
+        write_pool_state(&env, &pool);
+
+        pay_rewards(&env, &owner, amount);
+        emit_collect_rewards(&env, &owner, lower, upper, amount);
+
+        amount
+    }
+
+    /// Claim an id-based position's liquidity mining rewards
+    /// 
+    /// Same as `collect_rewards`.
+    /// 
+    /// # Returns
+    /// Reward tokens paid to `owner`
+    pub fn collect_rewards_by_id(env: Env, owner: Address, position_id: u64) -> u128 {
+        owner.require_auth();
+
+        let mut pool = read_pool_state(&env);
+        let mut record = load_owned_position(&env, &owner, position_id);
+
+        accrue_rewards(&env, &mut pool);
+
+        let amount = take_rewards_owed(
+            &env, &pool, &mut record.position, record.lower_tick, record.upper_tick,
+        );
+
+        write_position_record(&env, position_id, &record);
+        write_pool_state(&env, &pool);
+
+        pay_rewards(&env, &owner, amount);
+        emit_collect_rewards(&env, &owner, record.lower_tick, record.upper_tick, amount);
+
+        amount
+    }
+
+    /// Current reward stream (None if rewards were never funded)
+    pub fn get_reward_schedule(env: Env) -> Option<RewardSchedule> {
+        read_reward_schedule(&env)
+    }
+
+    /// Rewards a position could claim right now
+    pub fn get_pending_rewards(env: Env, owner: Address, lower_tick: i32, upper_tick: i32) -> u128 {
+        ensure(&env, validate::tick_range(lower_tick, upper_tick));
+
+        let pool = read_pool_state(&env);
+
+        let (lower, upper) = aligned_range(&env, lower_tick, upper_tick, pool.tick_spacing);
+        let pos = read_position(&env, &owner, lower, upper);
+
+        let reward_inside = get_reward_growth_inside(
+            &env, lower, upper, pool.current_tick, current_reward_growth(&env, &pool),
+        );
+
+        calculate_pending_rewards(&pos, reward_inside)
+    }
+
+    // ========================================================
+    // ADMIN TRANSFER
+    // ========================================================
+
+    /// Propose `new_admin` as the next pool admin
+    /// 
+    /// Takes effect only once `new_admin` calls `accept_admin`, so a typo
+    /// cannot lock the pool. A new proposal replaces any pending one.
+    pub fn propose_admin(env: Env, admin: Address, new_admin: Address) {
+        ensure(&env, validate::address_not_self(&env, &new_admin));
+
+        require_admin(&env, &admin);
+
+        write_pending_admin(&env, &new_admin);
+        emit_admin_proposed(&env, &admin, &new_admin);
+    }
+
+    /// Accept a pending admin proposal (authorized by the proposed admin)
+    pub fn accept_admin(env: Env) {
+        let pending = read_pending_admin(&env)
+            .unwrap_or_else(|| panic_with_error!(env, PoolError::NoPendingAdmin));
+        pending.require_auth();
+
+        let mut config = read_pool_config(&env);
+        let previous = config.admin;
+        config.admin = pending.clone();
+        write_pool_config(&env, &config);
+        remove_pending_admin(&env);
+
+        emit_admin_changed(&env, &previous, &pending);
+    }
+
+    /// Get the current pool admin
+    pub fn get_admin(env: Env) -> Address {
+        read_pool_config(&env).admin
+    }
+
+    /// Get the admin awaiting `accept_admin`, if any
+    pub fn get_pending_admin(env: Env) -> Option<Address> {
+        read_pending_admin(&env)
+    }
+
+    /// Replace the contract code with an uploaded WASM, keeping all storage
+    /// 
+    /// Only the admin of an initialized pool can upgrade. The new code
+    /// takes effect after this call returns. Emits `upgrade` with the
+    /// previously installed hash (None before the first upgrade) and the
+    /// new one.
+    pub fn upgrade(env: Env, admin: Address, new_wasm_hash: BytesN<32>) {
+        if !is_initialized(&env) {
+            panic_with_error!(env, PoolError::NotInitialized);
+        }
+        require_admin(&env, &admin);
+
+        let old_wasm_hash = read_wasm_hash(&env);
+        write_wasm_hash(&env, &new_wasm_hash);
+
+        env.deployer().update_current_contract_wasm(new_wasm_hash.clone());
+
+        emit_upgrade(&env, &old_wasm_hash, &new_wasm_hash);
+    }
+
+    /// Version of the code currently running (see `upgrade`)
+    pub fn version(_env: Env) -> u32 {
+        CONTRACT_VERSION
+    }
+
+    // ========================================================
+    // PROTOCOL FEES
+    // ========================================================
+
+    /// Collect accumulated protocol fees
+    /// 
+    /// With vesting disabled the fees are transferred to the admin directly.
+    /// With vesting enabled they are stacked onto the vesting schedule
+    /// (releasing whatever had already vested first) and must be claimed
+    /// through `release_vested`.
+    pub fn collect_protocol_fees(env: Env, admin: Address) -> (u128, u128) {
+        require_admin(&env, &admin);
+
+        let mut pool = read_pool_state(&env);
+
+        let amount0 = pool.protocol_fees_0;
+        let amount1 = pool.protocol_fees_1;
+
+        pool.protocol_fees_0 = 0;
+        pool.protocol_fees_1 = 0;
+        write_pool_state(&env, &pool);
+
+        let duration = read_vesting_duration(&env);
+
+        if duration == 0 {
+            transfer_out(&env, &pool, &admin, amount0 as i128, amount1 as i128);
+
+            emit_collect_protocol(&env, amount0, amount1, false);
+            return (amount0, amount1);
+        }
+
+        let now = env.ledger().timestamp();
+        let mut schedule = match read_vesting_schedule(&env) {
+            Some(mut existing) => {
+                // Settle what has vested under the old window before restarting it
+                let (released0, released1) = release_vested_fees(&env, &pool.token0, &pool.token1, &mut existing);
+                if released0 > 0 || released1 > 0 {
+                    emit_vested_release(&env, released0, released1);
+                }
+                existing
+            }
+            None => VestingSchedule {
+                beneficiary: admin.clone(),
+                start: now,
+                duration,
+                amount_0: 0,
+                amount_1: 0,
+                released_0: 0,
+                released_1: 0,
+            },
+        };
+
+        schedule.beneficiary = admin;
+        restack_schedule(&mut schedule, amount0, amount1, now, duration);
+        write_vesting_schedule(&env, &schedule);
+
+        emit_collect_protocol(&env, amount0, amount1, true);
+
+        (amount0, amount1)
+    }
+
+    /// Release the linearly vested portion of collected protocol fees
+    /// 
+    /// Permissionless: funds always go to the schedule's beneficiary.
+    pub fn release_vested(env: Env) -> (u128, u128) {
+        let pool = read_pool_state(&env);
+
+        let mut schedule = read_vesting_schedule(&env)
+            .unwrap_or_else(|| panic_with_error!(env, PoolError::NothingToRelease));
+
+        let (amount0, amount1) = release_vested_fees(&env, &pool.token0, &pool.token1, &mut schedule);
+
+        if amount0 == 0 && amount1 == 0 {
+            panic_with_error!(env, PoolError::NothingToRelease);
+        }
+
+        write_vesting_schedule(&env, &schedule);
+        emit_vested_release(&env, amount0, amount1);
+
+        (amount0, amount1)
+    }
+
+    /// Set the protocol fee vesting window in seconds (0 = collect directly)
+    /// 
+    /// Disabling vesting does not cancel an existing schedule; it keeps
+    /// releasing through `release_vested`.
+    pub fn set_protocol_fee_vesting(env: Env, admin: Address, duration_secs: u64) {
+        require_admin(&env, &admin);
+
+        write_vesting_duration(&env, duration_secs);
+        emit_vesting_set(&env, duration_secs);
+    }
+
+    /// Set the total swap fee in basis points, 1..=`MAX_SWAP_FEE_BPS`
+    /// 
+    /// Applies to swaps, quotes and flash loans from now on; fees already
+    /// credited to LPs and the protocol are not touched. A fee other than the
+    /// canonical one for the pool's tick spacing marks the pool
+    /// `FeeTier::Custom`; `set_fee_tier` moves it back onto its tier.
+    pub fn set_fee_bps(env: Env, admin: Address, new_fee_bps: u32) {
+        let mut config = require_admin(&env, &admin);
+
+        if new_fee_bps == 0 || new_fee_bps > MAX_SWAP_FEE_BPS {
+            panic_with_error!(env, PoolError::InvalidFee);
+        }
+
+        let fee_tier = FeeTier::from_params(new_fee_bps, read_pool_state(&env).tick_spacing);
+        write_swap_fee(&env, &mut config, new_fee_bps, fee_tier);
+    }
+
+    /// Set the swap fee to that of a canonical fee tier
+    /// 
+    /// The tick spacing is fixed at initialization, so the tier must be the
+    /// one for the pool's spacing; anything else, `Custom` included, fails
+    /// with `InvalidFeeTier`. Restores the canonical fee of a pool left on
+    /// `Custom`.
+    pub fn set_fee_tier(env: Env, admin: Address, fee_tier: FeeTier) {
+        let mut config = require_admin(&env, &admin);
+
+        let tick_spacing = read_pool_state(&env).tick_spacing;
+        let new_fee_bps = match fee_tier.params() {
+            Some((fee_bps, spacing)) if spacing == tick_spacing => fee_bps,
+            _ => panic_with_error!(env, PoolError::InvalidFeeTier),
+        };
+        write_swap_fee(&env, &mut config, new_fee_bps, fee_tier);
+    }
+
+    /// Set the protocol's share of swap fees in basis points
+    /// 
+    /// Applies to swaps from now on; fees already split between LPs and the
+    /// protocol are not touched.
+    pub fn set_protocol_fee(env: Env, admin: Address, protocol_fee_bps: u32) {
+        let mut config = require_admin(&env, &admin);
+
+        if protocol_fee_bps > MAX_PROTOCOL_FEE_BPS {
+            panic_with_error!(env, PoolError::InvalidProtocolFee);
+        }
+
+        config.protocol_fee_bps = protocol_fee_bps;
+        write_pool_config(&env, &config);
+
+        emit_protocol_fee_set(&env, protocol_fee_bps);
+    }
+
+    /// Set deposit and withdrawal fees on LP principal (0 disables each)
+    pub fn set_liquidity_fees(env: Env, admin: Address, deposit_fee_bps: u32, withdraw_fee_bps: u32) {
+        let mut config = require_admin(&env, &admin);
+
+        if deposit_fee_bps > MAX_LIQUIDITY_FEE_BPS || withdraw_fee_bps > MAX_LIQUIDITY_FEE_BPS {
+            panic_with_error!(env, PoolError::InvalidLiquidityFee);
+        }
+
+        config.deposit_fee_bps = deposit_fee_bps;
+        config.withdraw_fee_bps = withdraw_fee_bps;
+        write_pool_config(&env, &config);
+
+        emit_liquidity_fees_set(&env, deposit_fee_bps, withdraw_fee_bps);
+    }
+
+    /// Configure the withdrawal queue
+    /// 
+    /// Each position may remove at most `threshold` liquidity per
+    /// `interval_secs`, directly or through processed tranches; larger exits
+    /// go through `request_withdrawal`. A threshold of 0 disables the queue.
+    pub fn set_withdrawal_queue(env: Env, admin: Address, threshold: i128, interval_secs: u64) {
+        ensure(&env, validate::amount_non_negative(threshold));
+
+        require_admin(&env, &admin);
+
+        write_withdrawal_queue_config(&env, &WithdrawalQueueConfig {
+            threshold,
+            interval: interval_secs,
+        });
+
+        emit_withdrawal_queue_set(&env, threshold, interval_secs);
+    }
+
+    /// Pause or unpause swaps and liquidity additions
+    /// 
+    /// Emergency stop: while paused, swaps, adds (including
+    /// `add_liquidity_from_amounts` and `swap_and_add`) and `flash` loans
+    /// fail with `PoolPaused`. Removing liquidity, withdrawals and fee
+    /// collection stay available so LPs can always exit. Emits `pause` or
+    /// `unpause`.
+    pub fn set_paused(env: Env, admin: Address, paused: bool) {
+        require_admin(&env, &admin);
+
+        write_paused(&env, paused);
+        emit_paused_set(&env, &admin, paused);
+    }
+
+    /// Whether swaps and liquidity additions are paused
+    pub fn is_paused(env: Env) -> bool {
+        storage::is_paused(&env)
+    }
+
+    /// Set how far in the future a deadline may lie, in seconds
+    /// 
+    /// Deadlines later than `now + max_deadline_window_secs` are rejected
+    /// with `DeadlineTooFar`; a deadline exactly at the bound is accepted.
+    /// `0` still means no deadline, and a window of 0 removes the ceiling.
+    pub fn set_max_deadline_window(env: Env, admin: Address, max_deadline_window_secs: u64) {
+        require_admin(&env, &admin);
+
+        write_max_deadline_window(&env, max_deadline_window_secs);
+        emit_deadline_window_set(&env, max_deadline_window_secs);
+    }
+
+    /// Get the deadline ceiling window in seconds (0 = no ceiling)
+    pub fn get_max_deadline_window(env: Env) -> u64 {
+        read_max_deadline_window(&env)
+    }
+
+    /// Enable or disable the tick accounting audit on full burns
+    /// 
+    /// Every burn traps with `TickAccountingMismatch` if a boundary tick
+    /// holds less than the liquidity removed. When enabled, burning a
+    /// position down to zero also traps if either boundary tick is left
+    /// with more net than gross liquidity. Catches bookkeeping bugs at the
+    /// burn instead of at a later wrong cross. On by default, so testnet
+    /// deployments get it without a setup step; mainnet pools may turn it
+    /// off to save the extra tick reads.
+    pub fn set_tick_accounting_checks(env: Env, admin: Address, enabled: bool) {
+        require_admin(&env, &admin);
+
+        write_tick_accounting_checks(&env, enabled);
+        emit_tick_audit_set(&env, enabled);
+    }
+
+    /// Whether the tick accounting audit runs on full burns
+    pub fn get_tick_accounting_checks(env: Env) -> bool {
+        is_tick_accounting_checked(&env)
+    }
+
+    /// Get the withdrawal queue settings
+    pub fn get_withdrawal_queue(env: Env) -> WithdrawalQueueConfig {
+        read_withdrawal_queue_config(&env)
+    }
+
+    /// Get the protocol fee vesting schedule, if any
+    pub fn get_protocol_fee_vesting(env: Env) -> Option<VestingSchedule> {
+        read_vesting_schedule(&env)
+    }
+
+    // ========================================================
+    // RESERVE TRACKING
+    // ========================================================
+
+    /// Enable or disable reserve tracking for rebasing tokens
+    /// 
+    /// Rebasing tokens change the pool's balance without a transfer, which
+    /// the pool's accounting never sees. They are only supported with
+    /// tracking enabled and `sync_reserves` called periodically. Enabling
+    /// snapshots the current balances as the tracked reserves.
+    pub fn set_reserve_tracking(env: Env, admin: Address, enabled: bool) {
+        require_admin(&env, &admin);
+
+        if enabled {
+            let pool = read_pool_state(&env);
+            let pool_addr = env.current_contract_address();
+
+            write_tracked_reserves(&env, &TrackedReserves {
+                reserve0: token::Client::new(&env, &pool.token0).balance(&pool_addr),
+                reserve1: token::Client::new(&env, &pool.token1).balance(&pool_addr),
+            });
+        } else {
+            remove_tracked_reserves(&env);
+        }
+
+        emit_reserve_tracking_set(&env, enabled);
+    }
+
+    /// Reconcile tracked reserves with the pool's actual token balances
+    /// 
+    /// A surplus (positive rebase) is credited to the protocol fees. A
+    /// deficit (negative rebase) is absorbed by accrued protocol fees where
+    /// possible; any remainder is left as a shortfall against the tracked
+    /// reserves and flagged in the `reserve_sync` event. Callable by anyone.
+    /// 
+    /// # Returns
+    /// (delta0, delta1) = actual balance - tracked reserve before the sync;
+    /// negative values mean the pool holds less than it owes
+    pub fn sync_reserves(env: Env) -> (i128, i128) {
+        let mut reserves = match read_tracked_reserves(&env) {
+            Some(reserves) => reserves,
+            None => panic_with_error!(env, PoolError::ReserveTrackingDisabled),
+        };
+
+        let mut pool = read_pool_state(&env);
+        let pool_addr = env.current_contract_address();
+
+        let balance0 = token::Client::new(&env, &pool.token0).balance(&pool_addr);
+        let balance1 = token::Client::new(&env, &pool.token1).balance(&pool_addr);
+
+        let delta0 = balance0 - reserves.reserve0;
+        let delta1 = balance1 - reserves.reserve1;
+
+        reserves.reserve0 = settle_rebase(&mut pool.protocol_fees_0, reserves.reserve0, delta0);
+        reserves.reserve1 = settle_rebase(&mut pool.protocol_fees_1, reserves.reserve1, delta1);
+
+        write_pool_state(&env, &pool);
+        write_tracked_reserves(&env, &reserves);
+
+        emit_reserve_sync(&env, delta0, delta1);
+
+        (delta0, delta1)
+    }
+
+    /// Get the tracked reserves (None if reserve tracking is disabled)
+    pub fn get_tracked_reserves(env: Env) -> Option<TrackedReserves> {
+        read_tracked_reserves(&env)
+    }
+}
+
+// ============================================================
+// INTERNAL HELPERS
+// ============================================================
+
+/// Panic if the admin has paused swaps and liquidity additions
+fn check_not_paused(env: &Env) {
+    if is_paused(env) {
+        panic_with_error!(env, PoolError::PoolPaused);
+    }
+}
+
+/// Direction and output token of a route leg entering `pool` with `token_in`
+fn route_leg(env: &Env, pool: &PoolState, token_in: &Address) -> (bool, Address) {
+    if *token_in == pool.token0 {
+        (true, pool.token1.clone())
+    } else if *token_in == pool.token1 {
+        (false, pool.token0.clone())
+    } else {
+        panic_with_error!(env, PoolError::InvalidToken)
+    }
+}
+
+/// Order two token addresses as (token0, token1)
+fn sort_tokens(token_a: Address, token_b: Address) -> (Address, Address) {
+    if token_a < token_b {
+        (token_a, token_b)
+    } else {
+        (token_b, token_a)
+    }
+}
+
+/// Store a new swap fee and its tier, emitting the old and new fee
+fn write_swap_fee(env: &Env, config: &mut PoolConfig, fee_bps: u32, fee_tier: FeeTier) {
+    let old_fee_bps = config.fee_bps;
+    config.fee_bps = fee_bps;
+    config.fee_tier = fee_tier;
+    write_pool_config(env, config);
+
+    emit_swap_fee_set(env, old_fee_bps, fee_bps);
+}
+
+/// Validate initialization parameters and write the initial pool config,
+/// state and oracle observation
+fn init_pool(
+    env: &Env,
+    admin: Address,
+    token_a: Address,
+    token_b: Address,
+    fee_tier: FeeTier,
+    fee_bps: u32,
+    protocol_fee_bps: u32,
+    sqrt_price_x64: u128,
+    current_tick: i32,
+    tick_spacing: i32,
+) {
+    ensure(env, validate::sqrt_price_in_bounds(sqrt_price_x64));
+    ensure(env, validate::address_not_self(env, &token_a));
+    ensure(env, validate::address_not_self(env, &token_b));
+
+    admin.require_auth();
+
+    if is_initialized(env) {
+        panic_with_error!(env, PoolError::AlreadyInitialized);
+    }
+
+    if fee_bps == 0 || fee_bps > MAX_FEE_BPS {
+        panic_with_error!(env, PoolError::InvalidFee);
+    }
+
+    if protocol_fee_bps > MAX_PROTOCOL_FEE_BPS {
+        panic_with_error!(env, PoolError::InvalidProtocolFee);
+    }
+
+    if tick_spacing <= 0 {
+        panic_with_error!(env, PoolError::InvalidTickSpacing);
+    }
+
+    if fee_tier.params() != Some((fee_bps, tick_spacing)) {
+        panic_with_error!(env, PoolError::InvalidFeeTier);
+    }
+
+    // The tick must be the one the price is in; a mismatched tick would
+    // misplace every in-range check until a swap re-derived it
+    if !is_valid_tick(current_tick) || current_tick != get_tick_at_sqrt_ratio(env, sqrt_price_x64) {
+        panic_with_error!(env, PoolError::InvalidTick);
+    }
+
+    if token_a == token_b {
+        panic_with_error!(env, PoolError::SameToken);
+    }
+
+    // Config and state both hold the sorted pair, so every entrypoint sees
+    // the same token0/token1 whatever order the tokens were passed in
+    let (token0, token1) = sort_tokens(token_a, token_b);
+
+    let config = PoolConfig {
+        admin,
+        token_a: token0.clone(),
+        token_b: token1.clone(),
+        fee_bps,
+        protocol_fee_bps,
+        deposit_fee_bps: 0,
+        withdraw_fee_bps: 0,
+        fee_tier,
+    };
+    write_pool_config(env, &config);
+
+    init_pool_state(env, sqrt_price_x64, current_tick, tick_spacing, token0, token1);
+    set_initialized(env);
+
+    write_observation(env, &mut read_pool_state(env));
+
+    emit_pool_init(env, sqrt_price_x64, current_tick, tick_spacing);
+    emit_initialized(env, fee_bps, tick_spacing);
+}
+
+/// Check a range already validated with `validate::tick_range` is aligned
+/// to the tick spacing, and return it
+/// 
+/// Misaligned ticks are rejected rather than snapped, so the position key
+/// is always exactly the range the caller passed.
+fn aligned_range(env: &Env, lower: i32, upper: i32, tick_spacing: i32) -> (i32, i32) {
+    ensure(env, validate::ticks_aligned(lower, upper, tick_spacing));
+    (lower, upper)
+}
+
+/// Panic if `deadline` is set (non-zero) and the ledger time is past it,
+/// or if it lies beyond the admin's deadline window
+fn check_deadline(env: &Env, deadline: u64) {
+    let now = env.ledger().timestamp();
+
+    if deadline != 0 && now > deadline {
+        panic_with_error!(env, PoolError::DeadlineExpired);
+    }
+
+    ensure(env, validate::deadline_within_window(now, deadline, read_max_deadline_window(env)));
+}
+
+/// Mint `liquidity` into a position
+/// 
+/// Accrues liquidity mining rewards, then updates ticks (and the tick
+/// bitmap), the position's fee and reward checkpoints and active liquidity. The caller persists `pos` and `pool` and collects the
+/// deposit.
+fn mint_liquidity(
+    env: &Env,
+    pool: &mut PoolState,
+    pos: &mut Position,
+    lower: i32,
+    upper: i32,
+    liquidity: i128,
+) {
+    accrue_rewards(env, pool);
+
+    // Update ticks FIRST to initialize fee_growth_outside properly
+    let flipped_lower = update_tick(env, lower, pool.current_tick, liquidity,
+        pool.fee_growth_global_0, pool.fee_growth_global_1, pool.reward_growth_global, false);
+    let flipped_upper = update_tick(env, upper, pool.current_tick, liquidity,
+        pool.fee_growth_global_0, pool.fee_growth_global_1, pool.reward_growth_global, true);
+
+    if flipped_lower {
+        set_tick_initialized(env, lower, pool.tick_spacing, true);
+    }
+    if flipped_upper {
+        set_tick_initialized(env, upper, pool.tick_spacing, true);
+    }
+
+    // Get fee growth inside AFTER ticks are initialized
+    let (inside_0, inside_1) = get_fee_growth_inside(
+        env, lower, upper, pool.current_tick,
+        pool.fee_growth_global_0, pool.fee_growth_global_1,
+    );
+    let reward_inside = get_reward_growth_inside(
+        env, lower, upper, pool.current_tick, pool.reward_growth_global,
+    );
+
+    modify_position(env, pos, liquidity, inside_0, inside_1, reward_inside);
+
+    // Update pool liquidity if position is in range
+    if pool.current_tick >= lower && pool.current_tick < upper {
+        let liquidity_before = pool.liquidity;
+        pool.liquidity = pool.liquidity.saturating_add(liquidity);
+        emit_liquidity_change(env, liquidity_before, pool.liquidity, LiquidityChange::Mint);
+    }
+}
+
+/// Run a validated exact-input or exact-output swap for `caller`
+/// 
+/// `caller` pays the input and `recipient` receives the output. Persists
+/// the pool state, settles both transfers and emits `swap`. Unless
+/// `allow_partial`, a swap that runs out of liquidity before its price
+/// limit reverts with `IncompleteSwap`. A positive `max_amount_in` reverts
+/// with `SlippageExceeded` if the input charged exceeds it.
+fn execute_swap(
+    env: &Env,
+    caller: &Address,
+    recipient: &Address,
+    amount_specified: i128,
+    zero_for_one: bool,
+    min_amount_out: i128,
+    max_amount_in: i128,
+    sqrt_price_limit_x64: u128,
+    allow_partial: bool,
+) -> SwapResult {
+    check_not_paused(env);
+
+    let config = read_pool_config(env);
+    let mut pool = read_pool_state(env);
+
+    let fee_bps = config.fee_bps;
+    let protocol_fee_bps = config.protocol_fee_bps;
+
+    let validation = validate_and_preview_swap(
+        env, &pool, amount_specified, min_amount_out, max_amount_in,
+        zero_for_one, sqrt_price_limit_x64, fee_bps,
+    );
+
+    if let Err(e) = validation {
+        panic_with_error!(env, PoolError::from_swap_symbol(&e));
+    }
+
+    write_observation(env, &mut pool);
+    accrue_rewards(env, &mut pool);
+
+    let outcome = engine_swap(
+        env, &mut pool, amount_specified, zero_for_one,
+        sqrt_price_limit_x64, fee_bps, protocol_fee_bps,
+    );
+    let (amount_in_total, amount_out_total) = (outcome.amount_in, outcome.amount_out);
+
+    if outcome.liquidity_exhausted && !allow_partial {
+        panic_with_error!(env, PoolErrorExt::IncompleteSwap);
+    }
+    if max_amount_in > 0 && amount_in_total > max_amount_in {
+        panic_with_error!(env, PoolError::SlippageExceeded);
+    }
+
+    write_pool_state(env, &pool);
+
+    let pool_addr = env.current_contract_address();
+
+    // Transfer tokens
+    if zero_for_one {
+        token::Client::new(env, &pool.token0).transfer(caller, &pool_addr, &amount_in_total);
+        token::Client::new(env, &pool.token1).transfer(&pool_addr, recipient, &amount_out_total);
+    } else {
+        token::Client::new(env, &pool.token1).transfer(caller, &pool_addr, &amount_in_total);
+        token::Client::new(env, &pool.token0).transfer(&pool_addr, recipient, &amount_out_total);
+    }
+
+    if zero_for_one {
+        track_reserves(env, amount_in_total, -amount_out_total);
+    } else {
+        track_reserves(env, -amount_out_total, amount_in_total);
+    }
+
+    emit_swap(
+        env, caller, zero_for_one, amount_in_total, amount_out_total, outcome.fee,
+        outcome.ticks_crossed, outcome.amount_remaining, pool.sqrt_price_x64, pool.current_tick,
+    );
+
+    SwapResult {
+        amount_in: amount_in_total,
+        amount_out: amount_out_total,
+        fee_amount: outcome.fee,
+        ticks_crossed: outcome.ticks_crossed,
+        amount_remaining: outcome.amount_remaining,
+        current_tick: pool.current_tick,
+        sqrt_price_x64: pool.sqrt_price_x64,
+        fully_filled: is_fully_filled(amount_specified, amount_in_total, amount_out_total),
+    }
+}
+
+/// Size liquidity from desired amounts, mint it into `pos` and collect the
+/// deposit from `payer`
+/// 
+/// `lower`/`upper` must already be aligned to the spacing. Persists the pool
+/// state but not `pos`; the caller writes it under its own key.
+/// 
+/// # Returns
+/// (liquidity, amount0, amount1) credited to the position
+fn deposit_liquidity(
+    env: &Env,
+    payer: &Address,
+    pos: &mut Position,
+    lower: i32,
+    upper: i32,
+    amount0_desired: i128,
+    amount1_desired: i128,
+    amount0_min: i128,
+    amount1_min: i128,
+) -> (i128, i128, i128) {
+    check_not_paused(env);
+
+    let config = read_pool_config(env);
+    let mut pool = read_pool_state(env);
+    let pool_addr = env.current_contract_address();
+
+    if lower >= upper {
+        panic_with_error!(env, PoolError::InvalidTickRange);
+    }
+
+    let sqrt_lower = get_sqrt_ratio_at_tick(env, lower);
+    let sqrt_upper = get_sqrt_ratio_at_tick(env, upper);
+
+    let liquidity = liquidity_for_deposit(
+        env, &config, &pool, sqrt_lower, sqrt_upper, amount0_desired, amount1_desired,
+    );
+
+    if liquidity < MIN_LIQUIDITY {
+        panic_with_error!(env, PoolError::LiquidityTooLow);
+    }
+
+    // Amounts charged are always derived from the liquidity credited,
+    // never taken from the caller; a position must be backed by a deposit.
+    // Rounded up so the deposit always covers what a burn can pay out
+    let (amount0_actual, amount1_actual) = get_amounts_for_liquidity_rounding(
+        env, liquidity, sqrt_lower, sqrt_upper, pool.sqrt_price_x64, true,
+    );
+
+    if amount0_actual <= 0 && amount1_actual <= 0 {
+        panic_with_error!(env, PoolError::UnfundedPosition);
+    }
+
+    if amount0_actual < amount0_min || amount1_actual < amount1_min {
+        panic_with_error!(env, PoolError::SlippageExceeded);
+    }
+
+    write_observation(env, &mut pool);
+
+    mint_liquidity(env, &mut pool, pos, lower, upper, liquidity);
+
+    // Deposit fee is charged on top of the principal credited to the position
+    let deposit_fee0 = apply_bps_ceil(env, amount0_actual, config.deposit_fee_bps);
+    let deposit_fee1 = apply_bps_ceil(env, amount1_actual, config.deposit_fee_bps);
+    pool.protocol_fees_0 = pool.protocol_fees_0.saturating_add(deposit_fee0 as u128);
+    pool.protocol_fees_1 = pool.protocol_fees_1.saturating_add(deposit_fee1 as u128);
+
+    write_pool_state(env, &pool);
+
+    // Transfer tokens
+    let amount0_in = amount0_actual + deposit_fee0;
+    let amount1_in = amount1_actual + deposit_fee1;
+    if amount0_in > 0 {
+        token::Client::new(env, &pool.token0).transfer(payer, &pool_addr, &amount0_in);
+    }
+    if amount1_in > 0 {
+        token::Client::new(env, &pool.token1).transfer(payer, &pool_addr, &amount1_in);
+    }
+    track_reserves(env, amount0_in, amount1_in);
+
+    emit_add_liquidity(env, payer, lower, upper, liquidity, amount0_actual, amount1_actual);
+
+    (liquidity, amount0_actual, amount1_actual)
+}
+
+/// Liquidity a deposit of at most the desired amounts can credit
+/// 
+/// Reserves the deposit fee out of the desired amounts so that principal
+/// plus fee never exceeds what the owner offered, and keeps one unit of
+/// headroom per side for the deposit being rounded up.
+fn liquidity_for_deposit(
+    env: &Env,
+    config: &PoolConfig,
+    pool: &PoolState,
+    sqrt_lower: u128,
+    sqrt_upper: u128,
+    amount0_desired: i128,
+    amount1_desired: i128,
+) -> i128 {
+    let amount0_budget = deduct_bps(env, amount0_desired.max(0), config.deposit_fee_bps);
+    let amount1_budget = deduct_bps(env, amount1_desired.max(0), config.deposit_fee_bps);
+
+    get_liquidity_for_amounts(
+        env, amount0_budget - 1, amount1_budget - 1,
+        sqrt_lower, sqrt_upper, pool.sqrt_price_x64,
+    )
+}
+
+/// Split a flash loan fee into LP fee growth and the protocol share
+/// 
+/// Without active liquidity there is nobody to credit, so the whole fee
+/// goes to the protocol.
+/// 
+/// # Returns
+/// (fee_growth_delta, protocol_fee)
+fn split_flash_fee(env: &Env, pool: &PoolState, fee: i128, protocol_fee_bps: u32) -> (u128, i128) {
+    if fee <= 0 {
+        return (0, 0);
+    }
+    if pool.liquidity <= 0 {
+        return (0, fee);
+    }
+
+    let protocol_fee = if protocol_fee_bps > 0 { apply_bps(env, fee, protocol_fee_bps) } else { 0 };
+    let lp_fee = fee - protocol_fee;
+
+    // Rounded down: LPs can never be credited more than was paid in
+    let growth = mul_div_checked(lp_fee as u128, Q64, pool.liquidity as u128)
+        .unwrap_or_else(|| panic_with_error!(env, PoolError::InvalidFeeCalculation));
+    (growth, protocol_fee)
+}
+
+/// Burn `liquidity_delta` from `pos` outside the withdrawal queue
+/// 
+/// Counts the removal against the position's per-interval queue allowance
+/// stored under `window` (None = exempt from the queue), checks the
+/// position's liquidity, then burns and persists the pool state. The caller
+/// persists `pos` and pays the returned (amount0, amount1) with
+/// `pay_withdrawal`.
+fn withdraw_liquidity(
+    env: &Env,
+    pos: &mut Position,
+    lower: i32,
+    upper: i32,
+    liquidity_delta: i128,
+    window: Option<&DataKey>,
+) -> (i128, i128) {
+    let config = read_pool_config(env);
+    let mut pool = read_pool_state(env);
+
+    let queue = read_withdrawal_queue_config(env);
+    if let (true, Some(key)) = (queue.threshold > 0, window) {
+        let mut current = current_withdrawal_window(env, &queue, key);
+        if liquidity_delta > queue.threshold - current.removed {
+            panic_with_error!(env, PoolError::WithdrawalAboveThreshold);
+        }
+        current.removed += liquidity_delta;
+        write_withdrawal_window(env, key, &current);
+    }
+
+    write_observation(env, &mut pool);
+
+    if liquidity_delta > pos.liquidity {
+        panic_with_error!(env, PoolError::InsufficientLiquidity);
+    }
+
+    let amounts = burn_liquidity(env, &config, &mut pool, pos, lower, upper, liquidity_delta);
+
+    write_pool_state(env, &pool);
+
+    amounts
+}
+
+/// A position's withdrawal window as of now
+/// 
+/// Starts a fresh, empty window once `interval` has passed since the stored
+/// one opened.
+fn current_withdrawal_window(env: &Env, queue: &WithdrawalQueueConfig, key: &DataKey) -> WithdrawalWindow {
+    let now = env.ledger().timestamp();
+    match read_withdrawal_window(env, key) {
+        Some(window) if now < window.start.saturating_add(queue.interval) => window,
+        _ => WithdrawalWindow { start: now, removed: 0 },
+    }
+}
+
+/// Transfer a withdrawal to the position's `owner` and emit `remove_liquidity`
+fn pay_withdrawal(
+    env: &Env,
+    owner: &Address,
+    lower: i32,
+    upper: i32,
+    liquidity_delta: i128,
+    amount0: i128,
+    amount1: i128,
+) -> (i128, i128) {
+    transfer_out(env, &read_pool_state(env), owner, amount0, amount1);
+
+    emit_remove_liquidity(env, owner, lower, upper, liquidity_delta, amount0, amount1);
+
+    (amount0, amount1)
+}
+
+/// Load an id-based position, panicking if it does not exist
+fn load_position_record(env: &Env, position_id: u64) -> PositionRecord {
+    read_position_record(env, position_id)
+        .unwrap_or_else(|| panic_with_error!(env, PoolError::PositionNotFound))
+}
+
+/// Load an id-based position and check that `owner` owns it
+fn load_owned_position(env: &Env, owner: &Address, position_id: u64) -> PositionRecord {
+    let record = load_position_record(env, position_id);
+    if record.owner != *owner {
+        panic_with_error!(env, PoolError::NotPositionOwner);
+    }
+    record
+}
+
+/// (lower, upper, info) for each of `owner`'s positions on `ranges`
+fn owner_positions(env: &Env, owner: &Address, ranges: &Vec<(i32, i32)>) -> Vec<(i32, i32, PositionInfo)> {
+    let pool = read_pool_state(env);
+
+    let mut positions = Vec::new(env);
+    for (lower, upper) in ranges.iter() {
+        let pos = read_position(env, owner, lower, upper);
+        positions.push_back((lower, upper, position_info(env, &pool, &pos, lower, upper)));
+    }
+
+    positions
+}
+
+/// Principal and pending fees of a position at the current price
+fn position_info(env: &Env, pool: &PoolState, pos: &Position, lower: i32, upper: i32) -> PositionInfo {
+    if !has_liquidity(pos) {
+        return PositionInfo {
+            liquidity: 0,
+            amount0: 0,
+            amount1: 0,
+            fees_owed_0: pos.tokens_owed_0,
+            fees_owed_1: pos.tokens_owed_1,
+            lifetime_fees_0: pos.lifetime_fees_0,
+            lifetime_fees_1: pos.lifetime_fees_1,
+        };
+    }
+
+    let sqrt_lower = get_sqrt_ratio_at_tick(env, lower);
+    let sqrt_upper = get_sqrt_ratio_at_tick(env, upper);
+
+    let (amount0, amount1) = get_amounts_for_liquidity(
+        env, pos.liquidity, sqrt_lower, sqrt_upper, pool.sqrt_price_x64,
+    );
+
+    let (inside_0, inside_1) = get_fee_growth_inside(
+        env, lower, upper, pool.current_tick,
+        pool.fee_growth_global_0, pool.fee_growth_global_1,
+    );
+
+    let (pending_0, pending_1) = calculate_pending_fees(pos, inside_0, inside_1);
+
+    PositionInfo {
+        liquidity: pos.liquidity,
+        amount0,
+        amount1,
+        fees_owed_0: pos.tokens_owed_0.saturating_add(pending_0),
+        fees_owed_1: pos.tokens_owed_1.saturating_add(pending_1),
+        lifetime_fees_0: pos.lifetime_fees_0.saturating_add(pending_0),
+        lifetime_fees_1: pos.lifetime_fees_1.saturating_add(pending_1),
+    }
+}
+
+/// Burn `liquidity_delta` from a position and settle its principal
+/// 
+/// Accrues liquidity mining rewards, updates fees, ticks and active
+/// liquidity, skims the withdrawal fee into protocol fees and returns the
+/// payout (amount0, amount1). The caller persists `pos` and `pool` and
+/// transfers the payout.
+fn burn_liquidity(
+    env: &Env,
+    config: &PoolConfig,
+    pool: &mut PoolState,
+    pos: &mut Position,
+    lower: i32,
+    upper: i32,
+    liquidity_delta: i128,
+) -> (i128, i128) {
+    accrue_rewards(env, pool);
+
+    let (inside_0, inside_1) = get_fee_growth_inside(
+        env, lower, upper, pool.current_tick,
+        pool.fee_growth_global_0, pool.fee_growth_global_1,
+    );
+    let reward_inside = get_reward_growth_inside(
+        env, lower, upper, pool.current_tick, pool.reward_growth_global,
+    );
+
+    // Boundary gross liquidity before the burn, for the full-burn audit
+    let audit = if liquidity_delta == pos.liquidity && is_tick_accounting_checked(env) {
+        Some((read_tick_info(env, lower).liquidity_gross, read_tick_info(env, upper).liquidity_gross))
+    } else {
+        None
+    };
+
+    modify_position(env, pos, -liquidity_delta, inside_0, inside_1, reward_inside);
+
+    let flipped_lower = update_tick(env, lower, pool.current_tick, -liquidity_delta,
+        pool.fee_growth_global_0, pool.fee_growth_global_1, pool.reward_growth_global, false);
+    let flipped_upper = update_tick(env, upper, pool.current_tick, -liquidity_delta,
+        pool.fee_growth_global_0, pool.fee_growth_global_1, pool.reward_growth_global, true);
+
+    if let Some((gross_lower, gross_upper)) = audit {
+        ensure(env, validate::tick_accounting(gross_lower, liquidity_delta, &read_tick_info(env, lower)));
+        ensure(env, validate::tick_accounting(gross_upper, liquidity_delta, &read_tick_info(env, upper)));
+    }
+
+    if flipped_lower {
+        set_tick_initialized(env, lower, pool.tick_spacing, false);
+    }
+    if flipped_upper {
+        set_tick_initialized(env, upper, pool.tick_spacing, false);
+    }
+
+    if pool.current_tick >= lower && pool.current_tick < upper {
+        let liquidity_before = pool.liquidity;
+        pool.liquidity = pool.liquidity.saturating_sub(liquidity_delta);
+        emit_liquidity_change(env, liquidity_before, pool.liquidity, LiquidityChange::Burn);
+    }
+
+    let sqrt_lower = get_sqrt_ratio_at_tick(env, lower);
+    let sqrt_upper = get_sqrt_ratio_at_tick(env, upper);
+
+    let (principal0, principal1) = get_amounts_for_liquidity(
+        env, liquidity_delta, sqrt_lower, sqrt_upper, pool.sqrt_price_x64,
+    );
+
+    // Withdrawal fee is skimmed from the principal paid out
+    let withdraw_fee0 = apply_bps_ceil(env, principal0, config.withdraw_fee_bps);
+    let withdraw_fee1 = apply_bps_ceil(env, principal1, config.withdraw_fee_bps);
+    pool.protocol_fees_0 = pool.protocol_fees_0.saturating_add(withdraw_fee0 as u128);
+    pool.protocol_fees_1 = pool.protocol_fees_1.saturating_add(withdraw_fee1 as u128);
+
+    (principal0 - withdraw_fee0, principal1 - withdraw_fee1)
+}
+
+/// Deduct up to the requested amounts from a position's `tokens_owed`,
+/// capped to the pool balance
+/// 
+/// Returns the amounts deducted; the caller persists `pos` and pays them
+/// with `pay_tokens_owed`.
+fn take_tokens_owed(
+    env: &Env,
+    pool: &PoolState,
+    pos: &mut Position,
+    amount0_requested: u128,
+    amount1_requested: u128,
+) -> (u128, u128) {
+    let pool_addr = env.current_contract_address();
+
+    let amount0 = pos.tokens_owed_0.min(amount0_requested);
+    let amount1 = pos.tokens_owed_1.min(amount1_requested);
+
+    // Cap fees to available balance
+    let pool_balance_0 = token::Client::new(env, &pool.token0).balance(&pool_addr) as u128;
+    let pool_balance_1 = token::Client::new(env, &pool.token1).balance(&pool_addr) as u128;
+
+    let amount0_capped = amount0.min(pool_balance_0);
+    let amount1_capped = amount1.min(pool_balance_1);
+
+    pos.tokens_owed_0 = pos.tokens_owed_0.saturating_sub(amount0_capped);
+    pos.tokens_owed_1 = pos.tokens_owed_1.saturating_sub(amount1_capped);
+
+    (amount0_capped, amount1_capped)
+}
+
+/// Transfer fees taken with `take_tokens_owed` from the position of
+/// `owner` on `[lower, upper]` to `recipient` and emit `collect`
+fn pay_tokens_owed(
+    env: &Env,
+    pool: &PoolState,
+    owner: &Address,
+    lower: i32,
+    upper: i32,
+    recipient: &Address,
+    amount0: u128,
+    amount1: u128,
+) -> (u128, u128) {
+    // Capped to a token balance, so both fit in i128
+    transfer_out(env, pool, recipient, amount0 as i128, amount1 as i128);
+
+    emit_collect(env, owner, lower, upper, recipient, amount0, amount1);
+
+    (amount0, amount1)
+}
+
+/// Bring a position's rewards up to the pool's reward growth and take
+/// everything owed
+/// 
+/// Fees are credited to `tokens_owed` along the way. The caller accrues
+/// rewards into `pool` first, persists `pos` and pays the amount with
+/// `pay_rewards`.
+fn take_rewards_owed(env: &Env, pool: &PoolState, pos: &mut Position, lower: i32, upper: i32) -> u128 {
+    let (inside_0, inside_1) = get_fee_growth_inside(
+        env, lower, upper, pool.current_tick,
+        pool.fee_growth_global_0, pool.fee_growth_global_1,
+    );
+    let reward_inside = get_reward_growth_inside(
+        env, lower, upper, pool.current_tick, pool.reward_growth_global,
+    );
+
+    update_position(env, pos, inside_0, inside_1, reward_inside);
+
+    let amount = pos.rewards_owed;
+    pos.rewards_owed = 0;
+    amount
+}
+
+/// Apply a rebase of `delta` to one token's protocol fees
+/// 
+/// Surplus is credited in full; a deficit is debited up to the accrued
+/// fees. Returns the new tracked reserve, which keeps any uncovered
+/// shortfall.
+fn settle_rebase(protocol_fees: &mut u128, reserve: i128, delta: i128) -> i128 {
+    if delta >= 0 {
+        *protocol_fees = protocol_fees.saturating_add(delta as u128);
+        return reserve + delta;
+    }
+
+    let covered = (*protocol_fees).min(delta.unsigned_abs());
+    *protocol_fees -= covered;
+    reserve - covered as i128
+}
+
+/// Transfer token0/token1 amounts from the pool to `to`
+fn transfer_out(env: &Env, pool: &PoolState, to: &Address, amount0: i128, amount1: i128) {
+    let pool_addr = env.current_contract_address();
+
+    if amount0 > 0 {
+        token::Client::new(env, &pool.token0).transfer(&pool_addr, to, &amount0);
+    }
+    if amount1 > 0 {
+        token::Client::new(env, &pool.token1).transfer(&pool_addr, to, &amount1);
     }
-}
\ No newline at end of file
+    track_reserves(env, -amount0, -amount1);
+}
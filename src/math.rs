@@ -8,11 +8,23 @@
 //! - Example: 1.0 = 2^64 = 18446744073709551616
 //! 
 //! ## Safety
-//! - Uses U256 for intermediate calculations to prevent overflow
-//! - All functions are designed to saturate rather than overflow
+//! - Products are formed in full 256-bit intermediates (two u128 limbs) and
+//!   divided exactly, so `mul_div` results are exact to the unit
+//! - Price helpers and liquidity sizing saturate (rounding liquidity
+//!   down is always safe); token amounts and `mul_q64` fail with
+//!   `Overflow` so a bad value aborts the call instead of being charged or
+//!   paid
+//! - Out-of-range ticks and prices and a non-positive spacing raise the
+//!   matching `PoolError`; a zero divisor raises `DivisionByZero`
+//! 
+//! ## Rounding
+//! Every result rounds in the pool's favor, as in Uniswap's SqrtPriceMath:
+//! amounts paid in (swap input, deposits) round up, amounts paid out (swap
+//! output, burns) round down, and liquidity minted rounds down.
 
-use soroban_sdk::{Env, U256};
+use soroban_sdk::{panic_with_error, Env, U256};
 
+use crate::error::{PoolError, PoolErrorExt};
 use crate::constants::{MIN_TICK, MAX_TICK, Q64, MIN_LIQUIDITY as CONST_MIN_LIQUIDITY};
 
 // ============================================================
@@ -27,6 +39,19 @@ const MIN_PRICE_DELTA: u128 = 1;
 /// Minimum liquidity (exported for use in lib.rs)
 pub const MIN_LIQUIDITY: i128 = CONST_MIN_LIQUIDITY;
 
+/// Sqrt price at MIN_TICK, i.e. `get_sqrt_ratio_at_tick(MIN_TICK)`
+pub const MIN_SQRT_RATIO: u128 = 2;
+
+/// Sqrt price at MAX_TICK, i.e. `get_sqrt_ratio_at_tick(MAX_TICK)`
+pub const MAX_SQRT_RATIO: u128 = 340269576638287423012608907232989748563;
+
+/// Lowest tick from which `get_sqrt_ratio_at_tick` is strictly increasing
+/// 
+/// Below it Q64.64 cannot tell adjacent ticks' sqrt prices apart, so ticks
+/// and sqrt prices only round-trip in [MIN_STRICT_TICK, MAX_TICK].
+#[allow(dead_code)]
+pub const MIN_STRICT_TICK: i32 = -689382;
+
 // ============================================================
 // TYPE CONVERSION HELPERS
 // ============================================================
@@ -43,66 +68,121 @@ fn u128_to_i128_saturating(x: u128) -> i128 {
     if x > i128::MAX as u128 { i128::MAX } else { x as i128 }
 }
 
+/// Convert a token amount to i128
+/// 
+/// # Panics
+/// `Overflow` if the amount does not fit, so an overflowed amount aborts
+/// the call instead of being charged or paid out as i128::MAX
+#[inline]
+fn amount_to_i128(env: &Env, x: u128) -> i128 {
+    if x > i128::MAX as u128 { panic_with_error!(env, PoolErrorExt::Overflow); }
+    x as i128
+}
+
 // ============================================================
 // Q64.64 ARITHMETIC
 // ============================================================
 
-/// Multiply two Q64.64 numbers, returning Q64.64 result
-/// Uses decomposition to avoid overflow
+/// Multiply two Q64.64 numbers, returning Q64.64 result (rounded down)
+/// 
+/// # Panics
+/// `Overflow` if the product does not fit in Q64.64
+#[allow(dead_code)]
 #[inline]
-pub fn mul_q64(a: u128, b: u128) -> u128 {
-    let a_hi = a >> 64;
-    let a_lo = a & 0xFFFFFFFFFFFFFFFF;
-    let b_hi = b >> 64;
-    let b_lo = b & 0xFFFFFFFFFFFFFFFF;
-    
-    let term_hh = a_hi * b_hi;
-    let term_hl = a_hi * b_lo;
-    let term_lh = a_lo * b_hi;
-    let term_ll = a_lo * b_lo;
-    
-    (term_hh << 64) + term_hl + term_lh + (term_ll >> 64)
+pub fn mul_q64(env: &Env, a: u128, b: u128) -> u128 {
+    mul_q64_checked(a, b).unwrap_or_else(|| panic_with_error!(env, PoolErrorExt::Overflow))
 }
 
-/// Divide in Q64.64 format: (a * 2^64) / b
-/// Returns Q64.64 result
+/// Multiply two Q64.64 numbers, or None if the product does not fit in
+/// Q64.64
+#[allow(dead_code)]
 #[inline]
-pub fn div_q64(a: u128, b: u128) -> u128 {
-    if b == 0 { return u128::MAX; }
-    
-    // Try direct calculation if no overflow
-    if a <= (u128::MAX >> 64) {
-        return (a << 64) / b;
+pub fn mul_q64_checked(a: u128, b: u128) -> Option<u128> {
+    let (hi, lo) = full_mul(a, b);
+    if hi >> 64 != 0 {
+        return None;
     }
-    
-    // Decompose for large values
-    let q = a / b;
-    let r = a % b;
-    
-    let q_part = q << 64;
-    let r_part = if r <= (u128::MAX >> 64) {
-        (r << 64) / b
-    } else {
-        ((r >> 32) << 32) / (b >> 32).max(1)
-    };
-    
-    q_part.saturating_add(r_part)
+    Some((hi << 64) | (lo >> 64))
 }
 
-/// Safe multiply-divide using U256 to prevent overflow
-/// Calculates: (a * b) / denominator
+/// Multiply two Q64.64 numbers, returning Q64.64 result rounded up
+/// 
+/// # Panics
+/// `Overflow` if the product does not fit in Q64.64
+#[allow(dead_code)]
+#[inline]
+pub fn mul_q64_round_up(env: &Env, a: u128, b: u128) -> u128 {
+    let (hi, lo) = full_mul(a, b);
+    let carry = u128::from(lo as u64 != 0);
+    if hi >> 64 != 0 {
+        panic_with_error!(env, PoolErrorExt::Overflow);
+    }
+    ((hi << 64) | (lo >> 64))
+        .checked_add(carry)
+        .unwrap_or_else(|| panic_with_error!(env, PoolErrorExt::Overflow))
+}
+
+/// Calculates floor((a * b) / denominator) with a 256-bit intermediate
+/// 
+/// Saturates at u128::MAX when the quotient does not fit.
+#[inline]
 pub fn mul_div(env: &Env, a: u128, b: u128, denominator: u128) -> u128 {
-    if denominator == 0 { panic!("mul_div: divide by zero"); }
+    mul_div_rounding(env, a, b, denominator, false)
+}
 
-    // Use U256 for intermediate calculation
-    let a_256 = U256::from_u128(env, a);
-    let b_256 = U256::from_u128(env, b);
-    let den_256 = U256::from_u128(env, denominator);
+/// Calculates floor((a * b) / denominator), or None if the quotient does
+/// not fit in u128 (including a zero denominator)
+pub fn mul_div_checked(a: u128, b: u128, denominator: u128) -> Option<u128> {
+    if denominator == 0 { return None; }
 
-    let product = a_256.mul(&b_256);
-    let result = product.div(&den_256);
+    let (hi, lo) = full_mul(a, b);
+    if hi >= denominator {
+        return None;
+    }
+    Some(div_256_by_128(hi, lo, denominator).0)
+}
 
-    result.to_u128().unwrap_or(u128::MAX)
+/// Full 256-bit product a * b
+/// 
+/// # Returns
+/// (high 128 bits, low 128 bits)
+#[inline]
+fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = 0xFFFFFFFFFFFFFFFF;
+
+    let (a_hi, a_lo) = (a >> 64, a & MASK);
+    let (b_hi, b_lo) = (b >> 64, b & MASK);
+
+    let ll = a_lo * b_lo;
+    let lh = a_lo * b_hi;
+    let hl = a_hi * b_lo;
+    let hh = a_hi * b_hi;
+
+    let mid = (ll >> 64) + (lh & MASK) + (hl & MASK);
+    let lo = (mid << 64) | (ll & MASK);
+    let hi = hh + (lh >> 64) + (hl >> 64) + (mid >> 64);
+
+    (hi, lo)
+}
+
+/// Calculates (a * b) / denominator with a 256-bit intermediate
+/// 
+/// Used by the price, amount and liquidity helpers. Rounds down, or up if
+/// `round_up`; saturates at u128::MAX.
+pub fn mul_div_rounding(env: &Env, a: u128, b: u128, denominator: u128, round_up: bool) -> u128 {
+    if denominator == 0 { panic_with_error!(env, PoolErrorExt::DivisionByZero); }
+
+    let (hi, lo) = full_mul(a, b);
+    if hi >= denominator {
+        return u128::MAX;
+    }
+
+    let (quotient, rem) = div_256_by_128(hi, lo, denominator);
+    if round_up && rem != 0 {
+        quotient.saturating_add(1)
+    } else {
+        quotient
+    }
 }
 
 /// Divide with rounding up
@@ -110,7 +190,7 @@ pub fn mul_div(env: &Env, a: u128, b: u128, denominator: u128) -> u128 {
 fn div_round_up(numerator: u128, denominator: u128) -> u128 {
     if denominator == 0 { return 0; }
     let result = numerator / denominator;
-    if numerator % denominator != 0 {
+    if !numerator.is_multiple_of(denominator) {
         result.saturating_add(1)
     } else {
         result
@@ -122,60 +202,246 @@ fn div_round_up(numerator: u128, denominator: u128) -> u128 {
 // ============================================================
 
 /// Snap a tick to the nearest lower multiple of spacing
-pub fn snap_tick_to_spacing(tick: i32, spacing: i32) -> i32 {
+pub fn snap_tick_to_spacing(env: &Env, tick: i32, spacing: i32) -> i32 {
     if spacing <= 0 {
-        panic!("tick_spacing must be positive");
+        panic_with_error!(env, PoolError::InvalidTickSpacing);
     }
     let rem = tick.rem_euclid(spacing);
     tick - rem
 }
 
+/// 1/sqrt(1.0001)^(2^i) in Q128.128 for i = 0..19
+/// 
+/// Same table as Uniswap V3's TickMath; 2^20 > MAX_TICK so 20 bits cover
+/// the whole tick range.
+const INV_SQRT_RATIOS_X128: [u128; 20] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
+];
+
+/// High 128 bits of the 256-bit product a * b
+#[inline]
+fn mul_shift_128(a: u128, b: u128) -> u128 {
+    full_mul(a, b).0
+}
+
+/// Divide the 256-bit value (hi, lo) by `d`, requires hi < d
+/// 
+/// # Returns
+/// (quotient, remainder)
+fn div_256_by_128(hi: u128, lo: u128, d: u128) -> (u128, u128) {
+    let mut rem = hi;
+    let mut quotient = 0u128;
+
+    for i in (0..128).rev() {
+        let carry = rem >> 127;
+        rem = (rem << 1) | ((lo >> i) & 1);
+        quotient <<= 1;
+        if carry != 0 || rem >= d {
+            rem = rem.wrapping_sub(d);
+            quotient |= 1;
+        }
+    }
+
+    (quotient, rem)
+}
+
 /// Convert tick to sqrt price in Q64.64 format
-/// Formula: sqrt(1.0001^tick) * 2^64
-pub fn get_sqrt_ratio_at_tick(tick: i32) -> u128 {
+/// Formula: sqrt(1.0001^tick) * 2^64, rounded up
+/// 
+/// Builds sqrt(1.0001)^-|tick| in Q128.128 from the bits of |tick|, then
+/// inverts it for positive ticks. Strictly increasing from MIN_STRICT_TICK
+/// upward; below that adjacent ticks can share a Q64.64 value.
+pub fn get_sqrt_ratio_at_tick(env: &Env, tick: i32) -> u128 {
     if !(MIN_TICK..=MAX_TICK).contains(&tick) { 
-        panic!("tick out of range"); 
+        panic_with_error!(env, PoolError::InvalidTick);
     }
     
     if tick == 0 { return ONE_X64; }
     
     let abs_tick = tick.unsigned_abs();
-    let mut ratio: u128 = ONE_X64;
-    
-    // Binary decomposition of sqrt(1.0001^tick)
-    // Each constant is sqrt(1.0001^(2^n)) * 2^64
-    if abs_tick & 0x1 != 0 { ratio = mul_q64(ratio, 18447666387855958016); }
-    if abs_tick & 0x2 != 0 { ratio = mul_q64(ratio, 18448588748116922368); }
-    if abs_tick & 0x4 != 0 { ratio = mul_q64(ratio, 18450433606991732736); }
-    if abs_tick & 0x8 != 0 { ratio = mul_q64(ratio, 18454123878217469952); }
-    if abs_tick & 0x10 != 0 { ratio = mul_q64(ratio, 18461506635090006016); }
-    if abs_tick & 0x20 != 0 { ratio = mul_q64(ratio, 18476281010653908992); }
-    if abs_tick & 0x40 != 0 { ratio = mul_q64(ratio, 18505849059060717568); }
-    if abs_tick & 0x80 != 0 { ratio = mul_q64(ratio, 18565033932859791360); }
-    if abs_tick & 0x100 != 0 { ratio = mul_q64(ratio, 18683636815981789184); }
-    if abs_tick & 0x200 != 0 { ratio = mul_q64(ratio, 18922376066158198784); }
-    if abs_tick & 0x400 != 0 { ratio = mul_q64(ratio, 19403906064415539200); }
-    if abs_tick & 0x800 != 0 { ratio = mul_q64(ratio, 20388321338895749120); }
-    if abs_tick & 0x1000 != 0 { ratio = mul_q64(ratio, 22486086334269071360); }
-    if abs_tick & 0x2000 != 0 { ratio = mul_q64(ratio, 27241267204663885824); }
-    if abs_tick & 0x4000 != 0 { ratio = mul_q64(ratio, 40198444615281172480); }
-    if abs_tick & 0x8000 != 0 { ratio = mul_q64(ratio, 87150709742682460160); }
-    if abs_tick & 0x10000 != 0 { ratio = mul_q64(ratio, 409916713094318874624); }
-    
-    // For negative ticks, invert the ratio
+
+    // None stands for 1.0, which does not fit in Q128.128
+    let mut ratio: Option<u128> = None;
+    for (bit, factor) in INV_SQRT_RATIOS_X128.iter().enumerate() {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = Some(match ratio {
+                Some(r) => mul_shift_128(r, *factor),
+                None => *factor,
+            });
+        }
+    }
+    let ratio = ratio.unwrap_or(u128::MAX);
+
     if tick < 0 {
-        if ratio == 0 { return u128::MAX; }
-        let numerator = ONE_X64.saturating_mul(ONE_X64); 
-        ratio = numerator / ratio;
+        // Q128.128 -> Q64.64
+        return div_round_up(ratio, ONE_X64);
     }
-    
-    ratio
+
+    // 2^192 / ratio, i.e. the inverse in Q64.64
+    let (quotient, rem) = div_256_by_128(1u128 << 64, 0, ratio);
+    if rem != 0 { quotient + 1 } else { quotient }
+}
+
+/// Convert sqrt price in Q64.64 format to a tick
+/// Returns the greatest tick whose sqrt price is <= `sqrt_price_x64`
+/// 
+/// Binary search over the tick range against `get_sqrt_ratio_at_tick`, so the
+/// result is always consistent with the forward conversion:
+/// `get_tick_at_sqrt_ratio(get_sqrt_ratio_at_tick(t)) == t` for every tick
+/// in [MIN_STRICT_TICK, MAX_TICK].
+/// 
+/// # Precision
+/// Below MIN_STRICT_TICK Q64.64 runs out of precision: runs of adjacent
+/// ticks share one sqrt price (thousands of them near MIN_TICK) and the
+/// highest tick of the run is returned, so converting a tick there and back
+/// can land above it. The result's price is still never above the input.
+pub fn get_tick_at_sqrt_ratio(env: &Env, sqrt_price_x64: u128) -> i32 {
+    if sqrt_price_x64 < get_sqrt_ratio_at_tick(env, MIN_TICK) {
+        panic_with_error!(env, PoolError::SqrtPriceOutOfBounds);
+    }
+
+    let mut low = MIN_TICK;
+    let mut high = MAX_TICK;
+
+    while low < high {
+        // Round up so the loop always makes progress when low = mid
+        let mid = low + (high - low + 1) / 2;
+        if get_sqrt_ratio_at_tick(env, mid) <= sqrt_price_x64 {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+
+    low
+}
+
+/// Sqrt price in Q64.64 for the raw price `numerator / denominator`
+/// 
+/// floor(sqrt(numerator / denominator) * 2^64), saturating at u128::MAX.
+/// Very large inputs are scaled down together first, which only costs
+/// precision far below one unit of the result.
+pub fn get_sqrt_price_for_ratio(env: &Env, numerator: &U256, denominator: &U256) -> u128 {
+    let zero = U256::from_u32(env, 0);
+    if *denominator == zero {
+        panic_with_error!(env, PoolErrorExt::DivisionByZero);
+    }
+
+    // Keep numerator << 128 inside 256 bits
+    let mut num = numerator.clone();
+    let mut den = denominator.clone();
+    while num.shr(128) != zero {
+        num = num.shr(1);
+        den = den.shr(1);
+    }
+    if den == zero {
+        return u128::MAX;
+    }
+
+    // price * 2^128, whose square root is the Q64.64 sqrt price
+    isqrt_u256(env, &num.shl(128).div(&den))
+}
+
+/// floor(sqrt(n)) by Newton's method
+/// 
+/// Starts from a power of two at or above the root, so the iterates
+/// decrease monotonically and the first one that stops decreasing is the
+/// floor. Takes a handful of divisions even for 256-bit inputs.
+pub fn isqrt_u256(env: &Env, n: &U256) -> u128 {
+    let zero = U256::from_u32(env, 0);
+    if *n == zero {
+        return 0;
+    }
+
+    let mut bits = 0;
+    let mut rest = n.clone();
+    for shift in [128, 64, 32, 16, 8, 4, 2, 1] {
+        if rest.shr(shift) != zero {
+            rest = rest.shr(shift);
+            bits += shift;
+        }
+    }
+    bits += 1;
+
+    let mut x = U256::from_u32(env, 1).shl(bits.div_ceil(2));
+    loop {
+        let next = x.add(&n.div(&x)).shr(1);
+        if next >= x {
+            break;
+        }
+        x = next;
+    }
+
+    // The root of a 256-bit value always fits in 128 bits
+    x.to_u128().unwrap_or(u128::MAX)
+}
+
+/// Price `(sqrt_price_x64 / 2^64)^2 * scale_num / scale_den` as a
+/// numerator/denominator pair that both fit in i128
+/// 
+/// Inverse of `get_sqrt_price_for_ratio`. The exact ratio is scaled down
+/// until both terms fit, which only drops precision beyond 127 bits.
+/// Prices too large to express saturate to `(i128::MAX, 1)`; prices below
+/// 1 / i128::MAX come out as a zero numerator.
+pub fn get_ratio_for_sqrt_price(
+    env: &Env,
+    sqrt_price_x64: u128,
+    scale_num: u128,
+    scale_den: u128,
+) -> (i128, i128) {
+    let zero = U256::from_u32(env, 0);
+    let sqrt = U256::from_u128(env, sqrt_price_x64);
+
+    // Keep both terms within 128 bits before scaling, so the products
+    // below stay inside 256 bits
+    let mut num = sqrt.mul(&sqrt);
+    let mut den = U256::from_u32(env, 1).shl(128);
+    while num.shr(128) != zero {
+        num = num.shr(1);
+        den = den.shr(1);
+    }
+
+    num = num.mul(&U256::from_u128(env, scale_num));
+    den = den.mul(&U256::from_u128(env, scale_den));
+
+    let limit = U256::from_u128(env, i128::MAX as u128);
+    while num > limit || den > limit {
+        num = num.shr(1);
+        den = den.shr(1);
+    }
+    if den == zero {
+        return (i128::MAX, 1);
+    }
+
+    (
+        num.to_u128().unwrap_or(0) as i128,
+        den.to_u128().unwrap_or(1) as i128,
+    )
 }
 
 /// Alias for get_sqrt_ratio_at_tick
 #[allow(dead_code)]
-pub fn tick_to_sqrt_price_x64(_env: &Env, tick: i32) -> u128 { 
-    get_sqrt_ratio_at_tick(tick) 
+pub fn tick_to_sqrt_price_x64(env: &Env, tick: i32) -> u128 { 
+    get_sqrt_ratio_at_tick(env, tick) 
 }
 
 // ============================================================
@@ -196,23 +462,29 @@ pub fn get_next_sqrt_price_from_input(
     }
 
     if zero_for_one {
-        // Token0 in -> Price decreases
-        let product = amount_in.saturating_mul(sqrt_price);
-        let numerator = liquidity.saturating_mul(sqrt_price);
-        let liq_shifted = liquidity << 64; 
-        let denominator = liq_shifted.saturating_add(product);
-        
-        if denominator == 0 { return sqrt_price; }
-        mul_div(env, numerator, ONE_X64, denominator)
+        // Token0 in -> Price decreases: L / (L / sqrt_price + amount_in),
+        // rounded up so the price never moves further than paid for
+        let virtual_reserve0 = mul_div_rounding(env, liquidity, ONE_X64, sqrt_price, false);
+        if virtual_reserve0 != u128::MAX {
+            let denominator = virtual_reserve0.saturating_add(amount_in);
+            return mul_div_rounding(env, liquidity, ONE_X64, denominator, true);
+        }
+
+        // L / sqrt_price does not fit (huge liquidity at a tiny price), and
+        // a saturated reserve would move the price up. Same price as
+        // L * sqrt_price / (L + amount_in * sqrt_price), which never exceeds
+        // sqrt_price
+        let amount_scaled = mul_div_rounding(env, amount_in, sqrt_price, ONE_X64, false);
+        mul_div_rounding(env, liquidity, sqrt_price, liquidity.saturating_add(amount_scaled), true)
     } else {
-        // Token1 in -> Price increases
-        let quotient = div_q64(amount_in, liquidity);
+        // Token1 in -> Price increases by amount_in / L, rounded down
+        let quotient = mul_div_rounding(env, amount_in, ONE_X64, liquidity, false);
         sqrt_price.saturating_add(quotient)
     }
 }
 
 /// Calculate next sqrt price given output amount
-#[allow(dead_code)]
+/// Used during exact-output swaps to determine price movement
 pub fn get_next_sqrt_price_from_output(
     env: &Env,
     sqrt_price: u128,
@@ -224,19 +496,17 @@ pub fn get_next_sqrt_price_from_output(
         return sqrt_price;
     }
 
+    // Both branches round the price move up by one unit so the resulting
+    // price always delivers at least `amount_out`
     if zero_for_one {
-        // Token1 out -> Price decreases
-        let quotient = div_q64(amount_out, liquidity); 
+        // Token1 out -> Price decreases by amount_out / L
+        let quotient = mul_div_rounding(env, amount_out, ONE_X64, liquidity, true).saturating_add(1);
         sqrt_price.saturating_sub(quotient)
     } else {
-        // Token0 out -> Price increases
-        let product = amount_out.saturating_mul(sqrt_price);
-        let numerator = liquidity.saturating_mul(sqrt_price);
-        let liq_shifted = liquidity << 64;
-        let denominator = liq_shifted.saturating_sub(product);
-        
-        if denominator == 0 { return u128::MAX; } 
-        mul_div(env, numerator, ONE_X64, denominator)
+        // Token0 out -> Price increases: L / (L / sqrt_price - amount_out)
+        let virtual_reserve0 = mul_div_rounding(env, liquidity, ONE_X64, sqrt_price, false);
+        if amount_out >= virtual_reserve0 { return u128::MAX; }
+        mul_div_rounding(env, liquidity, ONE_X64, virtual_reserve0 - amount_out, true).saturating_add(1)
     }
 }
 
@@ -247,6 +517,7 @@ pub fn get_next_sqrt_price_from_output(
 /// Calculate token0 amount for a liquidity and price range
 /// Formula: L * (sqrt_upper - sqrt_lower) / (sqrt_upper * sqrt_lower)
 pub fn get_amount_0_delta(
+    env: &Env,
     sqrt_price_a: u128,
     sqrt_price_b: u128,
     liquidity: u128,
@@ -258,23 +529,26 @@ pub fn get_amount_0_delta(
         (sqrt_price_b, sqrt_price_a)
     };
 
-    let delta_price = sqrt_upper.saturating_sub(sqrt_lower);
-    let product_prices = mul_q64(sqrt_upper, sqrt_lower);
-    
-    if product_prices == 0 { return 0; }
-    
-    let numerator = liquidity.saturating_mul(delta_price);
-    
-    if round_up {
-        div_round_up(numerator, product_prices)
+    if sqrt_lower == 0 { return 0; }
+
+    // Both divisions round the same way, so the result stays on the
+    // requested side. L / lower first keeps the error under one unit (it is
+    // scaled by delta / upper < 1); if that overflows, L * delta / upper
+    // first always fits but amplifies the error by 2^64 / lower.
+    let delta_price = sqrt_upper - sqrt_lower;
+    let per_lower = mul_div_rounding(env, liquidity, ONE_X64, sqrt_lower, round_up);
+    if per_lower != u128::MAX {
+        mul_div_rounding(env, per_lower, delta_price, sqrt_upper, round_up)
     } else {
-        numerator / product_prices
+        let scaled = mul_div_rounding(env, liquidity, delta_price, sqrt_upper, round_up);
+        mul_div_rounding(env, scaled, ONE_X64, sqrt_lower, round_up)
     }
 }
 
 /// Calculate token1 amount for a liquidity and price range
 /// Formula: L * (sqrt_upper - sqrt_lower)
 pub fn get_amount_1_delta(
+    env: &Env,
     sqrt_price_a: u128,
     sqrt_price_b: u128,
     liquidity: u128,
@@ -286,18 +560,8 @@ pub fn get_amount_1_delta(
         (sqrt_price_b, sqrt_price_a)
     };
 
-    let delta = sqrt_upper.saturating_sub(sqrt_lower);
-    let product = liquidity.saturating_mul(delta);
-    
-    if round_up {
-        if product & 0xFFFFFFFFFFFFFFFF != 0 {
-            (product >> 64) + 1
-        } else {
-            product >> 64
-        }
-    } else {
-        product >> 64
-    }
+    let delta = sqrt_upper - sqrt_lower;
+    mul_div_rounding(env, liquidity, delta, ONE_X64, round_up)
 }
 
 // ============================================================
@@ -305,6 +569,9 @@ pub fn get_amount_1_delta(
 // ============================================================
 
 /// Compute a single swap step
+/// 
+/// The input is rounded up and the output down, as in
+/// `compute_swap_step_with_target`.
 /// Returns: (next_sqrt_price, amount_in, amount_out)
 #[allow(dead_code)]
 pub fn compute_swap_step(
@@ -335,26 +602,34 @@ pub fn compute_swap_step(
         return (sqrt_price_current, 0, 0);
     }
 
-    let amount_0 = get_amount_0_delta(sqrt_price_current, next_sqrt_price, liq_u, true);
-    let amount_1 = get_amount_1_delta(sqrt_price_current, next_sqrt_price, liq_u, true);
-    
     let (amount_in, amount_out) = if zero_for_one {
-        (amount_0, amount_1)
+        (
+            get_amount_0_delta(env, sqrt_price_current, next_sqrt_price, liq_u, true),
+            get_amount_1_delta(env, sqrt_price_current, next_sqrt_price, liq_u, false),
+        )
     } else {
-        (amount_1, amount_0)
+        (
+            get_amount_1_delta(env, sqrt_price_current, next_sqrt_price, liq_u, true),
+            get_amount_0_delta(env, sqrt_price_current, next_sqrt_price, liq_u, false),
+        )
     };
     
     let final_amount_in = amount_in.min(amt_in_remaining);
 
     (
         next_sqrt_price,
-        u128_to_i128_saturating(final_amount_in),
-        u128_to_i128_saturating(amount_out)
+        amount_to_i128(env, final_amount_in),
+        amount_to_i128(env, amount_out)
     )
 }
 
 /// Compute swap step with a target price
 /// Used when approaching a tick boundary
+/// 
+/// A positive `amount_specified` is the input still available (exact input);
+/// a negative one is the output still owed to the trader (exact output),
+/// which the step's output never exceeds.
+/// 
 /// Returns: (next_sqrt_price, amount_in, amount_out)
 pub fn compute_swap_step_with_target(
     env: &Env,
@@ -365,53 +640,67 @@ pub fn compute_swap_step_with_target(
     sqrt_price_target: u128,
 ) -> (u128, i128, i128) {
     let liq_u = i128_to_u128_safe(liquidity);
-    let amount_rem_u = i128_to_u128_safe(amount_specified);
-
-    let next_price_input = get_next_sqrt_price_from_input(
-        env,
-        sqrt_price_current,
-        liq_u,
-        amount_rem_u,
-        zero_for_one
-    );
+    let exact_input = amount_specified >= 0;
+    let amount_rem_u = amount_specified.unsigned_abs();
+
+    let next_price = if exact_input {
+        get_next_sqrt_price_from_input(
+            env,
+            sqrt_price_current,
+            liq_u,
+            amount_rem_u,
+            zero_for_one
+        )
+    } else {
+        get_next_sqrt_price_from_output(
+            env,
+            sqrt_price_current,
+            liq_u,
+            amount_rem_u,
+            zero_for_one
+        )
+    };
 
     // Check if we reach the target price
     let target_reached = if zero_for_one {
-        next_price_input <= sqrt_price_target
+        next_price <= sqrt_price_target
     } else {
-        next_price_input >= sqrt_price_target
+        next_price >= sqrt_price_target
     };
 
     let sqrt_price_next = if target_reached {
         sqrt_price_target
     } else {
-        next_price_input
+        next_price
     };
 
     // Calculate amounts based on final price
     let (amount_in, amount_out) = if zero_for_one {
         (
-            get_amount_0_delta(sqrt_price_current, sqrt_price_next, liq_u, true),
-            get_amount_1_delta(sqrt_price_current, sqrt_price_next, liq_u, false),
+            get_amount_0_delta(env, sqrt_price_current, sqrt_price_next, liq_u, true),
+            get_amount_1_delta(env, sqrt_price_current, sqrt_price_next, liq_u, false),
         )
     } else {
         (
-            get_amount_1_delta(sqrt_price_current, sqrt_price_next, liq_u, true),
-            get_amount_0_delta(sqrt_price_current, sqrt_price_next, liq_u, false),
+            get_amount_1_delta(env, sqrt_price_current, sqrt_price_next, liq_u, true),
+            get_amount_0_delta(env, sqrt_price_current, sqrt_price_next, liq_u, false),
         )
     };
 
-    // Cap amount_in to remaining amount if target not reached
-    let final_amount_in = if !target_reached && amount_in > amount_rem_u {
-        amount_rem_u
+    // Cap the specified side to what remains. Exact input only needs it
+    // short of the target; exact output always does, since the output price
+    // is rounded past what was asked for and reaching the target can
+    // deliver more than is still owed
+    let (final_amount_in, final_amount_out) = if exact_input {
+        if target_reached { (amount_in, amount_out) } else { (amount_in.min(amount_rem_u), amount_out) }
     } else {
-        amount_in
+        (amount_in, amount_out.min(amount_rem_u))
     };
 
     (
         sqrt_price_next,
-        u128_to_i128_saturating(final_amount_in),
-        u128_to_i128_saturating(amount_out)
+        amount_to_i128(env, final_amount_in),
+        amount_to_i128(env, final_amount_out)
     )
 }
 
@@ -421,25 +710,23 @@ pub fn compute_swap_step_with_target(
 
 /// Calculate liquidity from token0 amount
 pub fn get_liquidity_for_amount0(
-    _env: &Env, 
+    env: &Env, 
     amount0: i128, 
     sqrt_price_lower: u128, 
     sqrt_price_upper: u128
 ) -> i128 {
     if amount0 <= 0 || sqrt_price_lower >= sqrt_price_upper { return 0; }
     
+    // amount0 * upper * lower / (upper - lower), in two rounded-down steps
     let amt0_u = i128_to_u128_safe(amount0);
-    let product = mul_q64(sqrt_price_upper, sqrt_price_lower);
-    let numerator = amt0_u.saturating_mul(product); 
-    let denominator = sqrt_price_upper.saturating_sub(sqrt_price_lower);
-    
-    if denominator == 0 { return 0; }
-    u128_to_i128_saturating(numerator / denominator)
+    let denominator = sqrt_price_upper - sqrt_price_lower;
+    let scaled = mul_div_rounding(env, amt0_u, sqrt_price_upper, denominator, false);
+    u128_to_i128_saturating(mul_div_rounding(env, scaled, sqrt_price_lower, ONE_X64, false))
 }
 
 /// Calculate liquidity from token1 amount
 pub fn get_liquidity_for_amount1(
-    _env: &Env, 
+    env: &Env, 
     amount1: i128, 
     sqrt_price_lower: u128, 
     sqrt_price_upper: u128
@@ -447,10 +734,8 @@ pub fn get_liquidity_for_amount1(
     if amount1 <= 0 || sqrt_price_lower >= sqrt_price_upper { return 0; }
     
     let amt1_u = i128_to_u128_safe(amount1);
-    let diff = sqrt_price_upper.saturating_sub(sqrt_price_lower);
-    
-    if diff == 0 { return 0; }
-    let liq_u = div_q64(amt1_u, diff);
+    let diff = sqrt_price_upper - sqrt_price_lower;
+    let liq_u = mul_div_rounding(env, amt1_u, ONE_X64, diff, false);
     u128_to_i128_saturating(liq_u)
 }
 
@@ -480,14 +765,32 @@ pub fn get_liquidity_for_amounts(
     }
 }
 
-/// Calculate token amounts from liquidity
+/// Calculate token amounts from liquidity, rounded down
 /// Returns: (amount0, amount1)
 pub fn get_amounts_for_liquidity(
-    _env: &Env, 
+    env: &Env, 
     liquidity: i128, 
     sqrt_price_lower: u128, 
     sqrt_price_upper: u128, 
     current_sqrt_price: u128,
+) -> (i128, i128) {
+    get_amounts_for_liquidity_rounding(
+        env, liquidity, sqrt_price_lower, sqrt_price_upper, current_sqrt_price, false,
+    )
+}
+
+/// Calculate token amounts from liquidity
+/// 
+/// Round up for amounts paid into the pool (deposits) and down for
+/// amounts paid out, so rounding always favors the pool.
+/// Returns: (amount0, amount1)
+pub fn get_amounts_for_liquidity_rounding(
+    env: &Env, 
+    liquidity: i128, 
+    sqrt_price_lower: u128, 
+    sqrt_price_upper: u128, 
+    current_sqrt_price: u128,
+    round_up: bool,
 ) -> (i128, i128) {
     if liquidity <= 0 { return (0, 0); }
     
@@ -499,16 +802,16 @@ pub fn get_amounts_for_liquidity(
         .min(sqrt_price_upper);
     
     let amount0_u = if sp < sqrt_price_upper { 
-        get_amount_0_delta(sp, sqrt_price_upper, liq_u, false) 
+        get_amount_0_delta(env, sp, sqrt_price_upper, liq_u, round_up) 
     } else { 
         0 
     };
     
     let amount1_u = if sp > sqrt_price_lower { 
-        get_amount_1_delta(sqrt_price_lower, sp, liq_u, false) 
+        get_amount_1_delta(env, sqrt_price_lower, sp, liq_u, round_up) 
     } else { 
         0 
     };
     
-    (u128_to_i128_saturating(amount0_u), u128_to_i128_saturating(amount1_u))
+    (amount_to_i128(env, amount0_u), amount_to_i128(env, amount1_u))
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+use soroban_sdk::{panic_with_error, Env};
+
+use crate::constants::{MAX_TWAP_OBSERVATIONS, MIN_TWAP_OBSERVATION_INTERVAL, Q64};
+use crate::math::mul_div_checked;
+use crate::error::PoolError;
+use crate::storage::{
+    get_twap_newest_index, has_twap_observation, is_twap_initialized, read_twap_observation,
+    set_twap_initialized, set_twap_newest_index, write_twap_observation,
+};
+use crate::types::{PoolState, TWAPObservation};
+
+// ============================================================
+// OBSERVATION WRITES
+// ============================================================
+
+/// Pool tick cumulative extrapolated to `now` at the current tick
+fn tick_cumulative_at(pool: &PoolState, now: u64) -> i128 {
+    let elapsed = now.saturating_sub(pool.last_update_ts);
+    pool.tick_cumulative
+        .wrapping_add(pool.current_tick as i128 * elapsed as i128)
+}
+
+/// Build the observation for `now` from the previous one and the pool state
+/// that has been in effect since it
+///
+/// The tick cumulative comes from the pool itself, so it stays exact even
+/// when the tick moved in ledgers that were too close together to record
+/// an observation.
+fn transform(last: &TWAPObservation, now: u64, pool: &PoolState) -> TWAPObservation {
+    let elapsed = now.saturating_sub(last.timestamp);
+
+    let liquidity_delta = if pool.liquidity > 0 {
+        ((elapsed as u128) << 64) / (pool.liquidity as u128)
+    } else {
+        0
+    };
+
+    TWAPObservation {
+        timestamp: now,
+        tick_cumulative: tick_cumulative_at(pool, now),
+        liquidity_cumulative: last.liquidity_cumulative.wrapping_add(liquidity_delta),
+        sqrt_price_x64: pool.sqrt_price_x64,
+        fee_growth_global_0: pool.fee_growth_global_0,
+        fee_growth_global_1: pool.fee_growth_global_1,
+    }
+}
+
+/// Record the pool state into the observation ring buffer
+///
+/// Must be called BEFORE the pool is modified so the observation captures
+/// the state that held up to this ledger. Brings the pool's tick cumulative
+/// up to date, so the caller must persist `pool`. At most one observation
+/// is kept per `MIN_TWAP_OBSERVATION_INTERVAL`; later calls in the same
+/// window only update the pool.
+pub fn write_observation(env: &Env, pool: &mut PoolState) {
+    let now = env.ledger().timestamp();
+
+    pool.tick_cumulative = tick_cumulative_at(pool, now);
+    pool.last_update_ts = now;
+
+    if !is_twap_initialized(env) {
+        let first = TWAPObservation {
+            timestamp: now,
+            tick_cumulative: pool.tick_cumulative,
+            liquidity_cumulative: 0,
+            sqrt_price_x64: pool.sqrt_price_x64,
+            fee_growth_global_0: pool.fee_growth_global_0,
+            fee_growth_global_1: pool.fee_growth_global_1,
+        };
+        write_twap_observation(env, 0, &first);
+        set_twap_newest_index(env, 0);
+        set_twap_initialized(env);
+        return;
+    }
+
+    let newest_index = get_twap_newest_index(env);
+    let last = read_twap_observation(env, newest_index);
+
+    if now < last.timestamp.saturating_add(MIN_TWAP_OBSERVATION_INTERVAL) {
+        return;
+    }
+
+    let next_index = (newest_index + 1) % MAX_TWAP_OBSERVATIONS;
+    write_twap_observation(env, next_index, &transform(&last, now, pool));
+    set_twap_newest_index(env, next_index);
+}
+
+// ============================================================
+// OBSERVATION READS
+// ============================================================
+
+/// Reconstruct the observation at `target` from the ring buffer
+///
+/// Price and fee growth are taken from the first observation at or after
+/// `target` (they are constant between observations); cumulatives are
+/// interpolated linearly. Targets after the newest observation are
+/// extrapolated from the current pool state.
+///
+/// Fails with `ObservationTooOld` if `target` predates the oldest
+/// observation still in the buffer.
+pub fn observe_at(env: &Env, pool: &PoolState, target: u64) -> TWAPObservation {
+    if !is_twap_initialized(env) {
+        panic_with_error!(env, PoolError::ObservationTooOld);
+    }
+
+    let newest_index = get_twap_newest_index(env);
+    let newest = read_twap_observation(env, newest_index);
+
+    if target > newest.timestamp {
+        return transform(&newest, target, pool);
+    }
+    if target == newest.timestamp {
+        return newest;
+    }
+
+    // Once the buffer has wrapped, the slot after the newest is the oldest
+    let wrapped = has_twap_observation(env, (newest_index + 1) % MAX_TWAP_OBSERVATIONS);
+    let (oldest_index, count) = if wrapped {
+        ((newest_index + 1) % MAX_TWAP_OBSERVATIONS, MAX_TWAP_OBSERVATIONS)
+    } else {
+        (0, newest_index + 1)
+    };
+
+    let at = |i: u32| read_twap_observation(env, (oldest_index + i) % MAX_TWAP_OBSERVATIONS);
+
+    let oldest = at(0);
+    if target < oldest.timestamp {
+        panic_with_error!(env, PoolError::ObservationTooOld);
+    }
+    if target == oldest.timestamp {
+        return oldest;
+    }
+
+    // Binary search for the first observation with timestamp >= target.
+    // Invariant: at(lo).timestamp < target <= at(hi).timestamp
+    let mut lo = 0u32;
+    let mut hi = count - 1;
+    while hi - lo > 1 {
+        let mid = lo + (hi - lo) / 2;
+        if at(mid).timestamp < target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    let before = at(lo);
+    let after = at(hi);
+
+    if after.timestamp == target {
+        return after;
+    }
+
+    let window = (after.timestamp - before.timestamp) as i128;
+    let elapsed = (target - before.timestamp) as i128;
+
+    let tick_delta = after.tick_cumulative.wrapping_sub(before.tick_cumulative);
+    let liquidity_delta = after.liquidity_cumulative.wrapping_sub(before.liquidity_cumulative);
+
+    TWAPObservation {
+        timestamp: target,
+        tick_cumulative: before
+            .tick_cumulative
+            .wrapping_add(tick_delta / window * elapsed),
+        liquidity_cumulative: before
+            .liquidity_cumulative
+            .wrapping_add(liquidity_delta / window as u128 * elapsed as u128),
+        sqrt_price_x64: after.sqrt_price_x64,
+        fee_growth_global_0: after.fee_growth_global_0,
+        fee_growth_global_1: after.fee_growth_global_1,
+    }
+}
+
+// ============================================================
+// HISTORICAL ESTIMATES
+// ============================================================
+
+/// Upper bound on fees earned by `liquidity` while global fee growth moved
+/// from `growth_then` to `growth_now`
+///
+/// Assumes the position was in range the whole time; saturates instead of
+/// overflowing.
+pub fn fees_earned_between(liquidity: i128, growth_then: u128, growth_now: u128) -> u128 {
+    if liquidity <= 0 {
+        return 0;
+    }
+
+    let delta = growth_now.wrapping_sub(growth_then);
+    mul_div_checked(liquidity as u128, delta, Q64).unwrap_or(u128::MAX)
+}
@@ -1,6 +1,12 @@
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{panic_with_error, Address, Env};
 
-use crate::storage::{read_position as storage_read, write_position as storage_write};
+use crate::constants::Q64;
+use crate::error::PoolErrorExt;
+use crate::math::mul_div_checked;
+use crate::storage::{
+    has_position as storage_has, read_owner_positions, read_position as storage_read,
+    remove_position as storage_remove, write_owner_positions, write_position as storage_write,
+};
 use crate::types::Position;
 
 // ============================================================
@@ -12,16 +18,45 @@ pub fn read_position(env: &Env, owner: &Address, lower: i32, upper: i32) -> Posi
     storage_read(env, owner, lower, upper)
 }
 
-/// Write a position to storage
+/// Write a position to storage and keep the owner's position index in step
+/// 
+/// The range joins the owner's index when the position is first written
+/// non-empty and leaves it once it holds no liquidity, owed fees or rewards.
 pub fn write_position(env: &Env, owner: &Address, lower: i32, upper: i32, pos: &Position) {
     storage_write(env, owner, lower, upper, pos);
+
+    let mut ranges = read_owner_positions(env, owner);
+    match (ranges.first_index_of((lower, upper)), is_empty(pos)) {
+        (None, false) => ranges.push_back((lower, upper)),
+        (Some(index), true) => {
+            ranges.remove(index);
+        }
+        _ => return,
+    }
+    write_owner_positions(env, owner, &ranges);
+}
+
+/// Whether a position is stored for the range (empty positions may be)
+pub fn has_position(env: &Env, owner: &Address, lower: i32, upper: i32) -> bool {
+    storage_has(env, owner, lower, upper)
+}
+
+/// Delete a position and drop it from the owner's position index
+pub fn remove_position(env: &Env, owner: &Address, lower: i32, upper: i32) {
+    storage_remove(env, owner, lower, upper);
+
+    let mut ranges = read_owner_positions(env, owner);
+    if let Some(index) = ranges.first_index_of((lower, upper)) {
+        ranges.remove(index);
+        write_owner_positions(env, owner, &ranges);
+    }
 }
 
 // ============================================================
 // POSITION UPDATE (Fee Accumulation)
 // ============================================================
 
-/// Update a position's fee checkpoints and calculate owed tokens
+/// Update a position's fee and reward checkpoints and calculate owed tokens
 /// 
 /// This is the core Uniswap V3 fee collection pattern:
 /// 1. Calculate delta = current_inside - last_inside (using wrapping arithmetic)
@@ -32,13 +67,17 @@ pub fn write_position(env: &Env, owner: &Address, lower: i32, upper: i32, pos: &
 /// because fee_growth_inside is consistent through tick crossings.
 /// 
 /// # Arguments
+/// * `env` - Environment, for the `Overflow` error
 /// * `pos` - Mutable reference to position
 /// * `fee_growth_inside_0` - Current fee growth inside for token0
 /// * `fee_growth_inside_1` - Current fee growth inside for token1
+/// * `reward_growth_inside` - Current reward growth inside
 pub fn update_position(
+    env: &Env,
     pos: &mut Position,
     fee_growth_inside_0: u128,
     fee_growth_inside_1: u128,
+    reward_growth_inside: u128,
 ) {
     if pos.liquidity > 0 {
         let liquidity_u = pos.liquidity as u128;
@@ -47,30 +86,38 @@ pub fn update_position(
         let delta_0 = fee_growth_inside_0.wrapping_sub(pos.fee_growth_inside_last_0);
         let delta_1 = fee_growth_inside_1.wrapping_sub(pos.fee_growth_inside_last_1);
         
-        // Calculate owed fees using checked multiplication to detect overflow
-        // fee = (liquidity * delta) >> 64
-        let fee_0 = match liquidity_u.checked_mul(delta_0) {
-            Some(product) => product >> 64,
-            None => {
-                // Overflow indicates invalid delta (likely underflow in subtraction)
-                // This should not happen in correct Uniswap V3 implementation
-                0
-            }
-        };
-        
-        let fee_1 = match liquidity_u.checked_mul(delta_1) {
-            Some(product) => product >> 64,
-            None => 0
-        };
+        // fee = (liquidity * delta) >> 64 with a 256-bit product. Fees
+        // earned can never exceed the tokens paid in, so a result that
+        // does not fit means broken fee accounting: fail with `Overflow`
+        // rather than credit a saturated or zeroed amount
+        let fee_0 = position_fee(liquidity_u, delta_0)
+            .unwrap_or_else(|| panic_with_error!(env, PoolErrorExt::Overflow));
+        let fee_1 = position_fee(liquidity_u, delta_1)
+            .unwrap_or_else(|| panic_with_error!(env, PoolErrorExt::Overflow));
         
         // Accumulate owed tokens
-        pos.tokens_owed_0 = pos.tokens_owed_0.saturating_add(fee_0);
-        pos.tokens_owed_1 = pos.tokens_owed_1.saturating_add(fee_1);
+        pos.tokens_owed_0 = pos.tokens_owed_0
+            .checked_add(fee_0)
+            .unwrap_or_else(|| panic_with_error!(env, PoolErrorExt::Overflow));
+        pos.tokens_owed_1 = pos.tokens_owed_1
+            .checked_add(fee_1)
+            .unwrap_or_else(|| panic_with_error!(env, PoolErrorExt::Overflow));
+        pos.lifetime_fees_0 = pos.lifetime_fees_0.saturating_add(fee_0);
+        pos.lifetime_fees_1 = pos.lifetime_fees_1.saturating_add(fee_1);
+
+        // Rewards follow the same pattern and are bounded by the amount funded
+        let reward_delta = reward_growth_inside.wrapping_sub(pos.reward_growth_inside_last);
+        let reward = position_fee(liquidity_u, reward_delta)
+            .unwrap_or_else(|| panic_with_error!(env, PoolErrorExt::Overflow));
+        pos.rewards_owed = pos.rewards_owed
+            .checked_add(reward)
+            .unwrap_or_else(|| panic_with_error!(env, PoolErrorExt::Overflow));
     }
     
     // Always update checkpoints to current values
     pos.fee_growth_inside_last_0 = fee_growth_inside_0;
     pos.fee_growth_inside_last_1 = fee_growth_inside_1;
+    pos.reward_growth_inside_last = reward_growth_inside;
 }
 
 // ============================================================
@@ -84,18 +131,22 @@ pub fn update_position(
 /// 2. Then adjust liquidity
 /// 
 /// # Arguments
+/// * `env` - Environment, for the `Overflow` error
 /// * `pos` - Mutable reference to position
 /// * `liquidity_delta` - Change in liquidity (positive = add, negative = remove)
 /// * `fee_growth_inside_0` - Current fee growth inside for token0
 /// * `fee_growth_inside_1` - Current fee growth inside for token1
+/// * `reward_growth_inside` - Current reward growth inside
 pub fn modify_position(
+    env: &Env,
     pos: &mut Position,
     liquidity_delta: i128,
     fee_growth_inside_0: u128,
     fee_growth_inside_1: u128,
+    reward_growth_inside: u128,
 ) {
-    // First update fees
-    update_position(pos, fee_growth_inside_0, fee_growth_inside_1);
+    // First update fees and rewards
+    update_position(env, pos, fee_growth_inside_0, fee_growth_inside_1, reward_growth_inside);
     
     // Then adjust liquidity
     if liquidity_delta > 0 {
@@ -136,24 +187,38 @@ pub fn calculate_pending_fees(
     let delta_0 = fee_growth_inside_0.wrapping_sub(pos.fee_growth_inside_last_0);
     let delta_1 = fee_growth_inside_1.wrapping_sub(pos.fee_growth_inside_last_1);
     
-    // Calculate pending fees with overflow protection
-    let pending_0 = match liquidity_u.checked_mul(delta_0) {
-        Some(product) => product >> 64,
-        None => 0,
-    };
-    
-    let pending_1 = match liquidity_u.checked_mul(delta_1) {
-        Some(product) => product >> 64,
-        None => 0,
-    };
+    // Saturates for display; update_position fails on the same values
+    let pending_0 = position_fee(liquidity_u, delta_0).unwrap_or(u128::MAX);
+    let pending_1 = position_fee(liquidity_u, delta_1).unwrap_or(u128::MAX);
     
     (pending_0, pending_1)
 }
 
+/// Calculate unclaimed rewards, including those not yet credited, without
+/// modifying the position
+pub fn calculate_pending_rewards(pos: &Position, reward_growth_inside: u128) -> u128 {
+    if pos.liquidity <= 0 {
+        return pos.rewards_owed;
+    }
+
+    let delta = reward_growth_inside.wrapping_sub(pos.reward_growth_inside_last);
+
+    // Saturates for display; update_position fails on the same values
+    let pending = position_fee(pos.liquidity as u128, delta).unwrap_or(u128::MAX);
+    pos.rewards_owed.saturating_add(pending)
+}
+
 // ============================================================
 // POSITION HELPERS
 // ============================================================
 
+/// Fees owed for `liquidity` over a fee growth delta, or None if they do
+/// not fit in u128
+#[inline]
+fn position_fee(liquidity: u128, fee_growth_delta: u128) -> Option<u128> {
+    mul_div_checked(liquidity, fee_growth_delta, Q64)
+}
+
 /// Check if a position has any liquidity
 #[inline]
 pub fn has_liquidity(pos: &Position) -> bool {
@@ -167,11 +232,10 @@ pub fn has_uncollected_fees(pos: &Position) -> bool {
     pos.tokens_owed_0 > 0 || pos.tokens_owed_1 > 0
 }
 
-/// Check if a position is empty (no liquidity and no fees)
+/// Check if a position is empty (no liquidity, fees or rewards)
 #[inline]
-#[allow(dead_code)]
 pub fn is_empty(pos: &Position) -> bool {
-    pos.liquidity == 0 && pos.tokens_owed_0 == 0 && pos.tokens_owed_1 == 0
+    pos.liquidity == 0 && pos.tokens_owed_0 == 0 && pos.tokens_owed_1 == 0 && pos.rewards_owed == 0
 }
 
 /// Clear collected fees from position
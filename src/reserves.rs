@@ -0,0 +1,27 @@
+use soroban_sdk::Env;
+
+use crate::storage::{read_tracked_reserves, write_tracked_reserves};
+
+// ============================================================
+// RESERVE TRACKING
+// ============================================================
+//
+// Opt-in bookkeeping for rebasing tokens. When enabled, every token flow
+// through the pool adjusts the tracked reserves, so the difference between
+// the tracked and actual balance is exactly what rebased in or out.
+
+/// Apply a token flow to the tracked reserves
+///
+/// Positive deltas are tokens received by the pool, negative deltas tokens
+/// sent out. No-op while reserve tracking is disabled.
+pub fn track_reserves(env: &Env, delta0: i128, delta1: i128) {
+    if delta0 == 0 && delta1 == 0 {
+        return;
+    }
+
+    if let Some(mut reserves) = read_tracked_reserves(env) {
+        reserves.reserve0 = reserves.reserve0.saturating_add(delta0);
+        reserves.reserve1 = reserves.reserve1.saturating_add(delta1);
+        write_tracked_reserves(env, &reserves);
+    }
+}
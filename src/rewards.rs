@@ -0,0 +1,133 @@
+use soroban_sdk::{panic_with_error, token, Address, Env};
+
+use crate::constants::Q64;
+use crate::error::PoolError;
+use crate::math::mul_div_checked;
+use crate::storage::{read_reward_schedule, write_reward_schedule};
+use crate::types::{PoolState, RewardSchedule};
+
+// ============================================================
+// ACCRUAL
+// ============================================================
+
+/// Reward growth global and schedule end after streaming up to `now`
+/// 
+/// Only the in-range liquidity earns: a stretch with none streams nothing
+/// and pushes the end of the schedule back by the same amount, so every
+/// funded token is eventually distributed. So does a stretch whose growth
+/// does not fit in u128 because the active liquidity is tiny; wrapping it
+/// into the accumulator would leave every in-range position owed more than
+/// exists, and unable to be modified again.
+fn streamed_to(schedule: &RewardSchedule, pool: &PoolState, now: u64) -> (u128, u64) {
+    if now <= schedule.last_update || schedule.last_update >= schedule.end {
+        return (pool.reward_growth_global, schedule.end);
+    }
+
+    let elapsed = now.min(schedule.end) - schedule.last_update;
+
+    let growth = if pool.liquidity > 0 {
+        mul_div_checked(schedule.rate_x64, elapsed as u128, pool.liquidity as u128)
+    } else {
+        None
+    };
+
+    match growth {
+        Some(growth) => (pool.reward_growth_global.wrapping_add(growth), schedule.end),
+        None => {
+            let remaining = schedule.end - schedule.last_update;
+            (pool.reward_growth_global, now.saturating_add(remaining))
+        }
+    }
+}
+
+/// Bring the pool's reward growth global up to now
+/// 
+/// Must run before anything changes the active liquidity or crosses a
+/// tick, so the time since the last update is credited to the liquidity
+/// that was in range during it. The caller persists `pool`.
+pub fn accrue_rewards(env: &Env, pool: &mut PoolState) {
+    let Some(mut schedule) = read_reward_schedule(env) else {
+        return;
+    };
+
+    let now = env.ledger().timestamp();
+    if now <= schedule.last_update {
+        return;
+    }
+
+    let (growth, end) = streamed_to(&schedule, pool, now);
+    pool.reward_growth_global = growth;
+    schedule.end = end;
+    schedule.last_update = now;
+    write_reward_schedule(env, &schedule);
+}
+
+/// Reward growth global as of now, without writing anything
+pub fn current_reward_growth(env: &Env, pool: &PoolState) -> u128 {
+    match read_reward_schedule(env) {
+        Some(schedule) => streamed_to(&schedule, pool, env.ledger().timestamp()).0,
+        None => pool.reward_growth_global,
+    }
+}
+
+// ============================================================
+// FUNDING
+// ============================================================
+
+/// Stream `amount` more of `token` over the next `duration` seconds
+/// 
+/// Whatever the current schedule has not streamed yet is rolled into the
+/// new one. Rewards must already be accrued up to now. The reward token is
+/// fixed by the first funding; later ones must use the same token.
+pub fn fund_schedule(env: &Env, token: Address, amount: i128, duration: u64) -> RewardSchedule {
+    let now = env.ledger().timestamp();
+
+    let carried = match read_reward_schedule(env) {
+        Some(schedule) => {
+            if schedule.token != token {
+                panic_with_error!(env, PoolError::InvalidToken);
+            }
+            if schedule.end > now {
+                mul_div_checked(schedule.rate_x64, (schedule.end - now) as u128, duration as u128)
+            } else {
+                Some(0)
+            }
+        }
+        None => Some(0),
+    };
+
+    let rate_x64 = mul_div_checked(amount as u128, Q64, duration as u128)
+        .zip(carried)
+        .and_then(|(added, carried)| added.checked_add(carried))
+        .unwrap_or_else(|| panic_with_error!(env, PoolError::AmountAboveCap));
+
+    let schedule = RewardSchedule {
+        token,
+        rate_x64,
+        end: now.saturating_add(duration),
+        last_update: now,
+    };
+    write_reward_schedule(env, &schedule);
+
+    schedule
+}
+
+// ============================================================
+// PAYOUT
+// ============================================================
+
+/// Transfer `amount` of the reward token from the pool to `to`
+/// 
+/// Only called with rewards a position has been credited, which exist only
+/// once a schedule has been funded.
+pub fn pay_rewards(env: &Env, to: &Address, amount: u128) {
+    if amount == 0 {
+        return;
+    }
+
+    let schedule = read_reward_schedule(env)
+        .unwrap_or_else(|| panic_with_error!(env, PoolError::InvalidToken));
+
+    token::Client::new(env, &schedule.token)
+        .transfer(&env.current_contract_address(), to, &(amount as i128));
+}
@@ -1,6 +1,11 @@
-use soroban_sdk::{contracttype, Address, Env};
+use soroban_sdk::{contracttype, panic_with_error, Address, BytesN, Env, IntoVal, TryFromVal, Val, Vec};
 
-use crate::types::{PoolConfig, PoolState, Position, TickInfo, TWAPObservation};
+use crate::constants::{MAX_TWAP_OBSERVATIONS, PERSISTENT_TTL_EXTEND_TO, PERSISTENT_TTL_THRESHOLD};
+use crate::error::PoolError;
+use crate::types::{
+    LimitOrder, PoolConfig, PoolState, Position, PositionRecord, RewardSchedule, TickInfo, TrackedReserves,
+    TWAPObservation, VestingSchedule, WithdrawalQueueConfig, WithdrawalRequest, WithdrawalWindow,
+};
 
 // ============================================================
 // STORAGE KEYS
@@ -8,6 +13,7 @@ use crate::types::{PoolConfig, PoolState, Position, TickInfo, TWAPObservation};
 
 /// All storage keys used in the contract
 #[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DataKey {
     /// Pool state (prices, liquidity, fees)
     PoolState,
@@ -19,12 +25,132 @@ pub enum DataKey {
     Tick(i32),
     /// Position by (owner, lower_tick, upper_tick)
     Position(Address, i32, i32),
+    /// Position by numeric id
+    PositionById(u64),
+    /// Limit order by the id of the position it rests on
+    LimitOrder(u64),
+    /// Ranges of an owner's non-empty range-keyed positions
+    OwnerPositions(Address),
     /// TWAP observation by index
     TWAPObservation(u32),
     /// Newest TWAP observation index
     TWAPNewestIndex,
     /// TWAP initialization flag
     TWAPInitialized,
+    /// Protocol fee vesting duration in seconds (0 = direct collection)
+    ProtocolFeeVestingDuration,
+    /// Active protocol fee vesting schedule
+    ProtocolFeeVesting,
+    /// Liquidity mining reward stream
+    RewardSchedule,
+    /// Withdrawal queue settings
+    WithdrawalQueueConfig,
+    /// Queued withdrawal by (owner, lower_tick, upper_tick)
+    WithdrawalRequest(Address, i32, i32),
+    /// Liquidity removed in the current queue interval by (owner, lower_tick, upper_tick)
+    WithdrawalWindow(Address, i32, i32),
+    /// Liquidity removed in the current queue interval by position id
+    WithdrawalWindowById(u64),
+    /// Tracked token reserves (absent = reserve tracking disabled)
+    TrackedReserves,
+    /// Admin proposed by `propose_admin`, awaiting `accept_admin`
+    PendingAdmin,
+    /// Furthest a deadline may lie in the future, in seconds (0 = no ceiling)
+    MaxDeadlineWindow,
+    /// Emergency stop for swaps and liquidity additions
+    Paused,
+    /// Tick accounting audit on full position burns
+    TickAccountingChecks,
+    /// Tick bitmap word by word index (128 spacing-aligned ticks per word)
+    TickBitmap(i32),
+    /// WASM hash installed by the last `upgrade` (instance storage)
+    WasmHash,
+    /// Every tick ever written (only tracked with the testutils feature)
+    TickRegistry,
+    /// Every position ever written (only tracked with the testutils feature)
+    PositionRegistry,
+}
+
+// ============================================================
+// TTL
+// ============================================================
+
+/// Extend a persistent entry's TTL to `PERSISTENT_TTL_EXTEND_TO` once it
+/// drops below `PERSISTENT_TTL_THRESHOLD`
+/// 
+/// The entry must exist. Called on every write and successful read of the
+/// pool, tick, bitmap and position entries so anything in use stays live;
+/// pool state reads and writes also extend the contract instance.
+fn extend_persistent(env: &Env, key: &DataKey) {
+    env.storage()
+        .persistent()
+        .extend_ttl(key, PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+}
+
+/// Extend the contract instance (code reference and instance storage) with
+/// the same thresholds as persistent entries
+pub fn extend_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(PERSISTENT_TTL_THRESHOLD, PERSISTENT_TTL_EXTEND_TO);
+}
+
+/// Extend `key` if it exists in persistent storage
+/// 
+/// # Returns
+/// Whether the entry exists
+pub fn extend_if_present(env: &Env, key: &DataKey) -> bool {
+    if !env.storage().persistent().has(key) {
+        return false;
+    }
+    extend_persistent(env, key);
+    true
+}
+
+/// Extend the contract instance and every pool-wide entry that exists
+/// 
+/// Covers what calls read whatever ticks and positions they touch: pool
+/// state and config, admin settings and the TWAP ring buffer.
+pub fn extend_pool_entries(env: &Env) {
+    extend_instance(env);
+
+    let pool_wide = [
+        DataKey::Initialized,
+        DataKey::PoolState,
+        DataKey::PoolConfig,
+        DataKey::TWAPNewestIndex,
+        DataKey::TWAPInitialized,
+        DataKey::ProtocolFeeVestingDuration,
+        DataKey::ProtocolFeeVesting,
+        DataKey::RewardSchedule,
+        DataKey::WithdrawalQueueConfig,
+        DataKey::TrackedReserves,
+        DataKey::PendingAdmin,
+        DataKey::MaxDeadlineWindow,
+        DataKey::Paused,
+        DataKey::TickAccountingChecks,
+    ];
+    for key in pool_wide.iter() {
+        extend_if_present(env, key);
+    }
+    for index in 0..MAX_TWAP_OBSERVATIONS {
+        extend_if_present(env, &DataKey::TWAPObservation(index));
+    }
+}
+
+/// Write a persistent entry and extend it
+fn set_persistent<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    extend_persistent(env, key);
+}
+
+/// Read a persistent entry, extending it if present
+fn get_persistent<V: TryFromVal<Env, Val>>(env: &Env, key: &DataKey) -> Option<V> {
+    let value = env.storage().persistent().get(key);
+    if value.is_some() {
+        extend_persistent(env, key);
+    }
+    value
 }
 
 // ============================================================
@@ -33,12 +159,13 @@ pub enum DataKey {
 
 /// Check if pool is initialized
 pub fn is_initialized(env: &Env) -> bool {
-    env.storage().persistent().has(&DataKey::Initialized)
+    extend_if_present(env, &DataKey::Initialized)
 }
 
 /// Mark pool as initialized
 pub fn set_initialized(env: &Env) {
     env.storage().persistent().set(&DataKey::Initialized, &true);
+    extend_persistent(env, &DataKey::Initialized);
 }
 
 // ============================================================
@@ -48,14 +175,17 @@ pub fn set_initialized(env: &Env) {
 /// Write pool configuration
 pub fn write_pool_config(env: &Env, config: &PoolConfig) {
     env.storage().persistent().set(&DataKey::PoolConfig, config);
+    extend_persistent(env, &DataKey::PoolConfig);
 }
 
 /// Read pool configuration
 pub fn read_pool_config(env: &Env) -> PoolConfig {
-    env.storage()
+    let config = env.storage()
         .persistent()
         .get(&DataKey::PoolConfig)
-        .unwrap_or_else(|| panic!("pool not initialized"))
+        .unwrap_or_else(|| panic_with_error!(env, PoolError::NotInitialized));
+    extend_persistent(env, &DataKey::PoolConfig);
+    config
 }
 
 // ============================================================
@@ -65,14 +195,24 @@ pub fn read_pool_config(env: &Env) -> PoolConfig {
 /// Write pool state
 pub fn write_pool_state(env: &Env, state: &PoolState) {
     env.storage().persistent().set(&DataKey::PoolState, state);
+    extend_persistent(env, &DataKey::PoolState);
+    extend_instance(env);
 }
 
 /// Read pool state
 pub fn read_pool_state(env: &Env) -> PoolState {
-    env.storage()
+    let state = env.storage()
         .persistent()
         .get(&DataKey::PoolState)
-        .unwrap_or_else(|| panic!("pool not initialized"))
+        .unwrap_or_else(|| panic_with_error!(env, PoolError::NotInitialized));
+    extend_persistent(env, &DataKey::PoolState);
+    extend_instance(env);
+    state
+}
+
+/// Read pool state, or None if the pool has not been initialized
+pub fn try_read_pool_state(env: &Env) -> Option<PoolState> {
+    env.storage().persistent().get(&DataKey::PoolState)
 }
 
 /// Initialize pool state
@@ -93,8 +233,14 @@ pub fn init_pool_state(
         token1,
         fee_growth_global_0: 0,
         fee_growth_global_1: 0,
+        reward_growth_global: 0,
         protocol_fees_0: 0,
         protocol_fees_1: 0,
+        zero_growth_fees_0: 0,
+        zero_growth_fees_1: 0,
+        tick_cumulative: 0,
+        last_update_ts: env.ledger().timestamp(),
+        next_position_id: 1,
     };
     
     write_pool_state(env, &state);
@@ -106,15 +252,43 @@ pub fn init_pool_state(
 
 /// Write tick info
 pub fn write_tick_info(env: &Env, tick: i32, info: &TickInfo) {
-    env.storage().persistent().set(&DataKey::Tick(tick), info);
+    let key = DataKey::Tick(tick);
+    env.storage().persistent().set(&key, info);
+    extend_persistent(env, &key);
+
+    #[cfg(feature = "testutils")]
+    register_tick(env, tick);
 }
 
 /// Read tick info (returns default if not exists)
 pub fn read_tick_info(env: &Env, tick: i32) -> TickInfo {
-    env.storage()
-        .persistent()
-        .get(&DataKey::Tick(tick))
-        .unwrap_or_default()
+    let key = DataKey::Tick(tick);
+    match env.storage().persistent().get(&key) {
+        Some(info) => {
+            extend_persistent(env, &key);
+            info
+        }
+        None => TickInfo::default(),
+    }
+}
+
+/// Write a tick bitmap word
+pub fn write_tick_bitmap_word(env: &Env, word_pos: i32, word: u128) {
+    let key = DataKey::TickBitmap(word_pos);
+    env.storage().persistent().set(&key, &word);
+    extend_persistent(env, &key);
+}
+
+/// Read a tick bitmap word (0 if no tick in it was ever initialized)
+pub fn read_tick_bitmap_word(env: &Env, word_pos: i32) -> u128 {
+    let key = DataKey::TickBitmap(word_pos);
+    match env.storage().persistent().get(&key) {
+        Some(word) => {
+            extend_persistent(env, &key);
+            word
+        }
+        None => 0,
+    }
 }
 
 // ============================================================
@@ -123,67 +297,415 @@ pub fn read_tick_info(env: &Env, tick: i32) -> TickInfo {
 
 /// Write position
 pub fn write_position(env: &Env, owner: &Address, lower: i32, upper: i32, pos: &Position) {
-    env.storage()
-        .persistent()
-        .set(&DataKey::Position(owner.clone(), lower, upper), pos);
+    let key = DataKey::Position(owner.clone(), lower, upper);
+    env.storage().persistent().set(&key, pos);
+    extend_persistent(env, &key);
+
+    #[cfg(feature = "testutils")]
+    register_position(env, owner, lower, upper);
 }
 
 /// Read position (returns default if not exists)
 pub fn read_position(env: &Env, owner: &Address, lower: i32, upper: i32) -> Position {
+    let key = DataKey::Position(owner.clone(), lower, upper);
+    match env.storage().persistent().get(&key) {
+        Some(pos) => {
+            extend_persistent(env, &key);
+            pos
+        }
+        None => Position::default(),
+    }
+}
+
+/// Whether a position entry is stored for the range
+pub fn has_position(env: &Env, owner: &Address, lower: i32, upper: i32) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DataKey::Position(owner.clone(), lower, upper))
+}
+
+/// Remove a position entry
+pub fn remove_position(env: &Env, owner: &Address, lower: i32, upper: i32) {
     env.storage()
         .persistent()
-        .get(&DataKey::Position(owner.clone(), lower, upper))
-        .unwrap_or_default()
+        .remove(&DataKey::Position(owner.clone(), lower, upper));
+}
+
+/// Read the ranges of an owner's non-empty positions, oldest first
+pub fn read_owner_positions(env: &Env, owner: &Address) -> Vec<(i32, i32)> {
+    let key = DataKey::OwnerPositions(owner.clone());
+    match env.storage().persistent().get(&key) {
+        Some(ranges) => {
+            extend_persistent(env, &key);
+            ranges
+        }
+        None => Vec::new(env),
+    }
+}
+
+/// Write an owner's position ranges (removes the entry once empty)
+pub fn write_owner_positions(env: &Env, owner: &Address, ranges: &Vec<(i32, i32)>) {
+    let key = DataKey::OwnerPositions(owner.clone());
+    if ranges.is_empty() {
+        env.storage().persistent().remove(&key);
+        return;
+    }
+    env.storage().persistent().set(&key, ranges);
+    extend_persistent(env, &key);
+}
+
+/// Write a position record under its id
+pub fn write_position_record(env: &Env, id: u64, record: &PositionRecord) {
+    let key = DataKey::PositionById(id);
+    env.storage().persistent().set(&key, record);
+    extend_persistent(env, &key);
+}
+
+/// Read a position record by id
+pub fn read_position_record(env: &Env, id: u64) -> Option<PositionRecord> {
+    let key = DataKey::PositionById(id);
+    let record = env.storage().persistent().get(&key);
+    if record.is_some() {
+        extend_persistent(env, &key);
+    }
+    record
+}
+
+/// Write a limit order under its position id
+pub fn write_limit_order(env: &Env, id: u64, order: &LimitOrder) {
+    let key = DataKey::LimitOrder(id);
+    env.storage().persistent().set(&key, order);
+    extend_persistent(env, &key);
+}
+
+/// Read a limit order by position id
+pub fn read_limit_order(env: &Env, id: u64) -> Option<LimitOrder> {
+    let key = DataKey::LimitOrder(id);
+    let order = env.storage().persistent().get(&key);
+    if order.is_some() {
+        extend_persistent(env, &key);
+    }
+    order
+}
+
+/// Delete a settled limit order
+pub fn remove_limit_order(env: &Env, id: u64) {
+    env.storage().persistent().remove(&DataKey::LimitOrder(id));
 }
 
 // ============================================================
-// TWAP STORAGE (Reserved for future use)
+// PROTOCOL FEE VESTING STORAGE
 // ============================================================
 
-/// Write TWAP observation
-#[allow(dead_code)]
-pub fn write_twap_observation(env: &Env, index: u32, obs: &TWAPObservation) {
+/// Write protocol fee vesting duration
+pub fn write_vesting_duration(env: &Env, duration: u64) {
+    set_persistent(env, &DataKey::ProtocolFeeVestingDuration, &duration);
+}
+
+/// Read protocol fee vesting duration (0 = vesting disabled)
+pub fn read_vesting_duration(env: &Env) -> u64 {
+    get_persistent(env, &DataKey::ProtocolFeeVestingDuration).unwrap_or(0)
+}
+
+/// Write protocol fee vesting schedule
+pub fn write_vesting_schedule(env: &Env, schedule: &VestingSchedule) {
+    set_persistent(env, &DataKey::ProtocolFeeVesting, schedule);
+}
+
+/// Read protocol fee vesting schedule (None if fees were never vested)
+pub fn read_vesting_schedule(env: &Env) -> Option<VestingSchedule> {
+    get_persistent(env, &DataKey::ProtocolFeeVesting)
+}
+
+// ============================================================
+// LIQUIDITY MINING STORAGE
+// ============================================================
+
+/// Write the reward stream
+pub fn write_reward_schedule(env: &Env, schedule: &RewardSchedule) {
+    set_persistent(env, &DataKey::RewardSchedule, schedule);
+}
+
+/// Read the reward stream (None if rewards were never funded)
+pub fn read_reward_schedule(env: &Env) -> Option<RewardSchedule> {
+    get_persistent(env, &DataKey::RewardSchedule)
+}
+
+// ============================================================
+// WITHDRAWAL QUEUE STORAGE
+// ============================================================
+
+/// Write withdrawal queue settings
+pub fn write_withdrawal_queue_config(env: &Env, config: &WithdrawalQueueConfig) {
+    set_persistent(env, &DataKey::WithdrawalQueueConfig, config);
+}
+
+/// Read withdrawal queue settings (default = queue disabled)
+pub fn read_withdrawal_queue_config(env: &Env) -> WithdrawalQueueConfig {
+    get_persistent(env, &DataKey::WithdrawalQueueConfig).unwrap_or_default()
+}
+
+/// Write a queued withdrawal
+pub fn write_withdrawal_request(env: &Env, owner: &Address, lower: i32, upper: i32, request: &WithdrawalRequest) {
+    set_persistent(env, &DataKey::WithdrawalRequest(owner.clone(), lower, upper), request);
+}
+
+/// Read a queued withdrawal (None if nothing is queued)
+pub fn read_withdrawal_request(env: &Env, owner: &Address, lower: i32, upper: i32) -> Option<WithdrawalRequest> {
+    get_persistent(env, &DataKey::WithdrawalRequest(owner.clone(), lower, upper))
+}
+
+/// Remove a queued withdrawal
+pub fn remove_withdrawal_request(env: &Env, owner: &Address, lower: i32, upper: i32) {
     env.storage()
         .persistent()
-        .set(&DataKey::TWAPObservation(index), obs);
+        .remove(&DataKey::WithdrawalRequest(owner.clone(), lower, upper));
 }
 
-/// Read TWAP observation (returns default if not exists)
-#[allow(dead_code)]
-pub fn read_twap_observation(env: &Env, index: u32) -> TWAPObservation {
+/// Write a position's withdrawal window under its `WithdrawalWindow` key
+pub fn write_withdrawal_window(env: &Env, key: &DataKey, window: &WithdrawalWindow) {
+    set_persistent(env, key, window);
+}
+
+/// Read a position's withdrawal window (None if nothing was removed yet)
+pub fn read_withdrawal_window(env: &Env, key: &DataKey) -> Option<WithdrawalWindow> {
+    get_persistent(env, key)
+}
+
+/// Remove a position's withdrawal window
+pub fn remove_withdrawal_window(env: &Env, key: &DataKey) {
+    env.storage().persistent().remove(key);
+}
+
+// ============================================================
+// DEADLINE STORAGE
+// ============================================================
+
+/// Write the deadline ceiling window
+pub fn write_max_deadline_window(env: &Env, window_secs: u64) {
+    set_persistent(env, &DataKey::MaxDeadlineWindow, &window_secs);
+}
+
+/// Read the deadline ceiling window (0 = no ceiling)
+pub fn read_max_deadline_window(env: &Env) -> u64 {
+    get_persistent(env, &DataKey::MaxDeadlineWindow).unwrap_or(0)
+}
+
+// ============================================================
+// PAUSE STORAGE
+// ============================================================
+
+/// Write the pause flag
+pub fn write_paused(env: &Env, paused: bool) {
+    set_persistent(env, &DataKey::Paused, &paused);
+}
+
+/// Read the pause flag (default = not paused)
+pub fn is_paused(env: &Env) -> bool {
+    get_persistent(env, &DataKey::Paused).unwrap_or(false)
+}
+
+/// Write the tick accounting audit flag
+pub fn write_tick_accounting_checks(env: &Env, enabled: bool) {
+    set_persistent(env, &DataKey::TickAccountingChecks, &enabled);
+}
+
+/// Read the tick accounting audit flag (default = enabled)
+pub fn is_tick_accounting_checked(env: &Env) -> bool {
+    get_persistent(env, &DataKey::TickAccountingChecks).unwrap_or(true)
+}
+
+// ============================================================
+// ADMIN TRANSFER STORAGE
+// ============================================================
+
+/// Write the proposed admin
+pub fn write_pending_admin(env: &Env, admin: &Address) {
+    set_persistent(env, &DataKey::PendingAdmin, admin);
+}
+
+/// Read the proposed admin (None if no transfer is pending)
+pub fn read_pending_admin(env: &Env) -> Option<Address> {
+    get_persistent(env, &DataKey::PendingAdmin)
+}
+
+/// Remove the proposed admin
+pub fn remove_pending_admin(env: &Env) {
     env.storage()
         .persistent()
-        .get(&DataKey::TWAPObservation(index))
-        .unwrap_or_default()
+        .remove(&DataKey::PendingAdmin);
 }
 
-/// Set newest TWAP index
-#[allow(dead_code)]
-pub fn set_twap_newest_index(env: &Env, index: u32) {
+// ============================================================
+// UPGRADE STORAGE
+// ============================================================
+
+/// Record the WASM hash installed by `upgrade`
+pub fn write_wasm_hash(env: &Env, hash: &BytesN<32>) {
+    env.storage()
+        .instance()
+        .set(&DataKey::WasmHash, hash);
+}
+
+/// Read the WASM hash installed by the last `upgrade` (None if the
+/// contract still runs the code it was deployed with)
+pub fn read_wasm_hash(env: &Env) -> Option<BytesN<32>> {
+    env.storage()
+        .instance()
+        .get(&DataKey::WasmHash)
+}
+
+// ============================================================
+// RESERVE TRACKING STORAGE
+// ============================================================
+
+/// Write tracked reserves (enables tracking)
+pub fn write_tracked_reserves(env: &Env, reserves: &TrackedReserves) {
+    set_persistent(env, &DataKey::TrackedReserves, reserves);
+}
+
+/// Read tracked reserves (None if tracking is disabled)
+pub fn read_tracked_reserves(env: &Env) -> Option<TrackedReserves> {
+    get_persistent(env, &DataKey::TrackedReserves)
+}
+
+/// Remove tracked reserves (disables tracking)
+pub fn remove_tracked_reserves(env: &Env) {
     env.storage()
         .persistent()
-        .set(&DataKey::TWAPNewestIndex, &index);
+        .remove(&DataKey::TrackedReserves);
+}
+
+// ============================================================
+// TWAP STORAGE
+// ============================================================
+
+/// Write TWAP observation
+pub fn write_twap_observation(env: &Env, index: u32, obs: &TWAPObservation) {
+    set_persistent(env, &DataKey::TWAPObservation(index), obs);
+}
+
+/// Read TWAP observation (returns default if not exists)
+pub fn read_twap_observation(env: &Env, index: u32) -> TWAPObservation {
+    get_persistent(env, &DataKey::TWAPObservation(index)).unwrap_or_default()
+}
+
+/// Check if a TWAP observation slot has been written
+pub fn has_twap_observation(env: &Env, index: u32) -> bool {
+    extend_if_present(env, &DataKey::TWAPObservation(index))
+}
+
+/// Set newest TWAP index
+pub fn set_twap_newest_index(env: &Env, index: u32) {
+    set_persistent(env, &DataKey::TWAPNewestIndex, &index);
 }
 
 /// Get newest TWAP index
-#[allow(dead_code)]
 pub fn get_twap_newest_index(env: &Env) -> u32 {
-    env.storage()
-        .persistent()
-        .get(&DataKey::TWAPNewestIndex)
-        .unwrap_or(0)
+    get_persistent(env, &DataKey::TWAPNewestIndex).unwrap_or(0)
 }
 
 /// Check if TWAP is initialized
-#[allow(dead_code)]
 pub fn is_twap_initialized(env: &Env) -> bool {
-    env.storage().persistent().has(&DataKey::TWAPInitialized)
+    extend_if_present(env, &DataKey::TWAPInitialized)
 }
 
 /// Mark TWAP as initialized
-#[allow(dead_code)]
 pub fn set_twap_initialized(env: &Env) {
+    set_persistent(env, &DataKey::TWAPInitialized, &true);
+}
+
+// ============================================================
+// KEY REGISTRIES (testutils only)
+// ============================================================
+
+/// Record a tick key so test helpers can find it again
+#[cfg(feature = "testutils")]
+fn register_tick(env: &Env, tick: i32) {
+    let mut ticks = read_tick_registry(env);
+    if !ticks.contains(tick) {
+        ticks.push_back(tick);
+        env.storage().persistent().set(&DataKey::TickRegistry, &ticks);
+    }
+}
+
+/// Record a position key so test helpers can find it again
+#[cfg(feature = "testutils")]
+fn register_position(env: &Env, owner: &Address, lower: i32, upper: i32) {
+    let mut positions = read_position_registry(env);
+    let key = (owner.clone(), lower, upper);
+    if !positions.contains(&key) {
+        positions.push_back(key);
+        env.storage().persistent().set(&DataKey::PositionRegistry, &positions);
+    }
+}
+
+/// Every tick written since the registries were last cleared
+#[cfg(feature = "testutils")]
+pub fn read_tick_registry(env: &Env) -> Vec<i32> {
     env.storage()
         .persistent()
-        .set(&DataKey::TWAPInitialized, &true);
-}
\ No newline at end of file
+        .get(&DataKey::TickRegistry)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Every position written since the registries were last cleared
+#[cfg(feature = "testutils")]
+pub fn read_position_registry(env: &Env) -> Vec<(Address, i32, i32)> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::PositionRegistry)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Remove every registered tick and position (and its withdrawal queue state),
+/// plus every position opened by id
+#[cfg(feature = "testutils")]
+pub fn clear_registered_keys(env: &Env) {
+    let storage = env.storage().persistent();
+    let tick_spacing = read_pool_state(env).tick_spacing;
+
+    for tick in read_tick_registry(env).iter() {
+        storage.remove(&DataKey::Tick(tick));
+        storage.remove(&DataKey::TickBitmap(tick.div_euclid(tick_spacing).div_euclid(128)));
+    }
+    for (owner, lower, upper) in read_position_registry(env).iter() {
+        storage.remove(&DataKey::Position(owner.clone(), lower, upper));
+        storage.remove(&DataKey::WithdrawalRequest(owner.clone(), lower, upper));
+        storage.remove(&DataKey::WithdrawalWindow(owner.clone(), lower, upper));
+        storage.remove(&DataKey::OwnerPositions(owner));
+    }
+
+    for id in 1..read_pool_state(env).next_position_id {
+        storage.remove(&DataKey::PositionById(id));
+        storage.remove(&DataKey::LimitOrder(id));
+        storage.remove(&DataKey::WithdrawalWindowById(id));
+    }
+
+    storage.remove(&DataKey::TickRegistry);
+    storage.remove(&DataKey::PositionRegistry);
+}
+
+/// Remove the whole TWAP ring buffer
+#[cfg(feature = "testutils")]
+pub fn clear_twap_observations(env: &Env) {
+    let storage = env.storage().persistent();
+
+    for index in 0..crate::constants::MAX_TWAP_OBSERVATIONS {
+        storage.remove(&DataKey::TWAPObservation(index));
+    }
+    storage.remove(&DataKey::TWAPNewestIndex);
+    storage.remove(&DataKey::TWAPInitialized);
+}
+
+/// Remove the protocol fee vesting schedule
+#[cfg(feature = "testutils")]
+pub fn clear_vesting_schedule(env: &Env) {
+    env.storage().persistent().remove(&DataKey::ProtocolFeeVesting);
+}
+
+/// Remove the liquidity mining reward stream
+#[cfg(feature = "testutils")]
+pub fn clear_reward_schedule(env: &Env) {
+    env.storage().persistent().remove(&DataKey::RewardSchedule);
+}
@@ -1,14 +1,41 @@
-use soroban_sdk::{Env, Symbol};
+use soroban_sdk::{panic_with_error, Env, Symbol};
 
+use crate::bps::{apply_bps, deduct_bps, gross_up};
 use crate::constants::{
-    MIN_SWAP_AMOUNT, MIN_OUTPUT_AMOUNT, MAX_SLIPPAGE_BPS, MAX_SWAP_ITERATIONS,
+    MIN_TICK, MAX_TICK, MIN_SWAP_AMOUNT, MIN_OUTPUT_AMOUNT, MAX_SLIPPAGE_BPS, MAX_SWAP_ITERATIONS, Q64,
+};
+use crate::error::{ErrorSymbol, PoolError};
+use crate::events::{emit_fee_zero_growth, emit_liquidity_change};
+use crate::math::{
+    compute_swap_step_with_target, get_sqrt_ratio_at_tick, get_tick_at_sqrt_ratio, mul_div_checked, MIN_SQRT_RATIO, MAX_SQRT_RATIO,
 };
-use crate::error::ErrorSymbol;
-use crate::events::emit_sync_tick;
-use crate::math::{compute_swap_step_with_target, get_sqrt_ratio_at_tick, div_q64};
 use crate::storage::read_tick_info;
 use crate::tick::{find_next_initialized_tick, cross_tick};
-use crate::types::PoolState;
+use crate::types::{LiquidityChange, PoolState};
+
+// ============================================================
+// SWAP OUTCOME
+// ============================================================
+
+/// What a run of the swap engine consumed and delivered
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapOutcome {
+    /// Input consumed, fee included
+    pub amount_in: i128,
+    /// Output delivered
+    pub amount_out: i128,
+    /// LP and protocol fee charged on the input
+    pub fee: i128,
+    /// Initialized ticks crossed
+    pub ticks_crossed: u32,
+    /// Part of `amount_specified` left unfilled: unspent input for exact
+    /// input, undelivered output for exact output
+    pub amount_remaining: i128,
+    /// The swap stopped short because no liquidity was left before the
+    /// price limit, as opposed to reaching a caller's limit or leaving
+    /// rounding dust
+    pub liquidity_exhausted: bool,
+}
 
 // ============================================================
 // PUBLIC SWAP FUNCTIONS
@@ -19,36 +46,34 @@ use crate::types::PoolState;
 /// # Arguments
 /// * `env` - Soroban environment
 /// * `pool` - Mutable pool state
-/// * `amount_specified` - Input amount
+/// * `amount_specified` - Input amount (> 0, exact input) or negated output amount (< 0, exact output)
 /// * `zero_for_one` - Direction (true = token0 -> token1)
 /// * `sqrt_price_limit_x64` - Price limit (0 for no limit)
 /// * `fee_bps` - Fee in basis points
 /// * `protocol_fee_bps` - Protocol fee in basis points
 /// 
 /// # Returns
-/// (amount_in, amount_out)
+/// The `SwapOutcome`: amounts consumed and delivered, fee, ticks crossed
+/// and the unfilled remainder
 /// 
 /// # Panics
-/// If swap amount is too small or no liquidity available
+/// If swap amount is too small, no liquidity lies in the swap direction, or the swap
+/// needs more than `MAX_SWAP_ITERATIONS` steps to fill
 pub fn engine_swap(
     env: &Env,
     pool: &mut PoolState,
     amount_specified: i128,
     zero_for_one: bool,
     sqrt_price_limit_x64: u128,
-    fee_bps: i128,
-    protocol_fee_bps: i128,
-) -> (i128, i128) {
-    if amount_specified < MIN_SWAP_AMOUNT {
-        panic!("swap amount too small");
+    fee_bps: u32,
+    protocol_fee_bps: u32,
+) -> SwapOutcome {
+    if amount_specified.unsigned_abs() < MIN_SWAP_AMOUNT as u128 {
+        panic_with_error!(env, PoolError::SwapAmountTooSmall);
     }
 
-    if amount_specified <= 0 {
-        return (0, 0);
-    }
-
-    if pool.liquidity <= 0 {
-        panic!("no liquidity available");
+    if !is_valid_price_limit(pool.sqrt_price_x64, sqrt_price_limit_x64, zero_for_one) {
+        panic_with_error!(env, PoolError::InvalidPriceLimit);
     }
 
     engine_swap_internal(
@@ -64,6 +89,47 @@ pub fn engine_swap(
     )
 }
 
+/// Run `engine_swap` against a copy of the pool without touching storage
+/// 
+/// Same checks, panics and fee handling as a real swap, so the amounts are
+/// identical to what `engine_swap` would produce for the same inputs.
+/// 
+/// # Returns
+/// (outcome, pool state after the swap)
+pub fn simulate_swap(
+    env: &Env,
+    pool: &PoolState,
+    amount_specified: i128,
+    zero_for_one: bool,
+    sqrt_price_limit_x64: u128,
+    fee_bps: u32,
+    protocol_fee_bps: u32,
+) -> (SwapOutcome, PoolState) {
+    if amount_specified.unsigned_abs() < MIN_SWAP_AMOUNT as u128 {
+        panic_with_error!(env, PoolError::SwapAmountTooSmall);
+    }
+
+    if !is_valid_price_limit(pool.sqrt_price_x64, sqrt_price_limit_x64, zero_for_one) {
+        panic_with_error!(env, PoolError::InvalidPriceLimit);
+    }
+
+    let mut sim_pool = pool.clone();
+
+    let outcome = engine_swap_internal(
+        env,
+        &mut sim_pool,
+        amount_specified,
+        zero_for_one,
+        sqrt_price_limit_x64,
+        fee_bps,
+        protocol_fee_bps,
+        true,  // allow_panic
+        true,  // dry_run
+    );
+
+    (outcome, sim_pool)
+}
+
 /// Quote a swap without executing it
 /// 
 /// # Returns
@@ -74,9 +140,9 @@ pub fn quote_swap(
     amount_in: i128,
     zero_for_one: bool,
     sqrt_price_limit_x64: u128,
-    fee_bps: i128,
+    fee_bps: u32,
 ) -> (i128, i128, u128) {
-    if amount_in < MIN_SWAP_AMOUNT || amount_in <= 0 || pool.liquidity <= 0 {
+    if amount_in.unsigned_abs() < MIN_SWAP_AMOUNT as u128 {
         return (0, 0, pool.sqrt_price_x64);
     }
 
@@ -98,6 +164,9 @@ pub fn quote_swap(
 
 /// Validate and preview a swap
 /// 
+/// A positive `max_amount_in` caps the input charged, fee included, which
+/// is how exact-output swaps bound their cost; 0 leaves it unchecked.
+/// 
 /// # Returns
 /// Ok((amount_in_used, amount_out, fee_paid, final_price)) or Err(Symbol)
 pub fn validate_and_preview_swap(
@@ -105,18 +174,19 @@ pub fn validate_and_preview_swap(
     pool: &PoolState,
     amount_in: i128,
     min_amount_out: i128,
+    max_amount_in: i128,
     zero_for_one: bool,
     sqrt_price_limit_x64: u128,
-    fee_bps: i128,
+    fee_bps: u32,
 ) -> Result<(i128, i128, i128, u128), Symbol> {
-    // Validate input amount
-    if amount_in < MIN_SWAP_AMOUNT {
+    // Validate specified amount (negative = exact output)
+    if amount_in.unsigned_abs() < MIN_SWAP_AMOUNT as u128 {
         return Err(ErrorSymbol::amt_low());
     }
 
-    // Validate liquidity
-    if pool.liquidity <= 0 {
-        return Err(ErrorSymbol::no_liq());
+    // Validate price limit
+    if !is_valid_price_limit(pool.sqrt_price_x64, sqrt_price_limit_x64, zero_for_one) {
+        return Err(ErrorSymbol::bad_limit());
     }
 
     // Get quote
@@ -129,10 +199,18 @@ pub fn validate_and_preview_swap(
         fee_bps,
     );
 
-    // Check slippage
+    // Nothing filled: no liquidity in the swap direction
+    if amount_in_used == 0 && amount_out == 0 {
+        return Err(ErrorSymbol::no_liq());
+    }
+
+    // Check slippage on both sides
     if amount_out < min_amount_out {
         return Err(ErrorSymbol::slip_hi());
     }
+    if max_amount_in > 0 && amount_in_used > max_amount_in {
+        return Err(ErrorSymbol::slip_hi());
+    }
 
     // Check minimum output
     if amount_out < MIN_OUTPUT_AMOUNT {
@@ -142,11 +220,12 @@ pub fn validate_and_preview_swap(
     // Calculate fee paid
     let fee_paid = amount_in_used.saturating_sub(amount_out);
 
-    // Calculate slippage in basis points
+    // Calculate slippage in basis points (relative to the input side)
+    let input_basis = if amount_in > 0 { amount_in } else { amount_in_used };
     let slippage_bps = if amount_in_used > 0 {
-        (amount_in.saturating_sub(amount_out))
+        (input_basis.saturating_sub(amount_out))
             .saturating_mul(10000)
-            .saturating_div(amount_in)
+            .saturating_div(input_basis)
     } else {
         0
     };
@@ -170,18 +249,18 @@ fn engine_swap_safe(
     amount_specified: i128,
     zero_for_one: bool,
     sqrt_price_limit_x64: u128,
-    fee_bps: i128,
-    protocol_fee_bps: i128,
+    fee_bps: u32,
+    protocol_fee_bps: u32,
 ) -> (i128, i128) {
-    if amount_specified < MIN_SWAP_AMOUNT || amount_specified <= 0 {
+    if amount_specified.unsigned_abs() < MIN_SWAP_AMOUNT as u128 {
         return (0, 0);
     }
 
-    if pool.liquidity <= 0 {
+    if !is_valid_price_limit(pool.sqrt_price_x64, sqrt_price_limit_x64, zero_for_one) {
         return (0, 0);
     }
 
-    engine_swap_internal(
+    let outcome = engine_swap_internal(
         env,
         pool,
         amount_specified,
@@ -191,57 +270,92 @@ fn engine_swap_safe(
         protocol_fee_bps,
         false, // allow_panic
         true,  // dry_run - DON'T modify tick state!
-    )
+    );
+
+    (outcome.amount_in, outcome.amount_out)
+}
+
+/// Whether `sqrt_price_limit_x64` is usable for a swap from `sqrt_price`
+/// 
+/// 0 means no limit. Otherwise the limit must lie strictly between the
+/// current price and MIN_SQRT_RATIO (`zero_for_one`) or MAX_SQRT_RATIO.
+fn is_valid_price_limit(sqrt_price: u128, sqrt_price_limit_x64: u128, zero_for_one: bool) -> bool {
+    if sqrt_price_limit_x64 == 0 {
+        return true;
+    }
+
+    if zero_for_one {
+        sqrt_price_limit_x64 < sqrt_price && sqrt_price_limit_x64 > MIN_SQRT_RATIO
+    } else {
+        sqrt_price_limit_x64 > sqrt_price && sqrt_price_limit_x64 < MAX_SQRT_RATIO
+    }
 }
 
 /// Core swap logic following Uniswap V3 pattern
 /// 
 /// # Arguments
 /// * `dry_run` - If true, tick storage is NOT modified (for quotes)
+/// 
+/// # Returns
+/// The `SwapOutcome`, or an empty one on a failure when `allow_panic` is
+/// false
 fn engine_swap_internal(
     env: &Env,
     pool: &mut PoolState,
     amount_specified: i128,
     zero_for_one: bool,
     sqrt_price_limit_x64: u128,
-    fee_bps: i128,
-    protocol_fee_bps: i128,
+    fee_bps: u32,
+    protocol_fee_bps: u32,
     allow_panic: bool,
     dry_run: bool,
-) -> (i128, i128) {
+) -> SwapOutcome {
     // Initialize swap state
+    // Exact input counts amount_remaining down to zero; exact output holds
+    // the negated output still owed and counts it up to zero
+    let exact_input = amount_specified > 0;
     let mut amount_remaining = amount_specified;
+    let mut amount_in_total: i128 = 0;
     let mut amount_out_total: i128 = 0;
+    let mut total_fee: i128 = 0;
     let mut total_protocol_fee: i128 = 0;
+    let mut ticks_crossed: u32 = 0;
 
     let mut sqrt_price = pool.sqrt_price_x64;
     let mut liquidity = pool.liquidity;
     let mut current_tick = pool.current_tick;
 
-    // Set default price limits
+    // Default to just inside the outermost tick prices: landing on
+    // MIN_TICK's price going down would leave current_tick below the valid
+    // range. User limits were checked against the same bounds by the caller.
     let sqrt_limit = if sqrt_price_limit_x64 == 0 {
-        if zero_for_one {
-            100 // Minimum price
-        } else {
-            u128::MAX - 1000 // Maximum price
-        }
+        if zero_for_one { MIN_SQRT_RATIO + 1 } else { MAX_SQRT_RATIO - 1 }
     } else {
         sqrt_price_limit_x64
     };
 
+    // Price and tick where the swap entered a zero-liquidity gap. Gaps are
+    // crossed for free; if no liquidity lies beyond, the price goes back here
+    let mut gap_start: Option<(u128, i32)> = None;
+
+    // The price moved within a tick range since `current_tick` was last
+    // set. A stale tick still finds the right next initialized tick, so it
+    // is only re-derived from the price once the loop ends
+    let mut tick_stale = false;
+
     // Main swap loop
     let mut iterations = 0;
 
-    while iterations < MAX_SWAP_ITERATIONS {
-        iterations += 1;
-
-        // Exit conditions
-        if amount_remaining <= 0 {
+    loop {
+        // Exit conditions: the input is spent or the output delivered
+        if (exact_input && amount_remaining <= 0) || (!exact_input && amount_remaining >= 0) {
             break;
         }
 
-        if liquidity <= 0 {
-            break;
+        if liquidity > 0 {
+            gap_start = None;
+        } else if gap_start.is_none() {
+            gap_start = Some((sqrt_price, current_tick));
         }
 
         // Check price limit
@@ -252,8 +366,8 @@ fn engine_swap_internal(
             break;
         }
 
-        // Find next initialized tick
-        let next_tick = find_next_initialized_tick(
+        // Find next initialized tick (or the bitmap word boundary)
+        let (next_tick, initialized) = find_next_initialized_tick(
             env,
             current_tick,
             pool.tick_spacing,
@@ -261,39 +375,57 @@ fn engine_swap_internal(
         );
 
         // Get sqrt price at next tick
-        let mut sqrt_target = get_sqrt_ratio_at_tick(next_tick);
+        let sqrt_next_tick = get_sqrt_ratio_at_tick(env, next_tick);
 
-        // Clamp target to user's price limit
-        if zero_for_one {
-            if sqrt_target < sqrt_limit {
-                sqrt_target = sqrt_limit;
-            }
-        } else if sqrt_target > sqrt_limit {
-            sqrt_target = sqrt_limit;
+        // Already at the edge of the tick range with nothing left to cross
+        if sqrt_next_tick == sqrt_price && !initialized && (next_tick == MIN_TICK || next_tick == MAX_TICK) {
+            break;
         }
 
-        // Calculate fee divisor
-        let fee_divisor = 10000 - fee_bps;
-        if fee_divisor <= 0 {
+        // Clamp target to the price limit
+        let sqrt_target = if zero_for_one {
+            sqrt_next_tick.max(sqrt_limit)
+        } else {
+            sqrt_next_tick.min(sqrt_limit)
+        };
+
+        // A 100% fee leaves nothing to swap
+        if fee_bps >= 10000 {
             if allow_panic {
-                panic!("fee too high");
+                panic_with_error!(env, PoolError::InvalidFee);
             } else {
                 break;
             }
         }
 
-        // Amount available after fee reservation
-        let amount_available = amount_remaining
-            .saturating_mul(fee_divisor)
-            .saturating_div(10000);
+        // Exact input reserves the fee up front; exact output adds it on top
+        let amount_available = if exact_input {
+            deduct_bps(env, amount_remaining, fee_bps)
+        } else {
+            amount_remaining
+        };
 
-        if amount_available < MIN_OUTPUT_AMOUNT {
+        if exact_input && amount_available < MIN_OUTPUT_AMOUNT {
             break;
         }
 
-        // Compute swap step
-        let (sqrt_next, amount_in, amount_out) = if sqrt_price == sqrt_target {
-            (sqrt_price, 0, 0)
+        // Out of steps with input still left and no legitimate stop reached:
+        // stopping here would silently fill only part of the swap. Quotes
+        // panic too, since a swap that cannot fill has no meaningful quote
+        // and (0, 0) would be reported as dust output.
+        if iterations == MAX_SWAP_ITERATIONS {
+            // A gap this wide is treated as having no liquidity beyond it
+            if liquidity <= 0 {
+                break;
+            }
+            panic_with_error!(env, PoolError::SwapTooManyIterations);
+        }
+        iterations += 1;
+
+        // Compute swap step (none needed if the price already sits on the
+        // next tick, nor in a gap, where the price moves to it for free)
+        let (sqrt_next, amount_in, amount_out) = if sqrt_price == sqrt_target || liquidity <= 0 {
+            (sqrt_target, 0, 0)
         } else {
             compute_swap_step_with_target(
                 env,
@@ -305,18 +437,24 @@ fn engine_swap_internal(
             )
         };
 
-        // Check minimum amounts
-        if amount_in < MIN_OUTPUT_AMOUNT || amount_out < MIN_OUTPUT_AMOUNT {
+        let target_reached = sqrt_next == sqrt_target;
+
+        // Check minimum amounts (a step that only lands on the next tick may be empty)
+        if !target_reached && (amount_in < MIN_OUTPUT_AMOUNT || amount_out < MIN_OUTPUT_AMOUNT) {
             break;
         }
 
         // Calculate step fee
-        let step_fee = calculate_step_fee(amount_in, amount_remaining, amount_available, fee_bps, fee_divisor);
+        let step_fee = if exact_input {
+            calculate_step_fee(env, amount_in, amount_remaining, amount_available, fee_bps)
+        } else {
+            gross_up(env, amount_in, fee_bps) - amount_in
+        };
 
         // Validate fee
         if step_fee < 0 || step_fee > amount_in {
             if allow_panic {
-                panic!("invalid fee calculation");
+                panic_with_error!(env, PoolError::InvalidFeeCalculation);
             } else {
                 break;
             }
@@ -324,7 +462,7 @@ fn engine_swap_internal(
 
         // Calculate protocol fee
         let protocol_fee = if protocol_fee_bps > 0 && step_fee > 0 {
-            step_fee.saturating_mul(protocol_fee_bps).saturating_div(10000)
+            apply_bps(env, step_fee, protocol_fee_bps)
         } else {
             0
         };
@@ -332,60 +470,88 @@ fn engine_swap_internal(
         let lp_fee = step_fee.saturating_sub(protocol_fee);
 
         // Update amounts
-        amount_remaining = amount_remaining
-            .saturating_sub(amount_in)
-            .saturating_sub(step_fee);
+        if exact_input {
+            amount_remaining = amount_remaining
+                .saturating_sub(amount_in)
+                .saturating_sub(step_fee);
+        } else {
+            amount_remaining = amount_remaining.saturating_add(amount_out);
+        }
+        amount_in_total = amount_in_total
+            .saturating_add(amount_in)
+            .saturating_add(step_fee);
         amount_out_total = amount_out_total.saturating_add(amount_out);
+        total_fee = total_fee.saturating_add(step_fee);
         total_protocol_fee = total_protocol_fee.saturating_add(protocol_fee);
 
         // Update fee growth global (Uniswap V3 style)
         if liquidity > 0 && lp_fee > 0 {
             let fee_u = lp_fee as u128;
             let liq_u = liquidity as u128;
-            let growth_delta = div_q64(fee_u, liq_u);
+            // Rounded down: LPs can never be credited more than was paid in.
+            // A delta that does not fit would wrap fee growth backwards
+            let growth_delta = match mul_div_checked(fee_u, Q64, liq_u) {
+                Some(delta) => delta,
+                None if allow_panic => panic_with_error!(env, PoolError::InvalidFeeCalculation),
+                None => break,
+            };
 
             if zero_for_one {
                 pool.fee_growth_global_0 = pool.fee_growth_global_0.wrapping_add(growth_delta);
             } else {
                 pool.fee_growth_global_1 = pool.fee_growth_global_1.wrapping_add(growth_delta);
             }
-        }
 
-        // Handle tick crossing
-        let target_reached = sqrt_next == sqrt_target;
-        let should_cross = if zero_for_one {
-            sqrt_target <= sqrt_price
-        } else {
-            sqrt_target >= sqrt_price
-        };
-        let at_user_limit = sqrt_price_limit_x64 != 0 && sqrt_target == sqrt_limit;
+            // Fee too small relative to liquidity to register in Q64.64 growth
+            if growth_delta == 0 && !dry_run {
+                if zero_for_one {
+                    pool.zero_growth_fees_0 = pool.zero_growth_fees_0.saturating_add(fee_u);
+                } else {
+                    pool.zero_growth_fees_1 = pool.zero_growth_fees_1.saturating_add(fee_u);
+                }
+                emit_fee_zero_growth(env, zero_for_one, fee_u, liq_u);
+            }
+        }
 
-        if target_reached && should_cross && !at_user_limit {
+        // Handle tick crossing: only when the step landed on the tick itself,
+        // not on a price limit short of it
+        if target_reached && sqrt_target == sqrt_next_tick {
             // Update price first
             sqrt_price = sqrt_target;
 
-            // Cross tick - but only modify storage if NOT dry_run
-            let liquidity_net = if dry_run {
-                // For dry run (quotes), just read the liquidity_net without modifying storage
-                let tick_info = read_tick_info(env, next_tick);
-                tick_info.liquidity_net
-            } else {
-                // Actually cross the tick and modify storage
-                cross_tick(
-                    env,
-                    next_tick,
-                    pool.fee_growth_global_0,
-                    pool.fee_growth_global_1,
-                )
-            };
-
-            // Update liquidity based on direction
-            if zero_for_one {
-                // Moving left (price decreasing)
-                liquidity = liquidity.saturating_sub(liquidity_net);
-            } else {
-                // Moving right (price increasing)
-                liquidity = liquidity.saturating_add(liquidity_net);
+            // Only initialized ticks carry liquidity; word boundaries are just stepped over
+            if initialized {
+                // Cross tick - but only modify storage if NOT dry_run
+                let liquidity_net = if dry_run {
+                    // For dry run (quotes), just read the liquidity_net without modifying storage
+                    let tick_info = read_tick_info(env, next_tick);
+                    tick_info.liquidity_net
+                } else {
+                    // Actually cross the tick and modify storage
+                    cross_tick(
+                        env,
+                        next_tick,
+                        pool.fee_growth_global_0,
+                        pool.fee_growth_global_1,
+                        pool.reward_growth_global,
+                    )
+                };
+
+                // Update liquidity based on direction
+                let liquidity_before = liquidity;
+                if zero_for_one {
+                    // Moving left (price decreasing)
+                    liquidity = liquidity.saturating_sub(liquidity_net);
+                } else {
+                    // Moving right (price increasing)
+                    liquidity = liquidity.saturating_add(liquidity_net);
+                }
+
+                ticks_crossed += 1;
+
+                if !dry_run && liquidity != liquidity_before {
+                    emit_liquidity_change(env, liquidity_before, liquidity, LiquidityChange::Cross);
+                }
             }
 
             // Update current tick
@@ -394,11 +560,14 @@ fn engine_swap_internal(
             } else {
                 next_tick
             };
+            tick_stale = false;
         } else if sqrt_next != sqrt_price {
-            // Moved within tick range
+            // Moved within tick range; the step can still span many
+            // uninitialized ticks, so the tick is re-derived after the loop
             sqrt_price = sqrt_next;
+            tick_stale = true;
 
-            if amount_remaining <= 0 {
+            if (exact_input && amount_remaining <= 0) || (!exact_input && amount_remaining >= 0) {
                 break;
             }
         } else {
@@ -407,12 +576,34 @@ fn engine_swap_internal(
         }
     }
 
+    if tick_stale {
+        current_tick = get_tick_at_sqrt_ratio(env, sqrt_price);
+    }
+
+    // Ended in a gap with no liquidity beyond it: nothing was traded past
+    // where the gap started, and no initialized tick was crossed in it
+    if let Some((gap_price, gap_tick)) = gap_start {
+        if liquidity <= 0 {
+            sqrt_price = gap_price;
+            current_tick = gap_tick;
+        }
+    }
+
+    // Nothing to trade against in the swap direction
+    if amount_in_total == 0 && liquidity <= 0 {
+        if allow_panic {
+            panic_with_error!(env, PoolError::NoLiquidity);
+        } else {
+            return SwapOutcome::default();
+        }
+    }
+
     // Validate output
     if amount_out_total < MIN_OUTPUT_AMOUNT {
         if allow_panic {
-            panic!("output amount too small");
+            panic_with_error!(env, PoolError::OutputTooSmall);
         } else {
-            return (0, 0);
+            return SwapOutcome::default();
         }
     }
 
@@ -430,37 +621,51 @@ fn engine_swap_internal(
         }
     }
 
-    // Emit sync event
-    emit_sync_tick(env, pool.current_tick, pool.sqrt_price_x64);
+    // Exact output counts the negated output still owed up to zero
+    let unfilled = if exact_input { amount_remaining } else { amount_remaining.saturating_neg() };
 
-    let amount_in_total = amount_specified.saturating_sub(amount_remaining);
-    (amount_in_total, amount_out_total)
+    // Ended in a gap, or at the edge of the price range without a limit
+    let liquidity_exhausted = unfilled > 0
+        && (liquidity <= 0 || (sqrt_price_limit_x64 == 0 && sqrt_price == sqrt_limit));
+
+    SwapOutcome {
+        amount_in: amount_in_total,
+        amount_out: amount_out_total,
+        fee: total_fee,
+        ticks_crossed,
+        amount_remaining: unfilled.max(0),
+        liquidity_exhausted,
+    }
 }
 
 // ============================================================
 // HELPER FUNCTIONS
 // ============================================================
 
+/// Whether a swap delivered everything `amount_specified` asked for:
+/// all of the input for exact input, all of the output for exact output
+pub fn is_fully_filled(amount_specified: i128, amount_in: i128, amount_out: i128) -> bool {
+    if amount_specified > 0 {
+        amount_in == amount_specified
+    } else {
+        amount_out == amount_specified.saturating_neg()
+    }
+}
+
 /// Calculate the fee for a swap step
 #[inline]
 fn calculate_step_fee(
+    env: &Env,
     amount_in: i128,
     amount_remaining: i128,
     amount_available: i128,
-    fee_bps: i128,
-    fee_divisor: i128,
+    fee_bps: u32,
 ) -> i128 {
     if amount_in == amount_available {
         // Used all available amount
         amount_remaining.saturating_sub(amount_in)
     } else {
-        // Calculate fee on amount_in
-        let fee_num = amount_in.saturating_mul(fee_bps);
-        let fee = fee_num.saturating_div(fee_divisor);
-        if fee_num % fee_divisor != 0 {
-            fee.saturating_add(1) // Round up
-        } else {
-            fee
-        }
+        // Fee on amount_in such that amount_in + fee is the gross input (rounded up)
+        gross_up(env, amount_in, fee_bps) - amount_in
     }
-}
\ No newline at end of file
+}
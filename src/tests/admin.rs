@@ -0,0 +1,69 @@
+//! Two-step admin transfer: propose, accept and what each admin may do after.
+
+use soroban_sdk::testutils::{Address as _, MockAuth, MockAuthInvoke};
+use soroban_sdk::{Address, IntoVal};
+
+use super::{assert_error, Fixture};
+use crate::error::PoolError;
+
+// ============================================================
+// ADMIN TRANSFER
+// ============================================================
+
+#[test]
+fn accept_without_a_proposal_fails() {
+    let fixture = Fixture::new();
+    assert_error(fixture.pool.try_accept_admin(), PoolError::NoPendingAdmin);
+    assert_eq!(fixture.pool.get_pending_admin(), None);
+}
+
+#[test]
+fn only_the_admin_can_propose() {
+    let fixture = Fixture::new();
+    let candidate = Address::generate(&fixture.env);
+    assert_error(fixture.pool.try_propose_admin(&candidate, &candidate), PoolError::Unauthorized);
+
+    fixture.pool.propose_admin(&fixture.admin, &candidate);
+    assert_eq!(fixture.pool.get_pending_admin(), Some(candidate));
+    assert_eq!(fixture.pool.get_admin(), fixture.admin);
+}
+
+#[test]
+fn accept_needs_the_proposed_admin_to_sign() {
+    let fixture = Fixture::new();
+    let candidate = Address::generate(&fixture.env);
+    fixture.pool.propose_admin(&fixture.admin, &candidate);
+
+    // Only the current admin signs, which is not enough
+    let accepted = fixture
+        .pool
+        .mock_auths(&[MockAuth {
+            address: &fixture.admin,
+            invoke: &MockAuthInvoke {
+                contract: &fixture.pool.address,
+                fn_name: "accept_admin",
+                args: ().into_val(&fixture.env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_accept_admin();
+    assert!(accepted.is_err());
+    assert_eq!(fixture.pool.get_admin(), fixture.admin);
+    assert_eq!(fixture.pool.get_pending_admin(), Some(candidate));
+}
+
+#[test]
+fn accepted_admin_replaces_the_old_one() {
+    let fixture = Fixture::new();
+    let candidate = Address::generate(&fixture.env);
+    fixture.pool.propose_admin(&fixture.admin, &candidate);
+
+    fixture.env.mock_all_auths();
+    fixture.pool.accept_admin();
+    assert_eq!(fixture.pool.get_admin(), candidate);
+    assert_eq!(fixture.pool.get_pending_admin(), None);
+
+    assert_error(fixture.pool.try_set_protocol_fee(&fixture.admin, &10), PoolError::Unauthorized);
+    fixture.pool.set_protocol_fee(&candidate, &10);
+    assert_error(fixture.pool.try_accept_admin(), PoolError::NoPendingAdmin);
+}
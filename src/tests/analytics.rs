@@ -0,0 +1,114 @@
+//! Range analytics: break-even fees, what-if estimates and the price that
+//! activates a range.
+
+use super::Fixture;
+use crate::math::get_sqrt_ratio_at_tick;
+
+// ============================================================
+// BREAK-EVEN FEES
+// ============================================================
+
+#[test]
+fn narrower_ranges_need_higher_break_even_fees() {
+    let fixture = Fixture::new();
+
+    let fees = [60, 600, 6_000, 60_000].map(|w| fixture.pool.break_even_fees(&-w, &w, &500));
+
+    for pair in fees.windows(2) {
+        assert!(pair[0] > pair[1], "{fees:?}");
+    }
+}
+
+#[test]
+fn break_even_fees_grow_with_volatility() {
+    let fixture = Fixture::new();
+
+    assert_eq!(fixture.pool.break_even_fees(&-600, &600, &0), 0);
+    let calm = fixture.pool.break_even_fees(&-600, &600, &100);
+    let volatile = fixture.pool.break_even_fees(&-600, &600, &1_000);
+    assert!(0 < calm && calm < volatile);
+}
+
+// ============================================================
+// WHAT-IF
+// ============================================================
+
+#[test]
+fn what_if_values_are_pinned_for_a_symmetric_range() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let user = &fixture.user;
+
+    // (tick, principal at that tick, value versus holding in token1)
+    let cases = [
+        (-900, (2_030_452_986, 0), -58_232_641),
+        (-600, (2_030_452_986, 0), -29_553_010),
+        (-300, (1_511_362_559, 496_250_257), -7_499_484),
+        (0, (999_999_998, 999_999_998), 0),
+        (300, (496_250_257, 1_511_362_559), -7_727_866),
+        (600, (0, 2_030_452_986), -31_380_372),
+        (900, (0, 2_030_452_986), -63_716_371),
+    ];
+    for (tick, principal, delta) in cases {
+        assert_eq!(fixture.pool.get_position_value_at_tick(user, &-600, &600, &tick), principal, "{tick}");
+        assert_eq!(fixture.pool.get_impermanent_delta(user, &-600, &600, &tick), delta, "{tick}");
+    }
+}
+
+#[test]
+fn impermanent_delta_matches_lp_minus_held_value() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let user = &fixture.user;
+    let (held0, held1) = fixture.pool.get_position_value_at_tick(user, &-600, &600, &0);
+
+    for tick in [-900, -300, -60, 60, 300, 900] {
+        let price = 1.0001f64.powi(tick);
+        let (lp0, lp1) = fixture.pool.get_position_value_at_tick(user, &-600, &600, &tick);
+        let expected = (lp0 as f64 * price + lp1 as f64) - (held0 as f64 * price + held1 as f64);
+
+        let delta = fixture.pool.get_impermanent_delta(user, &-600, &600, &tick);
+        assert!(delta <= 0);
+        assert!((delta as f64 - expected).abs() < 5.0, "{tick}: {delta} vs {expected}");
+    }
+}
+
+// ============================================================
+// PRICE TO ACTIVATE
+// ============================================================
+
+#[test]
+fn ranges_out_of_range_target_their_nearest_boundary() {
+    let fixture = Fixture::at_tick(30, 60, 300);
+    let sqrt_at = |tick| get_sqrt_ratio_at_tick(&fixture.env, tick);
+
+    // Above the price: rise to the lower tick
+    assert_eq!(fixture.pool.price_to_activate(&600, &1_200), (sqrt_at(600), true));
+    // Below the price: fall to the upper tick
+    assert_eq!(fixture.pool.price_to_activate(&-1_200, &-600), (sqrt_at(-600), false));
+    // The upper tick itself is out of range
+    assert_eq!(fixture.pool.price_to_activate(&0, &300), (sqrt_at(300), false));
+}
+
+#[test]
+fn active_ranges_report_the_current_price() {
+    let fixture = Fixture::at_tick(30, 60, 300);
+    let current = fixture.pool.get_pool_state().sqrt_price_x64;
+
+    assert_eq!(fixture.pool.price_to_activate(&-600, &600), (current, false));
+    assert_eq!(fixture.pool.price_to_activate(&300, &600), (current, false));
+}
+
+#[test]
+fn reaching_the_target_activates_the_range() {
+    let fixture = Fixture::at_tick(30, 60, 0);
+    fixture.add(-6_000, 6_000, 1_000_000_000);
+
+    let (target, must_rise) = fixture.pool.price_to_activate(&600, &1_200);
+    assert!(must_rise);
+    fixture.pool.swap_advanced(&fixture.user, &200_000_000, &0, &0, &false, &target, &0);
+    assert_eq!(fixture.pool.get_pool_state().sqrt_price_x64, target);
+
+    // On the lower tick the range is active
+    assert_eq!(fixture.pool.price_to_activate(&600, &1_200), (target, false));
+}
@@ -0,0 +1,126 @@
+use soroban_sdk::{Env, U256};
+
+use crate::bps::{apply_bps, apply_bps_ceil, deduct_bps, gross_up, BPS_DENOMINATOR};
+
+// ============================================================
+// ERROR CODES
+// ============================================================
+
+#[test]
+#[should_panic(expected = "Error(Contract, #2)")]
+fn negative_amount_is_amount_negative() {
+    apply_bps(&Env::default(), -1, 30);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #12)")]
+fn bps_above_denominator_is_invalid_fee() {
+    apply_bps_ceil(&Env::default(), 1_000, 10_001);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #12)")]
+fn gross_up_at_full_fee_is_invalid_fee() {
+    gross_up(&Env::default(), 1_000, 10_000);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn gross_up_overflow() {
+    gross_up(&Env::default(), i128::MAX, 30);
+}
+
+#[test]
+fn deduct_rounds_the_share_up() {
+    let env = Env::default();
+    assert_eq!(deduct_bps(&env, 1_001, 30), 997);
+    assert_eq!(deduct_bps(&env, 1_000, 0), 1_000);
+    assert_eq!(deduct_bps(&env, 1_000, 10_000), 0);
+}
+
+// ============================================================
+// EXACTNESS
+// ============================================================
+
+/// `amount * bps / 10000` computed in 256 bits, rounded down or up
+fn reference(env: &Env, amount: i128, bps: u32, round_up: bool) -> i128 {
+    let product = U256::from_u128(env, amount as u128).mul(&U256::from_u32(env, bps));
+    let denominator = U256::from_u32(env, BPS_DENOMINATOR as u32);
+    let quotient = product.div(&denominator);
+    let exact = quotient.mul(&denominator) == product;
+    let quotient = quotient.to_u128().unwrap() as i128;
+    if round_up && !exact { quotient + 1 } else { quotient }
+}
+
+/// Amounts from dust to i128::MAX, including values around each power of
+/// two and around multiples of the denominator
+fn amounts() -> impl Iterator<Item = i128> {
+    let edges = (0..127).flat_map(|bit| {
+        let base = 1i128 << bit;
+        [base - 1, base, base + 1, base + 9_999]
+    });
+    let near_max = (0..300).map(|d| i128::MAX - d);
+    [0, 1, 9_999, 10_000, 10_001, 123_456_789].into_iter().chain(edges).chain(near_max)
+}
+
+const BPS: [u32; 9] = [0, 1, 5, 30, 100, 3_333, 9_999, 10_000, 7];
+
+#[test]
+fn split_matches_256_bit_reference() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+    for amount in amounts() {
+        for bps in BPS {
+            assert_eq!(apply_bps(&env, amount, bps), reference(&env, amount, bps, false), "{amount} * {bps}");
+            assert_eq!(apply_bps_ceil(&env, amount, bps), reference(&env, amount, bps, true), "{amount} * {bps}");
+            assert_eq!(deduct_bps(&env, amount, bps), amount - reference(&env, amount, bps, true));
+        }
+    }
+}
+
+#[test]
+fn boundaries_at_max_amount_and_full_bps() {
+    let env = Env::default();
+    let max = i128::MAX;
+
+    assert_eq!(apply_bps(&env, max, 10_000), max);
+    assert_eq!(apply_bps_ceil(&env, max, 10_000), max);
+    assert_eq!(deduct_bps(&env, max, 10_000), 0);
+    assert_eq!(apply_bps(&env, max, 0), 0);
+    assert_eq!(deduct_bps(&env, max, 0), max);
+
+    // i128::MAX = 17014118346046923173168730371588410572 * 10^4 + 7
+    assert_eq!(apply_bps(&env, max, 1), max / 10_000);
+    assert_eq!(apply_bps_ceil(&env, max, 1), max / 10_000 + 1);
+    assert_eq!(apply_bps(&env, max, 9_999), max - max / 10_000 - 1);
+
+    assert_eq!(gross_up(&env, max / 2, 5_000), max - 1);
+    assert_eq!(gross_up(&env, 0, 9_999), 0);
+}
+
+#[test]
+fn gross_up_is_the_smallest_gross_leaving_net() {
+    let env = Env::default();
+    for net in [1i128, 997, 10_000, 123_456_789, 1i128 << 100] {
+        for bps in [0u32, 1, 30, 100, 9_999] {
+            let gross = gross_up(&env, net, bps);
+            let kept = BPS_DENOMINATOR - bps as i128;
+            assert!(gross * kept >= net * BPS_DENOMINATOR, "{net} @ {bps}: {gross} too small");
+            assert!((gross - 1) * kept < net * BPS_DENOMINATOR, "{net} @ {bps}: {gross} not smallest");
+        }
+    }
+}
+
+#[test]
+fn identical_to_plain_arithmetic_for_normal_amounts() {
+    let env = Env::default();
+    let mut amount = 1i128;
+    while amount < 1i128 << 100 {
+        for bps in BPS {
+            let plain = amount * bps as i128;
+            assert_eq!(apply_bps(&env, amount, bps), plain / 10_000);
+            assert_eq!(apply_bps_ceil(&env, amount, bps), (plain + 9_999) / 10_000);
+        }
+        amount = amount * 3 + 7;
+    }
+}
@@ -0,0 +1,195 @@
+//! Deadlines on state-changing entrypoints and the admin's ceiling on how
+//! far ahead they may be set.
+
+use soroban_sdk::testutils::Ledger;
+use soroban_sdk::TryFromVal;
+
+use super::{assert_error, events_named, Fixture};
+use crate::error::PoolError;
+
+const NOW: u64 = 50_000;
+
+/// Pool with liquidity around the price at ledger time `NOW`
+fn pool<'a>() -> Fixture<'a> {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture.env.ledger().with_mut(|l| l.timestamp = NOW);
+    fixture
+}
+
+// ============================================================
+// EXPIRY
+// ============================================================
+
+#[test]
+fn expired_deadlines_revert() {
+    let fixture = pool();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+    let expired = NOW - 1;
+
+    assert_error(
+        fixture.pool.try_swap(user, token0, token1, &1_000_000, &0, &0, &expired),
+        PoolError::DeadlineExpired,
+    );
+    assert_error(
+        fixture.pool.try_add_liquidity(user, token0, token1, &1_000_000, &1_000_000, &0, &0, &-600, &600, &expired),
+        PoolError::DeadlineExpired,
+    );
+    assert_error(
+        fixture.pool.try_remove_liquidity(user, &-600, &600, &1_000, &expired),
+        PoolError::DeadlineExpired,
+    );
+    assert_error(
+        fixture.pool.try_collect(user, &-600, &600, &u128::MAX, &u128::MAX, &expired),
+        PoolError::DeadlineExpired,
+    );
+}
+
+#[test]
+fn calls_succeed_exactly_at_the_deadline() {
+    let fixture = pool();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+
+    fixture.pool.swap(user, token0, token1, &1_000_000, &0, &0, &NOW);
+    fixture.pool.add_liquidity(user, token0, token1, &1_000_000, &1_000_000, &0, &0, &-600, &600, &NOW);
+    fixture.pool.remove_liquidity(user, &-600, &600, &1_000, &NOW);
+    fixture.pool.collect(user, &-600, &600, &u128::MAX, &u128::MAX, &NOW);
+}
+
+#[test]
+fn deadline_passes_as_the_ledger_advances() {
+    let fixture = pool();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+    let deadline = NOW + 30;
+
+    fixture.pool.swap(user, token0, token1, &1_000_000, &0, &0, &deadline);
+
+    fixture.env.ledger().with_mut(|l| l.timestamp = deadline + 1);
+    assert_error(
+        fixture.pool.try_swap(user, token0, token1, &1_000_000, &0, &0, &deadline),
+        PoolError::DeadlineExpired,
+    );
+
+    // 0 never expires
+    fixture.pool.swap(user, token0, token1, &1_000_000, &0, &0, &0);
+}
+
+#[test]
+fn expired_calls_leave_balances_and_state_untouched() {
+    let fixture = pool();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+    let balances = || (fixture.balance(token0, user), fixture.balance(token1, user));
+    let (before, state) = (balances(), fixture.pool.get_pool_state());
+
+    fixture.env.ledger().with_mut(|l| l.timestamp = NOW + 3_600);
+    assert_error(
+        fixture.pool.try_swap(user, token0, token1, &1_000_000, &0, &0, &NOW),
+        PoolError::DeadlineExpired,
+    );
+    assert_error(
+        fixture.pool.try_add_liquidity(user, token0, token1, &1_000_000, &1_000_000, &0, &0, &-600, &600, &NOW),
+        PoolError::DeadlineExpired,
+    );
+
+    assert_eq!(balances(), before);
+    let after = fixture.pool.get_pool_state();
+    assert_eq!((after.sqrt_price_x64, after.liquidity), (state.sqrt_price_x64, state.liquidity));
+}
+
+#[test]
+fn every_deadline_variant_checks_expiry() {
+    let fixture = pool();
+    let (pool, user) = (&fixture.pool, &fixture.user);
+    let expired = NOW - 1;
+    let route = soroban_sdk::vec![&fixture.env, pool.address.clone()];
+
+    assert_error(pool.try_swap_advanced(user, &1_000_000, &0, &0, &true, &0, &expired), PoolError::DeadlineExpired);
+    assert_error(
+        pool.try_swap_exact_in_multi(user, &route, &fixture.token0, &1_000_000, &0, &expired),
+        PoolError::DeadlineExpired,
+    );
+    assert_error(
+        pool.try_add_liquidity_advanced(user, &-600, &600, &1_000, &1_000, &0, &0, &expired),
+        PoolError::DeadlineExpired,
+    );
+    assert_error(
+        pool.try_decrease_liquidity_and_collect(user, &-600, &600, &1_000, &expired),
+        PoolError::DeadlineExpired,
+    );
+    assert_error(pool.try_collect_owed(user, &-600, &600, &expired), PoolError::DeadlineExpired);
+    assert_error(pool.try_close_position(user, &-600, &600, &expired), PoolError::DeadlineExpired);
+}
+
+// ============================================================
+// DEADLINE WINDOW
+// ============================================================
+
+const WINDOW: u64 = 300;
+
+#[test]
+fn deadlines_past_the_window_revert() {
+    let fixture = pool();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+    fixture.pool.set_max_deadline_window(&fixture.admin, &WINDOW);
+    let too_far = NOW + WINDOW + 1;
+
+    assert_error(
+        fixture.pool.try_swap(user, token0, token1, &1_000_000, &0, &0, &too_far),
+        PoolError::DeadlineTooFar,
+    );
+    assert_error(
+        fixture.pool.try_add_liquidity(user, token0, token1, &1_000_000, &1_000_000, &0, &0, &-600, &600, &too_far),
+        PoolError::DeadlineTooFar,
+    );
+    assert_error(
+        fixture.pool.try_remove_liquidity(user, &-600, &600, &1_000, &too_far),
+        PoolError::DeadlineTooFar,
+    );
+    assert_error(
+        fixture.pool.try_collect(user, &-600, &600, &u128::MAX, &u128::MAX, &too_far),
+        PoolError::DeadlineTooFar,
+    );
+}
+
+#[test]
+fn calls_succeed_exactly_at_the_window_edge() {
+    let fixture = pool();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+    fixture.pool.set_max_deadline_window(&fixture.admin, &WINDOW);
+    let edge = NOW + WINDOW;
+
+    fixture.pool.swap(user, token0, token1, &1_000_000, &0, &0, &edge);
+    fixture.pool.add_liquidity(user, token0, token1, &1_000_000, &1_000_000, &0, &0, &-600, &600, &edge);
+    fixture.pool.remove_liquidity(user, &-600, &600, &1_000, &edge);
+    fixture.pool.collect(user, &-600, &600, &u128::MAX, &u128::MAX, &edge);
+
+    // 0 still means no deadline
+    fixture.pool.swap(user, token0, token1, &1_000_000, &0, &0, &0);
+}
+
+#[test]
+fn zero_window_disables_the_ceiling() {
+    let fixture = pool();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+    assert_eq!(fixture.pool.get_max_deadline_window(), 0);
+    fixture.pool.swap(user, token0, token1, &1_000_000, &0, &0, &u64::MAX);
+
+    fixture.pool.set_max_deadline_window(&fixture.admin, &WINDOW);
+    fixture.pool.set_max_deadline_window(&fixture.admin, &0);
+    fixture.pool.swap(user, token0, token1, &1_000_000, &0, &0, &u64::MAX);
+}
+
+#[test]
+fn window_changes_are_admin_only_and_emitted() {
+    let fixture = pool();
+    assert_error(
+        fixture.pool.try_set_max_deadline_window(&fixture.user, &WINDOW),
+        PoolError::Unauthorized,
+    );
+
+    fixture.pool.set_max_deadline_window(&fixture.admin, &WINDOW);
+    assert_eq!(fixture.pool.get_max_deadline_window(), WINDOW);
+    let emitted = events_named(&fixture.env, "deadline_window");
+    assert_eq!(emitted.len(), 1);
+    assert_eq!(u64::try_from_val(&fixture.env, &emitted[0]).unwrap(), WINDOW);
+}
@@ -0,0 +1,129 @@
+//! Event schema: topics and payloads indexers filter and decode.
+
+extern crate std;
+
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{Address, Env, Symbol, TryFromVal, Val, Vec};
+
+use super::Fixture;
+use crate::types::{CollectEvent, LiquidityEvent, SwapEvent};
+
+/// Topics and data of every event named `name` published so far, oldest
+/// first
+fn published(fixture: &Fixture, name: &str) -> std::vec::Vec<(Vec<Val>, Val)> {
+    let env = &fixture.env;
+    let name = Symbol::new(env, name);
+    env.events()
+        .all()
+        .iter()
+        .filter(|(_, topics, _)| {
+            topics.get(0).and_then(|topic| Symbol::try_from_val(env, &topic).ok()) == Some(name.clone())
+        })
+        .map(|(_, topics, data)| (topics, data))
+        .collect()
+}
+
+/// Topic at `index`, decoded
+fn topic<T>(fixture: &Fixture, topics: &Vec<Val>, index: u32) -> T
+where
+    T: TryFromVal<Env, Val>,
+    T::Error: core::fmt::Debug,
+{
+    T::try_from_val(&fixture.env, &topics.get(index).unwrap()).unwrap()
+}
+
+// ============================================================
+// SWAP EVENTS
+// ============================================================
+
+#[test]
+fn swap_event_indexes_caller_and_direction() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+
+    let down = fixture.pool.swap_advanced(&fixture.user, &10_000_000, &0, &0, &true, &0, &0);
+    let up = fixture.pool.swap(&fixture.user, &fixture.token1, &fixture.token0, &5_000_000, &0, &0, &0);
+
+    let swaps = published(&fixture, "swap");
+    assert_eq!(swaps.len(), 2);
+    for ((topics, data), (result, zero_for_one)) in swaps.iter().zip([(&down, true), (&up, false)]) {
+        assert_eq!(topics.len(), 3);
+        assert_eq!(topic::<Address>(&fixture, topics, 1), fixture.user);
+        assert_eq!(topic::<bool>(&fixture, topics, 2), zero_for_one);
+
+        let event = SwapEvent::try_from_val(&fixture.env, data).unwrap();
+        assert_eq!(
+            (event.amount_in, event.amount_out, event.fee, event.ticks_crossed, event.amount_remaining),
+            (result.amount_in, result.amount_out, result.fee_amount, result.ticks_crossed, result.amount_remaining),
+        );
+        assert_eq!((event.sqrt_price_x64, event.tick), (result.sqrt_price_x64, result.current_tick));
+    }
+}
+
+// ============================================================
+// LIQUIDITY EVENTS
+// ============================================================
+
+#[test]
+fn liquidity_events_index_owner_and_range() {
+    let fixture = Fixture::new();
+    let (liquidity, amount0, amount1) = fixture.pool.add_liquidity(
+        &fixture.user, &fixture.token0, &fixture.token1, &1_000_000, &2_000_000, &0, &0, &-600, &1_200, &0,
+    );
+    let (out0, out1) = fixture.pool.remove_liquidity(&fixture.user, &-600, &1_200, &(liquidity / 2), &0);
+
+    for (name, expected) in [
+        ("add_liq", LiquidityEvent { liquidity, amount0, amount1 }),
+        ("remove_liq", LiquidityEvent { liquidity: liquidity / 2, amount0: out0, amount1: out1 }),
+    ] {
+        let events = published(&fixture, name);
+        assert_eq!(events.len(), 1);
+        let (topics, data) = &events[0];
+        assert_eq!(topics.len(), 4);
+        assert_eq!(topic::<Address>(&fixture, topics, 1), fixture.user);
+        assert_eq!((topic::<i32>(&fixture, topics, 2), topic::<i32>(&fixture, topics, 3)), (-600, 1_200));
+        assert_eq!(LiquidityEvent::try_from_val(&fixture.env, data).unwrap(), expected);
+    }
+}
+
+#[test]
+fn collect_event_reports_fees_paid_out() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.swap(&fixture.user, &fixture.token0, &fixture.token1, &10_000_000, &0, &0, &0);
+    let (fee0, fee1) = fixture.pool.collect(&fixture.user, &-600, &600, &u128::MAX, &u128::MAX, &0);
+    assert!(fee0 > 0);
+
+    let events = published(&fixture, "collect");
+    assert_eq!(events.len(), 1);
+    let (topics, data) = &events[0];
+    assert_eq!(topics.len(), 4);
+    assert_eq!(topic::<Address>(&fixture, topics, 1), fixture.user);
+    assert_eq!((topic::<i32>(&fixture, topics, 2), topic::<i32>(&fixture, topics, 3)), (-600, 600));
+    assert_eq!(
+        CollectEvent::try_from_val(&fixture.env, data).unwrap(),
+        CollectEvent { recipient: fixture.user.clone(), amount0: fee0, amount1: fee1 },
+    );
+}
+
+#[test]
+fn owner_topic_filters_one_wallet_out_of_many() {
+    let fixture = Fixture::new();
+    let other = Address::generate(&fixture.env);
+    fixture.fund(&other, super::USER_BALANCE);
+    fixture.add(-600, 600, 1_000_000);
+    fixture.pool.add_liquidity(
+        &other, &fixture.token0, &fixture.token1, &2_000_000, &2_000_000, &0, &0, &-1_200, &1_200, &0,
+    );
+    fixture.add(-60, 60, 3_000_000);
+
+    let owned_by = |owner: &Address| -> std::vec::Vec<i32> {
+        published(&fixture, "add_liq")
+            .iter()
+            .filter(|(topics, _)| topic::<Address>(&fixture, topics, 1) == *owner)
+            .map(|(topics, _)| topic::<i32>(&fixture, topics, 2))
+            .collect()
+    };
+    assert_eq!(owned_by(&fixture.user), [-600, -60]);
+    assert_eq!(owned_by(&other), [-1_200]);
+}
@@ -0,0 +1,459 @@
+//! Fee accounting: LP fee growth, protocol fees and their vesting, and
+//! fees on LP principal.
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{Address, TryFromVal};
+
+use super::{assert_error, events_named, Fixture};
+use crate::constants::MAX_SWAP_FEE_BPS;
+use crate::error::{PoolError, PoolErrorExt};
+use crate::math::{get_amount_0_delta, get_amount_1_delta, get_amounts_for_liquidity, get_sqrt_ratio_at_tick};
+use crate::storage::{read_position, write_position};
+use crate::types::{FeeTier, Position};
+
+const VESTING: u64 = 1_000;
+
+/// Pool with a 10% protocol cut and liquidity around the price
+fn pool<'a>() -> Fixture<'a> {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.set_protocol_fee(&fixture.admin, &1_000);
+    fixture.env.ledger().with_mut(|l| l.timestamp = 10_000);
+    fixture
+}
+
+fn advance(fixture: &Fixture, secs: u64) {
+    fixture.env.ledger().with_mut(|l| l.timestamp += secs);
+}
+
+/// Swap token0 in and return the protocol's share of the fee
+fn accrue(fixture: &Fixture, amount: i128) -> u128 {
+    let before = fixture.pool.get_pool_state().protocol_fees_0;
+    fixture.pool.swap_advanced(&fixture.user, &amount, &0, &0, &true, &0, &0);
+    fixture.pool.get_pool_state().protocol_fees_0 - before
+}
+
+// ============================================================
+// SWAP FEE
+// ============================================================
+
+#[test]
+fn configured_fee_drives_output_and_fee_growth() {
+    let trade = |fee_bps: u32| {
+        let fixture = Fixture::at_tick(fee_bps, 60, 0);
+        fixture.add(-600, 600, 1_000_000_000);
+        let result = fixture.pool.swap_advanced(&fixture.user, &100_000_000, &0, &0, &true, &0, &0);
+        (result, fixture.pool.get_pool_state().fee_growth_global_0)
+    };
+
+    let (low, low_growth) = trade(5);
+    let (mid, mid_growth) = trade(30);
+    let (high, high_growth) = trade(100);
+
+    assert_eq!((low.fee_amount, mid.fee_amount, high.fee_amount), (50_000, 300_000, 1_000_000));
+    assert!(low.amount_out > mid.amount_out && mid.amount_out > high.amount_out);
+    assert!(low_growth < mid_growth && mid_growth < high_growth);
+
+    // Growth is proportional to the fee, up to rounding
+    assert!((low_growth * 6).abs_diff(mid_growth) <= 6);
+    assert!((low_growth * 20).abs_diff(high_growth) <= 20);
+}
+
+// ============================================================
+// PROTOCOL CUT
+// ============================================================
+
+#[test]
+fn protocol_cut_shrinks_lp_fee_growth_proportionally() {
+    let growth = |protocol_fee_bps: u32| {
+        let fixture = pool();
+        fixture.pool.set_protocol_fee(&fixture.admin, &protocol_fee_bps);
+        let result = fixture.pool.swap_advanced(&fixture.user, &100_000_000, &0, &0, &true, &0, &0);
+        let state = fixture.pool.get_pool_state();
+        assert_eq!(state.fee_growth_global_1, 0);
+        (result.fee_amount, state.protocol_fees_0, state.fee_growth_global_0)
+    };
+
+    let (fee, protocol_none, full) = growth(0);
+    assert_eq!((fee, protocol_none), (300_000, 0));
+
+    for protocol_fee_bps in [1_000, 2_500, 5_000] {
+        let (fee_cut, protocol, cut) = growth(protocol_fee_bps);
+        assert_eq!(fee_cut, fee);
+        assert_eq!(protocol, (fee as u128) * protocol_fee_bps as u128 / 10_000);
+
+        // Growth is rounded down, so allow one unit either way
+        let kept = 10_000 - protocol_fee_bps as u128;
+        let expected = full * kept / 10_000;
+        assert!(cut.abs_diff(expected) <= 1);
+    }
+}
+
+// ============================================================
+// ZERO GROWTH
+// ============================================================
+
+#[test]
+fn fees_too_small_for_growth_are_counted_and_reported() {
+    let fixture = Fixture::new();
+    fixture.fund(&fixture.user, 1_000_000_000_000_000_000);
+    let liquidity = fixture.add(-60, 60, 1_000_000_000_000_000_000);
+
+    // A 3 unit fee moves Q64.64 growth only once liquidity is below 3 * 2^64
+    assert!(liquidity as u128 > 3u128 << 64);
+    fixture.pool.swap_advanced(&fixture.user, &1_000, &0, &0, &true, &0, &0);
+
+    let events = events_named(&fixture.env, "fee0");
+    assert_eq!(events.len(), 1);
+    let (zero_for_one, fee, event_liquidity) = <(bool, u128, u128)>::try_from_val(&fixture.env, &events[0]).unwrap();
+    assert_eq!((zero_for_one, fee, event_liquidity), (true, 3, liquidity as u128));
+
+    let state = fixture.pool.get_pool_state();
+    assert_eq!(state.fee_growth_global_0, 0);
+    assert_eq!((state.zero_growth_fees_0, state.zero_growth_fees_1), (3, 0));
+
+    // The counter accumulates per token
+    fixture.pool.swap_advanced(&fixture.user, &1_000, &0, &0, &false, &0, &0);
+    fixture.pool.swap_advanced(&fixture.user, &1_000, &0, &0, &true, &0, &0);
+    let state = fixture.pool.get_pool_state();
+    assert_eq!((state.zero_growth_fees_0, state.zero_growth_fees_1), (6, 3));
+}
+
+#[test]
+fn fees_that_move_growth_are_not_reported() {
+    let fixture = pool();
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000, &0, &0, &true, &0, &0);
+
+    assert!(events_named(&fixture.env, "fee0").is_empty());
+    assert_eq!(fixture.pool.get_pool_state().zero_growth_fees_0, 0);
+}
+
+// ============================================================
+// VESTING
+// ============================================================
+
+#[test]
+fn collected_fees_vest_linearly() {
+    let fixture = pool();
+    fixture.pool.set_protocol_fee_vesting(&fixture.admin, &VESTING);
+    let fees = accrue(&fixture, 100_000_000);
+    assert_eq!(fees, 30_000);
+
+    let before = fixture.balance(&fixture.token0, &fixture.admin);
+    assert_eq!(fixture.pool.collect_protocol_fees(&fixture.admin), (fees, 0));
+    assert_eq!(fixture.balance(&fixture.token0, &fixture.admin), before);
+    assert_eq!(fixture.pool.get_pool_state().protocol_fees_0, 0);
+
+    advance(&fixture, VESTING / 4);
+    assert_eq!(fixture.pool.release_vested(), (fees / 4, 0));
+    advance(&fixture, VESTING / 4);
+    assert_eq!(fixture.pool.release_vested(), (fees / 4, 0));
+
+    // Nothing more than the total is ever released
+    advance(&fixture, 10 * VESTING);
+    assert_eq!(fixture.pool.release_vested(), (fees / 2, 0));
+    assert_eq!(fixture.balance(&fixture.token0, &fixture.admin) - before, fees as i128);
+}
+
+#[test]
+fn release_before_anything_vested_fails() {
+    let fixture = pool();
+
+    assert_error(fixture.pool.try_release_vested(), PoolError::NothingToRelease);
+
+    fixture.pool.set_protocol_fee_vesting(&fixture.admin, &VESTING);
+    accrue(&fixture, 100_000_000);
+    fixture.pool.collect_protocol_fees(&fixture.admin);
+    assert_error(fixture.pool.try_release_vested(), PoolError::NothingToRelease);
+
+    advance(&fixture, VESTING);
+    fixture.pool.release_vested();
+    assert_error(fixture.pool.try_release_vested(), PoolError::NothingToRelease);
+}
+
+#[test]
+fn recollection_stacks_onto_the_schedule() {
+    let fixture = pool();
+    fixture.pool.set_protocol_fee_vesting(&fixture.admin, &VESTING);
+    let first = accrue(&fixture, 100_000_000);
+    fixture.pool.collect_protocol_fees(&fixture.admin);
+    let before = fixture.balance(&fixture.token0, &fixture.admin);
+
+    // Collecting again releases the vested half first, then restarts the
+    // window over the unvested half plus the new fees
+    advance(&fixture, VESTING / 2);
+    let second = accrue(&fixture, 100_000_000);
+    fixture.pool.collect_protocol_fees(&fixture.admin);
+    assert_eq!(fixture.balance(&fixture.token0, &fixture.admin) - before, (first / 2) as i128);
+
+    let schedule = fixture.pool.get_protocol_fee_vesting().unwrap();
+    assert_eq!(schedule.start, fixture.env.ledger().timestamp());
+    assert_eq!(schedule.amount_0, first - first / 2 + second);
+    assert_eq!(schedule.released_0, 0);
+
+    advance(&fixture, VESTING);
+    fixture.pool.release_vested();
+    assert_eq!(fixture.balance(&fixture.token0, &fixture.admin) - before, (first + second) as i128);
+}
+
+#[test]
+fn disabling_vesting_collects_directly() {
+    let fixture = pool();
+    fixture.pool.set_protocol_fee_vesting(&fixture.admin, &VESTING);
+    let vesting = accrue(&fixture, 100_000_000);
+    fixture.pool.collect_protocol_fees(&fixture.admin);
+
+    fixture.pool.set_protocol_fee_vesting(&fixture.admin, &0);
+    let direct = accrue(&fixture, 100_000_000);
+    let before = fixture.balance(&fixture.token0, &fixture.admin);
+    assert_eq!(fixture.pool.collect_protocol_fees(&fixture.admin), (direct, 0));
+    assert_eq!(fixture.balance(&fixture.token0, &fixture.admin) - before, direct as i128);
+
+    // The existing schedule keeps releasing
+    advance(&fixture, VESTING);
+    assert_eq!(fixture.pool.release_vested(), (vesting, 0));
+}
+
+// ============================================================
+// LIQUIDITY FEES
+// ============================================================
+
+/// Fee of `bps` on `amount`, rounded up in the protocol's favor
+fn skim(amount: i128, bps: i128) -> i128 {
+    (amount * bps + 9_999) / 10_000
+}
+
+#[test]
+fn deposit_fee_is_charged_on_top_of_the_principal() {
+    let fixture = Fixture::new();
+    fixture.pool.set_liquidity_fees(&fixture.admin, &100, &0);
+    let before0 = fixture.balance(&fixture.token0, &fixture.user);
+    let before1 = fixture.balance(&fixture.token1, &fixture.user);
+
+    let (liquidity, amount0, amount1) = fixture.pool.add_liquidity(
+        &fixture.user, &fixture.token0, &fixture.token1,
+        &1_000_000_007, &1_000_000_007, &0, &0, &-600, &600, &0,
+    );
+
+    let state = fixture.pool.get_pool_state();
+    assert_eq!(state.protocol_fees_0, skim(amount0, 100) as u128);
+    assert_eq!(state.protocol_fees_1, skim(amount1, 100) as u128);
+    assert_eq!(before0 - fixture.balance(&fixture.token0, &fixture.user), amount0 + skim(amount0, 100));
+    assert_eq!(before1 - fixture.balance(&fixture.token1, &fixture.user), amount1 + skim(amount1, 100));
+
+    // The position holds the net principal, and principal plus fee stays
+    // within what was offered
+    assert_eq!(fixture.pool.get_position(&fixture.user, &-600, &600).liquidity, liquidity);
+    assert!(amount0 + skim(amount0, 100) <= 1_000_000_007);
+}
+
+#[test]
+fn withdrawal_fee_is_skimmed_from_the_principal() {
+    let fixture = Fixture::new();
+    let liquidity = fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.set_liquidity_fees(&fixture.admin, &0, &250);
+    let env = &fixture.env;
+    let (principal0, principal1) = get_amounts_for_liquidity(
+        env, liquidity, get_sqrt_ratio_at_tick(env, -600), get_sqrt_ratio_at_tick(env, 600),
+        fixture.pool.get_pool_state().sqrt_price_x64,
+    );
+    let before0 = fixture.balance(&fixture.token0, &fixture.user);
+
+    let (amount0, amount1) = fixture.pool.remove_liquidity(&fixture.user, &-600, &600, &liquidity, &0);
+
+    assert_eq!(amount0, principal0 - skim(principal0, 250));
+    assert_eq!(amount1, principal1 - skim(principal1, 250));
+    assert_eq!(fixture.balance(&fixture.token0, &fixture.user) - before0, amount0);
+
+    let state = fixture.pool.get_pool_state();
+    assert_eq!(state.protocol_fees_0, skim(principal0, 250) as u128);
+    assert_eq!(state.protocol_fees_1, skim(principal1, 250) as u128);
+}
+
+#[test]
+fn liquidity_fees_are_capped() {
+    let fixture = Fixture::new();
+
+    assert_error(fixture.pool.try_set_liquidity_fees(&fixture.admin, &1_001, &0), PoolError::InvalidLiquidityFee);
+    assert_error(fixture.pool.try_set_liquidity_fees(&fixture.admin, &0, &1_001), PoolError::InvalidLiquidityFee);
+}
+
+// ============================================================
+// CREDITED FEES
+// ============================================================
+
+/// The fixture user's stored position over [-600, 600)
+fn stored_position(fixture: &Fixture) -> Position {
+    fixture.env.as_contract(&fixture.pool.address, || read_position(&fixture.env, &fixture.user, -600, 600))
+}
+
+#[test]
+fn credited_fees_stay_collectible_when_the_live_update_fails() {
+    let fixture = pool();
+    accrue(&fixture, 10_000_000);
+
+    // Credit the swap fees to tokens_owed without paying anything out
+    fixture.pool.collect(&fixture.user, &-600, &600, &0, &0, &0);
+    let owed = stored_position(&fixture);
+    assert!(owed.tokens_owed_0 > 0);
+
+    // Break the next fee-growth update: the owed fee no longer fits in u128
+    fixture.env.as_contract(&fixture.pool.address, || {
+        let mut pos = read_position(&fixture.env, &fixture.user, -600, 600);
+        pos.liquidity = 1 << 100;
+        pos.fee_growth_inside_last_0 = pos.fee_growth_inside_last_0.wrapping_sub(1 << 100);
+        write_position(&fixture.env, &fixture.user, -600, 600, &pos);
+    });
+    assert_error(
+        fixture.pool.try_collect(&fixture.user, &-600, &600, &u128::MAX, &u128::MAX, &0),
+        PoolErrorExt::Overflow,
+    );
+
+    let before = fixture.balance(&fixture.token0, &fixture.user);
+    let (paid0, paid1) = fixture.pool.collect_owed(&fixture.user, &-600, &600, &0);
+
+    assert_eq!((paid0, paid1), (owed.tokens_owed_0, owed.tokens_owed_1));
+    assert_eq!(fixture.balance(&fixture.token0, &fixture.user), before + paid0 as i128);
+    assert_eq!(stored_position(&fixture).tokens_owed_0, 0);
+}
+
+// ============================================================
+// COLLECT TO
+// ============================================================
+
+#[test]
+fn collect_to_pays_a_partial_amount_to_a_third_party() {
+    let fixture = pool();
+    accrue(&fixture, 10_000_000);
+    fixture.pool.collect(&fixture.user, &-600, &600, &0, &0, &0);
+    let owed = stored_position(&fixture);
+    let treasury = Address::generate(&fixture.env);
+    let user_before = fixture.balance(&fixture.token0, &fixture.user);
+
+    let (paid0, paid1) = fixture.pool.collect_to(&fixture.user, &-600, &600, &treasury, &500, &u128::MAX);
+
+    assert_eq!((paid0, paid1), (500, owed.tokens_owed_1));
+    assert_eq!(fixture.balance(&fixture.token0, &treasury), 500);
+    assert_eq!(fixture.balance(&fixture.token0, &fixture.user), user_before);
+    assert_eq!(stored_position(&fixture).tokens_owed_0, owed.tokens_owed_0 - 500);
+
+    // The leftover is still the owner's to collect
+    let (rest0, _) = fixture.pool.collect(&fixture.user, &-600, &600, &u128::MAX, &u128::MAX, &0);
+    assert_eq!(rest0, owed.tokens_owed_0 - 500);
+}
+
+// ============================================================
+// LIFETIME FEES
+// ============================================================
+
+#[test]
+fn lifetime_fees_add_up_collections_and_pending() {
+    let fixture = pool();
+    let user = &fixture.user;
+
+    accrue(&fixture, 10_000_000);
+    let first = fixture.pool.get_position(user, &-600, &600);
+    assert_eq!(first.lifetime_fees_0, first.fees_owed_0);
+    let (collected_first, _) = fixture.pool.collect(user, &-600, &600, &u128::MAX, &u128::MAX, &0);
+    assert_eq!(collected_first, first.fees_owed_0);
+
+    // Collecting resets what is owed, not the lifetime total
+    let after = fixture.pool.get_position(user, &-600, &600);
+    assert_eq!((after.fees_owed_0, after.lifetime_fees_0), (0, collected_first));
+
+    accrue(&fixture, 20_000_000);
+    let (collected_second, _) = fixture.pool.collect(user, &-600, &600, &1_000, &0, &0);
+    accrue(&fixture, 5_000_000);
+
+    let info = fixture.pool.get_position(user, &-600, &600);
+    assert!(info.fees_owed_0 > 0);
+    assert_eq!(info.lifetime_fees_0, collected_first + collected_second + info.fees_owed_0);
+    assert_eq!(info.lifetime_fees_1, 0);
+}
+
+#[test]
+fn lifetime_fees_survive_a_full_exit() {
+    let fixture = pool();
+    let user = &fixture.user;
+    accrue(&fixture, 10_000_000);
+
+    let liquidity = fixture.pool.get_position(user, &-600, &600).liquidity;
+    fixture.pool.remove_liquidity(user, &-600, &600, &liquidity, &0);
+    let (collected, _) = fixture.pool.collect(user, &-600, &600, &u128::MAX, &u128::MAX, &0);
+
+    let info = fixture.pool.get_position(user, &-600, &600);
+    assert_eq!((info.liquidity, info.fees_owed_0), (0, 0));
+    assert_eq!(info.lifetime_fees_0, collected);
+}
+
+// ============================================================
+// FEE CHANGES
+// ============================================================
+
+#[test]
+fn lp_fees_accrue_at_each_rate_in_force() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+
+    let before = fixture.pool.swap_advanced(&fixture.user, &100_000_000, &0, &0, &true, &0, &0);
+    fixture.pool.set_fee_bps(&fixture.admin, &100);
+    let after = fixture.pool.swap_advanced(&fixture.user, &100_000_000, &0, &0, &false, &0, &0);
+    assert_eq!((before.fee_amount, after.fee_amount), (300_000, 1_000_000));
+
+    // 30 bps of the token0 trade and 100 bps of the token1 trade, less
+    // rounding in growth
+    let (fees0, fees1) = fixture.pool.collect(&fixture.user, &-600, &600, &u128::MAX, &u128::MAX, &0);
+    assert!((299_990..=300_000).contains(&fees0));
+    assert!((999_990..=1_000_000).contains(&fees1));
+}
+
+#[test]
+fn fee_changes_are_admin_only_bounded_and_emitted() {
+    let fixture = Fixture::new();
+    let stranger = Address::generate(&fixture.env);
+
+    assert_error(fixture.pool.try_set_fee_bps(&stranger, &100), PoolError::Unauthorized);
+    for invalid in [0, MAX_SWAP_FEE_BPS + 1, 10_000] {
+        assert_error(fixture.pool.try_set_fee_bps(&fixture.admin, &invalid), PoolError::InvalidFee);
+    }
+    assert_eq!(fixture.pool.get_pool_config().fee_bps, 30);
+
+    // Off the tier the pool turns Custom, up to the top of the range
+    for (fee_bps, fee_tier) in [(100, FeeTier::Custom), (MAX_SWAP_FEE_BPS, FeeTier::Custom), (30, FeeTier::Medium)] {
+        fixture.pool.set_fee_bps(&fixture.admin, &fee_bps);
+        let config = fixture.pool.get_pool_config();
+        assert_eq!((config.fee_bps, config.fee_tier), (fee_bps, fee_tier));
+    }
+
+    let events = events_named(&fixture.env, "fee_set");
+    assert_eq!(events.len(), 3);
+    assert_eq!(<(u32, u32)>::try_from_val(&fixture.env, &events[0]).unwrap(), (30, 100));
+    assert_eq!(<(u32, u32)>::try_from_val(&fixture.env, &events[2]).unwrap(), (MAX_SWAP_FEE_BPS, 30));
+}
+
+// ============================================================
+// FEE IN AMOUNT IN
+// ============================================================
+
+#[test]
+fn amount_in_is_the_net_swapped_plus_the_fee() {
+    for (amount, zero_for_one) in [(10_000_000, true), (10_000_000, false), (-10_000_000, true), (-10_000_000, false)] {
+        let fixture = Fixture::new();
+        let liquidity = fixture.add(-600, 600, 1_000_000_000) as u128;
+        let start = fixture.pool.get_pool_state().sqrt_price_x64;
+        let pool_in = if zero_for_one { &fixture.token0 } else { &fixture.token1 };
+        let reserve = fixture.balance(pool_in, &fixture.pool.address);
+
+        let result = fixture.pool.swap_advanced(&fixture.user, &amount, &0, &0, &zero_for_one, &0, &0);
+        let end = fixture.pool.get_pool_state().sqrt_price_x64;
+
+        // What the price move itself costs, rounded against the trader
+        let net = if zero_for_one {
+            get_amount_0_delta(&fixture.env, end, start, liquidity, true)
+        } else {
+            get_amount_1_delta(&fixture.env, start, end, liquidity, true)
+        };
+        assert_eq!(result.amount_in, net as i128 + result.fee_amount);
+        assert_eq!(fixture.balance(pool_in, &fixture.pool.address) - reserve, result.amount_in);
+    }
+}
@@ -0,0 +1,106 @@
+//! Flash loans: receiver contracts that repay, short-pay or re-enter
+//! during the callback.
+
+use soroban_sdk::testutils::ContractFunctionSet;
+use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Bytes, Env};
+
+use super::{assert_error, Fixture};
+use crate::error::PoolError;
+use crate::flash::FlashLoanReceiver;
+use crate::BelugaSwapClient;
+
+/// Repays the loan plus fee from its own balance of the tokens stored under
+/// `t`; `data` of [SHORT] repays one unit less on token0, [REENTER] swaps
+/// against the pool first
+#[contract]
+pub struct LoanReceiver;
+
+const SHORT: u8 = 1;
+const REENTER: u8 = 2;
+
+#[contractimpl]
+impl FlashLoanReceiver for LoanReceiver {
+    fn on_flash_loan(env: Env, pool: Address, amount0: i128, amount1: i128, fee0: i128, fee1: i128, data: Bytes) {
+        let (token0, token1): (Address, Address) = env.storage().instance().get(&symbol_short!("t")).unwrap();
+        let me = env.current_contract_address();
+        let mode = data.get(0);
+        if mode == Some(REENTER) {
+            BelugaSwapClient::new(&env, &pool).swap(&me, &token0, &token1, &1_000, &0, &0, &0);
+        }
+        let short = i128::from(mode == Some(SHORT));
+        token::Client::new(&env, &token0).transfer(&me, &pool, &(amount0 + fee0 - short));
+        token::Client::new(&env, &token1).transfer(&me, &pool, &(amount1 + fee1));
+    }
+}
+
+/// Register `contract` holding the fixture tokens
+fn register_holding(fixture: &Fixture, contract: impl ContractFunctionSet + 'static) -> Address {
+    let receiver = fixture.env.register_contract(None, contract);
+    fixture.env.as_contract(&receiver, || {
+        fixture.env.storage().instance().set(&symbol_short!("t"), &(fixture.token0.clone(), fixture.token1.clone()));
+    });
+    fixture.fund(&receiver, 1_000_000_000);
+    receiver
+}
+
+// ============================================================
+// FLASH LOANS
+// ============================================================
+
+#[test]
+fn flash_loan_repaid_with_fee_credits_lps() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let receiver = register_holding(&fixture, LoanReceiver);
+    let reserve0 = fixture.balance(&fixture.token0, &fixture.pool.address);
+
+    // 30 bps of the loan, all to in-range LPs with no protocol fee
+    let (paid0, paid1) = fixture.pool.flash(&fixture.user, &receiver, &100_000_000, &0, &Bytes::new(&fixture.env));
+    assert_eq!((paid0, paid1), (300_000, 0));
+    assert_eq!(fixture.balance(&fixture.token0, &fixture.pool.address), reserve0 + 300_000);
+    assert_eq!(fixture.balance(&fixture.token0, &receiver), 1_000_000_000 - 300_000);
+    assert!(fixture.pool.get_pool_state().fee_growth_global_0 > 0);
+
+    let (fees0, fees1) = fixture.pool.collect(&fixture.user, &-600, &600, &u128::MAX, &u128::MAX, &0);
+    assert!((299_990..=300_000).contains(&(fees0 as i128)));
+    assert_eq!(fees1, 0);
+}
+
+#[test]
+fn flash_loan_repaid_short_reverts() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let receiver = register_holding(&fixture, LoanReceiver);
+    let before = fixture.pool.get_pool_state();
+    let reserve0 = fixture.balance(&fixture.token0, &fixture.pool.address);
+
+    assert_error(
+        fixture.pool.try_flash(&fixture.user, &receiver, &100_000_000, &0, &Bytes::from_array(&fixture.env, &[SHORT])),
+        PoolError::FlashNotFunded,
+    );
+    assert_eq!(fixture.balance(&fixture.token0, &fixture.pool.address), reserve0);
+    assert_eq!(fixture.pool.get_pool_state().fee_growth_global_0, before.fee_growth_global_0);
+}
+
+#[test]
+fn flash_loan_cannot_reenter_the_pool() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let receiver = register_holding(&fixture, LoanReceiver);
+    let before = fixture.pool.get_pool_state();
+
+    let result =
+        fixture.pool.try_flash(&fixture.user, &receiver, &100_000, &0, &Bytes::from_array(&fixture.env, &[REENTER]));
+    assert!(result.is_err());
+    assert_eq!(fixture.pool.get_pool_state().sqrt_price_x64, before.sqrt_price_x64);
+}
+
+#[test]
+fn flash_loan_of_nothing_is_rejected() {
+    let fixture = Fixture::new();
+    let receiver = register_holding(&fixture, LoanReceiver);
+    assert_error(
+        fixture.pool.try_flash(&fixture.user, &receiver, &0, &0, &Bytes::new(&fixture.env)),
+        PoolError::AmountNotPositive,
+    );
+}
@@ -0,0 +1,320 @@
+//! Pool initialization and the readers callers use to probe it.
+
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Symbol};
+
+use super::{assert_error, Fixture};
+use crate::constants::{POOL_INTERFACE_VERSION, POOL_MARKER};
+use crate::error::PoolError;
+use crate::math::get_sqrt_ratio_at_tick;
+use crate::types::FeeTier;
+use crate::BelugaSwapClient;
+
+// ============================================================
+// INITIALIZED READERS
+// ============================================================
+
+#[test]
+fn fresh_contract_reports_uninitialized_without_panicking() {
+    let fixture = Fixture::uninitialized();
+
+    assert!(!fixture.pool.is_initialized());
+    assert!(fixture.pool.get_pool_state_opt().is_none());
+    assert_error(fixture.pool.try_get_pool_state(), PoolError::NotInitialized);
+}
+
+#[test]
+fn initialized_contract_reports_its_state() {
+    let fixture = Fixture::new();
+
+    assert!(fixture.pool.is_initialized());
+    let state = fixture.pool.get_pool_state_opt().unwrap();
+    assert_eq!(state.sqrt_price_x64, fixture.pool.get_pool_state().sqrt_price_x64);
+    assert_eq!((state.token0, state.token1), (fixture.token0.clone(), fixture.token1.clone()));
+
+    assert_error(
+        fixture.pool.try_initialize(
+            &fixture.admin, &fixture.token0, &fixture.token1, &30, &0, &state.sqrt_price_x64, &0, &60,
+        ),
+        PoolError::AlreadyInitialized,
+    );
+}
+
+// ============================================================
+// POOL MARKER
+// ============================================================
+
+#[test]
+fn marker_is_readable_before_initialize() {
+    let fixture = Fixture::uninitialized();
+
+    let (marker, version) = fixture.pool.belugaswap_pool_marker();
+    assert_eq!(marker, Symbol::new(&fixture.env, POOL_MARKER));
+    assert_eq!(version, POOL_INTERFACE_VERSION);
+}
+
+#[test]
+fn info_carries_the_marker() {
+    let fixture = Fixture::new();
+
+    let info = fixture.pool.get_info();
+    assert_eq!((info.marker, info.interface_version), fixture.pool.belugaswap_pool_marker());
+    assert_eq!((info.token0, info.token1), (fixture.token0.clone(), fixture.token1.clone()));
+    assert_eq!((info.fee_bps, info.tick_spacing), (30, 60));
+}
+
+#[test]
+fn non_pool_contract_fails_the_probe() {
+    let fixture = Fixture::new();
+
+    // A token contract has no marker entrypoint
+    let probe = BelugaSwapClient::new(&fixture.env, &fixture.token0);
+    assert!(probe.try_belugaswap_pool_marker().is_err());
+}
+
+// ============================================================
+// IDEMPOTENT INITIALIZE
+// ============================================================
+
+#[test]
+fn initialize_or_get_initializes_a_fresh_pool() {
+    let fixture = Fixture::uninitialized();
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, 0);
+
+    let config = fixture.pool.initialize_or_get(
+        &fixture.admin, &fixture.token0, &fixture.token1, &30, &0, &sqrt_price, &0, &60,
+    );
+
+    assert!(fixture.pool.is_initialized());
+    assert_eq!(config.fee_bps, 30);
+    assert_eq!(fixture.pool.get_pool_state().sqrt_price_x64, sqrt_price);
+}
+
+#[test]
+fn initialize_or_get_retry_returns_the_existing_config() {
+    let fixture = Fixture::new();
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, 0);
+    fixture.add(-600, 600, 1_000_000_000);
+    let before = fixture.pool.get_pool_state();
+
+    // Tokens in either order, price within one tick
+    let config = fixture.pool.initialize_or_get(
+        &fixture.admin, &fixture.token1, &fixture.token0, &30, &0, &(sqrt_price + 1), &0, &60,
+    );
+
+    assert_eq!(config.fee_bps, 30);
+    let after = fixture.pool.get_pool_state();
+    assert_eq!((after.sqrt_price_x64, after.liquidity), (before.sqrt_price_x64, before.liquidity));
+}
+
+#[test]
+fn initialize_or_get_with_different_params_fails_without_changes() {
+    let fixture = Fixture::new();
+    let before = fixture.pool.get_pool_state();
+    let at_zero = get_sqrt_ratio_at_tick(&fixture.env, 0);
+    let far_away = get_sqrt_ratio_at_tick(&fixture.env, 600);
+
+    assert_error(
+        fixture.pool.try_initialize_or_get(
+            &fixture.admin, &fixture.token0, &fixture.token1, &100, &0, &at_zero, &0, &60,
+        ),
+        PoolError::InitializationMismatch,
+    );
+    assert_error(
+        fixture.pool.try_initialize_or_get(
+            &fixture.admin, &fixture.token0, &fixture.token1, &30, &0, &far_away, &600, &60,
+        ),
+        PoolError::InitializationMismatch,
+    );
+    let after = fixture.pool.get_pool_state();
+    assert_eq!((after.sqrt_price_x64, after.liquidity), (before.sqrt_price_x64, before.liquidity));
+    assert_eq!(fixture.pool.get_info().fee_bps, 30);
+}
+
+// ============================================================
+// STARTING TICK
+// ============================================================
+
+#[test]
+fn tick_not_matching_the_price_is_rejected() {
+    let fixture = Fixture::uninitialized();
+    let (admin, token0, token1) = (&fixture.admin, &fixture.token0, &fixture.token1);
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, 1_000);
+
+    for tick in [0, 999, 1_001] {
+        assert_error(
+            fixture.pool.try_initialize(admin, token0, token1, &30, &0, &sqrt_price, &tick, &60),
+            PoolError::InvalidTick,
+        );
+        assert_error(
+            fixture.pool.try_initialize_with_tier(admin, token0, token1, &FeeTier::Medium, &0, &sqrt_price, &tick),
+            PoolError::InvalidTick,
+        );
+    }
+    assert!(!fixture.pool.is_initialized());
+
+    // Between two ticks, only the lower one is the tick the price is in
+    let between = sqrt_price + 1;
+    assert_error(
+        fixture.pool.try_initialize(admin, token0, token1, &30, &0, &between, &1_001, &60),
+        PoolError::InvalidTick,
+    );
+    fixture.pool.initialize(admin, token0, token1, &30, &0, &between, &1_000, &60);
+    assert_eq!(fixture.pool.get_pool_state().current_tick, 1_000);
+}
+
+// ============================================================
+// FEE TIERS
+// ============================================================
+
+/// Pool initialized from `tier` at price 1.0
+fn with_tier<'a>(tier: FeeTier) -> Fixture<'a> {
+    let fixture = Fixture::uninitialized();
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, 0);
+    fixture.pool.initialize_with_tier(&fixture.admin, &fixture.token0, &fixture.token1, &tier, &0, &sqrt_price, &0);
+    fixture
+}
+
+#[test]
+fn tier_sets_fee_spacing_and_config() {
+    let tiers = [(FeeTier::Low, 5, 10), (FeeTier::Medium, 30, 60), (FeeTier::High, 100, 200)];
+    for (tier, fee_bps, tick_spacing) in tiers {
+        let fixture = with_tier(tier);
+        let config = fixture.pool.get_pool_config();
+        assert_eq!((config.fee_tier, config.fee_bps), (tier, fee_bps));
+        assert_eq!(fixture.pool.get_pool_state().tick_spacing, tick_spacing);
+    }
+}
+
+#[test]
+fn derived_spacing_is_what_ranges_and_swaps_use() {
+    let fixture = with_tier(FeeTier::Low);
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+
+    // Spacing 10: multiples of 10 are valid, the Medium tier's 60 grid is
+    // not required, and anything off the 10 grid is rejected
+    assert_error(
+        fixture.pool.try_add_liquidity(user, token0, token1, &1_000_000, &1_000_000, &0, &0, &-15, &10, &0),
+        PoolError::InvalidTick,
+    );
+    fixture.add(-10, 10, 100_000_000);
+    fixture.add(-1_000, 1_000, 100_000_000);
+
+    // Crosses the -10 boundary and pays the Low tier's 5 bps
+    let result = fixture.pool.swap(user, token0, token1, &150_000_000, &0, &0, &0);
+    assert_eq!(result.ticks_crossed, 1);
+    // 5 bps of the input, rounded up in each of the two steps
+    assert!((75_000..=75_002).contains(&result.fee_amount));
+}
+
+#[test]
+fn custom_is_not_an_initialization_tier() {
+    let fixture = Fixture::uninitialized();
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, 0);
+    assert_error(
+        fixture.pool.try_initialize_with_tier(
+            &fixture.admin, &fixture.token0, &fixture.token1, &FeeTier::Custom, &0, &sqrt_price, &0,
+        ),
+        PoolError::InvalidFeeTier,
+    );
+}
+
+#[test]
+fn initialize_accepts_only_the_tier_pairs() {
+    let at_zero = |fixture: &Fixture| get_sqrt_ratio_at_tick(&fixture.env, 0);
+
+    for (fee_bps, tick_spacing) in [(5, 10), (30, 60), (100, 200)] {
+        let fixture = Fixture::uninitialized();
+        fixture.pool.initialize(
+            &fixture.admin, &fixture.token0, &fixture.token1, &fee_bps, &0, &at_zero(&fixture), &0, &tick_spacing,
+        );
+        assert_eq!(fixture.pool.get_pool_config().fee_tier, FeeTier::from_params(fee_bps, tick_spacing));
+    }
+
+    // Each fee with another tier's spacing, and a spacing no tier uses
+    for (fee_bps, tick_spacing) in [(5, 60), (30, 200), (100, 10), (30, 1)] {
+        let fixture = Fixture::uninitialized();
+        assert_error(
+            fixture.pool.try_initialize(
+                &fixture.admin, &fixture.token0, &fixture.token1, &fee_bps, &0, &at_zero(&fixture), &0, &tick_spacing,
+            ),
+            PoolError::InvalidFeeTier,
+        );
+        assert!(!fixture.pool.is_initialized());
+    }
+}
+
+#[test]
+fn set_fee_tier_restores_the_canonical_fee_for_the_spacing() {
+    let fixture = Fixture::new();
+    fixture.pool.set_fee_bps(&fixture.admin, &45);
+    assert_eq!(fixture.pool.get_pool_config().fee_tier, FeeTier::Custom);
+
+    // Spacing is fixed at 60, so only the Medium tier fits
+    for tier in [FeeTier::Low, FeeTier::High, FeeTier::Custom] {
+        assert_error(fixture.pool.try_set_fee_tier(&fixture.admin, &tier), PoolError::InvalidFeeTier);
+    }
+    let stranger = Address::generate(&fixture.env);
+    assert_error(fixture.pool.try_set_fee_tier(&stranger, &FeeTier::Medium), PoolError::Unauthorized);
+
+    fixture.pool.set_fee_tier(&fixture.admin, &FeeTier::Medium);
+    let config = fixture.pool.get_pool_config();
+    assert_eq!((config.fee_tier, config.fee_bps), (FeeTier::Medium, 30));
+}
+
+#[test]
+fn set_fee_bps_off_the_tier_marks_the_pool_custom() {
+    for (tier, spacing) in [(FeeTier::Low, 10), (FeeTier::Medium, 60), (FeeTier::High, 200)] {
+        let fixture = with_tier(tier);
+        let (fee_bps, _) = tier.params().unwrap();
+
+        // Every other tier's fee is off-tier for this spacing
+        for other in [5, 30, 100].into_iter().filter(|&fee| fee != fee_bps).chain([45]) {
+            fixture.pool.set_fee_bps(&fixture.admin, &other);
+            let config = fixture.pool.get_pool_config();
+            assert_eq!((config.fee_tier, config.fee_bps), (FeeTier::Custom, other));
+        }
+        assert_eq!(fixture.pool.get_pool_state().tick_spacing, spacing);
+
+        fixture.pool.set_fee_bps(&fixture.admin, &fee_bps);
+        assert_eq!(fixture.pool.get_pool_config().fee_tier, tier);
+    }
+}
+
+// ============================================================
+// TOKEN ORDER
+// ============================================================
+
+/// Pool initialized with its tokens in the given order, with liquidity
+fn ordered<'a>(reversed: bool) -> Fixture<'a> {
+    let fixture = Fixture::uninitialized();
+    let (first, second) =
+        if reversed { (&fixture.token1, &fixture.token0) } else { (&fixture.token0, &fixture.token1) };
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, 0);
+    fixture.pool.initialize(&fixture.admin, first, second, &30, &0, &sqrt_price, &0, &60);
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture
+}
+
+#[test]
+fn either_token_order_gives_the_same_pool() {
+    let forward = ordered(false);
+    let reversed = ordered(true);
+
+    for fixture in [&forward, &reversed] {
+        let (config, state) = (fixture.pool.get_pool_config(), fixture.pool.get_pool_state());
+        assert_eq!((&config.token_a, &config.token_b), (&fixture.token0, &fixture.token1));
+        assert_eq!((&state.token0, &state.token1), (&fixture.token0, &fixture.token1));
+    }
+
+    // Selling token0 is zero-for-one in both, with identical results
+    let sell = |fixture: &Fixture| {
+        let before = fixture.balance(&fixture.token1, &fixture.user);
+        let result = fixture.pool.swap(&fixture.user, &fixture.token0, &fixture.token1, &1_000_000, &0, &0, &0);
+        assert_eq!(fixture.balance(&fixture.token1, &fixture.user) - before, result.amount_out);
+        (result.amount_out, fixture.pool.get_pool_state().current_tick)
+    };
+    let (out, tick) = sell(&forward);
+    assert_eq!(sell(&reversed), (out, tick));
+    assert!(tick < 0);
+}
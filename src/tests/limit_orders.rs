@@ -0,0 +1,111 @@
+//! Limit orders: placement, settlement once crossed, and the queue
+//! exemption for settlement.
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::Address;
+
+use super::{assert_error, Fixture};
+use crate::error::{PoolError, PoolErrorExt};
+use crate::math::get_sqrt_ratio_at_tick;
+use crate::types::LimitOrder;
+
+/// Pool with liquidity over [-6000, 6000] and a funded maker
+fn market() -> (Fixture<'static>, Address) {
+    let fx = Fixture::new();
+    fx.add(-6000, 6000, 1_000_000_000);
+    let maker = Address::generate(&fx.env);
+    fx.fund(&maker, 1_000_000_000);
+    (fx, maker)
+}
+
+/// Swap the fixture user's tokens up to `tick`
+fn swap_to(fx: &Fixture, amount: i128, zero_for_one: bool, tick: i32) {
+    let limit = get_sqrt_ratio_at_tick(&fx.env, tick);
+    fx.pool.swap_advanced(&fx.user, &amount, &0, &0, &zero_for_one, &limit, &0);
+}
+
+#[test]
+fn order_must_rest_beyond_the_price() {
+    let (fx, maker) = market();
+
+    assert_error(
+        fx.pool.try_place_limit_order(&maker, &-120, &true, &1_000_000, &0),
+        PoolError::PostOnlyWouldFill,
+    );
+    assert!(fx.pool.try_place_limit_order(&maker, &61, &true, &1_000_000, &0).is_err());
+}
+
+#[test]
+fn placement_honours_deadline() {
+    let (fx, maker) = market();
+    fx.env.ledger().with_mut(|l| l.timestamp = 1_000);
+
+    assert_error(
+        fx.pool.try_place_limit_order(&maker, &60, &true, &1_000_000, &999),
+        PoolError::DeadlineExpired,
+    );
+    fx.pool.place_limit_order(&maker, &60, &true, &1_000_000, &1_000);
+}
+
+#[test]
+fn settles_only_once_fully_crossed() {
+    let (fx, maker) = market();
+    let id = fx.pool.place_limit_order(&maker, &60, &true, &1_000_000, &0);
+    assert_eq!(fx.pool.get_limit_order(&id), Some(LimitOrder { zero_for_one: true }));
+    assert_error(fx.pool.try_settle_limit_order(&maker, &id), PoolErrorExt::LimitOrderNotCrossed);
+
+    // Partly filled
+    swap_to(&fx, 20_000_000, false, 90);
+    assert_eq!(fx.pool.get_pool_state().current_tick, 90);
+    assert_error(fx.pool.try_settle_limit_order(&maker, &id), PoolErrorExt::LimitOrderNotCrossed);
+
+    swap_to(&fx, 25_000_000, false, 200);
+    let other = Address::generate(&fx.env);
+    assert!(fx.pool.try_settle_limit_order(&other, &id).is_err());
+
+    let before0 = fx.balance(&fx.token0, &maker);
+    let before1 = fx.balance(&fx.token1, &maker);
+    let (amount0, amount1, fees0, fees1) = fx.pool.settle_limit_order(&maker, &id);
+    assert_eq!(amount0, 0);
+    assert!(amount1 > 1_000_000 && amount1 < 1_020_000);
+    assert!(fees1 > 0);
+    assert_eq!(fx.balance(&fx.token1, &maker) - before1, amount1 + fees1 as i128);
+    assert_eq!(fx.balance(&fx.token0, &maker) - before0, fees0 as i128);
+    assert_eq!(fx.pool.get_limit_order(&id), None);
+    assert!(fx.pool.try_settle_limit_order(&maker, &id).is_err());
+}
+
+#[test]
+fn price_returning_into_range_blocks_settlement() {
+    let (fx, maker) = market();
+    let id = fx.pool.place_limit_order(&maker, &-60, &false, &1_000_000, &0);
+
+    swap_to(&fx, 15_000_000, true, -70);
+    swap_to(&fx, 5_000_000, false, -30);
+    assert!((-60..0).contains(&fx.pool.get_pool_state().current_tick));
+    assert_error(fx.pool.try_settle_limit_order(&maker, &id), PoolErrorExt::LimitOrderNotCrossed);
+
+    swap_to(&fx, 6_000_000, true, -70);
+    assert!(fx.pool.get_pool_state().current_tick < -60);
+    let (amount0, amount1, _, _) = fx.pool.settle_limit_order(&maker, &id);
+    assert_eq!(amount1, 0);
+    assert!(amount0 > 990_000);
+}
+
+#[test]
+fn settlement_is_exempt_from_withdrawal_queue() {
+    let (fx, maker) = market();
+    let id = fx.pool.place_limit_order(&maker, &60, &true, &1_000_000, &0);
+    let liquidity = fx.pool.get_position_by_id(&id).liquidity;
+
+    fx.pool.set_withdrawal_queue(&fx.admin, &(liquidity / 10), &3_600);
+    assert_error(
+        fx.pool.try_remove_liquidity_by_id(&maker, &id, &liquidity, &0),
+        PoolError::WithdrawalAboveThreshold,
+    );
+
+    swap_to(&fx, 25_000_000, false, 200);
+    let (_, amount1, _, _) = fx.pool.settle_limit_order(&maker, &id);
+    assert!(amount1 > 1_000_000);
+    assert_eq!(fx.pool.get_position_by_id(&id).liquidity, 0);
+}
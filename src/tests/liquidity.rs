@@ -0,0 +1,649 @@
+//! Adding and removing liquidity: sizing, funding and active liquidity.
+
+extern crate std;
+
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{token, Address, TryFromVal};
+
+use super::{assert_error, events_named, Fixture};
+use crate::constants::{MAX_TICK, MIN_TICK};
+use crate::error::PoolError;
+use crate::math::{get_amounts_for_liquidity, get_sqrt_ratio_at_tick, get_tick_at_sqrt_ratio};
+use crate::types::{LiquidityChange, MintParams};
+
+// ============================================================
+// MINT PARAMS
+// ============================================================
+
+#[test]
+fn default_params_match_add_liquidity_advanced() {
+    let (legacy, params) = (Fixture::new(), Fixture::new());
+
+    let expected = legacy.pool.add_liquidity_advanced(
+        &legacy.user, &-600, &600, &1_000_000_000, &500_000_000, &0, &0, &0,
+    );
+    let result = params.pool.mint_with_params(&params.user, &MintParams::new(-600, 600, 1_000_000_000, 500_000_000));
+
+    assert_eq!(result, expected);
+    assert_eq!(params.pool.get_position(&params.user, &-600, &600).liquidity, expected.0);
+    assert_eq!(params.pool.get_pool_state().liquidity, legacy.pool.get_pool_state().liquidity);
+}
+
+#[test]
+fn fully_set_params_match_the_legacy_entrypoints() {
+    let (legacy, advanced, params) = (Fixture::new(), Fixture::new(), Fixture::new());
+
+    let expected = legacy.pool.add_liquidity(
+        &legacy.user, &legacy.token1, &legacy.token0,
+        &500_000_000, &1_000_000_000, &1, &1, &-600, &600, &1_000,
+    );
+    let via_advanced = advanced.pool.add_liquidity_advanced(
+        &advanced.user, &-600, &600, &1_000_000_000, &500_000_000, &1, &1, &1_000,
+    );
+    let result = params.pool.mint_with_params(
+        &params.user,
+        &MintParams::new(-600, 600, 1_000_000_000, 500_000_000)
+            .with_min_amounts(1, 1)
+            .with_deadline(1_000)
+            .with_recipient(params.user.clone()),
+    );
+
+    // `add_liquidity` reports amounts in the order the tokens were passed
+    assert_eq!(result, (expected.0, expected.2, expected.1));
+    assert_eq!(result, via_advanced);
+}
+
+#[test]
+fn mint_recipient_owns_the_position_the_payer_funds() {
+    let fixture = Fixture::new();
+    let recipient = Address::generate(&fixture.env);
+    let before = fixture.balance(&fixture.token0, &fixture.user);
+
+    let (liquidity, amount0, _) = fixture.pool.mint_with_params(
+        &fixture.user,
+        &MintParams::new(-600, 600, 1_000_000, 1_000_000).with_recipient(recipient.clone()),
+    );
+
+    assert_eq!(fixture.pool.get_position(&recipient, &-600, &600).liquidity, liquidity);
+    assert_eq!(fixture.pool.get_position(&fixture.user, &-600, &600).liquidity, 0);
+    assert_eq!(before - fixture.balance(&fixture.token0, &fixture.user), amount0);
+    assert_eq!(fixture.balance(&fixture.token0, &recipient), 0);
+}
+
+// ============================================================
+// FUNDING
+// ============================================================
+
+#[test]
+fn one_sided_offer_for_an_in_range_position_reverts() {
+    let fixture = Fixture::new();
+
+    // The missing side caps the liquidity at zero instead of being trusted
+    assert_error(
+        fixture.pool.try_add_liquidity_advanced(&fixture.user, &-600, &600, &1_000_000_000, &0, &0, &0, &0),
+        PoolError::LiquidityTooLow,
+    );
+    assert_error(
+        fixture.pool.try_add_liquidity_advanced(&fixture.user, &-600, &600, &0, &1_000_000_000, &0, &0, &0),
+        PoolError::LiquidityTooLow,
+    );
+}
+
+#[test]
+fn dust_offer_reverts() {
+    let fixture = Fixture::new();
+
+    assert_error(
+        fixture.pool.try_add_liquidity_advanced(&fixture.user, &-600, &600, &1, &1, &0, &0, &0),
+        PoolError::LiquidityTooLow,
+    );
+}
+
+#[test]
+fn payer_without_the_tokens_cannot_open_a_position() {
+    let fixture = Fixture::new();
+    let broke = Address::generate(&fixture.env);
+
+    assert!(fixture.pool
+        .try_add_liquidity_advanced(&broke, &-600, &600, &1_000_000, &1_000_000, &0, &0, &0)
+        .is_err());
+    assert_eq!(fixture.pool.get_position(&broke, &-600, &600).liquidity, 0);
+    assert_eq!(fixture.pool.get_pool_state().liquidity, 0);
+}
+
+#[test]
+fn position_never_pays_out_more_than_was_deposited() {
+    let fixture = Fixture::new();
+
+    for (lower, upper) in [(-600, 600), (-60, 60), (600, 1_200), (-1_200, -600)] {
+        let (liquidity, amount0, amount1) = fixture.pool.add_liquidity_advanced(
+            &fixture.user, &lower, &upper, &1_000_003, &999_997, &0, &0, &0,
+        );
+        assert!(amount0 <= 1_000_003 && amount1 <= 999_997);
+
+        let (out0, out1) = fixture.pool.remove_liquidity(&fixture.user, &lower, &upper, &liquidity, &0);
+        assert!(out0 <= amount0 && out1 <= amount1);
+    }
+}
+
+// ============================================================
+// ACTIVE LIQUIDITY
+// ============================================================
+
+#[test]
+fn only_in_range_positions_count_toward_pool_liquidity() {
+    let fixture = Fixture::new();
+
+    let in_range = fixture.add(-600, 600, 1_000_000_000);
+    assert_eq!(fixture.pool.get_pool_state().liquidity, in_range);
+
+    fixture.add(-1_200, -600, 1_000_000_000);
+    assert_eq!(fixture.pool.get_pool_state().liquidity, in_range);
+
+    fixture.add(600, 1_200, 1_000_000_000);
+    assert_eq!(fixture.pool.get_pool_state().liquidity, in_range);
+}
+
+#[test]
+fn lower_bound_is_inclusive_and_upper_exclusive() {
+    let fixture = Fixture::new();
+
+    // Current tick 0: [0, 60) is in range, [-60, 0) is not
+    let starting_here = fixture.add(0, 60, 1_000_000_000);
+    fixture.add(-60, 0, 1_000_000_000);
+
+    assert_eq!(fixture.pool.get_pool_state().liquidity, starting_here);
+}
+
+#[test]
+fn removal_mirrors_addition() {
+    let fixture = Fixture::new();
+    let in_range = fixture.add(-600, 600, 1_000_000_000);
+    let below = fixture.add(-1_200, -600, 1_000_000_000);
+
+    fixture.pool.remove_liquidity(&fixture.user, &-1_200, &-600, &below, &0);
+    assert_eq!(fixture.pool.get_pool_state().liquidity, in_range);
+
+    fixture.pool.remove_liquidity(&fixture.user, &-600, &600, &(in_range / 2), &0);
+    assert_eq!(fixture.pool.get_pool_state().liquidity, in_range - in_range / 2);
+}
+
+// ============================================================
+// RANGE EDGES
+// ============================================================
+
+/// Widest range a spacing-60 pool accepts
+const FULL_RANGE: (i32, i32) = (-689_340, 887_220);
+
+#[test]
+fn full_range_position_round_trips() {
+    let fixture = Fixture::new();
+    let (lower, upper) = FULL_RANGE;
+
+    let (liquidity, amount0, amount1) = fixture.pool.add_liquidity_advanced(
+        &fixture.user, &lower, &upper, &1_000_000_000, &1_000_000_000, &0, &0, &0,
+    );
+    let (out0, out1) = fixture.pool.remove_liquidity(&fixture.user, &lower, &upper, &liquidity, &0);
+
+    assert!(amount0 > 999_000_000 && amount1 > 999_000_000);
+    assert!(amount0 - out0 <= 1 && amount1 - out1 <= 1);
+}
+
+#[test]
+fn position_up_to_max_tick_round_trips() {
+    let fixture = Fixture::at_tick(30, 1, 887_200);
+
+    let (liquidity, amount0, amount1) = fixture.pool.add_liquidity_advanced(
+        &fixture.user, &887_200, &MAX_TICK, &1_000_000_000_000, &0, &0, &0, &0,
+    );
+    let (out0, out1) = fixture.pool.remove_liquidity(&fixture.user, &887_200, &MAX_TICK, &liquidity, &0);
+
+    assert_eq!(amount1, 0);
+    assert!(amount0 > 999_999_000_000);
+    assert!(amount0 - out0 <= 1 && out1 == 0);
+}
+
+#[test]
+fn position_down_to_min_tick_round_trips() {
+    let fixture = Fixture::at_tick(30, 1, -689_000);
+    fixture.fund(&fixture.user, 1i128 << 115);
+
+    let (liquidity, amount0, amount1) = fixture.pool.add_liquidity_advanced(
+        &fixture.user, &MIN_TICK, &-688_000, &(1i128 << 110), &(1i128 << 110), &0, &0, &0,
+    );
+    let (out0, out1) = fixture.pool.remove_liquidity(&fixture.user, &MIN_TICK, &-688_000, &liquidity, &0);
+
+    assert!(amount0 > 0 && amount1 > 0);
+    assert!(amount0 - out0 <= 1 && amount1 - out1 <= 1);
+}
+
+// ============================================================
+// ROUNDING
+// ============================================================
+
+#[test]
+fn tiny_add_remove_round_trips_never_drain_the_pool() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let lp = Address::generate(&fixture.env);
+    fixture.fund(&lp, 1_000_000);
+    let start = (fixture.balance(&fixture.token0, &lp), fixture.balance(&fixture.token1, &lp));
+
+    let mut seed: u64 = 7;
+    let mut next = |bound: u64| {
+        seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) % bound
+    };
+
+    for round in 0..200 {
+        let amount = next(5_000) as i128 + 1;
+        let lower = -60 * (next(10) as i32 + 1);
+        let upper = 60 * (next(10) as i32 + 1);
+
+        if let Ok(Ok((liquidity, _, _))) = fixture.pool.try_add_liquidity_advanced(
+            &lp, &lower, &upper, &amount, &amount, &0, &0, &0,
+        ) {
+            fixture.pool.remove_liquidity(&lp, &lower, &upper, &liquidity, &0);
+            fixture.pool.collect(&lp, &lower, &upper, &u128::MAX, &u128::MAX, &0);
+        }
+
+        // Move the price so round trips see both sides of the range
+        if round % 20 == 0 {
+            let amount = next(100_000) as i128 + 1_000;
+            fixture.pool.swap_advanced(&fixture.user, &amount, &0, &0, &(round % 40 == 0), &0, &0);
+        }
+
+        let now = (fixture.balance(&fixture.token0, &lp), fixture.balance(&fixture.token1, &lp));
+        assert!(now.0 <= start.0 && now.1 <= start.1, "round {round}: {now:?} > {start:?}");
+    }
+
+    // Everything still owed can be paid out and the pool stays solvent
+    let liquidity = fixture.pool.get_position(&fixture.user, &-600, &600).liquidity;
+    fixture.pool.remove_liquidity(&fixture.user, &-600, &600, &liquidity, &0);
+    fixture.pool.collect(&fixture.user, &-600, &600, &u128::MAX, &u128::MAX, &0);
+
+    let state = fixture.pool.get_pool_state();
+    assert!(fixture.balance(&fixture.token0, &fixture.pool.address) as u128 >= state.protocol_fees_0);
+    assert!(fixture.balance(&fixture.token1, &fixture.pool.address) as u128 >= state.protocol_fees_1);
+}
+
+// ============================================================
+// LIQUIDITY EVENTS
+// ============================================================
+
+/// Global liquidity rebuilt from `liq` events alone
+#[derive(Default)]
+struct LiquiditySeries {
+    liquidity: i128,
+    events_seen: usize,
+}
+
+impl LiquiditySeries {
+    /// Apply the `liq` events published since the last step, check the
+    /// result against the pool and return their causes
+    fn step(&mut self, fixture: &Fixture) -> std::vec::Vec<LiquidityChange> {
+        let events = events_named(&fixture.env, "liq");
+        let mut causes = std::vec::Vec::new();
+        for data in &events[self.events_seen..] {
+            let (old, new, cause): (i128, i128, LiquidityChange) = <_>::try_from_val(&fixture.env, data).unwrap();
+            assert_eq!(old, self.liquidity, "{cause:?}");
+            self.liquidity = new;
+            causes.push(cause);
+        }
+        self.events_seen = events.len();
+        assert_eq!(self.liquidity, fixture.pool.get_pool_state().liquidity);
+        causes
+    }
+}
+
+#[test]
+fn liquidity_events_replay_to_pool_liquidity() {
+    let fixture = Fixture::new();
+    let mut series = LiquiditySeries::default();
+
+    fixture.add(-600, 600, 1_000_000_000);
+    assert_eq!(series.step(&fixture), [LiquidityChange::Mint]);
+
+    let narrow = fixture.add(-120, 120, 1_000_000_000);
+    assert_eq!(series.step(&fixture), [LiquidityChange::Mint]);
+
+    // Out of range: no change, no event
+    fixture.add(600, 1_200, 1_000_000_000);
+    assert!(series.step(&fixture).is_empty());
+
+    fixture.pool.swap_advanced(&fixture.user, &2_000_000_000, &0, &0, &true, &0, &0);
+    assert!(series.step(&fixture).contains(&LiquidityChange::Cross));
+
+    fixture.pool.swap_advanced(&fixture.user, &3_000_000_000, &0, &0, &false, &0, &0);
+    assert!(series.step(&fixture).contains(&LiquidityChange::Cross));
+
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &true, &0, &0);
+    series.step(&fixture);
+
+    fixture.pool.remove_liquidity(&fixture.user, &-120, &120, &(narrow / 2), &0);
+    assert_eq!(series.step(&fixture), [LiquidityChange::Burn]);
+
+    let wide = fixture.pool.get_position(&fixture.user, &-600, &600).liquidity;
+    fixture.pool.remove_liquidity(&fixture.user, &-600, &600, &wide, &0);
+    assert_eq!(series.step(&fixture), [LiquidityChange::Burn]);
+}
+
+
+// ============================================================
+// SWAP AND ADD
+// ============================================================
+
+#[test]
+fn swap_and_add_balances_a_single_token_and_refunds_the_residual() {
+    let fixture = Fixture::new();
+    fixture.add(-6_000, 6_000, 100_000_000);
+    let zapper = Address::generate(&fixture.env);
+    token::StellarAssetClient::new(&fixture.env, &fixture.token0).mint(&zapper, &10_000_000);
+
+    let result = fixture.pool.swap_and_add(&zapper, &-600, &1_200, &true, &10_000_000, &0, &0);
+    assert!(result.liquidity > 0 && result.amount0 > 0 && result.amount1 > 0);
+
+    // The residual stays with the zapper and is a sliver of the input
+    let left0 = fixture.balance(&fixture.token0, &zapper);
+    let left1 = fixture.balance(&fixture.token1, &zapper);
+    assert_eq!((left0, left1), (result.refund0, result.refund1));
+    assert!(result.refund0 + result.refund1 < 10_000_000 / 1_000);
+
+    // Near price 1.0 the value is conserved up to the swap fee
+    let position = fixture.pool.get_position(&zapper, &-600, &1_200);
+    let value = position.amount0 + position.amount1 + left0 + left1;
+    assert!(value <= 10_000_000 && value > 10_000_000 * 995 / 1_000);
+}
+
+#[test]
+fn swap_and_add_skips_the_swap_when_the_range_takes_the_input_token() {
+    let fixture = Fixture::new();
+    fixture.add(-6_000, 6_000, 100_000_000);
+    let zapper = Address::generate(&fixture.env);
+    token::StellarAssetClient::new(&fixture.env, &fixture.token0).mint(&zapper, &1_000_000);
+    let before = fixture.pool.get_pool_state().sqrt_price_x64;
+
+    // Above the price the range holds only token0
+    let result = fixture.pool.swap_and_add(&zapper, &1_200, &2_400, &true, &1_000_000, &0, &0);
+    assert_eq!(fixture.pool.get_pool_state().sqrt_price_x64, before);
+    assert_eq!(result.amount1, 0);
+    assert_eq!(result.amount0 + result.refund0, 1_000_000);
+}
+
+// ============================================================
+// POST-ONLY
+// ============================================================
+
+#[test]
+fn post_only_reverts_once_the_price_has_moved_into_the_range() {
+    let fixture = Fixture::new();
+    let user = &fixture.user;
+    fixture.add(-6_000, 6_000, 1_000_000_000);
+
+    // Resting above the price: token0 only
+    fixture.pool.add_liquidity_post_only(user, &120, &240, &1_000_000, &0, &0, &0, &0);
+
+    // The price runs up into [120, 240) before the next placement lands
+    let into_range = get_sqrt_ratio_at_tick(&fixture.env, 180);
+    fixture.pool.swap_advanced(user, &50_000_000, &0, &0, &false, &into_range, &0);
+    assert_eq!(fixture.pool.get_pool_state().current_tick, 180);
+    assert_error(
+        fixture.pool.try_add_liquidity_post_only(user, &120, &240, &1_000_000, &0, &0, &0, &0),
+        PoolError::PostOnlyWouldFill,
+    );
+
+    // Below the new price token1 rests, and above the range token0 still does
+    fixture.pool.add_liquidity_post_only(user, &0, &120, &0, &1_000_000, &0, &0, &0);
+    fixture.pool.add_liquidity_post_only(user, &240, &360, &1_000_000, &0, &0, &0, &0);
+
+    // Past the range is as bad as inside it
+    assert_error(
+        fixture.pool.try_add_liquidity_post_only(user, &0, &120, &1_000_000, &0, &0, &0, &0),
+        PoolError::PostOnlyWouldFill,
+    );
+}
+
+#[test]
+fn post_only_takes_a_single_token() {
+    let fixture = Fixture::new();
+    let user = &fixture.user;
+
+    assert_error(
+        fixture.pool.try_add_liquidity_post_only(user, &600, &1_200, &1_000_000, &1_000_000, &0, &0, &0),
+        PoolError::PostOnlyNotSingleSided,
+    );
+    assert_error(
+        fixture.pool.try_add_liquidity_post_only(user, &600, &1_200, &0, &0, &0, &0, &0),
+        PoolError::PostOnlyNotSingleSided,
+    );
+
+    // Resting exactly on the price is still out of range
+    fixture.pool.add_liquidity_post_only(user, &0, &600, &1_000_000, &0, &0, &0, &0);
+    fixture.pool.add_liquidity_post_only(user, &-600, &0, &0, &1_000_000, &0, &0, &0);
+}
+
+// ============================================================
+// DECREASE AND COLLECT
+// ============================================================
+
+/// Position over [-600, 600] that has earned fees on both tokens
+fn earning<'a>() -> Fixture<'a> {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.swap_advanced(&fixture.user, &50_000_000, &0, &0, &true, &0, &0);
+    fixture.pool.swap_advanced(&fixture.user, &30_000_000, &0, &0, &false, &0, &0);
+    fixture
+}
+
+#[test]
+fn decrease_and_collect_matches_remove_then_collect() {
+    let (one_call, two_calls) = (earning(), earning());
+    let liquidity = one_call.pool.get_position(&one_call.user, &-600, &600).liquidity;
+    let partial = liquidity / 3;
+
+    let combined = one_call.pool.decrease_liquidity_and_collect(&one_call.user, &-600, &600, &partial, &0);
+    let (principal0, principal1) = two_calls.pool.remove_liquidity(&two_calls.user, &-600, &600, &partial, &0);
+    let (fees0, fees1) = two_calls.pool.collect(&two_calls.user, &-600, &600, &u128::MAX, &u128::MAX, &0);
+
+    assert_eq!(combined, (principal0, principal1, fees0, fees1));
+    assert!(fees0 > 0 && fees1 > 0);
+    assert_eq!(
+        one_call.balance(&one_call.token0, &one_call.user),
+        two_calls.balance(&two_calls.token0, &two_calls.user),
+    );
+    assert_eq!(
+        one_call.balance(&one_call.token1, &one_call.user),
+        two_calls.balance(&two_calls.token1, &two_calls.user),
+    );
+
+    // Nothing is left owed, and the rest of the position is untouched
+    let rest = one_call.pool.get_position(&one_call.user, &-600, &600);
+    assert_eq!((rest.liquidity, rest.fees_owed_0, rest.fees_owed_1), (liquidity - partial, 0, 0));
+}
+
+// ============================================================
+// AMOUNTS USED
+// ============================================================
+
+#[test]
+fn add_returns_what_the_position_stores_and_the_user_pays() {
+    let fixture = Fixture::new();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+    let before = (fixture.balance(token0, user), fixture.balance(token1, user));
+
+    // Lopsided offer: token1 is the binding side, token0 is only partly used
+    let (liquidity, used0, used1) =
+        fixture.pool.add_liquidity(user, token0, token1, &5_000_000, &1_000_000, &0, &0, &-600, &1_200, &0);
+    assert!(used0 < 5_000_000 && used1 <= 1_000_000);
+
+    let position = fixture.pool.get_position(user, &-600, &1_200);
+    assert_eq!(position.liquidity, liquidity);
+    assert_eq!(fixture.pool.get_tick_info(&-600).liquidity_gross, liquidity);
+    assert_eq!(before.0 - fixture.balance(token0, user), used0);
+    assert_eq!(before.1 - fixture.balance(token1, user), used1);
+}
+
+#[test]
+fn misaligned_ticks_are_rejected_rather_than_snapped() {
+    let fixture = Fixture::new();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+
+    for (lower, upper) in [(-610, 600), (-600, 590), (-1, 1)] {
+        assert_error(
+            fixture.pool.try_add_liquidity(user, token0, token1, &1_000_000, &1_000_000, &0, &0, &lower, &upper, &0),
+            PoolError::InvalidTick,
+        );
+    }
+    assert!(fixture.pool.list_positions(user).is_empty());
+}
+
+// ============================================================
+// DEPOSIT PREVIEW
+// ============================================================
+
+#[test]
+fn calc_helpers_match_what_add_consumes() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    // Move off 1.0 so the preview has to read the live price
+    fixture.pool.swap(&fixture.user, &fixture.token0, &fixture.token1, &1_000_000, &0, &0, &0);
+
+    // Straddling, below-price (token1 only), above-price (token0 only), narrow
+    for (amount0, amount1, lower, upper) in [
+        (5_000_000, 7_000_000, -660, 600),
+        (0, 3_000_000, -1_200, -600),
+        (3_000_000, 0, 600, 1_800),
+        (10_000, 10_000_000, -60, 60),
+    ] {
+        let liquidity = fixture.pool.calc_liquidity_for_amounts(&amount0, &amount1, &lower, &upper);
+        let (preview0, preview1) = fixture.pool.calc_amounts_for_liquidity(&liquidity, &lower, &upper);
+        let added = fixture.pool.add_liquidity_advanced(&fixture.user, &lower, &upper, &amount0, &amount1, &0, &0, &0);
+        assert_eq!(added, (liquidity, preview0, preview1));
+    }
+}
+
+#[test]
+fn amounts_preview_of_an_existing_position_matches_its_withdrawal() {
+    let fixture = Fixture::new();
+    let liquidity = fixture.add(-1_200, 600, 50_000_000);
+    let (preview0, preview1) = fixture.pool.calc_amounts_for_liquidity(&liquidity, &-1_200, &600);
+
+    // Withdrawal rounds down where the deposit preview rounds up
+    let (out0, out1) = fixture.pool.remove_liquidity(&fixture.user, &-1_200, &600, &liquidity, &0);
+    assert!((0..=1).contains(&(preview0 - out0)) && (0..=1).contains(&(preview1 - out1)));
+}
+
+// ============================================================
+// WITHDRAWAL AMOUNTS
+// ============================================================
+
+/// Swap the fixture pool to just past `tick` and return (out0, out1) from
+/// removing half of a -600..600 position, with the in-range formula's
+/// amounts for that half at the price reached
+fn withdraw_half_after_moving_to(tick: i32) -> ((i128, i128), (i128, i128)) {
+    let fixture = Fixture::new();
+    let liquidity = fixture.add(-600, 600, 1_000_000_000);
+    fixture.add(-6_000, 6_000, 1_000_000_000);
+
+    let limit = get_sqrt_ratio_at_tick(&fixture.env, tick) + 12_345;
+    let zero_for_one = limit < fixture.pool.get_pool_state().sqrt_price_x64;
+    // Enough to leave the range, or little enough to stop inside it
+    let amount = if tick.abs() > 600 { 1_200_000_000 } else { 400_000_000 };
+    fixture.pool.swap_advanced(&fixture.user, &amount, &0, &0, &zero_for_one, &limit, &0);
+    let price = fixture.pool.get_pool_state().sqrt_price_x64;
+
+    let half = liquidity / 2;
+    let (lower, upper) = (get_sqrt_ratio_at_tick(&fixture.env, -600), get_sqrt_ratio_at_tick(&fixture.env, 600));
+    let expected = get_amounts_for_liquidity(&fixture.env, half, lower, upper, price);
+    (fixture.pool.remove_liquidity(&fixture.user, &-600, &600, &half, &0), expected)
+}
+
+#[test]
+fn below_the_range_withdrawal_pays_only_token0() {
+    let (out, expected) = withdraw_half_after_moving_to(-900);
+    assert_eq!(out, expected);
+    assert!(out.0 > 0 && out.1 == 0);
+}
+
+#[test]
+fn above_the_range_withdrawal_pays_only_token1() {
+    let (out, expected) = withdraw_half_after_moving_to(900);
+    assert_eq!(out, expected);
+    assert!(out.0 == 0 && out.1 > 0);
+}
+
+#[test]
+fn in_range_withdrawal_after_a_move_follows_the_price() {
+    let (out, expected) = withdraw_half_after_moving_to(300);
+    assert_eq!(out, expected);
+
+    // Above the deposit price: less token0 and more token1 than went in
+    assert!(out.0 < 500_000_000 && out.1 > 500_000_000);
+}
+
+// ============================================================
+// MID-TICK PRICE
+// ============================================================
+
+#[test]
+fn range_straddling_a_mid_tick_price_is_active() {
+    let fixture = Fixture::new();
+    fixture.add(-6_000, 6_000, 1_000_000_000);
+
+    // Lands between ticks, past the first spacing boundary above 0
+    fixture.pool.swap(&fixture.user, &fixture.token1, &fixture.token0, &20_000_000, &0, &0, &0);
+    let state = fixture.pool.get_pool_state();
+    assert_eq!(state.current_tick, get_tick_at_sqrt_ratio(&fixture.env, state.sqrt_price_x64));
+    assert!(state.sqrt_price_x64 > get_sqrt_ratio_at_tick(&fixture.env, state.current_tick));
+
+    let lower = state.current_tick - state.current_tick.rem_euclid(60);
+    assert!(lower > 0);
+    let straddling = fixture.add(lower, lower + 60, 1_000_000);
+    assert_eq!(fixture.pool.get_pool_state().liquidity, state.liquidity + straddling);
+
+    // The next range up starts above the price and stays inactive
+    fixture.add(lower + 60, lower + 120, 1_000_000);
+    assert_eq!(fixture.pool.get_pool_state().liquidity, state.liquidity + straddling);
+
+    // Swapping back down leaves the straddling range out again
+    fixture.pool.swap(&fixture.user, &fixture.token0, &fixture.token1, &20_000_000, &0, &0, &0);
+    assert_eq!(fixture.pool.get_pool_state().liquidity, state.liquidity);
+}
+
+// ============================================================
+// SHARED TICKS
+// ============================================================
+
+#[test]
+fn partial_removals_from_a_shared_tick_keep_it_consistent() {
+    let fixture = Fixture::new();
+    let other = Address::generate(&fixture.env);
+    fixture.fund(&other, super::USER_BALANCE);
+
+    // Both ranges start at -600
+    let mine = fixture.add(-600, 600, 1_000_000_000);
+    let (theirs, _, _) =
+        fixture.pool.add_liquidity_advanced(&other, &-600, &1_200, &500_000_000, &500_000_000, &0, &0, &0);
+    assert_eq!(fixture.pool.get_tick_info(&-600).liquidity_gross, mine + theirs);
+
+    let (mut left_mine, mut left_theirs) = (mine, theirs);
+    for _ in 0..3 {
+        fixture.pool.remove_liquidity(&fixture.user, &-600, &600, &(mine / 4), &0);
+        fixture.pool.remove_liquidity(&other, &-600, &1_200, &(theirs / 4), &0);
+        left_mine -= mine / 4;
+        left_theirs -= theirs / 4;
+
+        let shared = fixture.pool.get_tick_info(&-600);
+        assert_eq!(shared.liquidity_gross, left_mine + left_theirs);
+        assert_eq!(shared.liquidity_net, left_mine + left_theirs);
+        assert_eq!(fixture.pool.get_tick_info(&600).liquidity_gross, left_mine);
+    }
+
+    // Neither owner can burn into the other's share of the tick
+    assert_error(
+        fixture.pool.try_remove_liquidity(&fixture.user, &-600, &600, &(left_mine + 1), &0),
+        PoolError::InsufficientLiquidity,
+    );
+    fixture.pool.remove_liquidity(&fixture.user, &-600, &600, &left_mine, &0);
+    assert_eq!(fixture.pool.get_tick_info(&-600).liquidity_gross, left_theirs);
+}
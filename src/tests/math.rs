@@ -0,0 +1,330 @@
+use soroban_sdk::{Env, U256};
+
+use super::{assert_error, Fixture};
+
+use crate::constants::{MAX_TICK, MIN_TICK, Q64};
+use crate::error::PoolError;
+use crate::math::{
+    compute_swap_step_with_target, get_amount_1_delta, get_amounts_for_liquidity, get_liquidity_for_amount1,
+    get_sqrt_price_for_ratio, get_sqrt_ratio_at_tick, get_tick_at_sqrt_ratio, mul_div, mul_div_checked,
+    mul_div_rounding, mul_q64, mul_q64_checked, mul_q64_round_up, snap_tick_to_spacing, MAX_SQRT_RATIO,
+    MIN_SQRT_RATIO, MIN_STRICT_TICK,
+};
+
+// ============================================================
+// ERROR CODES
+// ============================================================
+
+#[test]
+#[should_panic(expected = "Error(Contract, #18)")]
+fn tick_above_max_is_invalid_tick() {
+    get_sqrt_ratio_at_tick(&Env::default(), MAX_TICK + 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #18)")]
+fn tick_below_min_is_invalid_tick() {
+    get_sqrt_ratio_at_tick(&Env::default(), MIN_TICK - 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #5)")]
+fn price_below_min_is_out_of_bounds() {
+    get_tick_at_sqrt_ratio(&Env::default(), MIN_SQRT_RATIO - 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #15)")]
+fn zero_spacing_is_invalid_tick_spacing() {
+    snap_tick_to_spacing(&Env::default(), 10, 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn mul_q64_overflow() {
+    mul_q64(&Env::default(), u128::MAX, 2u128 << 64);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn mul_q64_round_up_overflow() {
+    // Fits rounded down, but the carry from the dropped bits does not
+    mul_q64_round_up(&Env::default(), u128::MAX, (1u128 << 64) + 1);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #52)")]
+fn mul_div_by_zero() {
+    mul_div(&Env::default(), 1, 1, 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #52)")]
+fn sqrt_price_for_zero_denominator() {
+    let env = Env::default();
+    get_sqrt_price_for_ratio(&env, &U256::from_u32(&env, 1), &U256::from_u32(&env, 0));
+}
+
+#[test]
+fn mul_div_checked_zero_denominator_does_not_fit() {
+    assert_eq!(mul_div_checked(1, 1, 0), None);
+}
+
+#[test]
+fn tick_bounds_map_to_sqrt_bounds() {
+    let env = Env::default();
+    assert_eq!(get_sqrt_ratio_at_tick(&env, MIN_TICK), MIN_SQRT_RATIO);
+    assert_eq!(get_sqrt_ratio_at_tick(&env, MAX_TICK), MAX_SQRT_RATIO);
+}
+
+#[test]
+fn entrypoint_reports_math_errors_as_contract_errors() {
+    let fixture = Fixture::new();
+
+    // 1 / (u128::MAX * 10^38) is below the lowest representable price
+    assert_error(
+        fixture.pool.try_tick_for_price(&1, &u128::MAX, &38, &0),
+        PoolError::SqrtPriceOutOfBounds,
+    );
+}
+
+// ============================================================
+// OVERFLOW AT THE Q64.64 EDGE
+// ============================================================
+
+#[test]
+fn mul_q64_at_the_edge_is_exact() {
+    let env = Env::default();
+
+    // Largest integer part (u128::MAX >> 64) times one
+    assert_eq!(mul_q64(&env, u128::MAX, 1u128 << 64), u128::MAX);
+    assert_eq!(mul_q64_round_up(&env, u128::MAX, 1u128 << 64), u128::MAX);
+    assert_eq!(mul_q64_checked(u128::MAX, (1u128 << 64) + 1), None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn mul_q64_just_past_the_edge_overflows() {
+    mul_q64(&Env::default(), u128::MAX, (1u128 << 64) + (1u128 << 63));
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn amounts_for_huge_liquidity_overflow() {
+    let env = Env::default();
+    
+    // token1 for the whole range below the top price is about L * 2^64
+    get_amounts_for_liquidity(&env, i128::MAX, MIN_SQRT_RATIO, MAX_SQRT_RATIO, MAX_SQRT_RATIO);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn swap_step_output_overflow_aborts() {
+    let env = Env::default();
+
+    // token0 out of maximum liquidity moving from a tiny price up to 1.0
+    // does not fit in i128; the step must abort rather than report zero
+    compute_swap_step_with_target(&env, 1u128 << 40, i128::MAX, i128::MAX, false, 1u128 << 64);
+}
+
+// ============================================================
+// TICK ROUND TRIPS
+// ============================================================
+
+fn assert_round_trip(env: &Env, tick: i32) {
+    let sqrt_price = get_sqrt_ratio_at_tick(env, tick);
+    assert_eq!(get_tick_at_sqrt_ratio(env, sqrt_price), tick, "round trip at {tick}");
+    if tick < MAX_TICK {
+        assert!(get_sqrt_ratio_at_tick(env, tick + 1) > sqrt_price, "not strictly increasing at {tick}");
+        // One unit below the next tick's price still belongs to this tick
+        assert_eq!(get_tick_at_sqrt_ratio(env, get_sqrt_ratio_at_tick(env, tick + 1) - 1), tick);
+    }
+}
+
+/// Below MIN_STRICT_TICK: the greatest tick whose sqrt price is <= the input
+fn assert_greatest_tick_at_or_below(env: &Env, sqrt_price: u128) {
+    let tick = get_tick_at_sqrt_ratio(env, sqrt_price);
+    assert!(get_sqrt_ratio_at_tick(env, tick) <= sqrt_price, "tick {tick} above {sqrt_price}");
+    assert!(get_sqrt_ratio_at_tick(env, tick + 1) > sqrt_price, "tick {tick} not greatest for {sqrt_price}");
+}
+
+#[test]
+fn round_trip_at_fixed_ticks() {
+    let env = Env::default();
+    for tick in [0, 1, -1, 60, -60, 887_272, MIN_STRICT_TICK, MIN_STRICT_TICK + 1, MAX_TICK - 1] {
+        assert_round_trip(&env, tick);
+    }
+}
+
+#[test]
+fn round_trip_near_the_bounds() {
+    let env = Env::default();
+    for tick in (MIN_STRICT_TICK..MIN_STRICT_TICK + 2_000).chain(MAX_TICK - 2_000..=MAX_TICK) {
+        assert_round_trip(&env, tick);
+    }
+}
+
+#[test]
+fn round_trip_every_aligned_tick() {
+    let env = Env::default();
+    let first = MIN_STRICT_TICK - MIN_STRICT_TICK.rem_euclid(200) + 200;
+    for tick in (first..=MAX_TICK).step_by(200) {
+        assert_round_trip(&env, tick);
+    }
+}
+
+#[test]
+fn round_trip_pseudo_random_ticks() {
+    let env = Env::default();
+    let span = (MAX_TICK - MIN_STRICT_TICK + 1) as u64;
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    for _ in 0..2_000 {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        assert_round_trip(&env, MIN_STRICT_TICK + (seed % span) as i32);
+    }
+}
+
+#[test]
+fn min_strict_tick_is_where_prices_stop_being_distinct() {
+    let env = Env::default();
+    let at = |tick| get_sqrt_ratio_at_tick(&env, tick);
+    assert_eq!(at(MIN_STRICT_TICK - 1), at(MIN_STRICT_TICK));
+    assert!(at(MIN_STRICT_TICK + 1) > at(MIN_STRICT_TICK));
+}
+
+#[test]
+fn min_tick_is_the_mirror_of_max_tick() {
+    let env = Env::default();
+    assert_eq!(MIN_TICK, -MAX_TICK);
+    assert_eq!(get_sqrt_ratio_at_tick(&env, MIN_TICK), MIN_SQRT_RATIO);
+}
+
+#[test]
+fn below_min_strict_tick_returns_the_greatest_tick_at_or_below() {
+    let env = Env::default();
+
+    // Every distinct sqrt price down there, and one unit under the next
+    for sqrt_price in MIN_SQRT_RATIO..=get_sqrt_ratio_at_tick(&env, MIN_STRICT_TICK) {
+        assert_greatest_tick_at_or_below(&env, sqrt_price);
+    }
+
+    // A tick sharing its price with the ticks above it converts back to
+    // the top of that run
+    let tick = get_tick_at_sqrt_ratio(&env, MIN_SQRT_RATIO);
+    assert!(tick > MIN_TICK && tick < MIN_STRICT_TICK);
+    assert_eq!(get_sqrt_ratio_at_tick(&env, tick), MIN_SQRT_RATIO);
+}
+
+// ============================================================
+// Q64.64 DIVISION
+// ============================================================
+
+/// Next value of a 128-bit xorshift sequence
+fn next(seed: &mut u128) -> u128 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    *seed
+}
+
+/// floor(a * b / d) computed with the host's U256, or None past u128
+fn reference_mul_div(env: &Env, a: u128, b: u128, d: u128) -> Option<u128> {
+    U256::from_u128(env, a).mul(&U256::from_u128(env, b)).div(&U256::from_u128(env, d)).to_u128()
+}
+
+#[test]
+fn q64_division_is_exact_for_large_operands() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+
+    let mut seed = 0x9e37_79b9_7f4a_7c15_f39c_c060_5ced_c834u128;
+    for _ in 0..2_000 {
+        let x = next(&mut seed);
+        // Numerators up to the full width, denominators from tiny to huge
+        let numerator = x >> (x % 64);
+        let denominator = (x.rotate_left(37) >> (x % 90)).max(1);
+        let expected = reference_mul_div(&env, numerator, Q64, denominator);
+        assert_eq!(mul_div_checked(numerator, Q64, denominator), expected);
+        assert_eq!(mul_div(&env, numerator, Q64, denominator), expected.unwrap_or(u128::MAX));
+    }
+}
+
+#[test]
+fn q64_division_with_a_remainder_above_64_bits() {
+    let env = Env::default();
+
+    // The case the old shift-by-32 fallback truncated
+    let (numerator, denominator) = (u128::MAX >> 1, (1u128 << 100) + 12_345);
+    let expected = reference_mul_div(&env, numerator, Q64, denominator).unwrap();
+    assert_eq!(mul_div(&env, numerator, Q64, denominator), expected);
+    assert_eq!(mul_div_rounding(&env, numerator, Q64, denominator, true), expected + 1);
+}
+
+// ============================================================
+// MUL DIV
+// ============================================================
+
+#[test]
+fn mul_div_matches_a_widening_reference() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+
+    let mut seed = 0x1234_5678_90ab_cdef_1122_3344_5566_7788u128;
+    for _ in 0..2_000 {
+        let x = next(&mut seed);
+        let (a, b) = (x >> (x % 100), x.rotate_left(51) >> (x % 77));
+        let d = (x.rotate_left(23) >> (x % 120)).max(1);
+        assert_eq!(mul_div(&env, a, b, d), reference_mul_div(&env, a, b, d).unwrap_or(u128::MAX));
+
+        // Small operands: the product fits in u128 directly
+        let (a, b, d) = (a as u64 as u128, b as u64 as u128, (d as u64 as u128).max(1));
+        assert_eq!(mul_div(&env, a, b, d), a * b / d);
+        assert_eq!(mul_div_rounding(&env, a, b, d, true), (a * b).div_ceil(d));
+    }
+}
+
+#[test]
+fn amounts_are_exact_to_the_unit_at_1e26() {
+    let env = Env::default();
+    let liquidity = 100_000_000_000_000_000_000_000_000u128;
+    let (lower, upper) = (get_sqrt_ratio_at_tick(&env, -6_000), get_sqrt_ratio_at_tick(&env, 6_000));
+
+    let amount1 = get_amount_1_delta(&env, lower, upper, liquidity, false);
+    assert_eq!(Some(amount1), reference_mul_div(&env, liquidity, upper - lower, Q64));
+
+    // And back: the liquidity those tokens buy is within a unit of the start
+    let back = get_liquidity_for_amount1(&env, amount1 as i128, lower, upper) as u128;
+    assert!(liquidity - back <= 1);
+}
+
+// ============================================================
+// CHECKED Q64.64 MULTIPLY
+// ============================================================
+
+#[test]
+fn mul_q64_checked_is_exact_or_none_near_the_top() {
+    let env = Env::default();
+    env.budget().reset_unlimited();
+
+    let mut seed = 0x0f1e_2d3c_4b5a_6978_8796_a5b4_c3d2_e1f0u128;
+    for _ in 0..2_000 {
+        let x = next(&mut seed);
+        // Both operands in the top few bits, so most products overflow
+        let (a, b) = (x | ((1 << 127) >> (x % 8)), x.rotate_left(64) >> (x % 70));
+        let expected = reference_mul_div(&env, a, b, Q64);
+        assert_eq!(mul_q64_checked(a, b), expected);
+        if let Some(product) = expected {
+            assert_eq!(mul_q64(&env, a, b), product);
+        }
+    }
+    assert_eq!(mul_q64_checked(3 << 64, 5 << 63), Some(15 << 63));
+    assert_eq!(mul_q64_checked(1 << 96, 1 << 96), None);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn mul_q64_of_two_max_operands_reverts_instead_of_saturating() {
+    mul_q64(&Env::default(), u128::MAX, u128::MAX);
+}
@@ -0,0 +1,148 @@
+//! # Contract Tests
+//!
+//! Shared fixtures for the per-area test files. Pools are registered in a
+//! fresh `Env` with all auths mocked and an unlimited budget, against two
+//! Stellar asset contracts sorted into token0/token1 order.
+
+extern crate std;
+
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{token, Address, Env, InvokeError, Symbol, TryFromVal, Val};
+
+use crate::math::get_sqrt_ratio_at_tick;
+use crate::storage::{read_pool_config, read_pool_state, write_pool_config, write_pool_state};
+use crate::types::FeeTier;
+use crate::{BelugaSwap, BelugaSwapClient};
+
+mod admin;
+mod analytics;
+mod bps;
+mod deadlines;
+mod events;
+mod fees;
+mod flash;
+mod init;
+mod limit_orders;
+mod liquidity;
+mod math;
+mod oracle;
+mod pause;
+mod position;
+mod price;
+mod reentrancy;
+mod reserves;
+mod rewards;
+mod routing;
+mod swap;
+#[cfg(feature = "testutils")]
+mod testutils;
+mod tick;
+mod ttl;
+mod upgrade;
+mod validation;
+mod withdrawals;
+
+/// Balance every fixture user starts with in both tokens
+pub const USER_BALANCE: i128 = 1_000_000_000_000;
+
+/// A registered pool, its tokens and a funded user
+pub struct Fixture<'a> {
+    pub env: Env,
+    pub pool: BelugaSwapClient<'a>,
+    pub admin: Address,
+    pub token0: Address,
+    pub token1: Address,
+    pub user: Address,
+}
+
+impl<'a> Fixture<'a> {
+    /// Pool tokens registered but the pool not yet initialized
+    pub fn uninitialized() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.budget().reset_unlimited();
+
+        let admin = Address::generate(&env);
+        let token_a = env.register_stellar_asset_contract_v2(admin.clone()).address();
+        let token_b = env.register_stellar_asset_contract_v2(admin.clone()).address();
+        let (token0, token1) = if token_a < token_b { (token_a, token_b) } else { (token_b, token_a) };
+
+        let pool = BelugaSwapClient::new(&env, &env.register_contract(None, BelugaSwap));
+        let user = Address::generate(&env);
+
+        let fixture = Fixture { env, pool, admin, token0, token1, user };
+        fixture.fund(&fixture.user, USER_BALANCE);
+        fixture
+    }
+
+    /// 30 bps / spacing 60 pool at price 1.0
+    pub fn new() -> Self {
+        Self::at_tick(30, 60, 0)
+    }
+
+    /// Pool at `tick` with any fee and spacing, canonical tier or not
+    ///
+    /// Initializes the 30 bps tier and patches fee and spacing in storage,
+    /// so tests can cover pairs `initialize` rejects.
+    pub fn at_tick(fee_bps: u32, tick_spacing: i32, tick: i32) -> Self {
+        let fixture = Self::uninitialized();
+        let sqrt_price_x64 = get_sqrt_ratio_at_tick(&fixture.env, tick);
+        fixture.pool.initialize(
+            &fixture.admin, &fixture.token0, &fixture.token1, &30, &0, &sqrt_price_x64, &tick, &60,
+        );
+
+        fixture.env.as_contract(&fixture.pool.address, || {
+            let mut state = read_pool_state(&fixture.env);
+            state.tick_spacing = tick_spacing;
+            write_pool_state(&fixture.env, &state);
+
+            let mut config = read_pool_config(&fixture.env);
+            config.fee_bps = fee_bps;
+            config.fee_tier = FeeTier::from_params(fee_bps, tick_spacing);
+            write_pool_config(&fixture.env, &config);
+        });
+        fixture
+    }
+
+    /// Mint `amount` of both tokens to `to`
+    pub fn fund(&self, to: &Address, amount: i128) {
+        for t in [&self.token0, &self.token1] {
+            token::StellarAssetClient::new(&self.env, t).mint(to, &amount);
+        }
+    }
+
+    /// Token balance of `owner`
+    pub fn balance(&self, token: &Address, owner: &Address) -> i128 {
+        token::Client::new(&self.env, token).balance(owner)
+    }
+
+    /// Add up to `amount` of both tokens for the fixture user over
+    /// `[lower, upper]`, returning the liquidity minted
+    pub fn add(&self, lower: i32, upper: i32, amount: i128) -> i128 {
+        let (liquidity, _, _) = self.pool.add_liquidity(
+            &self.user, &self.token0, &self.token1, &amount, &amount, &0, &0, &lower, &upper, &0,
+        );
+        liquidity
+    }
+}
+
+/// Assert a `try_*` client call failed with the contract error `expected`
+pub fn assert_error<T: core::fmt::Debug>(
+    result: Result<T, Result<soroban_sdk::Error, InvokeError>>,
+    expected: impl Into<soroban_sdk::Error>,
+) {
+    assert_eq!(result.err(), Some(Ok(expected.into())));
+}
+
+/// Data of every event named `name` published so far, oldest first
+pub fn events_named(env: &Env, name: &str) -> std::vec::Vec<Val> {
+    let name = Symbol::new(env, name);
+    env.events()
+        .all()
+        .iter()
+        .filter(|(_, topics, _)| {
+            topics.get(0).and_then(|topic| Symbol::try_from_val(env, &topic).ok()) == Some(name.clone())
+        })
+        .map(|(_, _, data)| data)
+        .collect()
+}
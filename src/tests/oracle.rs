@@ -0,0 +1,173 @@
+//! Oracle history: tick cumulatives, mean ticks and historical position
+//! values.
+
+use soroban_sdk::testutils::Ledger;
+use soroban_sdk::vec;
+
+use super::{assert_error, Fixture};
+use crate::error::PoolError;
+use crate::math::get_sqrt_ratio_at_tick;
+
+fn set_time(fixture: &Fixture, timestamp: u64) {
+    fixture.env.ledger().with_mut(|l| l.timestamp = timestamp);
+}
+
+// ============================================================
+// POSITION VALUE
+// ============================================================
+
+/// Position over [-600, 600] from t = 1000, price pushed to tick 300 at
+/// t = 1100, queried at t = 1200
+fn moved<'a>() -> Fixture<'a> {
+    let fixture = Fixture::new();
+    set_time(&fixture, 1_000);
+    fixture.add(-600, 600, 1_000_000_000);
+
+    set_time(&fixture, 1_100);
+    let limit = get_sqrt_ratio_at_tick(&fixture.env, 300);
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &false, &limit, &0);
+    assert_eq!(fixture.pool.get_pool_state().current_tick, 300);
+
+    set_time(&fixture, 1_200);
+    fixture
+}
+
+#[test]
+fn position_value_pins_the_historical_price() {
+    let fixture = moved();
+    let user = &fixture.user;
+
+    // Before the move: the deposit at price 1.0 (rounded down), no fees yet
+    assert_eq!(fixture.pool.get_position_value_at(user, &-600, &600, &150), (999_999_998, 999_999_998));
+    assert_eq!(fixture.pool.get_position_value_at(user, &-600, &600, &200), (999_999_998, 999_999_998));
+
+    // After the move nothing else happened, so it is worth what it is now:
+    // the principal at tick 300 plus the fees of the swap
+    let now = fixture.pool.get_position_value_at(user, &-600, &600, &0);
+    assert_eq!(now, (496_250_257, 1_512_901_262));
+    assert_eq!(fixture.pool.get_position_value_at(user, &-600, &600, &50), now);
+    assert_eq!(fixture.pool.get_position_value_at(user, &-600, &600, &99), now);
+
+    // Observations capture the pool before a ledger's changes, so the
+    // ledger of the move itself still shows the old price
+    assert_eq!(fixture.pool.get_position_value_at(user, &-600, &600, &100), (999_999_998, 999_999_998));
+}
+
+#[test]
+fn position_value_beyond_history_fails() {
+    let fixture = moved();
+
+    assert_error(
+        fixture.pool.try_get_position_value_at(&fixture.user, &-600, &600, &1_201),
+        PoolError::ObservationTooOld,
+    );
+}
+
+// ============================================================
+// MEAN TICK
+// ============================================================
+
+#[test]
+fn mean_tick_converges_to_the_spot_tick() {
+    let fixture = Fixture::new();
+    set_time(&fixture, 1_000);
+    fixture.add(-600, 600, 1_000_000_000);
+
+    set_time(&fixture, 2_000);
+    let limit = get_sqrt_ratio_at_tick(&fixture.env, -300);
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &true, &limit, &0);
+    let spot = fixture.pool.get_pool_state().current_tick;
+    assert_eq!(spot, -300);
+
+    let mut previous = 0;
+    for step in 1..=10 {
+        set_time(&fixture, 2_000 + step * 100);
+        let mean = fixture.pool.get_mean_tick(&1_000);
+
+        // The window holds `step` tenths of the new price
+        assert_eq!(mean, (spot as i64 * step as i64).div_euclid(10) as i32);
+        assert!(mean < previous);
+        previous = mean;
+    }
+    assert_eq!(previous, spot);
+    assert_eq!(fixture.pool.get_mean_tick(&0), spot);
+}
+
+#[test]
+fn short_spike_barely_moves_the_mean() {
+    let fixture = Fixture::new();
+    set_time(&fixture, 1_000);
+    fixture.add(-600, 600, 1_000_000_000);
+
+    // Up for one second, back down the next
+    set_time(&fixture, 2_000);
+    let up = get_sqrt_ratio_at_tick(&fixture.env, 540);
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &false, &up, &0);
+    set_time(&fixture, 2_001);
+    let back = get_sqrt_ratio_at_tick(&fixture.env, 0);
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &true, &back, &0);
+
+    let settled = fixture.pool.get_pool_state().current_tick;
+
+    // 540 for one second, `settled` for 599, the rest of the window at 0
+    set_time(&fixture, 2_600);
+    let mean = fixture.pool.get_mean_tick(&1_000);
+    assert_eq!(mean, (540 + settled as i128 * 599).div_euclid(1_000) as i32);
+    assert!((mean - settled).abs() <= 1);
+}
+
+#[test]
+fn mean_tick_beyond_history_fails() {
+    let fixture = Fixture::new();
+    set_time(&fixture, 1_000);
+
+    assert_error(fixture.pool.try_get_mean_tick(&1_001), PoolError::ObservationTooOld);
+}
+
+// ============================================================
+// OBSERVE
+// ============================================================
+
+#[test]
+fn observe_interpolates_between_observations() {
+    let fixture = Fixture::new();
+    set_time(&fixture, 1_000);
+    fixture.add(-600, 600, 1_000_000_000);
+
+    // At tick -300 from t = 2000 to t = 3000
+    set_time(&fixture, 2_000);
+    let down = get_sqrt_ratio_at_tick(&fixture.env, -300);
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &true, &down, &0);
+    set_time(&fixture, 3_000);
+    let back = get_sqrt_ratio_at_tick(&fixture.env, 0);
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &false, &back, &0);
+
+    set_time(&fixture, 4_000);
+    let agos = vec![&fixture.env, 3_000, 2_000, 1_500, 1_000];
+    let cumulatives = fixture.pool.observe(&agos);
+    assert_eq!(cumulatives.len(), 4);
+
+    // Flat at tick 0, then -300 per second on both sides of the midpoint
+    assert_eq!(cumulatives.get_unchecked(1) - cumulatives.get_unchecked(0), 0);
+    assert_eq!(cumulatives.get_unchecked(2) - cumulatives.get_unchecked(1), -150_000);
+    assert_eq!(cumulatives.get_unchecked(3) - cumulatives.get_unchecked(2), -150_000);
+
+    // Extrapolated past the newest observation at the spot tick
+    let spot = fixture.pool.get_pool_state().current_tick as i64;
+    let latest = fixture.pool.observe(&vec![&fixture.env, 1_000, 0]);
+    assert_eq!(latest.get_unchecked(1) - latest.get_unchecked(0), spot * 1_000);
+}
+
+#[test]
+fn observe_beyond_history_fails() {
+    // History starts when the pool is initialized at t = 1000
+    let fixture = Fixture::uninitialized();
+    set_time(&fixture, 1_000);
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, 0);
+    fixture.pool.initialize(&fixture.admin, &fixture.token0, &fixture.token1, &30, &0, &sqrt_price, &0, &60);
+
+    set_time(&fixture, 1_500);
+    fixture.pool.observe(&vec![&fixture.env, 500]);
+    assert_error(fixture.pool.try_observe(&vec![&fixture.env, 0, 501]), PoolError::ObservationTooOld);
+    assert_error(fixture.pool.try_observe(&vec![&fixture.env, 1_501]), PoolError::ObservationTooOld);
+}
@@ -0,0 +1,124 @@
+//! Emergency pause: what is blocked and what stays open for LPs to exit.
+
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Bytes, TryFromVal};
+
+use super::{assert_error, events_named, Fixture};
+use crate::error::PoolError;
+
+/// Pool with a position over [-600, 600] that has earned fees, then paused
+fn paused<'a>() -> Fixture<'a> {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.swap_advanced(&fixture.user, &10_000_000, &0, &0, &true, &0, &0);
+    fixture.pool.set_paused(&fixture.admin, &true);
+    fixture
+}
+
+// ============================================================
+// PAUSE
+// ============================================================
+
+#[test]
+fn swaps_revert_while_paused() {
+    let fixture = paused();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+    assert!(fixture.pool.is_paused());
+
+    assert_error(fixture.pool.try_swap(user, token0, token1, &1_000_000, &0, &0, &0), PoolError::PoolPaused);
+    assert_error(fixture.pool.try_swap_advanced(user, &1_000_000, &0, &0, &false, &0, &0), PoolError::PoolPaused);
+}
+
+#[test]
+fn withdrawals_succeed_while_paused() {
+    let fixture = paused();
+    let user = &fixture.user;
+    let liquidity = fixture.pool.get_position(user, &-600, &600).liquidity;
+    let before = fixture.balance(&fixture.token0, user);
+    fixture.pool.remove_liquidity(user, &-600, &600, &liquidity, &0);
+    let removed = fixture.balance(&fixture.token0, user);
+    assert!(removed > before);
+
+    let (collected0, _) = fixture.pool.collect(user, &-600, &600, &u128::MAX, &u128::MAX, &0);
+    assert!(collected0 > 0);
+    assert_eq!(fixture.balance(&fixture.token0, user), removed + collected0 as i128);
+}
+
+#[test]
+fn unpausing_restores_swaps() {
+    let fixture = paused();
+    fixture.pool.set_paused(&fixture.admin, &false);
+    assert!(!fixture.pool.is_paused());
+
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000, &0, &0, &false, &0, &0);
+}
+
+// ============================================================
+// PAUSE MATRIX
+// ============================================================
+
+#[test]
+fn every_way_in_is_blocked_while_paused() {
+    let fixture = paused();
+    let (pool, user, token0, token1) = (&fixture.pool, &fixture.user, &fixture.token0, &fixture.token1);
+    let receiver = Address::generate(&fixture.env);
+    let no_data = Bytes::new(&fixture.env);
+    let amount = 1_000_000;
+
+    assert_error(pool.try_swap(user, token0, token1, &amount, &0, &0, &0), PoolError::PoolPaused);
+    assert_error(pool.try_swap_advanced(user, &amount, &0, &0, &true, &0, &0), PoolError::PoolPaused);
+    assert_error(
+        pool.try_add_liquidity(user, token0, token1, &amount, &amount, &0, &0, &-600, &600, &0),
+        PoolError::PoolPaused,
+    );
+    assert_error(
+        pool.try_add_liquidity_from_amounts(user, &-600, &600, &amount, &amount, &0, &0, &0),
+        PoolError::PoolPaused,
+    );
+    assert_error(
+        pool.try_add_liquidity_advanced(user, &-600, &600, &amount, &amount, &0, &0, &0),
+        PoolError::PoolPaused,
+    );
+    assert_error(pool.try_swap_and_add(user, &-600, &600, &true, &amount, &0, &0), PoolError::PoolPaused);
+    let id = pool.open_position(user, &-600, &600);
+    assert_error(pool.try_add_liquidity_by_id(user, &id, &amount, &amount, &0, &0, &0), PoolError::PoolPaused);
+    assert_error(pool.try_place_limit_order(user, &600, &true, &amount, &0), PoolError::PoolPaused);
+    assert_error(pool.try_flash(user, &receiver, &amount, &0, &no_data), PoolError::PoolPaused);
+}
+
+#[test]
+fn every_way_out_stays_open_while_paused() {
+    let fixture = Fixture::new();
+    let (pool, user) = (&fixture.pool, &fixture.user);
+    fixture.add(-600, 600, 1_000_000_000);
+    let id = pool.open_position(user, &-1_200, &1_200);
+    pool.add_liquidity_by_id(user, &id, &1_000_000_000, &1_000_000_000, &0, &0, &0);
+    pool.swap_advanced(user, &10_000_000, &0, &0, &true, &0, &0);
+    pool.set_paused(&fixture.admin, &true);
+
+    let liquidity = pool.get_position(user, &-600, &600).liquidity;
+    pool.decrease_liquidity_and_collect(user, &-600, &600, &(liquidity / 2), &0);
+    pool.remove_liquidity(user, &-600, &600, &(liquidity / 4), &0);
+    pool.collect(user, &-600, &600, &1, &1, &0);
+    pool.collect_to(user, &-600, &600, &Address::generate(&fixture.env), &u128::MAX, &u128::MAX);
+
+    let by_id = pool.get_position_by_id(&id).liquidity;
+    pool.remove_liquidity_by_id(user, &id, &by_id, &0);
+    pool.collect_by_id(user, &id, &u128::MAX, &u128::MAX, &0);
+    assert_eq!(pool.get_position_by_id(&id).liquidity, 0);
+}
+
+#[test]
+fn pausing_is_admin_only_and_emitted() {
+    let fixture = Fixture::new();
+    assert_error(fixture.pool.try_set_paused(&fixture.user, &true), PoolError::Unauthorized);
+    assert!(!fixture.pool.is_paused());
+
+    fixture.pool.set_paused(&fixture.admin, &true);
+    fixture.pool.set_paused(&fixture.admin, &false);
+    for name in ["pause", "unpause"] {
+        let emitted = events_named(&fixture.env, name);
+        assert_eq!(emitted.len(), 1);
+        assert_eq!(Address::try_from_val(&fixture.env, &emitted[0]).unwrap(), fixture.admin);
+    }
+}
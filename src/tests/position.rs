@@ -0,0 +1,238 @@
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, Env, Vec};
+
+use super::{assert_error, Fixture};
+use crate::error::PoolError;
+use crate::position::update_position;
+use crate::storage::has_position;
+use crate::types::Position;
+
+/// Largest liquidity whose fee on a full-range growth delta still fits
+const EDGE_LIQUIDITY: i128 = (u128::MAX >> 64) as i128 + 1;
+
+fn position(liquidity: i128) -> Position {
+    Position { liquidity, ..Default::default() }
+}
+
+// ============================================================
+// FEE OVERFLOW
+// ============================================================
+
+#[test]
+fn fee_at_the_edge_is_credited_exactly() {
+    let env = Env::default();
+    let mut pos = position(EDGE_LIQUIDITY);
+
+    update_position(&env, &mut pos, u128::MAX, 0, 0);
+
+    // 2^64 * (2^128 - 1) >> 64
+    assert_eq!(pos.tokens_owed_0, u128::MAX);
+    assert_eq!(pos.fee_growth_inside_last_0, u128::MAX);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn fee_past_the_edge_overflows() {
+    let env = Env::default();
+    let mut pos = position(EDGE_LIQUIDITY + 1);
+
+    update_position(&env, &mut pos, 0, u128::MAX, 0);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn owed_fees_overflow_instead_of_saturating() {
+    let env = Env::default();
+    let mut pos = position(EDGE_LIQUIDITY);
+    pos.tokens_owed_0 = 1;
+
+    update_position(&env, &mut pos, u128::MAX, 0, 0);
+}
+
+// ============================================================
+// REWARD OVERFLOW
+// ============================================================
+
+#[test]
+fn reward_at_the_edge_is_credited_exactly() {
+    let env = Env::default();
+    let mut pos = position(EDGE_LIQUIDITY);
+
+    update_position(&env, &mut pos, 0, 0, u128::MAX);
+
+    assert_eq!(pos.rewards_owed, u128::MAX);
+    assert_eq!(pos.reward_growth_inside_last, u128::MAX);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn reward_past_the_edge_overflows() {
+    let env = Env::default();
+    let mut pos = position(EDGE_LIQUIDITY + 1);
+
+    update_position(&env, &mut pos, 0, 0, u128::MAX);
+}
+
+#[test]
+#[should_panic(expected = "Error(Contract, #51)")]
+fn owed_rewards_overflow_instead_of_saturating() {
+    let env = Env::default();
+    let mut pos = position(EDGE_LIQUIDITY);
+    pos.rewards_owed = 1;
+
+    update_position(&env, &mut pos, 0, 0, u128::MAX);
+}
+
+// ============================================================
+// POSITION IDS
+// ============================================================
+
+#[test]
+fn positions_on_the_same_range_keep_separate_fee_checkpoints() {
+    let fixture = Fixture::new();
+    let (pool, user) = (&fixture.pool, &fixture.user);
+    let first = pool.open_position(user, &-600, &600);
+    let second = pool.open_position(user, &-600, &600);
+    assert_ne!(first, second);
+
+    // Only the first position is live for the token0 swap
+    pool.add_liquidity_by_id(user, &first, &1_000_000_000, &1_000_000_000, &0, &0, &0);
+    pool.swap_advanced(user, &10_000_000, &0, &0, &true, &0, &0);
+    pool.add_liquidity_by_id(user, &second, &1_000_000_000, &1_000_000_000, &0, &0, &0);
+    pool.swap_advanced(user, &10_000_000, &0, &0, &false, &0, &0);
+
+    let (first_info, second_info) = (pool.get_position_by_id(&first), pool.get_position_by_id(&second));
+    assert!(first_info.fees_owed_0 > 0 && second_info.fees_owed_0 == 0);
+    assert!(first_info.fees_owed_1 > 0 && second_info.fees_owed_1 > 0);
+
+    let collected = pool.collect_by_id(user, &first, &u128::MAX, &u128::MAX, &0);
+    assert_eq!((collected.0, collected.1), (first_info.fees_owed_0, first_info.fees_owed_1));
+    assert_eq!(pool.get_position_by_id(&second).fees_owed_1, second_info.fees_owed_1);
+
+    // Neither lives under the (owner, lower, upper) key
+    pool.remove_liquidity_by_id(user, &second, &second_info.liquidity, &0);
+    assert_eq!(pool.get_position_by_id(&second).liquidity, 0);
+    assert_eq!(pool.get_position_by_id(&first).liquidity, first_info.liquidity);
+    assert_eq!(pool.get_position(user, &-600, &600).liquidity, 0);
+}
+
+#[test]
+fn position_ids_belong_to_their_owner() {
+    let fixture = Fixture::new();
+    let id = fixture.pool.open_position(&fixture.user, &-600, &600);
+    fixture.pool.add_liquidity_by_id(&fixture.user, &id, &1_000_000, &1_000_000, &0, &0, &0);
+    let other = Address::generate(&fixture.env);
+
+    assert_error(fixture.pool.try_collect_by_id(&other, &id, &1, &1, &0), PoolError::NotPositionOwner);
+    assert_error(fixture.pool.try_remove_liquidity_by_id(&other, &id, &1, &0), PoolError::NotPositionOwner);
+    assert_error(fixture.pool.try_get_position_by_id(&99), PoolError::PositionNotFound);
+}
+
+// ============================================================
+// OWNER INDEX
+// ============================================================
+
+#[test]
+fn listing_follows_opens_and_closes() {
+    let fixture = Fixture::new();
+    let user = &fixture.user;
+    let ranges = [(-600, 600), (-1_200, 1_200), (0, 600)];
+    let liquidity = ranges.map(|(lower, upper)| fixture.add(lower, upper, 1_000_000));
+
+    fixture.pool.close_position(user, &-1_200, &1_200, &0);
+
+    let listed = fixture.pool.get_positions(user);
+    assert_eq!(listed.len(), 2);
+    for ((lower, upper, info), index) in listed.iter().zip([0, 2]) {
+        assert_eq!((lower, upper), ranges[index]);
+        assert_eq!(info.liquidity, liquidity[index]);
+    }
+    assert_eq!(fixture.pool.list_positions(user), vec![&fixture.env, (-600, 600), (0, 600)]);
+}
+
+#[test]
+fn positions_stay_listed_until_fees_are_collected() {
+    let fixture = Fixture::new();
+    let user = &fixture.user;
+    let liquidity = fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.swap(user, &fixture.token0, &fixture.token1, &10_000_000, &0, &0, &0);
+
+    // Principal gone but fees still owed
+    fixture.pool.remove_liquidity(user, &-600, &600, &liquidity, &0);
+    assert_eq!(fixture.pool.list_positions(user).len(), 1);
+
+    fixture.pool.collect(user, &-600, &600, &u128::MAX, &u128::MAX, &0);
+    assert!(fixture.pool.list_positions(user).is_empty());
+    assert!(fixture.pool.get_positions(user).is_empty());
+}
+
+#[test]
+fn pages_split_the_full_listing() {
+    let fixture = Fixture::new();
+    for upper in [120, 240, 360, 480, 600] {
+        fixture.add(-600, upper, 1_000_000);
+    }
+    let other = Address::generate(&fixture.env);
+    assert!(fixture.pool.get_positions(&other).is_empty());
+
+    let all = fixture.pool.get_positions(&fixture.user);
+    let mut paged = Vec::new(&fixture.env);
+    for (start, expected) in [(0, 2), (2, 2), (4, 1), (5, 0), (9, 0)] {
+        let page = fixture.pool.get_positions_page(&fixture.user, &start, &2);
+        assert_eq!(page.len(), expected);
+        paged.append(&page);
+    }
+    assert_eq!(paged, all);
+}
+
+#[test]
+fn ranges_are_listed_once_in_opening_order() {
+    let fixture = Fixture::new();
+    let user = &fixture.user;
+    for (lower, upper) in [(-600, 600), (-120, 120), (-600, 600), (600, 1_200)] {
+        fixture.add(lower, upper, 1_000_000);
+    }
+    assert_eq!(fixture.pool.list_positions(user), vec![&fixture.env, (-600, 600), (-120, 120), (600, 1_200)]);
+
+    // Closing everything empties the index; reopening appends again
+    for (lower, upper) in fixture.pool.list_positions(user).iter() {
+        fixture.pool.close_position(user, &lower, &upper, &0);
+    }
+    assert!(fixture.pool.list_positions(user).is_empty());
+    fixture.add(-120, 120, 1_000_000);
+    fixture.add(-600, 600, 1_000_000);
+    assert_eq!(fixture.pool.list_positions(user), vec![&fixture.env, (-120, 120), (-600, 600)]);
+}
+
+// ============================================================
+// CLOSE
+// ============================================================
+
+#[test]
+fn close_pays_everything_and_removes_the_entry() {
+    let fixture = Fixture::new();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.swap(user, token0, token1, &10_000_000, &0, &0, &0);
+    let before = (fixture.balance(token0, user), fixture.balance(token1, user));
+
+    let (principal0, principal1, fees0, fees1) = fixture.pool.close_position(user, &-600, &600, &0);
+    assert!(fees0 > 0);
+    assert_eq!(fixture.balance(token0, user) - before.0, principal0 + fees0 as i128);
+    assert_eq!(fixture.balance(token1, user) - before.1, principal1 + fees1 as i128);
+
+    let stored = fixture.env.as_contract(&fixture.pool.address, || has_position(&fixture.env, user, -600, 600));
+    assert!(!stored);
+}
+
+#[test]
+fn closing_a_missing_position_reverts() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000);
+    fixture.pool.close_position(&fixture.user, &-600, &600, &0);
+
+    // Already closed, and never opened
+    for (lower, upper) in [(-600, 600), (-1_200, 1_200)] {
+        assert_error(fixture.pool.try_close_position(&fixture.user, &lower, &upper, &0), PoolError::PositionNotFound);
+    }
+}
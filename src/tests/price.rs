@@ -0,0 +1,121 @@
+//! Human-readable prices: decimals-aware ticks and prices.
+
+use super::Fixture;
+use crate::constants::Q64;
+use crate::math::{get_sqrt_ratio_at_tick, get_tick_at_sqrt_ratio};
+
+/// Human price of one whole token0 at `tick`
+fn human_price(fixture: &Fixture, tick: i32, decimals0: u32, decimals1: u32) -> f64 {
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, tick);
+    let (num, den) = fixture.pool.price_from_sqrt(&sqrt_price, &decimals0, &decimals1);
+    num as f64 / den as f64
+}
+
+// ============================================================
+// TICK FOR PRICE
+// ============================================================
+
+#[test]
+fn tick_for_price_lands_on_the_human_price_for_6_18_decimals() {
+    let fixture = Fixture::new();
+
+    // token0 with 6 decimals worth 0.0005 of an 18-decimal token1, and the
+    // mirror pair: an 18-decimal token0 worth 2000 of a 6-decimal token1
+    for (num, den, decimals0, decimals1) in [(5u128, 10_000u128, 6u32, 18u32), (2_000, 1, 18, 6)] {
+        let target = num as f64 / den as f64;
+        let tick = fixture.pool.tick_for_price(&num, &den, &decimals0, &decimals1);
+
+        // Greatest tick at or below the price, one tick (1 bp) of precision
+        assert!(human_price(&fixture, tick, decimals0, decimals1) <= target * (1.0 + 1e-12));
+        assert!(human_price(&fixture, tick + 1, decimals0, decimals1) > target);
+    }
+
+    // The raw tick is far from the human one: 10^12 apart
+    assert!(fixture.pool.tick_for_price(&5, &10_000, &6, &18) > 200_000);
+    assert!(fixture.pool.tick_for_price(&2_000, &1, &18, &6) < -200_000);
+}
+
+#[test]
+fn pool_initialized_at_tick_for_price_quotes_the_human_price() {
+    let fixture = Fixture::uninitialized();
+    let tick = fixture.pool.tick_for_price(&5, &10_000, &6, &18);
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, tick);
+    fixture.pool.initialize(
+        &fixture.admin, &fixture.token0, &fixture.token1, &30, &0, &sqrt_price, &tick, &60,
+    );
+
+    let state = fixture.pool.get_pool_state();
+    let (num, den) = fixture.pool.price_from_sqrt(&state.sqrt_price_x64, &6, &18);
+    let price = num as f64 / den as f64;
+    assert!((price / 0.0005 - 1.0).abs() < 1e-4);
+}
+
+// ============================================================
+// PRICE FROM SQRT
+// ============================================================
+
+#[test]
+fn tick_zero_is_one_adjusted_for_decimals() {
+    let fixture = Fixture::new();
+    let one = get_sqrt_ratio_at_tick(&fixture.env, 0);
+
+    let (num, den) = fixture.pool.price_from_sqrt(&one, &7, &7);
+    assert_eq!(num, den);
+
+    // A 6-decimal token0 per raw unit is 10^12 times fewer whole tokens
+    // than an 18-decimal token1 at the same raw price, and the reverse
+    assert_eq!(human_price(&fixture, 0, 6, 18), 1e-12);
+    assert_eq!(human_price(&fixture, 0, 18, 6), 1e12);
+}
+
+#[test]
+fn known_ticks_match_powers_of_one_basis_point() {
+    let fixture = Fixture::new();
+
+    // 1.0001^tick: doubling, halving, tenfold and a hundredth
+    for (tick, expected) in [(6_932, 2.0), (-6_932, 0.5), (23_027, 10.0), (-46_054, 0.01)] {
+        let price = human_price(&fixture, tick, 7, 7);
+        assert!((price / expected - 1.0).abs() < 1e-4, "tick {tick}: {price}");
+        assert!((price / 1.0001f64.powi(tick) - 1.0).abs() < 1e-9);
+    }
+}
+
+// ============================================================
+// SQRT PRICE FROM RESERVES
+// ============================================================
+
+#[test]
+fn round_ratios_give_exact_prices_and_their_ticks() {
+    let fixture = Fixture::new();
+
+    // (amount0, amount1, exact sqrt price as a multiple or fraction of 1.0)
+    for (amount0, amount1, sqrt_num, sqrt_den) in
+        [(1, 1, 1, 1), (1, 4, 2, 1), (4, 1, 1, 2), (1, 100, 10, 1), (1_000_000, 1, 1, 1_000), (25, 9, 3, 5)]
+    {
+        let sqrt_price = fixture.pool.sqrt_price_from_reserves(&amount0, &amount1);
+        assert_eq!(sqrt_price, Q64 * sqrt_num / sqrt_den, "{amount1}/{amount0}");
+
+        // Greatest tick at or below the price: floor(log_1.0001(ratio))
+        let ratio = amount1 as f64 / amount0 as f64;
+        let tick = get_tick_at_sqrt_ratio(&fixture.env, sqrt_price);
+        assert_eq!(tick, (ratio.ln() / 1.0001f64.ln()).floor() as i32, "{amount1}/{amount0}");
+    }
+}
+
+#[test]
+fn initialize_from_reserves_prices_the_sorted_pair() {
+    // Reserves passed as (token_b, token_a): 4 of token1 per token0 either way
+    for reversed in [false, true] {
+        let fixture = Fixture::uninitialized();
+        let (token_a, token_b, amount_a, amount_b) = if reversed {
+            (&fixture.token1, &fixture.token0, 4_000_000, 1_000_000)
+        } else {
+            (&fixture.token0, &fixture.token1, 1_000_000, 4_000_000)
+        };
+        fixture.pool.initialize_from_reserves(&fixture.admin, token_a, token_b, &30, &0, &amount_a, &amount_b, &60);
+
+        let state = fixture.pool.get_pool_state();
+        assert_eq!(state.sqrt_price_x64, 2 * Q64);
+        assert_eq!(state.current_tick, get_tick_at_sqrt_ratio(&fixture.env, 2 * Q64));
+    }
+}
@@ -0,0 +1,106 @@
+//! Re-entry: a token whose `transfer` calls back into the pool mid-swap.
+
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::xdr::{ScErrorCode, ScErrorType};
+use soroban_sdk::{contract, contractimpl, symbol_short, token, Address, Env, Error};
+
+use super::assert_error;
+use crate::math::get_sqrt_ratio_at_tick;
+use crate::{BelugaSwap, BelugaSwapClient};
+
+/// Minimal token that, once armed with a pool and that pool's other token,
+/// swaps against the pool from inside every `transfer`
+#[contract]
+pub struct ReentrantToken;
+
+#[contractimpl]
+impl ReentrantToken {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage().persistent().set(&to, &(balance + amount));
+    }
+
+    pub fn arm(env: Env, pool: Address, other: Address) {
+        env.storage().instance().set(&symbol_short!("p"), &(pool, other));
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage().persistent().get(&id).unwrap_or(0)
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        if let Some((pool, other)) = env.storage().instance().get::<_, (Address, Address)>(&symbol_short!("p")) {
+            let me = env.current_contract_address();
+            BelugaSwapClient::new(&env, &pool).swap(&from, &me, &other, &10, &0, &0, &0);
+        }
+        let from_balance = Self::balance(env.clone(), from.clone());
+        env.storage().persistent().set(&from, &(from_balance - amount));
+        let to_balance = Self::balance(env.clone(), to.clone());
+        env.storage().persistent().set(&to, &(to_balance + amount));
+    }
+}
+
+/// What the host raises when a contract already on the call stack is
+/// invoked again
+fn reentry_error() -> Error {
+    Error::from_type_and_code(ScErrorType::Context, ScErrorCode::InvalidAction)
+}
+
+/// Pool pairing a reentrant token with a SAC, with liquidity around 1.0
+///
+/// # Returns
+/// (env, pool, user, reentrant token, SAC)
+fn pool<'a>() -> (Env, BelugaSwapClient<'a>, Address, ReentrantTokenClient<'a>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.budget().reset_unlimited();
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let reentrant = ReentrantTokenClient::new(&env, &env.register_contract(None, ReentrantToken));
+    let sac = env.register_stellar_asset_contract_v2(admin.clone()).address();
+    reentrant.mint(&user, &super::USER_BALANCE);
+    token::StellarAssetClient::new(&env, &sac).mint(&user, &super::USER_BALANCE);
+
+    let (token0, token1) = if reentrant.address < sac {
+        (reentrant.address.clone(), sac.clone())
+    } else {
+        (sac.clone(), reentrant.address.clone())
+    };
+    let pool = BelugaSwapClient::new(&env, &env.register_contract(None, BelugaSwap));
+    let sqrt_price = get_sqrt_ratio_at_tick(&env, 0);
+    pool.initialize(&admin, &token0, &token1, &30, &0, &sqrt_price, &0, &60);
+    pool.add_liquidity(&user, &token0, &token1, &1_000_000_000, &1_000_000_000, &0, &0, &-600, &600, &0);
+
+    (env, pool, user, reentrant, sac)
+}
+
+// ============================================================
+// RE-ENTRY
+// ============================================================
+
+#[test]
+fn token_calling_back_into_swap_reverts_the_swap() {
+    let (_env, pool, user, reentrant, sac) = pool();
+    reentrant.arm(&pool.address, &sac);
+    let before = pool.get_pool_state();
+
+    assert_error(pool.try_swap(&user, &sac, &reentrant.address, &1_000, &0, &0, &0), reentry_error());
+    let after = pool.get_pool_state();
+    assert_eq!((after.sqrt_price_x64, after.liquidity), (before.sqrt_price_x64, before.liquidity));
+}
+
+#[test]
+fn token_calling_back_into_swap_reverts_liquidity_changes() {
+    let (_env, pool, user, reentrant, sac) = pool();
+    reentrant.arm(&pool.address, &sac);
+    let (token0, token1) = (pool.get_pool_state().token0, pool.get_pool_state().token1);
+    let liquidity = pool.get_position(&user, &-600, &600).liquidity;
+
+    assert_error(
+        pool.try_add_liquidity(&user, &token0, &token1, &1_000_000, &1_000_000, &0, &0, &-600, &600, &0),
+        reentry_error(),
+    );
+    assert_error(pool.try_remove_liquidity(&user, &-600, &600, &(liquidity / 2), &0), reentry_error());
+    assert_eq!(pool.get_position(&user, &-600, &600).liquidity, liquidity);
+}
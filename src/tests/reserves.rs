@@ -0,0 +1,87 @@
+//! Reserve tracking for rebasing tokens.
+
+use soroban_sdk::{token, Address};
+
+use super::{assert_error, Fixture};
+use crate::error::PoolError;
+
+/// Tracked pool with liquidity, a 10% protocol cut and swaps both ways
+fn pool<'a>() -> Fixture<'a> {
+    let fixture = Fixture::new();
+    fixture.pool.set_protocol_fee(&fixture.admin, &1_000);
+    fixture.pool.set_reserve_tracking(&fixture.admin, &true);
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.swap_advanced(&fixture.user, &10_000_000, &0, &0, &true, &0, &0);
+    fixture.pool.swap_advanced(&fixture.user, &10_000_000, &0, &0, &false, &0, &0);
+    fixture
+}
+
+/// Change the pool's token balance without going through the pool
+fn rebase(fixture: &Fixture, token: &Address, delta: i128) {
+    let pool = &fixture.pool.address;
+    if delta > 0 {
+        token::StellarAssetClient::new(&fixture.env, token).mint(pool, &delta);
+    } else {
+        fixture.env.as_contract(pool, || {
+            token::Client::new(&fixture.env, token).transfer(pool, &fixture.admin, &-delta);
+        });
+    }
+}
+
+// ============================================================
+// SYNC
+// ============================================================
+
+#[test]
+fn tracked_reserves_follow_every_pool_flow() {
+    let fixture = pool();
+
+    let reserves = fixture.pool.get_tracked_reserves().unwrap();
+    assert_eq!(reserves.reserve0, fixture.balance(&fixture.token0, &fixture.pool.address));
+    assert_eq!(reserves.reserve1, fixture.balance(&fixture.token1, &fixture.pool.address));
+    assert_eq!(fixture.pool.sync_reserves(), (0, 0));
+}
+
+#[test]
+fn positive_rebase_is_credited_to_protocol_fees() {
+    let fixture = pool();
+    let fees_before = fixture.pool.get_pool_state().protocol_fees_0;
+
+    rebase(&fixture, &fixture.token0, 5_000);
+
+    assert_eq!(fixture.pool.sync_reserves(), (5_000, 0));
+    assert_eq!(fixture.pool.get_pool_state().protocol_fees_0, fees_before + 5_000);
+    assert_eq!(fixture.pool.sync_reserves(), (0, 0));
+
+    let (collected0, _) = fixture.pool.collect_protocol_fees(&fixture.admin);
+    assert_eq!(collected0, fees_before + 5_000);
+    assert_eq!(fixture.pool.sync_reserves(), (0, 0));
+}
+
+#[test]
+fn negative_rebase_is_absorbed_by_protocol_fees_first() {
+    let fixture = pool();
+    let fees = fixture.pool.get_pool_state().protocol_fees_1;
+    assert!(fees > 0);
+
+    rebase(&fixture, &fixture.token1, -(fees as i128) + 1);
+    assert_eq!(fixture.pool.sync_reserves(), (0, -(fees as i128) + 1));
+    assert_eq!(fixture.pool.get_pool_state().protocol_fees_1, 1);
+    assert_eq!(fixture.pool.sync_reserves(), (0, 0));
+
+    // Beyond the fees the shortfall stays flagged on every sync
+    rebase(&fixture, &fixture.token1, -1_000);
+    assert_eq!(fixture.pool.sync_reserves(), (0, -1_000));
+    assert_eq!(fixture.pool.get_pool_state().protocol_fees_1, 0);
+    assert_eq!(fixture.pool.sync_reserves(), (0, -999));
+}
+
+#[test]
+fn sync_requires_tracking() {
+    let fixture = pool();
+
+    fixture.pool.set_reserve_tracking(&fixture.admin, &false);
+
+    assert!(fixture.pool.get_tracked_reserves().is_none());
+    assert_error(fixture.pool.try_sync_reserves(), PoolError::ReserveTrackingDisabled);
+}
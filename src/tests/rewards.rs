@@ -0,0 +1,189 @@
+//! Liquidity mining: streaming funded rewards to in-range liquidity.
+
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{token, Address};
+
+use super::{assert_error, Fixture};
+use crate::constants::Q64;
+use crate::error::PoolError;
+use crate::math::get_sqrt_ratio_at_tick;
+
+/// Register a reward token and mint `amount` of it to the admin
+fn reward_token(fixture: &Fixture, amount: i128) -> Address {
+    let token = fixture.env.register_stellar_asset_contract_v2(fixture.admin.clone()).address();
+    token::StellarAssetClient::new(&fixture.env, &token).mint(&fixture.admin, &amount);
+    token
+}
+
+fn advance(fixture: &Fixture, secs: u64) {
+    fixture.env.ledger().with_mut(|l| l.timestamp += secs);
+}
+
+/// Stream 1_000_000 of a fresh reward token over 1_000 seconds
+fn fund(fixture: &Fixture) -> Address {
+    let token = reward_token(fixture, 1_000_000);
+    fixture.pool.fund_rewards(&fixture.admin, &token, &1_000_000, &1_000);
+    token
+}
+
+/// A second funded LP with `amount` of both tokens over `[lower, upper]`
+fn other_lp(fixture: &Fixture, lower: i32, upper: i32, amount: i128) -> Address {
+    let lp = Address::generate(&fixture.env);
+    fixture.fund(&lp, amount);
+    fixture.pool.add_liquidity(
+        &lp, &fixture.token0, &fixture.token1, &amount, &amount, &0, &0, &lower, &upper, &0,
+    );
+    lp
+}
+
+/// `paid` is `expected` less at most a few units of growth rounding
+fn assert_about(paid: u128, expected: u128) {
+    assert!(paid <= expected && expected - paid <= 10, "paid {paid}, expected about {expected}");
+}
+
+// ============================================================
+// ACCRUAL
+// ============================================================
+
+#[test]
+fn growth_too_large_for_tiny_liquidity_is_not_wrapped() {
+    let fixture = Fixture::new();
+    let tiny = fixture.add(-600, 600, 100);
+    assert!(tiny < 10_000);
+
+    // Rate of 2^124 per second: a single second over the tiny position
+    // already overflows the growth once scaled up by the elapsed time
+    let amount: i128 = 1 << 80;
+    let duration: u64 = 1 << 20;
+    let token = reward_token(&fixture, amount);
+    fixture.pool.fund_rewards(&fixture.admin, &token, &amount, &duration);
+    advance(&fixture, 1_000_000);
+
+    // Still modifiable, and nothing was credited for the stretch
+    fixture.pool.remove_liquidity(&fixture.user, &-600, &600, &tiny, &0);
+    assert_eq!(fixture.pool.get_pool_state().reward_growth_global, 0);
+
+    // The stretch was pushed back rather than lost, so a real position
+    // joining afterwards still earns from the schedule
+    let end = fixture.pool.get_reward_schedule().unwrap().end;
+    let liquidity = fixture.add(-600, 600, 1_000_000_000_000);
+    advance(&fixture, duration);
+    assert!(fixture.pool.get_reward_schedule().unwrap().end >= end);
+
+    let paid = fixture.pool.collect_rewards(&fixture.user, &-600, &600);
+    assert!(paid > 0 && paid <= amount as u128);
+    fixture.pool.remove_liquidity(&fixture.user, &-600, &600, &liquidity, &0);
+    assert_eq!(token::Client::new(&fixture.env, &token).balance(&fixture.user), paid as i128);
+}
+
+#[test]
+fn out_of_range_positions_earn_nothing() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let lp = other_lp(&fixture, 600, 1_200, 1_000_000_000);
+    fund(&fixture);
+
+    // 1_000 per second for 500 seconds, all of it to the in-range position
+    advance(&fixture, 500);
+    assert_about(fixture.pool.collect_rewards(&fixture.user, &-600, &600), 500_000);
+    assert_eq!(fixture.pool.collect_rewards(&lp, &600, &1_200), 0);
+}
+
+#[test]
+fn accrual_stops_when_a_swap_moves_the_price_out_of_range() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let lp = other_lp(&fixture, -1_200, 1_200, 1_000_000_000);
+    fund(&fixture);
+    assert_eq!(fixture.pool.get_tick_info(&-600).reward_growth_outside, 0);
+
+    advance(&fixture, 300);
+    let limit = get_sqrt_ratio_at_tick(&fixture.env, -900);
+    let to_limit = fixture.pool.quote_swap(&1_000_000_000_000, &true, &limit).amount_in;
+    fixture.pool.swap_advanced(&fixture.user, &to_limit, &0, &0, &true, &0, &0);
+    assert!(fixture.pool.get_pool_state().current_tick < -600);
+
+    // Crossing -600 flipped its outside growth to everything so far
+    let global = fixture.pool.get_pool_state().reward_growth_global;
+    assert!(global > 0);
+    assert_eq!(fixture.pool.get_tick_info(&-600).reward_growth_outside, global);
+
+    // The narrow position's share froze at the crossing; the wide one now
+    // takes the whole stream
+    let narrow = fixture.pool.get_pending_rewards(&fixture.user, &-600, &600);
+    let wide = fixture.pool.get_pending_rewards(&lp, &-1_200, &1_200);
+    assert_about(narrow + wide, 300_000);
+
+    advance(&fixture, 500);
+    assert_eq!(fixture.pool.get_pending_rewards(&fixture.user, &-600, &600), narrow);
+    assert_about(fixture.pool.get_pending_rewards(&lp, &-1_200, &1_200) - wide, 500_000);
+    assert_eq!(fixture.pool.collect_rewards(&fixture.user, &-600, &600), narrow);
+}
+
+#[test]
+fn pending_rewards_match_what_collect_pays() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let lp = other_lp(&fixture, -1_200, 600, 3_000_000_000);
+    let token = fund(&fixture);
+    advance(&fixture, 250);
+
+    for (owner, lower) in [(&fixture.user, -600), (&lp, -1_200)] {
+        let pending = fixture.pool.get_pending_rewards(owner, &lower, &600);
+        assert!(pending > 0);
+        assert_eq!(fixture.pool.collect_rewards(owner, &lower, &600), pending);
+        assert_eq!(token::Client::new(&fixture.env, &token).balance(owner), pending as i128);
+        assert_eq!(fixture.pool.get_pending_rewards(owner, &lower, &600), 0);
+    }
+}
+
+#[test]
+fn collect_rewards_by_id_pays_the_position_owner() {
+    let fixture = Fixture::new();
+    let id = fixture.pool.open_position(&fixture.user, &-600, &600);
+    fixture.pool.add_liquidity_by_id(&fixture.user, &id, &1_000_000_000, &1_000_000_000, &0, &0, &0);
+    let token = fund(&fixture);
+    advance(&fixture, 400);
+
+    let stranger = Address::generate(&fixture.env);
+    assert!(fixture.pool.try_collect_rewards_by_id(&stranger, &id).is_err());
+
+    let paid = fixture.pool.collect_rewards_by_id(&fixture.user, &id);
+    assert_about(paid, 400_000);
+    assert_eq!(token::Client::new(&fixture.env, &token).balance(&fixture.user), paid as i128);
+    assert_eq!(fixture.pool.collect_rewards_by_id(&fixture.user, &id), 0);
+}
+
+// ============================================================
+// FUNDING
+// ============================================================
+
+#[test]
+fn funding_again_rolls_the_unstreamed_rewards_in() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let token = fund(&fixture);
+    advance(&fixture, 400);
+
+    // 600_000 left over plus 600_000 new, over the next 1_000 seconds
+    token::StellarAssetClient::new(&fixture.env, &token).mint(&fixture.admin, &600_000);
+    fixture.pool.fund_rewards(&fixture.admin, &token, &600_000, &1_000);
+    let schedule = fixture.pool.get_reward_schedule().unwrap();
+    assert_eq!(schedule.rate_x64, 1_200 * Q64);
+    assert_eq!(schedule.end, schedule.last_update + 1_000);
+
+    advance(&fixture, 2_000);
+    assert_about(fixture.pool.collect_rewards(&fixture.user, &-600, &600), 1_600_000);
+}
+
+#[test]
+fn reward_token_is_fixed_and_never_a_pool_token() {
+    let fixture = Fixture::new();
+    fund(&fixture);
+
+    let other = reward_token(&fixture, 1_000);
+    assert_error(fixture.pool.try_fund_rewards(&fixture.admin, &other, &1_000, &100), PoolError::InvalidToken);
+    for pool_token in [&fixture.token0, &fixture.token1] {
+        assert_error(fixture.pool.try_fund_rewards(&fixture.admin, pool_token, &1_000, &100), PoolError::SameToken);
+    }
+}
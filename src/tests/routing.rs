@@ -0,0 +1,137 @@
+//! Multi-hop routing through a second pool registered in the same env.
+
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction};
+use soroban_sdk::{token, vec, Address, Symbol, Vec};
+
+use super::{assert_error, Fixture};
+use crate::error::PoolError;
+use crate::math::get_sqrt_ratio_at_tick;
+use crate::{BelugaSwap, BelugaSwapClient};
+
+/// Second pool pairing the fixture's token1 (B) with a new token C at
+/// price 1.0, with liquidity from the fixture user
+struct Hop<'a> {
+    pool: BelugaSwapClient<'a>,
+    token_c: Address,
+}
+
+fn hop<'a>(fixture: &Fixture<'a>) -> Hop<'a> {
+    let env = &fixture.env;
+    let token_c = env.register_stellar_asset_contract_v2(fixture.admin.clone()).address();
+    token::StellarAssetClient::new(env, &token_c).mint(&fixture.user, &super::USER_BALANCE);
+
+    let pool = BelugaSwapClient::new(env, &env.register_contract(None, BelugaSwap));
+    let sqrt_price = get_sqrt_ratio_at_tick(env, 0);
+    pool.initialize(&fixture.admin, &fixture.token1, &token_c, &30, &0, &sqrt_price, &0, &60);
+    pool.add_liquidity(
+        &fixture.user, &fixture.token1, &token_c, &1_000_000_000, &1_000_000_000, &0, &0, &-600, &600, &0,
+    );
+    Hop { pool, token_c }
+}
+
+/// Fixture pool (A/B) and hop pool (B/C), both with liquidity around 1.0
+fn two_pools<'a>() -> (Fixture<'a>, Hop<'a>) {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    let hop = hop(&fixture);
+    (fixture, hop)
+}
+
+fn route(fixture: &Fixture, hop: &Hop) -> Vec<Address> {
+    vec![&fixture.env, fixture.pool.address.clone(), hop.pool.address.clone()]
+}
+
+// ============================================================
+// MULTI-HOP
+// ============================================================
+
+#[test]
+fn route_chains_each_output_into_the_next_leg() {
+    let (fixture, hop) = two_pools();
+    let user = &fixture.user;
+    let (token_a, token_b, token_c) = (&fixture.token0, &fixture.token1, &hop.token_c);
+    let before = [token_a, token_b, token_c].map(|t| fixture.balance(t, user));
+
+    let legs = fixture.pool.swap_exact_in_multi(user, &route(&fixture, &hop), token_a, &1_000_000, &0, &0);
+    assert_eq!(legs.len(), 2);
+    let (first, second) = (legs.get_unchecked(0), legs.get_unchecked(1));
+    assert_eq!(first.amount_in, 1_000_000);
+    assert_eq!(second.amount_in, first.amount_out);
+
+    // A in, C out, and the intermediate B nets out
+    let after = [token_a, token_b, token_c].map(|t| fixture.balance(t, user));
+    assert_eq!(before[0] - after[0], 1_000_000);
+    assert_eq!(after[1], before[1]);
+    assert_eq!(after[2] - before[2], second.amount_out);
+
+    // Two fees at price 1.0: a little under 99.4% arrives
+    assert!(second.amount_out < 1_000_000 * 9_940 / 10_000);
+    assert!(second.amount_out > 1_000_000 * 9_930 / 10_000);
+}
+
+#[test]
+fn route_runs_in_reverse_from_the_other_pool() {
+    let (fixture, hop) = two_pools();
+    let user = &fixture.user;
+    let reversed = vec![&fixture.env, hop.pool.address.clone(), fixture.pool.address.clone()];
+    let before = fixture.balance(&fixture.token0, user);
+
+    // Called on the hop pool, which routes back into the fixture pool
+    let legs = hop.pool.swap_exact_in_multi(user, &reversed, &hop.token_c, &1_000_000, &0, &0);
+    assert_eq!(fixture.balance(&fixture.token0, user) - before, legs.get_unchecked(1).amount_out);
+}
+
+#[test]
+fn final_minimum_and_route_shape_are_enforced() {
+    let (fixture, hop) = two_pools();
+    let (pool, user, token_a) = (&fixture.pool, &fixture.user, &fixture.token0);
+    let quoted = pool.swap_exact_in_multi(user, &route(&fixture, &hop), token_a, &1_000_000, &0, &0);
+    let out = quoted.get_unchecked(1).amount_out;
+
+    assert_error(
+        pool.try_swap_exact_in_multi(user, &route(&fixture, &hop), token_a, &1_000_000, &(out * 2), &0),
+        PoolError::SlippageExceeded,
+    );
+    assert_error(
+        pool.try_swap_exact_in_multi(user, &Vec::new(&fixture.env), token_a, &1_000_000, &0, &0),
+        PoolError::InvalidRoute,
+    );
+    let too_long = Vec::from_array(&fixture.env, [(); 5].map(|_| pool.address.clone()));
+    assert_error(pool.try_swap_exact_in_multi(user, &too_long, token_a, &1_000_000, &0, &0), PoolError::InvalidRoute);
+
+    // Token C is not in the first pool
+    assert_error(
+        pool.try_swap_exact_in_multi(user, &route(&fixture, &hop), &hop.token_c, &1_000_000, &0, &0),
+        PoolError::InvalidToken,
+    );
+    let stranger = Address::generate(&fixture.env);
+    assert_error(
+        pool.try_swap_exact_in_multi(user, &route(&fixture, &hop), &stranger, &1_000_000, &0, &0),
+        PoolError::InvalidToken,
+    );
+}
+
+#[test]
+fn caller_authorizes_the_route_once() {
+    let (fixture, hop) = two_pools();
+    let env = &fixture.env;
+    fixture.pool.swap_exact_in_multi(&fixture.user, &route(&fixture, &hop), &fixture.token0, &1_000_000, &0, &0);
+
+    // One auth tree, rooted at the route call, with the second pool's swap
+    // nested inside it rather than signed separately
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    let (signer, root) = &auths[0];
+    assert_eq!(signer, &fixture.user);
+    assert_eq!(called(&root.function), Some((fixture.pool.address.clone(), Symbol::new(env, "swap_exact_in_multi"))));
+    let hop_swap = Some((hop.pool.address.clone(), Symbol::new(env, "swap")));
+    assert!(root.sub_invocations.iter().any(|sub| called(&sub.function) == hop_swap));
+}
+
+/// Contract and function name of an authorized contract call
+fn called(function: &AuthorizedFunction) -> Option<(Address, Symbol)> {
+    match function {
+        AuthorizedFunction::Contract((contract, name, _)) => Some((contract.clone(), name.clone())),
+        _ => None,
+    }
+}
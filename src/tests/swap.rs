@@ -0,0 +1,677 @@
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{Address, Env};
+
+use super::{assert_error, Fixture};
+use crate::bps::{deduct_bps, gross_up};
+use crate::constants::{MAX_AMOUNT, MAX_TICK, MIN_OUTPUT_AMOUNT, MIN_TICK};
+use crate::error::{PoolError, PoolErrorExt};
+use crate::types::{SwapContext, SwapParams};
+use crate::math::{
+    compute_swap_step_with_target, get_amount_1_delta, get_sqrt_ratio_at_tick, get_tick_at_sqrt_ratio, MAX_SQRT_RATIO,
+    MIN_SQRT_RATIO,
+};
+
+/// Two overlapping ranges, so larger swaps cross initialized ticks
+fn pool<'a>() -> Fixture<'a> {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture.add(-1_800, 1_800, 1_000_000_000);
+    fixture
+}
+
+// ============================================================
+// EXACT OUTPUT
+// ============================================================
+
+#[test]
+fn exact_output_delivers_exactly_the_amount_requested() {
+    for (amount_out, zero_for_one) in [(1_000, true), (250_000_000, true), (1_000, false), (250_000_000, false)] {
+        let fixture = pool();
+        let (token_in, token_out) = if zero_for_one {
+            (&fixture.token0, &fixture.token1)
+        } else {
+            (&fixture.token1, &fixture.token0)
+        };
+        let in_before = fixture.balance(token_in, &fixture.user);
+        let out_before = fixture.balance(token_out, &fixture.user);
+
+        let result = fixture.pool.swap_advanced(&fixture.user, &-amount_out, &0, &0, &zero_for_one, &0, &0);
+
+        assert_eq!(result.amount_out, amount_out);
+        assert!(result.fully_filled);
+        assert_eq!(result.amount_remaining, 0);
+        assert_eq!(fixture.balance(token_out, &fixture.user) - out_before, amount_out);
+        assert_eq!(in_before - fixture.balance(token_in, &fixture.user), result.amount_in);
+    }
+}
+
+#[test]
+fn exact_output_crossing_ticks_does_not_overshoot() {
+    let fixture = pool();
+
+    // Past the inner range's upper tick
+    let result = fixture.pool.swap_advanced(&fixture.user, &-1_500_000_000, &0, &0, &false, &0, &0);
+
+    assert!(result.ticks_crossed >= 1);
+    assert_eq!(result.amount_out, 1_500_000_000);
+}
+
+#[test]
+fn exact_output_step_never_exceeds_the_output_owed() {
+    let env = Env::default();
+    let current = get_sqrt_ratio_at_tick(&env, 0);
+
+    for tick in [1, 7, 60, 600] {
+        let target = get_sqrt_ratio_at_tick(&env, tick);
+        let step = |owed: i128| compute_swap_step_with_target(&env, current, 1 << 100, -owed, false, target);
+
+        // Just short of the whole range's output, the rounded-up price
+        // still lands on the target; at this liquidity one unit of price
+        // is worth many tokens
+        let (_, _, full) = step(i128::MAX);
+        for owed in [1, 1_000, full - 1_000_000, full - 1_000, full - 1, full] {
+            let (_, _, amount_out) = step(owed);
+            assert!(amount_out <= owed, "tick {tick}, owed {owed}: delivered {amount_out}");
+        }
+    }
+}
+
+#[test]
+fn exact_input_and_output_round_trip() {
+    for zero_for_one in [true, false] {
+        for amount_in in [10_000i128, 5_000_000, 400_000_000] {
+            let exact_in = pool().pool.swap_advanced(&pool().user, &amount_in, &0, &0, &zero_for_one, &0, &0);
+
+            // Buying back the same output costs no more than was paid, and
+            // only rounding less
+            let fixture = pool();
+            let exact_out = fixture.pool.swap_advanced(
+                &fixture.user, &-exact_in.amount_out, &0, &0, &zero_for_one, &0, &0,
+            );
+            assert_eq!(exact_out.amount_out, exact_in.amount_out);
+            assert!(exact_out.amount_in <= amount_in, "{amount_in}: {}", exact_out.amount_in);
+            assert!(amount_in - exact_out.amount_in <= 2 + amount_in / 1_000_000);
+            assert_eq!(exact_out.sqrt_price_x64 <= exact_in.sqrt_price_x64, !zero_for_one);
+        }
+    }
+}
+
+#[test]
+fn exact_output_quote_matches_swap() {
+    let fixture = pool();
+
+    let quote = fixture.pool.quote_swap(&-300_000_000, &true, &0);
+    let result = fixture.pool.swap_advanced(&fixture.user, &-300_000_000, &0, &0, &true, &0, &0);
+
+    assert_eq!(quote.amount_in, result.amount_in);
+    assert_eq!(quote.amount_out, result.amount_out);
+    assert_eq!(quote.sqrt_price_x64, result.sqrt_price_x64);
+}
+
+// ============================================================
+// QUOTES
+// ============================================================
+
+#[test]
+fn quote_matches_executed_swap_exactly() {
+    // Within one range, across a tick, up to a price limit, both directions
+    let cases = [(1_000_000, true, 0), (1_500_000_000, true, 0), (1_500_000_000, false, 0), (800_000_000, false, 300)];
+    for (amount, zero_for_one, limit_tick) in cases {
+        let fixture = pool();
+        fixture.pool.set_protocol_fee(&fixture.admin, &1_000);
+        let limit = if limit_tick == 0 { 0 } else { get_sqrt_ratio_at_tick(&fixture.env, limit_tick) };
+        let before = fixture.pool.get_pool_state();
+
+        let quote = fixture.pool.quote_swap(&amount, &zero_for_one, &limit);
+        assert_eq!(fixture.pool.get_pool_state().sqrt_price_x64, before.sqrt_price_x64);
+        let result = fixture.pool.swap_advanced(&fixture.user, &amount, &0, &0, &zero_for_one, &limit, &0);
+
+        assert_eq!(quote.amount_in, result.amount_in);
+        assert_eq!(quote.amount_out, result.amount_out);
+        assert_eq!(quote.fee_amount, result.fee_amount);
+        assert_eq!(quote.ticks_crossed, result.ticks_crossed);
+        assert_eq!(quote.amount_remaining, result.amount_remaining);
+        assert_eq!(quote.current_tick, result.current_tick);
+        assert_eq!(quote.sqrt_price_x64, result.sqrt_price_x64);
+        assert_eq!(quote.fully_filled, result.fully_filled);
+    }
+}
+
+// ============================================================
+// SWAP PARAMS
+// ============================================================
+
+#[test]
+fn default_params_match_swap_advanced() {
+    for (amount, zero_for_one) in [(50_000_000, true), (-50_000_000, false)] {
+        let (legacy, params) = (pool(), pool());
+
+        let expected = legacy.pool.swap_advanced(&legacy.user, &amount, &0, &0, &zero_for_one, &0, &0);
+        let result = params.pool.swap_with_params(&params.user, &SwapParams::new(amount, zero_for_one));
+
+        assert_eq!((result.amount_in, result.amount_out), (expected.amount_in, expected.amount_out));
+        assert_eq!(result.fee_amount, expected.fee_amount);
+        assert_eq!(result.sqrt_price_x64, expected.sqrt_price_x64);
+        assert_eq!(params.balance(&params.token0, &params.user), legacy.balance(&legacy.token0, &legacy.user));
+        assert_eq!(params.balance(&params.token1, &params.user), legacy.balance(&legacy.token1, &legacy.user));
+    }
+}
+
+#[test]
+fn fully_set_params_match_the_legacy_entrypoints() {
+    let (legacy, advanced, params) = (pool(), pool(), pool());
+    let limit = get_sqrt_ratio_at_tick(&legacy.env, -300);
+    let deadline = 1_000;
+
+    let expected = legacy.pool.swap(&legacy.user, &legacy.token0, &legacy.token1, &50_000_000, &1, &limit, &deadline);
+    let via_advanced = advanced.pool.swap_advanced(&advanced.user, &50_000_000, &1, &60_000_000, &true, &limit, &deadline);
+    let result = params.pool.swap_with_params(
+        &params.user,
+        &SwapParams::new(50_000_000, true)
+            .with_min_amount_out(1)
+            .with_max_amount_in(60_000_000)
+            .with_sqrt_price_limit(limit)
+            .with_deadline(deadline)
+            .with_recipient(params.user.clone())
+            .with_allow_partial(false),
+    );
+
+    for other in [&expected, &via_advanced] {
+        assert_eq!((result.amount_in, result.amount_out), (other.amount_in, other.amount_out));
+        assert_eq!(result.sqrt_price_x64, other.sqrt_price_x64);
+        assert_eq!(result.current_tick, other.current_tick);
+    }
+}
+
+#[test]
+fn output_lands_with_the_recipient_and_input_comes_from_the_caller() {
+    let fixture = pool();
+    let (user, token0, token1) = (&fixture.user, &fixture.token0, &fixture.token1);
+    let recipient = Address::generate(&fixture.env);
+    let before = (fixture.balance(token0, user), fixture.balance(token1, user));
+
+    // Exact input, then exact output
+    let (mut paid, mut received) = (0, 0);
+    for amount in [50_000_000, -20_000_000] {
+        let params = SwapParams::new(amount, true).with_recipient(recipient.clone());
+        let result = fixture.pool.swap_with_params(user, &params);
+        paid += result.amount_in;
+        received += result.amount_out;
+    }
+    assert_eq!(before.0 - fixture.balance(token0, user), paid);
+    assert_eq!(fixture.balance(token1, &recipient), received);
+    assert_eq!(fixture.balance(token1, user), before.1);
+    assert_eq!(fixture.balance(token0, &recipient), 0);
+
+    let to_pool = SwapParams::new(1_000_000, true).with_recipient(fixture.pool.address.clone());
+    assert_error(fixture.pool.try_swap_with_params(user, &to_pool), PoolError::SelfAddress);
+}
+
+// ============================================================
+// MAX AMOUNT IN
+// ============================================================
+
+#[test]
+fn max_amount_in_bounds_exact_output_cost() {
+    let fixture = pool();
+    let cost = fixture.pool.quote_swap(&-1_000_000, &true, &0).amount_in;
+
+    assert_error(
+        fixture.pool.try_swap_advanced(&fixture.user, &-1_000_000, &0, &(cost - 1), &true, &0, &0),
+        PoolError::SlippageExceeded,
+    );
+
+    let result = fixture.pool.swap_advanced(&fixture.user, &-1_000_000, &0, &cost, &true, &0, &0);
+    assert_eq!(result.amount_in, cost);
+}
+
+#[test]
+fn max_amount_in_must_not_be_negative() {
+    let fixture = pool();
+
+    assert_error(
+        fixture.pool.try_swap_advanced(&fixture.user, &-1_000_000, &0, &-1, &true, &0, &0),
+        PoolError::AmountNegative,
+    );
+}
+
+// ============================================================
+// PARTIAL FILLS
+// ============================================================
+
+#[test]
+fn running_out_of_liquidity_is_an_incomplete_swap() {
+    let fixture = pool();
+
+    // Both ranges together hold under 2e9 of token1
+    for amount in [2_500_000_000, -2_500_000_000] {
+        assert_error(
+            fixture.pool.try_swap_advanced(&fixture.user, &amount, &0, &0, &true, &0, &0),
+            PoolErrorExt::IncompleteSwap,
+        );
+    }
+}
+
+#[test]
+fn allow_partial_fills_what_liquidity_there_is() {
+    let fixture = pool();
+
+    let params = SwapParams::new(-2_500_000_000, true).with_allow_partial(true);
+    let result = fixture.pool.swap_with_params(&fixture.user, &params);
+
+    assert!(!result.fully_filled);
+    assert!(result.amount_out > 0);
+    assert_eq!(result.amount_out + result.amount_remaining, 2_500_000_000);
+}
+
+#[test]
+fn stopping_at_the_limit_is_not_an_incomplete_swap() {
+    let fixture = pool();
+    let limit = get_sqrt_ratio_at_tick(&fixture.env, -300);
+
+    // The caller's own limit: a partial fill, allow_partial or not
+    let at_limit = fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &true, &limit, &0);
+    assert!(!at_limit.fully_filled);
+    assert_eq!(at_limit.sqrt_price_x64, limit);
+
+    // Liquidity running out before the limit is reached: reverts unless
+    // partial fills are allowed, and then stops at the last range edge
+    let beyond = get_sqrt_ratio_at_tick(&fixture.env, -3_000);
+    let params = SwapParams::new(-2_500_000_000, true).with_sqrt_price_limit(beyond);
+    assert_error(fixture.pool.try_swap_with_params(&fixture.user, &params), PoolErrorExt::IncompleteSwap);
+    let drained = fixture.pool.swap_with_params(&fixture.user, &params.with_allow_partial(true));
+    assert!(!drained.fully_filled);
+    assert_eq!(drained.sqrt_price_x64, get_sqrt_ratio_at_tick(&fixture.env, -1_800));
+}
+
+// ============================================================
+// TICK TRACKING
+// ============================================================
+
+#[test]
+fn tick_matches_price_after_every_swap() {
+    let fixture = pool();
+
+    // Within a range, across one tick, up to a limit, and back
+    for (amount, zero_for_one) in [(1_000_000, true), (700_000_000, true), (-300_000_000, false), (5_000, false)] {
+        fixture.pool.swap_advanced(&fixture.user, &amount, &0, &0, &zero_for_one, &0, &0);
+        let state = fixture.pool.get_pool_state();
+        assert_eq!(state.current_tick, get_tick_at_sqrt_ratio(&fixture.env, state.sqrt_price_x64));
+    }
+
+    let limit = get_sqrt_ratio_at_tick(&fixture.env, 901);
+    fixture.pool.swap_advanced(&fixture.user, &2_000_000_000, &0, &0, &false, &limit, &0);
+    assert_eq!(fixture.pool.get_pool_state().current_tick, 901);
+}
+
+// ============================================================
+// RANGE EDGES
+// ============================================================
+
+#[test]
+fn swaps_through_a_full_range_position_stop_inside_the_tick_range() {
+    let fixture = Fixture::new();
+    fixture.pool.add_liquidity_advanced(
+        &fixture.user, &-689_340, &887_220, &1_000_000_000, &1_000_000_000, &0, &0, &0,
+    );
+
+    for zero_for_one in [true, false] {
+        let result = fixture.pool.quote_swap(&(1i128 << 100), &zero_for_one, &0);
+
+        assert!(!result.fully_filled);
+        assert!(result.amount_out > 999_000_000);
+        assert!((MIN_TICK..=MAX_TICK).contains(&result.current_tick));
+        assert!((MIN_SQRT_RATIO..=MAX_SQRT_RATIO).contains(&result.sqrt_price_x64));
+    }
+}
+
+#[test]
+fn swap_down_to_min_tick_stops_above_the_min_price() {
+    let fixture = Fixture::at_tick(30, 1, -689_000);
+    fixture.fund(&fixture.user, 1i128 << 115);
+    fixture.pool.add_liquidity_advanced(
+        &fixture.user, &MIN_TICK, &-688_000, &(1i128 << 110), &(1i128 << 110), &0, &0, &0,
+    );
+
+    let result = fixture.pool.quote_swap(&(1i128 << 119), &true, &0);
+
+    assert!(!result.fully_filled);
+    assert!(result.current_tick >= MIN_TICK);
+    assert!(result.sqrt_price_x64 > MIN_SQRT_RATIO);
+}
+
+#[test]
+fn swap_up_to_max_tick_that_needs_more_than_i128_reverts() {
+    let fixture = Fixture::at_tick(30, 1, 887_200);
+    fixture.pool.add_liquidity_advanced(
+        &fixture.user, &887_200, &MAX_TICK, &1_000_000_000_000, &0, &0, &0, &0,
+    );
+
+    // Buying the token0 back near MAX_TICK costs more token1 than fits in i128
+    assert_error(fixture.pool.try_quote_swap(&-(1i128 << 30), &false, &0), PoolErrorExt::Overflow);
+}
+
+// ============================================================
+// PRICE LIMITS
+// ============================================================
+
+#[test]
+fn limits_on_the_wrong_side_or_past_the_bounds_are_rejected() {
+    let fixture = pool();
+    let price = fixture.pool.get_pool_state().sqrt_price_x64;
+
+    let invalid = [
+        (true, price + 1),
+        (true, price),
+        (true, MIN_SQRT_RATIO),
+        (true, 1),
+        (false, price - 1),
+        (false, price),
+        (false, MAX_SQRT_RATIO),
+        (false, u128::MAX),
+    ];
+    for (zero_for_one, limit) in invalid {
+        assert_error(
+            fixture.pool.try_swap_advanced(&fixture.user, &1_000_000, &0, &0, &zero_for_one, &limit, &0),
+            PoolError::InvalidPriceLimit,
+        );
+        assert_error(
+            fixture.pool.try_quote_swap(&1_000_000, &zero_for_one, &limit),
+            PoolError::InvalidPriceLimit,
+        );
+    }
+}
+
+#[test]
+fn limits_strictly_inside_the_bounds_are_accepted() {
+    let fixture = pool();
+    let price = fixture.pool.get_pool_state().sqrt_price_x64;
+
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000, &0, &0, &true, &(price - price / 1_000), &0);
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000, &0, &0, &false, &(MAX_SQRT_RATIO - 1), &0);
+    fixture.pool.swap_advanced(&fixture.user, &1_000_000, &0, &0, &true, &(MIN_SQRT_RATIO + 1), &0);
+}
+
+#[test]
+fn stopping_at_the_limit_is_reported_as_a_partial_fill() {
+    let fixture = pool();
+    let limit = get_sqrt_ratio_at_tick(&fixture.env, -300);
+
+    let capped = fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &true, &limit, &0);
+    assert!(!capped.fully_filled);
+    assert!(capped.amount_remaining > 0);
+    assert_eq!(capped.amount_in + capped.amount_remaining, 1_000_000_000);
+    assert_eq!(capped.sqrt_price_x64, limit);
+
+    // The same size with the limit out of reach fills completely
+    let fixture = pool();
+    let far = get_sqrt_ratio_at_tick(&fixture.env, -1_200);
+    let filled = fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &true, &far, &0);
+    assert!(filled.fully_filled);
+    assert_eq!((filled.amount_in, filled.amount_remaining), (1_000_000_000, 0));
+}
+
+// ============================================================
+// SWAP CONTEXT
+// ============================================================
+
+/// Next initialized tick within one bitmap word, read from the context
+/// instead of storage (mirrors `next_initialized_tick_within_one_word`)
+fn next_tick_in_context(context: &SwapContext, tick: i32, lte: bool) -> (i32, bool) {
+    let word = |word_pos: i32| {
+        context.bitmap_words.iter().find(|(pos, _)| *pos == word_pos).expect("swap left the context").1
+    };
+    let compressed = tick.div_euclid(context.tick_spacing);
+
+    let (next, initialized) = if lte {
+        let bit_pos = compressed.rem_euclid(128) as u32;
+        let masked = word(compressed.div_euclid(128)) & (u128::MAX >> (127 - bit_pos));
+        if masked != 0 {
+            (compressed as i64 - (bit_pos - (127 - masked.leading_zeros())) as i64, true)
+        } else {
+            (compressed as i64 - bit_pos as i64, false)
+        }
+    } else {
+        let bit_pos = (compressed + 1).rem_euclid(128) as u32;
+        let masked = word((compressed + 1).div_euclid(128)) & (u128::MAX << bit_pos);
+        if masked != 0 {
+            (compressed as i64 + 1 + (masked.trailing_zeros() - bit_pos) as i64, true)
+        } else {
+            (compressed as i64 + 1 + (127 - bit_pos) as i64, false)
+        }
+    };
+
+    let unclamped = next * context.tick_spacing as i64;
+    let next_tick = unclamped.clamp(MIN_TICK as i64, MAX_TICK as i64) as i32;
+    (next_tick, initialized && next_tick as i64 == unclamped)
+}
+
+/// Exact-input swap replayed from a `SwapContext` alone, returning
+/// (amount_in, amount_out)
+fn simulate_from_context(env: &Env, context: &SwapContext, amount: i128, zero_for_one: bool) -> (i128, i128) {
+    let (mut sqrt_price, mut liquidity, mut tick) = (context.sqrt_price_x64, context.liquidity, context.current_tick);
+    let limit = if zero_for_one { MIN_SQRT_RATIO + 1 } else { MAX_SQRT_RATIO - 1 };
+    let (mut remaining, mut amount_in, mut amount_out) = (amount, 0, 0);
+
+    while remaining > 0 && liquidity > 0 {
+        if (zero_for_one && sqrt_price <= limit) || (!zero_for_one && sqrt_price >= limit) {
+            break;
+        }
+
+        let (next_tick, initialized) = next_tick_in_context(context, tick, zero_for_one);
+        let sqrt_next_tick = get_sqrt_ratio_at_tick(env, next_tick);
+        let target = if zero_for_one { sqrt_next_tick.max(limit) } else { sqrt_next_tick.min(limit) };
+
+        let available = deduct_bps(env, remaining, context.fee_bps);
+        if available < MIN_OUTPUT_AMOUNT {
+            break;
+        }
+
+        let (sqrt_next, step_in, step_out) = if sqrt_price == target {
+            (target, 0, 0)
+        } else {
+            compute_swap_step_with_target(env, sqrt_price, liquidity, available, zero_for_one, target)
+        };
+        let reached = sqrt_next == target;
+        if !reached && (step_in < MIN_OUTPUT_AMOUNT || step_out < MIN_OUTPUT_AMOUNT) {
+            break;
+        }
+
+        let fee = if step_in == available { remaining - step_in } else { gross_up(env, step_in, context.fee_bps) - step_in };
+        remaining -= step_in + fee;
+        amount_in += step_in + fee;
+        amount_out += step_out;
+
+        if reached && target == sqrt_next_tick {
+            sqrt_price = target;
+            if initialized {
+                let net = context.ticks.iter().find(|(t, _)| *t == next_tick).unwrap().1;
+                liquidity = if zero_for_one { liquidity - net } else { liquidity + net };
+            }
+            tick = if zero_for_one { next_tick - 1 } else { next_tick };
+        } else if sqrt_next != sqrt_price {
+            sqrt_price = sqrt_next;
+        } else {
+            break;
+        }
+    }
+
+    (amount_in, amount_out)
+}
+
+#[test]
+fn simulation_from_swap_context_matches_quotes() {
+    let fixture = Fixture::at_tick(30, 10, 0);
+    fixture.pool.set_protocol_fee(&fixture.admin, &1_000);
+    for (lower, upper) in [(-600, 600), (-100, 50), (-2_000, -500), (300, 1_500), (-3_000, 3_000)] {
+        fixture.add(lower, upper, 500_000_000);
+    }
+    let context = fixture.pool.get_swap_context();
+
+    // Within a range, and across three initialized ticks each way
+    let cases = [(1_000, true), (50_000_000, true), (1_500_000_000, true), (50_000_000, false), (1_500_000_000, false)];
+    for (amount, zero_for_one) in cases {
+        let quote = fixture.pool.quote_swap(&amount, &zero_for_one, &0);
+        assert_eq!(
+            simulate_from_context(&fixture.env, &context, amount, zero_for_one),
+            (quote.amount_in, quote.amount_out),
+            "{amount} {zero_for_one}",
+        );
+    }
+}
+
+// ============================================================
+// ITERATION CAP
+// ============================================================
+
+/// Spacing 1 pool with one-tick positions initializing ticks 0..=100 and
+/// a wide position above them spanning about 1000 bitmap words
+fn crowded<'a>() -> Fixture<'a> {
+    let fixture = Fixture::at_tick(30, 1, 0);
+    for tick in 0..100 {
+        fixture.add(tick, tick + 1, 10_000_000);
+    }
+    fixture.add(100, 130_000, 1_000_000);
+    fixture
+}
+
+#[test]
+fn swap_across_too_many_ticks_reverts_instead_of_truncating() {
+    let fixture = crowded();
+    let before = fixture.pool.get_pool_state();
+
+    // One step per tick, then one per word: more than the engine allows
+    assert_error(
+        fixture.pool.try_swap_advanced(&fixture.user, &100_000_000_000, &0, &0, &false, &0, &0),
+        PoolError::SwapTooManyIterations,
+    );
+    assert_error(fixture.pool.try_quote_swap(&100_000_000_000, &false, &0), PoolError::SwapTooManyIterations);
+    assert_eq!(fixture.pool.get_pool_state().sqrt_price_x64, before.sqrt_price_x64);
+
+    // Stopping short of the cap fills normally
+    let limit = get_sqrt_ratio_at_tick(&fixture.env, 90);
+    let result = fixture.pool.swap_advanced(&fixture.user, &1_000_000_000, &0, &0, &false, &limit, &0);
+    assert_eq!(result.current_tick, 90);
+}
+
+// ============================================================
+// PAST MAX TICK
+// ============================================================
+
+#[test]
+fn draining_every_range_upward_parks_at_the_last_edge() {
+    let fixture = pool();
+
+    // More token0 than exists: the price stops where liquidity ends
+    // instead of running on through empty ticks toward MAX_TICK
+    let params = SwapParams::new(-2_500_000_000, false).with_allow_partial(true);
+    let result = fixture.pool.swap_with_params(&fixture.user, &params);
+    assert!(!result.fully_filled);
+    assert_eq!(result.current_tick, 1_800);
+    assert_eq!(result.sqrt_price_x64, get_sqrt_ratio_at_tick(&fixture.env, 1_800));
+
+    // Nothing left above; the pool still trades back down
+    assert_error(fixture.pool.try_quote_swap(&1_000, &false, &0), PoolError::NoLiquidity);
+    assert!(fixture.pool.quote_swap(&1_000, &true, &0).fully_filled);
+}
+
+#[test]
+fn swap_at_the_top_of_the_range_cannot_push_past_max_tick() {
+    let fixture = Fixture::at_tick(30, 1, 880_000);
+    fixture.pool.add_liquidity_advanced(&fixture.user, &880_000, &MAX_TICK, &1_000_000, &0, &0, &0, &0);
+    let before = fixture.pool.get_pool_state();
+
+    // Near MAX_TICK one unit of token0 costs more than the amount cap, so
+    // the largest allowed input buys nothing rather than saturating
+    assert_error(fixture.pool.try_quote_swap(&MAX_AMOUNT, &false, &0), PoolError::OutputTooSmall);
+    assert_error(fixture.pool.try_quote_swap(&(MAX_AMOUNT + 1), &false, &0), PoolError::AmountAboveCap);
+    assert_error(
+        fixture.pool.try_quote_swap(&1_000_000, &false, &(MAX_SQRT_RATIO + 1)),
+        PoolError::InvalidPriceLimit,
+    );
+
+    let after = fixture.pool.get_pool_state();
+    assert_eq!((after.sqrt_price_x64, after.current_tick), (before.sqrt_price_x64, before.current_tick));
+    assert!(after.sqrt_price_x64 < MAX_SQRT_RATIO);
+}
+
+// ============================================================
+// LIQUIDITY GAPS
+// ============================================================
+
+#[test]
+fn swap_crosses_an_empty_gap_without_spending_input() {
+    let fixture = Fixture::new();
+    let low = fixture.add(-600, 600, 1_000_000_000);
+    let high = fixture.add(1_200, 1_800, 1_000_000_000);
+    let sqrt = |tick| get_sqrt_ratio_at_tick(&fixture.env, tick);
+
+    // Out of the low range, over the empty 600..1200 gap and into the high one
+    let result = fixture.pool.swap(&fixture.user, &fixture.token1, &fixture.token0, &1_500_000_000, &0, &0, &0);
+    let state = fixture.pool.get_pool_state();
+    assert_eq!(result.ticks_crossed, 2);
+    assert!(state.current_tick > 1_200 && state.current_tick < 1_800);
+    assert_eq!(state.liquidity, high);
+
+    // Net input pays only for the two ranges' price moves, none for the gap
+    let through_low = get_amount_1_delta(&fixture.env, sqrt(0), sqrt(600), low as u128, true);
+    let into_high = get_amount_1_delta(&fixture.env, sqrt(1_200), state.sqrt_price_x64, high as u128, true);
+    let spent = (result.amount_in - result.fee_amount) as u128;
+    assert!(spent.abs_diff(through_low + into_high) <= 2);
+
+    // And back down across the gap into the low range
+    fixture.pool.swap(&fixture.user, &fixture.token0, &fixture.token1, &1_500_000_000, &0, &0, &0);
+    let state = fixture.pool.get_pool_state();
+    assert!(state.current_tick < 600);
+    assert_eq!(state.liquidity, low);
+}
+
+// ============================================================
+// ROUNDING
+// ============================================================
+
+#[test]
+fn tiny_swaps_never_leave_the_pool_short() {
+    let fixture = pool();
+    let ranges = [(-600, 600), (-1_800, 1_800)];
+    let (token0, token1) = (&fixture.token0, &fixture.token1);
+
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut next = |bound: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed % bound
+    };
+
+    // Principal, fees and protocol fees the pool owes, all at once
+    let owed = || {
+        let state = fixture.pool.get_pool_state();
+        ranges.iter().fold((state.protocol_fees_0, state.protocol_fees_1), |(owed0, owed1), (lower, upper)| {
+            let info = fixture.pool.get_position(&fixture.user, lower, upper);
+            (owed0 + info.amount0 as u128 + info.fees_owed_0, owed1 + info.amount1 as u128 + info.fees_owed_1)
+        })
+    };
+
+    for round in 0..2_000 {
+        // Exact input or output of a few stroops either way; swaps too
+        // small to fill are rejected and simply skipped
+        let amount = next(1_000) as i128 + 1;
+        let amount = if next(2) == 0 { amount } else { -amount };
+        let _ = fixture.pool.try_swap_advanced(&fixture.user, &amount, &0, &0, &(next(2) == 0), &0, &0);
+
+        if round % 100 == 99 {
+            let (owed0, owed1) = owed();
+            assert!(fixture.balance(token0, &fixture.pool.address) as u128 >= owed0, "round {round}");
+            assert!(fixture.balance(token1, &fixture.pool.address) as u128 >= owed1, "round {round}");
+        }
+    }
+
+    // Every position can exit in full and the protocol's share is left
+    for (lower, upper) in ranges {
+        fixture.pool.close_position(&fixture.user, &lower, &upper, &0);
+    }
+    let state = fixture.pool.get_pool_state();
+    assert!(fixture.balance(token0, &fixture.pool.address) as u128 >= state.protocol_fees_0);
+    assert!(fixture.balance(token1, &fixture.pool.address) as u128 >= state.protocol_fees_1);
+}
@@ -0,0 +1,165 @@
+//! Test-only entrypoints: `reset_pool` for reusing one pool address across
+//! scenarios, and corrupting ticks to exercise the tick accounting audit.
+
+use soroban_sdk::testutils::Ledger;
+
+use super::{assert_error, Fixture};
+use crate::error::PoolError;
+use crate::math::get_sqrt_ratio_at_tick;
+use crate::storage::DataKey;
+
+// ============================================================
+// RESET POOL
+// ============================================================
+
+/// Run a scenario touching every kind of entry `reset_pool` must clear
+fn scenario(fixture: &Fixture, lower: i32, upper: i32) {
+    fixture.env.ledger().with_mut(|l| l.timestamp += 100);
+    fixture.add(lower, upper, 1_000_000_000);
+    let id = fixture.pool.open_position(&fixture.user, &lower, &upper);
+    fixture.pool.add_liquidity_by_id(&fixture.user, &id, &1_000_000, &1_000_000, &0, &0, &0);
+    fixture.pool.swap_advanced(&fixture.user, &10_000_000, &0, &0, &true, &0, &0);
+}
+
+/// Whether any entry of the scenario over `[lower, upper]` survived
+fn leaked(fixture: &Fixture, lower: i32, upper: i32) -> bool {
+    let user = fixture.user.clone();
+    let keys = [
+        DataKey::Position(user.clone(), lower, upper),
+        DataKey::OwnerPositions(user),
+        DataKey::PositionById(1),
+        DataKey::Tick(lower),
+        DataKey::Tick(upper),
+    ];
+    fixture.env.as_contract(&fixture.pool.address, || {
+        keys.iter().any(|key| fixture.env.storage().persistent().has(key))
+    })
+}
+
+#[test]
+fn reset_clears_everything_from_the_previous_scenario() {
+    let fixture = Fixture::new();
+    fixture.pool.set_protocol_fee(&fixture.admin, &1_000);
+    scenario(&fixture, -600, 600);
+    assert!(leaked(&fixture, -600, 600));
+
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, 1_000);
+    fixture.pool.reset_pool(&sqrt_price, &10);
+
+    assert!(!leaked(&fixture, -600, 600));
+    let state = fixture.pool.get_pool_state();
+    assert_eq!((state.sqrt_price_x64, state.current_tick, state.tick_spacing), (sqrt_price, 1_000, 10));
+    assert_eq!(state.liquidity, 0);
+    assert_eq!((state.fee_growth_global_0, state.fee_growth_global_1), (0, 0));
+    assert_eq!((state.protocol_fees_0, state.protocol_fees_1), (0, 0));
+    assert_eq!(state.next_position_id, 1);
+    assert!(fixture.pool.get_populated_words(&-100, &200).is_empty());
+    assert!(fixture.pool.list_positions(&fixture.user).is_empty());
+    assert!(!fixture.pool.get_tick_info(&-600).initialized);
+}
+
+#[test]
+fn scenarios_after_a_reset_start_clean() {
+    let fixture = Fixture::new();
+    scenario(&fixture, -600, 600);
+    fixture.pool.reset_pool(&get_sqrt_ratio_at_tick(&fixture.env, 0), &60);
+
+    // Same ranges and ids again: nothing is stacked onto the old entries
+    scenario(&fixture, -600, 600);
+    let fresh = Fixture::new();
+    scenario(&fresh, -600, 600);
+
+    let reused = fixture.pool.get_position(&fixture.user, &-600, &600);
+    let expected = fresh.pool.get_position(&fresh.user, &-600, &600);
+    assert_eq!(reused.liquidity, expected.liquidity);
+    assert_eq!(reused.lifetime_fees_0, expected.lifetime_fees_0);
+    assert_eq!(fixture.pool.get_pool_state().liquidity, fresh.pool.get_pool_state().liquidity);
+    assert_eq!(fixture.pool.get_pool_state().fee_growth_global_0, fresh.pool.get_pool_state().fee_growth_global_0);
+    assert_eq!(fixture.pool.get_tick_info(&600).liquidity_gross, fresh.pool.get_tick_info(&600).liquidity_gross);
+}
+
+// ============================================================
+// TICK ACCOUNTING AUDIT
+// ============================================================
+
+/// Pool with the fixture user's position over [-600, 600], returning its
+/// liquidity
+fn audited(fixture: &Fixture, checks: bool) -> i128 {
+    fixture.pool.set_tick_accounting_checks(&fixture.admin, &checks);
+    fixture.add(-600, 600, 1_000_000_000)
+}
+
+#[test]
+fn healthy_full_burns_pass_the_audit() {
+    let fixture = Fixture::new();
+    let liquidity = audited(&fixture, true);
+    assert!(fixture.pool.get_tick_accounting_checks());
+
+    fixture.pool.remove_liquidity(&fixture.user, &-600, &600, &liquidity, &0);
+    assert!(!fixture.pool.get_tick_info(&-600).initialized);
+}
+
+#[test]
+fn fresh_pools_audit_full_burns() {
+    let fixture = Fixture::new();
+    assert!(fixture.pool.get_tick_accounting_checks());
+
+    let liquidity = fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.corrupt_tick_liquidity(&-600, &liquidity, &(liquidity * 2));
+    assert_error(
+        fixture.pool.try_remove_liquidity(&fixture.user, &-600, &600, &liquidity, &0),
+        PoolError::TickAccountingMismatch,
+    );
+}
+
+#[test]
+fn tick_short_of_the_removal_traps_at_the_burn() {
+    let fixture = Fixture::new();
+    let liquidity = audited(&fixture, false);
+    fixture.pool.corrupt_tick_liquidity(&-600, &(liquidity / 2), &(liquidity / 2));
+
+    // Checked on every burn, audit or not
+    assert_error(
+        fixture.pool.try_remove_liquidity(&fixture.user, &-600, &600, &liquidity, &0),
+        PoolError::TickAccountingMismatch,
+    );
+}
+
+#[test]
+fn leftover_net_traps_a_full_burn_only_when_audited() {
+    for checks in [true, false] {
+        let fixture = Fixture::new();
+        let liquidity = audited(&fixture, checks);
+        fixture.pool.corrupt_tick_liquidity(&-600, &liquidity, &(liquidity * 2));
+
+        let burned = fixture.pool.try_remove_liquidity(&fixture.user, &-600, &600, &liquidity, &0);
+        if checks {
+            assert_error(burned, PoolError::TickAccountingMismatch);
+        } else {
+            // Unaudited, the orphaned net survives to corrupt a later cross
+            assert!(burned.is_ok());
+            assert_eq!(fixture.pool.get_tick_info(&-600).liquidity_net, liquidity);
+        }
+    }
+}
+
+#[test]
+fn drifted_shared_tick_traps_before_going_negative() {
+    let fixture = Fixture::new();
+    let narrow = fixture.add(-600, 600, 1_000_000_000);
+    fixture.add(-600, 1_200, 1_000_000_000);
+
+    // The shared tick lost track of the wider range entirely
+    fixture.pool.corrupt_tick_liquidity(&-600, &narrow, &narrow);
+
+    // Partial burns that the tick can still cover go through
+    fixture.pool.remove_liquidity(&fixture.user, &-600, &1_200, &(narrow / 2), &0);
+    assert_eq!(fixture.pool.get_tick_info(&-600).liquidity_gross, narrow - narrow / 2);
+
+    // The next one would take the tick below zero
+    assert_error(
+        fixture.pool.try_remove_liquidity(&fixture.user, &-600, &600, &narrow, &0),
+        PoolError::TickAccountingMismatch,
+    );
+    assert_eq!(fixture.pool.get_tick_info(&-600).liquidity_gross, narrow - narrow / 2);
+}
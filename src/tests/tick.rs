@@ -0,0 +1,264 @@
+//! Tick helpers: bracketing a price, range presets, the bitmap search for
+//! the next initialized tick and the bitmap words exposed to indexers.
+
+use soroban_sdk::{vec, Env, Vec};
+
+use super::{assert_error, Fixture};
+use crate::constants::RANGE_PRESET_TICK_DELTAS;
+use crate::error::PoolError;
+use crate::math::{get_sqrt_ratio_at_tick, MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::tick::{find_next_initialized_tick, nearest_valid_ticks};
+
+// ============================================================
+// NEAREST VALID TICKS
+// ============================================================
+
+const SPACINGS: [i32; 5] = [1, 10, 60, 200, 2_000];
+const TICKS: [i32; 9] = [-600_001, -45_678, -1_201, -1, 0, 1, 59, 12_345, 800_009];
+
+#[test]
+fn nearest_valid_ticks_bracket_the_price() {
+    let env = Env::default();
+
+    for spacing in SPACINGS {
+        for tick in TICKS {
+            let sqrt_tick = get_sqrt_ratio_at_tick(&env, tick);
+            for sqrt_price in [sqrt_tick + 1, (sqrt_tick + get_sqrt_ratio_at_tick(&env, tick + 1)) / 2] {
+                let (lower, upper) = nearest_valid_ticks(&env, sqrt_price, spacing);
+
+                assert_eq!(lower % spacing, 0);
+                assert_eq!(upper - lower, spacing);
+                assert!(get_sqrt_ratio_at_tick(&env, lower) <= sqrt_price);
+                assert!(sqrt_price < get_sqrt_ratio_at_tick(&env, upper));
+            }
+        }
+    }
+}
+
+#[test]
+fn price_on_a_valid_tick_is_its_own_bracket() {
+    let env = Env::default();
+
+    for spacing in SPACINGS {
+        for tick in TICKS {
+            let aligned = tick.div_euclid(spacing) * spacing;
+            let sqrt_price = get_sqrt_ratio_at_tick(&env, aligned);
+            assert_eq!(nearest_valid_ticks(&env, sqrt_price, spacing), (aligned, aligned));
+
+            // Just below an aligned tick belongs to the spacing under it
+            let (lower, upper) = nearest_valid_ticks(&env, sqrt_price - 1, spacing);
+            assert_eq!((lower, upper), (aligned - spacing, aligned));
+        }
+    }
+}
+
+#[test]
+fn brackets_stay_inside_the_tick_range() {
+    let env = Env::default();
+
+    for spacing in SPACINGS {
+        let (lower, _) = nearest_valid_ticks(&env, MIN_SQRT_RATIO + 1, spacing);
+        let (_, upper) = nearest_valid_ticks(&env, MAX_SQRT_RATIO - 1, spacing);
+        assert!(get_sqrt_ratio_at_tick(&env, lower) >= MIN_SQRT_RATIO);
+        assert!(get_sqrt_ratio_at_tick(&env, upper) <= MAX_SQRT_RATIO);
+    }
+}
+
+#[test]
+fn entrypoint_uses_the_pool_spacing() {
+    let fixture = Fixture::at_tick(30, 60, 0);
+    let sqrt_price = get_sqrt_ratio_at_tick(&fixture.env, 100);
+
+    assert_eq!(fixture.pool.nearest_valid_ticks(&sqrt_price), (60, 120));
+}
+
+// ============================================================
+// NEXT INITIALIZED TICK
+// ============================================================
+
+#[test]
+fn next_initialized_tick_is_found_words_away() {
+    let fixture = Fixture::at_tick(30, 60, 0);
+    fixture.add(300_000, 300_060, 1_000_000);
+    fixture.add(-300_060, -300_000, 1_000_000);
+
+    // 5000 spacings away: about 39 words of 128 spacings each
+    fixture.env.as_contract(&fixture.pool.address, || {
+        for (zero_for_one, expected) in [(false, 300_000), (true, -300_000)] {
+            let mut tick = 0;
+            let mut steps = 0;
+            loop {
+                let (next, initialized) = find_next_initialized_tick(&fixture.env, tick, 60, zero_for_one);
+                steps += 1;
+                if initialized {
+                    assert_eq!(next, expected);
+                    break;
+                }
+                tick = if zero_for_one { next - 1 } else { next };
+            }
+            assert!(steps <= 41);
+        }
+    });
+}
+
+#[test]
+fn swap_reaches_liquidity_thousands_of_ticks_away() {
+    let fixture = Fixture::at_tick(30, 1, 0);
+    fixture.add(3_000, 3_100, 1_000_000_000);
+    fixture.add(-3_100, -3_000, 1_000_000_000);
+
+    let up = fixture.pool.swap_advanced(&fixture.user, &1_000_000, &0, &0, &false, &0, &0);
+    assert!(up.fully_filled);
+    assert!((3_000..3_100).contains(&up.current_tick));
+
+    let down = fixture.pool.swap_advanced(&fixture.user, &1_000_000, &0, &0, &true, &0, &0);
+    assert!(down.fully_filled);
+    assert!((-3_100..-3_000).contains(&down.current_tick));
+}
+
+// ============================================================
+// BITMAP WORDS
+// ============================================================
+
+#[test]
+fn populated_words_encode_the_initialized_ticks() {
+    let fixture = Fixture::at_tick(30, 60, 0);
+    // Words of 128 spacings span 7680 ticks: these land in words -1, 0, 1, 2
+    let ranges = [(-600, 600), (600, 9_000), (-7_680, 15_540)];
+    for (lower, upper) in ranges {
+        fixture.add(lower, upper, 1_000_000);
+    }
+    let expected = [-7_680, -600, 600, 9_000, 15_540];
+
+    let mut indices = Vec::new(&fixture.env);
+    let mut decoded = Vec::new(&fixture.env);
+    for (index, word) in fixture.pool.get_populated_words(&-10, &20).iter() {
+        indices.push_back(index);
+        assert_eq!(fixture.pool.get_bitmap_word(&index), word);
+        for bit in 0..128 {
+            if word & (1 << bit) != 0 {
+                decoded.push_back((index * 128 + bit) * 60);
+            }
+        }
+    }
+    assert_eq!(indices, vec![&fixture.env, -1, 0, 1, 2]);
+    assert_eq!(decoded, Vec::from_array(&fixture.env, expected));
+    assert_eq!(fixture.pool.get_bitmap_word(&3), 0);
+}
+
+#[test]
+fn populated_words_page_through_the_range() {
+    let fixture = Fixture::at_tick(30, 60, 0);
+    fixture.add(-600, 9_000, 1_000_000);
+
+    assert_eq!(fixture.pool.get_populated_words(&-1, &1).len(), 1);
+    assert_eq!(fixture.pool.get_populated_words(&0, &1).len(), 0);
+    assert_eq!(fixture.pool.get_populated_words(&1, &1).get_unchecked(0).0, 1);
+
+    // Removing the last position over a tick clears its bit
+    let liquidity = fixture.pool.get_position(&fixture.user, &-600, &9_000).liquidity;
+    fixture.pool.remove_liquidity(&fixture.user, &-600, &9_000, &liquidity, &0);
+    assert!(fixture.pool.get_populated_words(&-10, &20).is_empty());
+}
+
+// ============================================================
+// RANGE PRESETS
+// ============================================================
+
+#[test]
+fn presets_are_symmetric_aligned_and_bracket_the_price() {
+    for (tick_spacing, tick) in [(60, 0), (10, 12_345), (60, -777), (1, 5), (200, 1_001)] {
+        let fixture = Fixture::at_tick(30, tick_spacing, tick);
+        let current = fixture.pool.get_pool_state().current_tick;
+        let base = current.div_euclid(tick_spacing) * tick_spacing;
+
+        for (preset, tick_delta) in RANGE_PRESET_TICK_DELTAS.iter().enumerate() {
+            let (lower, upper) = fixture.pool.preset_range(&(preset as u32));
+
+            assert_eq!((lower % tick_spacing, upper % tick_spacing), (0, 0));
+            assert_eq!(base - lower, upper - base);
+            assert!(lower <= current && current < upper);
+            // Half-width is the preset's tick delta rounded up to the spacing
+            assert!(upper - base >= *tick_delta && upper - base < tick_delta + tick_spacing);
+        }
+    }
+}
+
+#[test]
+fn preset_percentages_map_to_price_ratios() {
+    let fixture = Fixture::at_tick(30, 1, 0);
+
+    // ±5%, ±15% and ±50% of price, via 1.0001^delta
+    for (preset, percent) in [(0, 5.0), (1, 15.0), (2, 50.0)] {
+        let (_, upper) = fixture.pool.preset_range(&preset);
+        let ratio = 1.0001f64.powi(upper);
+        assert!((ratio - (1.0 + percent / 100.0)).abs() < 0.001, "{preset}: {ratio}");
+    }
+}
+
+#[test]
+fn presets_near_the_edges_are_clamped() {
+    let fixture = Fixture::at_tick(30, 200, 887_000);
+
+    let (lower, upper) = fixture.pool.preset_range(&2);
+
+    assert_eq!(upper, 887_200);
+    assert!(lower < 887_000);
+}
+
+#[test]
+fn unknown_preset_is_rejected() {
+    let fixture = Fixture::new();
+
+    assert_error(fixture.pool.try_preset_range(&3), PoolError::InvalidRangePreset);
+}
+
+// ============================================================
+// TICKS IN RANGE
+// ============================================================
+
+#[test]
+fn ticks_in_range_are_the_initialized_boundaries() {
+    let fixture = Fixture::new();
+    let a = fixture.add(-600, 600, 1_000_000);
+    let b = fixture.add(-1_200, 600, 1_000_000);
+    let c = fixture.add(1_800, 2_400, 1_000_000);
+
+    let (ticks, cursor) = fixture.pool.get_ticks_in_range(&-6_000, &6_000, &100);
+    assert_eq!(cursor, None);
+    let mut listed = Vec::new(&fixture.env);
+    for (tick, info) in ticks.iter() {
+        listed.push_back((tick, info.liquidity_gross, info.liquidity_net));
+    }
+    let expected = vec![
+        &fixture.env,
+        (-1_200, b, b),
+        (-600, a, a),
+        (600, a + b, -(a + b)),
+        (1_800, c, c),
+        (2_400, c, -c),
+    ];
+    assert_eq!(listed, expected);
+
+    // Bounds are inclusive, and a window with no boundaries is empty
+    let (inner, _) = fixture.pool.get_ticks_in_range(&-600, &600, &100);
+    assert_eq!(inner.len(), 2);
+    assert!(fixture.pool.get_ticks_in_range(&660, &1_740, &100).0.is_empty());
+}
+
+#[test]
+fn ticks_in_range_page_with_a_cursor() {
+    let fixture = Fixture::new();
+    for upper in [120, 240, 360, 480] {
+        fixture.add(-600, upper, 1_000_000);
+    }
+
+    let (first, cursor) = fixture.pool.get_ticks_in_range(&-6_000, &6_000, &3);
+    assert_eq!(first.len(), 3);
+    assert_eq!(cursor, Some(360));
+    let (rest, cursor) = fixture.pool.get_ticks_in_range(&360, &6_000, &3);
+    assert_eq!(rest.len(), 2);
+    assert_eq!(cursor, None);
+
+    assert_error(fixture.pool.try_get_ticks_in_range(&600, &-600, &10), PoolError::InvalidTickRange);
+}
@@ -0,0 +1,113 @@
+//! Storage TTL: entries in use, and those a keeper extends, outlive the
+//! archival boundary.
+
+use soroban_sdk::testutils::storage::Persistent;
+use soroban_sdk::testutils::Ledger;
+use soroban_sdk::{vec, Vec};
+
+use super::Fixture;
+use crate::constants::{PERSISTENT_TTL_EXTEND_TO, PERSISTENT_TTL_THRESHOLD};
+use crate::storage::DataKey;
+
+/// Ledgers past the threshold but short of the extended TTL
+const IDLE: u32 = PERSISTENT_TTL_EXTEND_TO - PERSISTENT_TTL_THRESHOLD + 1;
+
+/// Let `ledgers` pass without touching the pool
+fn idle(fixture: &Fixture, ledgers: u32) {
+    fixture.env.ledger().with_mut(|l| l.sequence_number += ledgers);
+}
+
+fn ttl(fixture: &Fixture, key: &DataKey) -> u32 {
+    fixture.env.as_contract(&fixture.pool.address, || fixture.env.storage().persistent().get_ttl(key))
+}
+
+fn keys(fixture: &Fixture) -> Vec<DataKey> {
+    vec![
+        &fixture.env,
+        DataKey::PoolState,
+        DataKey::PoolConfig,
+        DataKey::Tick(-600),
+        DataKey::Tick(600),
+        DataKey::Position(fixture.user.clone(), -600, 600),
+    ]
+}
+
+// ============================================================
+// STORAGE TTL
+// ============================================================
+
+#[test]
+fn writes_extend_every_entry_they_touch() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+
+    for key in keys(&fixture).iter() {
+        assert_eq!(ttl(&fixture, &key), PERSISTENT_TTL_EXTEND_TO, "{key:?}");
+    }
+}
+
+#[test]
+fn reads_keep_the_pool_live_across_the_archival_boundary() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+
+    // Each read lands after the threshold and extends the entry again, so
+    // the pool outlives its original TTL several times over
+    for _ in 0..3 {
+        idle(&fixture, IDLE);
+        assert!(ttl(&fixture, &DataKey::PoolState) < PERSISTENT_TTL_THRESHOLD);
+        fixture.pool.get_pool_state();
+        fixture.pool.get_position(&fixture.user, &-600, &600);
+        assert_eq!(ttl(&fixture, &DataKey::PoolState), PERSISTENT_TTL_EXTEND_TO);
+    }
+    assert!(fixture.env.ledger().sequence() > PERSISTENT_TTL_EXTEND_TO);
+}
+
+#[test]
+fn keeper_extends_rarely_touched_entries() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    idle(&fixture, IDLE);
+
+    // The position, its ticks, their bitmap words and the owner's index;
+    // pool-wide entries are always included
+    let mut listed = keys(&fixture);
+    listed.push_back(DataKey::TickBitmap(-1));
+    listed.push_back(DataKey::TickBitmap(0));
+    listed.push_back(DataKey::OwnerPositions(fixture.user.clone()));
+    listed.push_back(DataKey::Tick(1_200));
+    assert_eq!(fixture.pool.extend_entries(&listed), 8);
+
+    for key in keys(&fixture).iter() {
+        assert_eq!(ttl(&fixture, &key), PERSISTENT_TTL_EXTEND_TO, "{key:?}");
+    }
+
+    // Past the original expiry everything the position needs is still live
+    let liquidity = fixture.pool.get_position(&fixture.user, &-600, &600).liquidity;
+    idle(&fixture, IDLE);
+    assert_eq!(fixture.pool.get_position(&fixture.user, &-600, &600).liquidity, liquidity);
+    assert_eq!(fixture.pool.list_positions(&fixture.user).len(), 1);
+    assert!(fixture.pool.get_tick_info(&600).initialized);
+    assert_eq!(fixture.pool.get_populated_words(&-1, &2).len(), 2);
+    assert_eq!(fixture.pool.get_mean_tick(&0), 0);
+}
+
+#[test]
+fn settings_are_extended_with_the_pool() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.set_max_deadline_window(&fixture.admin, &300);
+    fixture.pool.set_paused(&fixture.admin, &false);
+
+    assert_eq!(ttl(&fixture, &DataKey::MaxDeadlineWindow), PERSISTENT_TTL_EXTEND_TO);
+
+    // Reading a setting extends it; a keeper call extends all of them
+    idle(&fixture, IDLE);
+    assert_eq!(fixture.pool.get_max_deadline_window(), 300);
+    assert_eq!(ttl(&fixture, &DataKey::MaxDeadlineWindow), PERSISTENT_TTL_EXTEND_TO);
+    assert!(ttl(&fixture, &DataKey::Paused) < PERSISTENT_TTL_THRESHOLD);
+
+    assert_eq!(fixture.pool.extend_entries(&Vec::new(&fixture.env)), 0);
+    assert_eq!(ttl(&fixture, &DataKey::Paused), PERSISTENT_TTL_EXTEND_TO);
+    assert_eq!(ttl(&fixture, &DataKey::TWAPInitialized), PERSISTENT_TTL_EXTEND_TO);
+}
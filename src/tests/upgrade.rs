@@ -0,0 +1,69 @@
+//! Admin-only code upgrades and the state they must leave untouched.
+
+use soroban_sdk::{Bytes, BytesN, TryFromVal};
+
+use super::{assert_error, events_named, Fixture};
+use crate::constants::CONTRACT_VERSION;
+use crate::error::PoolError;
+use crate::storage::{read_pool_state, read_position, read_tick_info, read_wasm_hash};
+
+/// Smallest module the host accepts: no code, only the env meta section
+/// declaring interface version 21
+const EMPTY_WASM: [u8; 40] = [
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, 0x00, 0x1e, 0x11, b'c', b'o', b'n', b't', b'r', b'a', b'c', b't',
+    b'e', b'n', b'v', b'm', b'e', b't', b'a', b'v', b'0', 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x15, 0x00, 0x00,
+    0x00, 0x00,
+];
+
+fn upload(fixture: &Fixture) -> BytesN<32> {
+    fixture.env.deployer().upload_contract_wasm(Bytes::from_array(&fixture.env, &EMPTY_WASM))
+}
+
+// ============================================================
+// UPGRADE
+// ============================================================
+
+#[test]
+fn version_reports_the_running_code() {
+    let fixture = Fixture::new();
+    assert_eq!(fixture.pool.version(), CONTRACT_VERSION);
+}
+
+#[test]
+fn upgrade_is_admin_only_and_needs_an_initialized_pool() {
+    let fixture = Fixture::new();
+    let hash = upload(&fixture);
+    assert_error(fixture.pool.try_upgrade(&fixture.user, &hash), PoolError::Unauthorized);
+
+    let fresh = Fixture::uninitialized();
+    let hash = upload(&fresh);
+    assert_error(fresh.pool.try_upgrade(&fresh.admin, &hash), PoolError::NotInitialized);
+}
+
+#[test]
+fn upgrade_keeps_pool_positions_and_ticks() {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture.pool.swap_advanced(&fixture.user, &10_000_000, &0, &0, &true, &0, &0);
+    let pool = fixture.pool.get_pool_state();
+    let position = fixture.pool.get_position(&fixture.user, &-600, &600);
+    let hash = upload(&fixture);
+
+    fixture.pool.upgrade(&fixture.admin, &hash);
+
+    // The new code exports nothing, so read storage directly
+    fixture.env.as_contract(&fixture.pool.address, || {
+        let after = read_pool_state(&fixture.env);
+        assert_eq!(
+            (after.sqrt_price_x64, after.current_tick, after.liquidity, after.fee_growth_global_0),
+            (pool.sqrt_price_x64, pool.current_tick, pool.liquidity, pool.fee_growth_global_0),
+        );
+        assert_eq!(read_position(&fixture.env, &fixture.user, -600, 600).liquidity, position.liquidity);
+        assert_eq!(read_tick_info(&fixture.env, -600).liquidity_gross, position.liquidity);
+        assert_eq!(read_wasm_hash(&fixture.env), Some(hash.clone()));
+    });
+
+    let emitted = events_named(&fixture.env, "upgrade");
+    let (old, new) = <(Option<BytesN<32>>, BytesN<32>)>::try_from_val(&fixture.env, &emitted[0]).unwrap();
+    assert_eq!((old, new), (None, hash));
+}
@@ -0,0 +1,202 @@
+//! Boundary validation: every entrypoint rejects each class of invalid
+//! input with the validator's error code before doing any work.
+
+use soroban_sdk::{vec, Address, Bytes};
+
+use super::{assert_error, Fixture};
+use crate::constants::{MAX_AMOUNT, MAX_TICK, MIN_TICK};
+use crate::error::PoolError;
+use crate::types::{MintParams, SwapParams};
+
+/// Above `MAX_AMOUNT`
+const TOO_BIG: i128 = MAX_AMOUNT + 1;
+
+fn pool<'a>() -> Fixture<'a> {
+    let fixture = Fixture::new();
+    fixture.add(-600, 600, 1_000_000_000);
+    fixture
+}
+
+// ============================================================
+// AMOUNTS
+// ============================================================
+
+#[test]
+fn negative_amounts_are_rejected() {
+    let fixture = pool();
+    let (pool, user) = (&fixture.pool, &fixture.user);
+    let expected = PoolError::AmountNegative;
+
+    assert_error(pool.try_swap_advanced(user, &1_000, &-1, &0, &true, &0, &0), expected);
+    assert_error(pool.try_swap_advanced(user, &1_000, &0, &-1, &true, &0, &0), expected);
+    assert_error(pool.try_add_liquidity_advanced(user, &-600, &600, &-1, &5, &0, &0, &0), expected);
+    assert_error(pool.try_add_liquidity_advanced(user, &-600, &600, &5, &5, &0, &-1, &0), expected);
+    assert_error(pool.try_mint_with_params(user, &MintParams::new(-600, 600, 5, -1)), expected);
+    assert_error(pool.try_add_liquidity_post_only(user, &600, &1_200, &-1, &0, &0, &0, &0), expected);
+    assert_error(pool.try_calc_liquidity_for_amounts(&-1, &5, &-600, &600), expected);
+    assert_error(pool.try_swap_and_add(user, &-600, &600, &true, &1_000, &-1, &0), expected);
+    assert_error(pool.try_flash(user, user, &-1, &0, &Bytes::new(&fixture.env)), expected);
+    assert_error(pool.try_set_withdrawal_queue(&fixture.admin, &-1, &0), expected);
+}
+
+#[test]
+fn zero_amounts_are_rejected_where_something_must_move() {
+    let fixture = pool();
+    let (pool, user) = (&fixture.pool, &fixture.user);
+    let expected = PoolError::AmountNotPositive;
+
+    assert_error(pool.try_remove_liquidity(user, &-600, &600, &0, &0), expected);
+    assert_error(pool.try_decrease_liquidity_and_collect(user, &-600, &600, &0, &0), expected);
+    assert_error(pool.try_remove_liquidity_by_id(user, &1, &0, &0), expected);
+    assert_error(pool.try_request_withdrawal(user, &-600, &600, &0, &0), expected);
+    assert_error(pool.try_place_limit_order(user, &600, &true, &0, &0), expected);
+    assert_error(pool.try_swap_and_add(user, &-600, &600, &true, &0, &0, &0), expected);
+    assert_error(pool.try_sqrt_price_from_reserves(&0, &5), expected);
+    assert_error(pool.try_fund_rewards(&fixture.admin, &fixture.token0, &0, &100), expected);
+}
+
+#[test]
+fn amounts_above_the_cap_are_rejected() {
+    let fixture = pool();
+    let (pool, user) = (&fixture.pool, &fixture.user);
+    let expected = PoolError::AmountAboveCap;
+
+    assert_error(pool.try_quote_swap(&TOO_BIG, &true, &0), expected);
+    assert_error(pool.try_swap_advanced(user, &TOO_BIG, &0, &0, &true, &0, &0), expected);
+    assert_error(pool.try_swap_with_params(user, &SwapParams::new(TOO_BIG, true)), expected);
+    assert_error(pool.try_add_liquidity_advanced(user, &-600, &600, &TOO_BIG, &5, &0, &0, &0), expected);
+    assert_error(pool.try_add_liquidity_by_id(user, &1, &5, &TOO_BIG, &0, &0, &0), expected);
+    assert_error(pool.try_remove_liquidity(user, &-600, &600, &TOO_BIG, &0), expected);
+    assert_error(pool.try_request_withdrawal(user, &-600, &600, &TOO_BIG, &0), expected);
+    assert_error(pool.try_place_limit_order(user, &600, &true, &TOO_BIG, &0), expected);
+    assert_error(pool.try_flash(user, user, &TOO_BIG, &0, &Bytes::new(&fixture.env)), expected);
+    assert_error(
+        pool.try_swap_exact_in_multi(user, &vec![&fixture.env, pool.address.clone()], &fixture.token0, &TOO_BIG, &0, &0),
+        expected,
+    );
+}
+
+// ============================================================
+// TICK RANGES
+// ============================================================
+
+#[test]
+fn inverted_empty_and_out_of_range_ticks_are_rejected() {
+    let fixture = pool();
+    let (pool, user) = (&fixture.pool, &fixture.user);
+    let expected = PoolError::InvalidTickRange;
+
+    for (lower, upper) in [(600, -600), (600, 600), (-900_000, 600), (-600, 900_000)] {
+        assert_error(pool.try_get_position(user, &lower, &upper), expected);
+        assert_error(pool.try_add_liquidity_advanced(user, &lower, &upper, &5, &5, &0, &0, &0), expected);
+        assert_error(pool.try_remove_liquidity(user, &lower, &upper, &1, &0), expected);
+        assert_error(pool.try_collect(user, &lower, &upper, &1, &1, &0), expected);
+        assert_error(pool.try_collect_owed(user, &lower, &upper, &0), expected);
+        assert_error(pool.try_collect_rewards(user, &lower, &upper), expected);
+        assert_error(pool.try_get_pending_rewards(user, &lower, &upper), expected);
+        assert_error(pool.try_close_position(user, &lower, &upper, &0), expected);
+        assert_error(pool.try_open_position(user, &lower, &upper), expected);
+        assert_error(pool.try_request_withdrawal(user, &lower, &upper, &1, &0), expected);
+        assert_error(pool.try_cancel_withdrawal(user, &lower, &upper), expected);
+        assert_error(pool.try_break_even_fees(&lower, &upper, &100), expected);
+        assert_error(pool.try_price_to_activate(&lower, &upper), expected);
+    }
+}
+
+#[test]
+fn misaligned_ticks_are_rejected_wherever_a_range_is_keyed() {
+    let fixture = pool();
+    let (pool, user) = (&fixture.pool, &fixture.user);
+    let expected = PoolError::InvalidTick;
+
+    // [-10, 10] used to snap onto [-60, 0]; that range must stay untouched
+    let snapped = fixture.add(-60, 0, 1_000_000);
+    for (lower, upper) in [(-10, 10), (-60, 10), (-10, 0), (-601, 600)] {
+        assert_error(pool.try_add_liquidity_advanced(user, &lower, &upper, &5, &5, &0, &0, &0), expected);
+        assert_error(pool.try_remove_liquidity(user, &lower, &upper, &1, &0), expected);
+        assert_error(pool.try_collect(user, &lower, &upper, &1, &1, &0), expected);
+        assert_error(pool.try_collect_owed(user, &lower, &upper, &0), expected);
+        assert_error(pool.try_decrease_liquidity_and_collect(user, &lower, &upper, &1, &0), expected);
+        assert_error(pool.try_close_position(user, &lower, &upper, &0), expected);
+        assert_error(pool.try_get_pending_rewards(user, &lower, &upper), expected);
+        assert_error(pool.try_calc_amounts_for_liquidity(&1_000, &lower, &upper), expected);
+    }
+    assert_eq!(pool.get_position(user, &-60, &0).liquidity, snapped);
+}
+
+#[test]
+fn bad_ranges_leave_tick_accounting_untouched() {
+    let fixture = pool();
+    let (pool, user) = (&fixture.pool, &fixture.user);
+    let before = pool.get_pool_state().liquidity;
+
+    for (lower, upper) in [(600, -600), (-600, -600), (1_200, 1_200)] {
+        assert_error(
+            pool.try_add_liquidity(user, &fixture.token0, &fixture.token1, &5, &5, &0, &0, &lower, &upper, &0),
+            PoolError::InvalidTickRange,
+        );
+    }
+    assert_eq!(pool.get_tick_info(&-600).liquidity_net, pool.get_tick_info(&600).liquidity_net.abs());
+    assert_eq!(pool.get_tick_info(&1_200).liquidity_gross, 0);
+    assert_eq!(pool.get_pool_state().liquidity, before);
+}
+
+#[test]
+fn ticks_one_past_the_bounds_are_rejected() {
+    let fixture = Fixture::at_tick(30, 1, 0);
+    let (pool, user) = (&fixture.pool, &fixture.user);
+
+    for (lower, upper) in [(MIN_TICK - 1, 0), (0, MAX_TICK + 1), (MIN_TICK - 1, MAX_TICK + 1)] {
+        assert_error(
+            pool.try_add_liquidity_advanced(user, &lower, &upper, &5, &5, &0, &0, &0),
+            PoolError::InvalidTickRange,
+        );
+    }
+    // The bounds themselves are a valid range
+    assert_eq!(pool.get_position(user, &MIN_TICK, &MAX_TICK).liquidity, 0);
+}
+
+// ============================================================
+// PRICES
+// ============================================================
+
+#[test]
+fn sqrt_prices_outside_the_tick_range_are_rejected() {
+    let fixture = Fixture::uninitialized();
+    let pool = &fixture.pool;
+    let expected = PoolError::SqrtPriceOutOfBounds;
+
+    for sqrt_price in [0, 1, u128::MAX] {
+        assert_error(
+            pool.try_initialize(&fixture.admin, &fixture.token0, &fixture.token1, &30, &0, &sqrt_price, &0, &60),
+            expected,
+        );
+        assert_error(pool.try_nearest_valid_ticks(&sqrt_price), expected);
+        assert_error(pool.try_price_from_sqrt(&sqrt_price, &7, &7), expected);
+    }
+}
+
+// ============================================================
+// ADDRESSES
+// ============================================================
+
+#[test]
+fn the_pool_itself_is_rejected_as_a_counterparty() {
+    let fixture = pool();
+    let (pool, user) = (&fixture.pool, &fixture.user);
+    let this: &Address = &pool.address;
+    let expected = PoolError::SelfAddress;
+
+    assert_error(
+        pool.try_initialize(&fixture.admin, this, &fixture.token1, &30, &0, &(1u128 << 64), &0, &60),
+        expected,
+    );
+    assert_error(pool.try_swap_with_params(user, &SwapParams::new(1_000, true).with_recipient(this.clone())), expected);
+    assert_error(pool.try_mint_with_params(user, &MintParams::new(-600, 600, 5, 5).with_recipient(this.clone())), expected);
+    assert_error(pool.try_open_position(this, &-600, &600), expected);
+    assert_error(pool.try_place_limit_order(this, &600, &true, &1_000, &0), expected);
+    assert_error(pool.try_flash(user, this, &1_000, &0, &Bytes::new(&fixture.env)), expected);
+    assert_error(pool.try_collect_to(user, &-600, &600, this, &1, &1), expected);
+    assert_error(pool.try_propose_admin(&fixture.admin, this), expected);
+    assert_error(pool.try_fund_rewards(&fixture.admin, this, &1_000, &100), expected);
+}
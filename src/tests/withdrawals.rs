@@ -0,0 +1,127 @@
+//! Withdrawal queue: the per-interval threshold shared by direct removals
+//! and processed tranches.
+
+use soroban_sdk::testutils::Ledger;
+
+use super::{assert_error, Fixture};
+use crate::error::PoolError;
+
+const THRESHOLD: i128 = 1_000_000;
+const INTERVAL: u64 = 3_600;
+
+/// Pool with the queue enabled and a position well above the threshold
+fn queued() -> (Fixture<'static>, i128) {
+    let fx = Fixture::new();
+    let liquidity = fx.add(-600, 600, 100_000_000);
+    assert!(liquidity > 3 * THRESHOLD);
+    fx.pool.set_withdrawal_queue(&fx.admin, &THRESHOLD, &INTERVAL);
+    (fx, liquidity)
+}
+
+fn advance(fx: &Fixture, secs: u64) {
+    fx.env.ledger().with_mut(|l| l.timestamp += secs);
+}
+
+fn liquidity_of(fx: &Fixture) -> i128 {
+    fx.pool.get_position(&fx.user, &-600, &600).liquidity
+}
+
+#[test]
+fn direct_removals_share_one_allowance_per_interval() {
+    let (fx, liquidity) = queued();
+
+    fx.pool.remove_liquidity(&fx.user, &-600, &600, &(THRESHOLD / 2), &0);
+    fx.pool.remove_liquidity(&fx.user, &-600, &600, &(THRESHOLD / 2), &0);
+    assert_error(
+        fx.pool.try_remove_liquidity(&fx.user, &-600, &600, &1, &0),
+        PoolError::WithdrawalAboveThreshold,
+    );
+
+    advance(&fx, INTERVAL - 1);
+    assert_error(
+        fx.pool.try_remove_liquidity(&fx.user, &-600, &600, &1, &0),
+        PoolError::WithdrawalAboveThreshold,
+    );
+
+    advance(&fx, 1);
+    fx.pool.remove_liquidity(&fx.user, &-600, &600, &THRESHOLD, &0);
+    assert_eq!(liquidity_of(&fx), liquidity - 2 * THRESHOLD);
+}
+
+#[test]
+fn single_removal_above_threshold_fails() {
+    let (fx, _) = queued();
+
+    assert_error(
+        fx.pool.try_remove_liquidity(&fx.user, &-600, &600, &(THRESHOLD + 1), &0),
+        PoolError::WithdrawalAboveThreshold,
+    );
+    assert_error(
+        fx.pool.try_close_position(&fx.user, &-600, &600, &0),
+        PoolError::WithdrawalAboveThreshold,
+    );
+}
+
+#[test]
+fn queued_withdrawal_is_released_in_tranches() {
+    let (fx, liquidity) = queued();
+    let request = 5 * THRESHOLD / 2;
+    fx.pool.request_withdrawal(&fx.user, &-600, &600, &request, &0);
+
+    let before = fx.balance(&fx.token0, &fx.user);
+    fx.pool.process_withdrawal(&fx.user, &-600, &600);
+    assert_eq!(liquidity_of(&fx), liquidity - THRESHOLD);
+    assert!(fx.balance(&fx.token0, &fx.user) > before);
+
+    assert_error(fx.pool.try_process_withdrawal(&fx.user, &-600, &600), PoolError::WithdrawalNotReady);
+
+    advance(&fx, INTERVAL);
+    fx.pool.process_withdrawal(&fx.user, &-600, &600);
+    assert_eq!(liquidity_of(&fx), liquidity - 2 * THRESHOLD);
+
+    advance(&fx, INTERVAL);
+    fx.pool.process_withdrawal(&fx.user, &-600, &600);
+    assert_eq!(liquidity_of(&fx), liquidity - request);
+    assert!(fx.pool.get_withdrawal_request(&fx.user, &-600, &600).is_none());
+}
+
+#[test]
+fn tranches_and_direct_removals_cannot_exceed_threshold() {
+    let (fx, liquidity) = queued();
+    fx.pool.request_withdrawal(&fx.user, &-600, &600, &(3 * THRESHOLD), &0);
+
+    // A processed tranche uses up the allowance for direct removals
+    fx.pool.process_withdrawal(&fx.user, &-600, &600);
+    assert_error(
+        fx.pool.try_remove_liquidity(&fx.user, &-600, &600, &1, &0),
+        PoolError::WithdrawalAboveThreshold,
+    );
+
+    // A direct removal shrinks the next tranche, and a used up allowance
+    // blocks it until the window expires
+    advance(&fx, INTERVAL);
+    fx.pool.remove_liquidity(&fx.user, &-600, &600, &(THRESHOLD / 4), &0);
+    fx.pool.process_withdrawal(&fx.user, &-600, &600);
+    assert_eq!(liquidity_of(&fx), liquidity - 2 * THRESHOLD);
+
+    advance(&fx, INTERVAL);
+    fx.pool.remove_liquidity(&fx.user, &-600, &600, &THRESHOLD, &0);
+    assert_error(fx.pool.try_process_withdrawal(&fx.user, &-600, &600), PoolError::WithdrawalNotReady);
+}
+
+#[test]
+fn id_positions_are_rate_limited() {
+    let (fx, _) = queued();
+    let id = fx.pool.open_position(&fx.user, &-600, &600);
+    let (liquidity, _, _) = fx.pool.add_liquidity_by_id(&fx.user, &id, &100_000_000, &100_000_000, &0, &0, &0);
+    assert!(liquidity > THRESHOLD);
+
+    fx.pool.remove_liquidity_by_id(&fx.user, &id, &THRESHOLD, &0);
+    assert_error(
+        fx.pool.try_remove_liquidity_by_id(&fx.user, &id, &1, &0),
+        PoolError::WithdrawalAboveThreshold,
+    );
+
+    advance(&fx, INTERVAL);
+    fx.pool.remove_liquidity_by_id(&fx.user, &id, &THRESHOLD, &0);
+}
@@ -0,0 +1,67 @@
+//! # Test Utilities
+//!
+//! Entrypoints for integration test suites only. Compiled in with the
+//! `testutils` feature, which must never reach the release WASM.
+
+#[cfg(target_family = "wasm")]
+compile_error!("the `testutils` feature must not be enabled for WASM builds");
+
+use soroban_sdk::{contractimpl, panic_with_error, Env};
+
+use crate::error::PoolError;
+use crate::events::emit_pool_init;
+use crate::math::get_tick_at_sqrt_ratio;
+use crate::oracle::write_observation;
+use crate::storage::{
+    clear_registered_keys, clear_reward_schedule, clear_twap_observations, clear_vesting_schedule,
+    init_pool_state, read_pool_config, read_pool_state, read_tick_info, write_tick_info,
+};
+use crate::validate::{self, ensure};
+use crate::{BelugaSwap, BelugaSwapClient};
+
+#[contractimpl]
+impl BelugaSwap {
+    /// Reset the pool to a fresh price and tick spacing without redeploying
+    ///
+    /// Clears pool state, fee accumulators, every registered tick and
+    /// position, queued withdrawals, oracle history, the vesting schedule and
+    /// the reward stream. Pool config (admin, tokens, fees) is kept. Token
+    /// balances held by the contract are not touched.
+    pub fn reset_pool(env: Env, sqrt_price_x64: u128, tick_spacing: i32) {
+        ensure(&env, validate::sqrt_price_in_bounds(sqrt_price_x64));
+
+        let config = read_pool_config(&env);
+        config.admin.require_auth();
+
+        if tick_spacing <= 0 {
+            panic_with_error!(env, PoolError::InvalidTickSpacing);
+        }
+
+        let pool = read_pool_state(&env);
+        let current_tick = get_tick_at_sqrt_ratio(&env, sqrt_price_x64);
+
+        clear_registered_keys(&env);
+        clear_twap_observations(&env);
+        clear_vesting_schedule(&env);
+        clear_reward_schedule(&env);
+
+        init_pool_state(&env, sqrt_price_x64, current_tick, tick_spacing, pool.token0, pool.token1);
+        write_observation(&env, &mut read_pool_state(&env));
+
+        emit_pool_init(&env, sqrt_price_x64, current_tick, tick_spacing);
+    }
+
+    /// Overwrite a tick's liquidity_gross and liquidity_net
+    /// 
+    /// Leaves fee growth and the bitmap untouched, so suites can put a tick
+    /// out of step with its positions and exercise the tick accounting
+    /// audit.
+    pub fn corrupt_tick_liquidity(env: Env, tick: i32, liquidity_gross: i128, liquidity_net: i128) {
+        read_pool_config(&env).admin.require_auth();
+
+        let mut info = read_tick_info(&env, tick);
+        info.liquidity_gross = liquidity_gross;
+        info.liquidity_net = liquidity_net;
+        write_tick_info(&env, tick, &info);
+    }
+}
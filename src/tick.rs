@@ -1,8 +1,10 @@
-use soroban_sdk::Env;
+use soroban_sdk::{Env, Vec};
 
-use crate::constants::{MIN_TICK, MAX_TICK, MAX_TICK_SEARCH_STEPS};
+use crate::constants::{MIN_TICK, MAX_TICK};
 use crate::storage::{read_tick_info, write_tick_info};
-use crate::math::snap_tick_to_spacing;
+use crate::math::{get_sqrt_ratio_at_tick, get_tick_at_sqrt_ratio, snap_tick_to_spacing};
+use crate::tick_bitmap::{next_initialized_tick_within_one_word, set_tick_initialized};
+use crate::validate::{self, ensure};
 
 // ============================================================
 // TICK UPDATE (Called when modifying liquidity)
@@ -22,10 +24,14 @@ use crate::math::snap_tick_to_spacing;
 /// * `liquidity_delta` - Liquidity change (positive = add, negative = remove)
 /// * `fee_growth_global_0` - Current global fee growth for token0
 /// * `fee_growth_global_1` - Current global fee growth for token1
+/// * `reward_growth_global` - Current global reward growth
 /// * `upper` - True if this is an upper tick boundary
 /// 
 /// # Returns
 /// True if the tick was flipped from uninitialized to initialized (or vice versa)
+/// 
+/// # Panics
+/// `TickAccountingMismatch` if removing more liquidity than the tick holds
 pub fn update_tick(
     env: &Env,
     tick: i32,
@@ -33,6 +39,7 @@ pub fn update_tick(
     liquidity_delta: i128,
     fee_growth_global_0: u128,
     fee_growth_global_1: u128,
+    reward_growth_global: u128,
     upper: bool,
 ) -> bool {
     let mut info = read_tick_info(env, tick);
@@ -41,7 +48,9 @@ pub fn update_tick(
     let liquidity_gross_after = if liquidity_delta > 0 {
         liquidity_gross_before.saturating_add(liquidity_delta)
     } else {
-        liquidity_gross_before.saturating_sub(liquidity_delta.abs())
+        // Drifted accounting must not be clamped to zero and carried on
+        ensure(env, validate::tick_covers_removal(liquidity_gross_before, liquidity_delta.abs()));
+        liquidity_gross_before - liquidity_delta.abs()
     };
     
     // Check if tick was flipped
@@ -54,10 +63,12 @@ pub fn update_tick(
         if current_tick >= tick {
             info.fee_growth_outside_0 = fee_growth_global_0;
             info.fee_growth_outside_1 = fee_growth_global_1;
+            info.reward_growth_outside = reward_growth_global;
         } else {
             // All fees were earned ABOVE this tick
             info.fee_growth_outside_0 = 0;
             info.fee_growth_outside_1 = 0;
+            info.reward_growth_outside = 0;
         }
         info.initialized = true;
     }
@@ -89,6 +100,11 @@ pub fn update_tick(
 
 /// Find the next initialized tick in the given direction
 /// 
+/// Looks at a single tick bitmap word: going down (`zero_for_one`) it finds
+/// the greatest initialized tick <= `current_tick`, going up the smallest
+/// initialized tick > `current_tick`. If the word has none, the word
+/// boundary is returned uninitialized and the swap loop steps to it.
+/// 
 /// # Arguments
 /// * `env` - Soroban environment
 /// * `current_tick` - Starting tick
@@ -96,42 +112,25 @@ pub fn update_tick(
 /// * `zero_for_one` - Direction (true = search left, false = search right)
 /// 
 /// # Returns
-/// The next initialized tick, or current_tick if none found
+/// (next_tick, initialized)
 pub fn find_next_initialized_tick(
     env: &Env,
     current_tick: i32,
     tick_spacing: i32,
     zero_for_one: bool,
-) -> i32 {
-    if tick_spacing <= 0 {
-        return current_tick;
-    }
-
-    let step = if zero_for_one { -tick_spacing } else { tick_spacing };
-
-    // Start from aligned tick
-    let mut tick = snap_tick_to_spacing(current_tick, tick_spacing);
-    
-    // Move to next tick boundary
-    tick = tick.saturating_add(step);
-
-    for _ in 0..MAX_TICK_SEARCH_STEPS {
-        // Check bounds
-        if !(MIN_TICK..=MAX_TICK).contains(&tick) {
-            return current_tick;
-        }
+) -> (i32, bool) {
+    next_initialized_tick_within_one_word(env, current_tick, tick_spacing, zero_for_one)
+}
 
+/// Rebuild bitmap bits for the given ticks from their stored TickInfo
+/// 
+/// Pools created before the tick bitmap existed have initialized ticks with
+/// no bit set; running this over those ticks migrates them. Idempotent.
+pub fn sync_tick_bitmap(env: &Env, ticks: &Vec<i32>, tick_spacing: i32) {
+    for tick in ticks.iter() {
         let info = read_tick_info(env, tick);
-        
-        // Found initialized tick with liquidity
-        if info.initialized && info.liquidity_gross > 0 {
-            return tick;
-        }
-        
-        tick = tick.saturating_add(step);
+        set_tick_initialized(env, tick, tick_spacing, info.liquidity_gross > 0);
     }
-
-    current_tick
 }
 
 // ============================================================
@@ -150,6 +149,7 @@ pub fn find_next_initialized_tick(
 /// * `tick` - Tick being crossed
 /// * `fee_growth_global_0` - Current global fee growth for token0
 /// * `fee_growth_global_1` - Current global fee growth for token1
+/// * `reward_growth_global` - Current global reward growth
 /// 
 /// # Returns
 /// The liquidity_net to add/subtract from active liquidity
@@ -158,6 +158,7 @@ pub fn cross_tick(
     tick: i32,
     fee_growth_global_0: u128,
     fee_growth_global_1: u128,
+    reward_growth_global: u128,
 ) -> i128 {
     let mut info = read_tick_info(env, tick);
     
@@ -166,6 +167,7 @@ pub fn cross_tick(
     // This is what makes Uniswap V3 fee tracking work!
     info.fee_growth_outside_0 = fee_growth_global_0.wrapping_sub(info.fee_growth_outside_0);
     info.fee_growth_outside_1 = fee_growth_global_1.wrapping_sub(info.fee_growth_outside_1);
+    info.reward_growth_outside = reward_growth_global.wrapping_sub(info.reward_growth_outside);
     
     write_tick_info(env, tick, &info);
     
@@ -252,6 +254,35 @@ pub fn get_fee_growth_inside(
     (fee_growth_inside_0, fee_growth_inside_1)
 }
 
+/// Calculate reward growth inside a tick range
+/// 
+/// Same formula as `get_fee_growth_inside`, applied to the liquidity
+/// mining accumulator: inside = global - below - above.
+pub fn get_reward_growth_inside(
+    env: &Env,
+    lower_tick: i32,
+    upper_tick: i32,
+    current_tick: i32,
+    reward_growth_global: u128,
+) -> u128 {
+    let lower_outside = read_tick_info(env, lower_tick).reward_growth_outside;
+    let upper_outside = read_tick_info(env, upper_tick).reward_growth_outside;
+
+    let below = if current_tick >= lower_tick {
+        lower_outside
+    } else {
+        reward_growth_global.wrapping_sub(lower_outside)
+    };
+
+    let above = if current_tick < upper_tick {
+        upper_outside
+    } else {
+        reward_growth_global.wrapping_sub(upper_outside)
+    };
+
+    reward_growth_global.wrapping_sub(below).wrapping_sub(above)
+}
+
 // ============================================================
 // TICK VALIDATION
 // ============================================================
@@ -259,7 +290,7 @@ pub fn get_fee_growth_inside(
 /// Check if a tick is within valid range
 #[inline]
 pub fn is_valid_tick(tick: i32) -> bool {
-    tick >= MIN_TICK && tick <= MAX_TICK
+    (MIN_TICK..=MAX_TICK).contains(&tick)
 }
 
 /// Check if a tick is properly aligned to spacing
@@ -268,4 +299,50 @@ pub fn is_valid_tick(tick: i32) -> bool {
 pub fn is_aligned_tick(tick: i32, tick_spacing: i32) -> bool {
     if tick_spacing <= 0 { return false; }
     tick % tick_spacing == 0
-}
\ No newline at end of file
+}
+
+/// Find the spacing-aligned ticks bracketing a sqrt price
+/// 
+/// # Returns
+/// (lower, upper) with `sqrt(lower) <= sqrt_price_x64 < sqrt(upper)`, or
+/// (tick, tick) if the price sits exactly on a valid tick. Bounds are kept
+/// inside [MIN_TICK, MAX_TICK], so the bracket may be one-sided at the edges.
+pub fn nearest_valid_ticks(env: &Env, sqrt_price_x64: u128, tick_spacing: i32) -> (i32, i32) {
+    let tick = get_tick_at_sqrt_ratio(env, sqrt_price_x64);
+    let lower = snap_tick_to_spacing(env, tick, tick_spacing);
+
+    if lower == tick && get_sqrt_ratio_at_tick(env, tick) == sqrt_price_x64 {
+        return (tick, tick);
+    }
+
+    let mut lower = lower;
+    let mut upper = lower.saturating_add(tick_spacing);
+
+    if lower < MIN_TICK {
+        lower = lower.saturating_add(tick_spacing);
+    }
+    if upper > MAX_TICK {
+        upper = upper.saturating_sub(tick_spacing);
+    }
+
+    (lower, upper)
+}
+
+/// Spacing-aligned range of `tick_delta` ticks on each side of `current_tick`
+/// 
+/// Centered on the aligned tick at or below `current_tick`, with the half
+/// width rounded up to whole spacings, so the range always brackets the
+/// current tick. Bounds are clamped to the aligned tick range, which only
+/// breaks symmetry at the extremes.
+pub fn preset_range(env: &Env, current_tick: i32, tick_spacing: i32, tick_delta: i32) -> (i32, i32) {
+    let base = snap_tick_to_spacing(env, current_tick, tick_spacing);
+    let half = (tick_delta + tick_spacing - 1) / tick_spacing * tick_spacing;
+
+    let min_aligned = -snap_tick_to_spacing(env, -MIN_TICK, tick_spacing);
+    let max_aligned = snap_tick_to_spacing(env, MAX_TICK, tick_spacing);
+
+    (
+        base.saturating_sub(half).max(min_aligned),
+        base.saturating_add(half).min(max_aligned),
+    )
+}
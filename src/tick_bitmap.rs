@@ -0,0 +1,116 @@
+use soroban_sdk::{Env, Vec};
+
+use crate::constants::{MIN_TICK, MAX_TICK};
+use crate::storage::{read_tick_bitmap_word, write_tick_bitmap_word};
+
+// ============================================================
+// TICK BITMAP
+// ============================================================
+//
+// One bit per spacing-aligned tick, 128 ticks per u128 word, keyed by
+// word index. Bit `b` of word `w` is the tick `(w * 128 + b) * tick_spacing`.
+
+/// Number of compressed ticks per bitmap word
+const WORD_BITS: i32 = 128;
+
+/// Word index and bit index of a compressed tick
+#[inline]
+fn position(compressed: i32) -> (i32, u32) {
+    (compressed.div_euclid(WORD_BITS), compressed.rem_euclid(WORD_BITS) as u32)
+}
+
+/// Compressed tick back to a real tick, clamped to the valid range
+#[inline]
+fn decompress(compressed: i64, tick_spacing: i32) -> i32 {
+    (compressed * tick_spacing as i64).clamp(MIN_TICK as i64, MAX_TICK as i64) as i32
+}
+
+/// Set or clear the bitmap bit for a spacing-aligned tick
+///
+/// Called when `update_tick` reports the tick flipped between having and
+/// not having liquidity_gross. Setting the target state (rather than
+/// toggling) keeps pools migrated through `sync_tick_bitmap` consistent.
+/// Only writes storage if the bit actually changes.
+pub fn set_tick_initialized(env: &Env, tick: i32, tick_spacing: i32, initialized: bool) {
+    let (word_pos, bit_pos) = position(tick.div_euclid(tick_spacing));
+    let word = read_tick_bitmap_word(env, word_pos);
+    let mask = 1u128 << bit_pos;
+
+    let updated = if initialized { word | mask } else { word & !mask };
+    if updated != word {
+        write_tick_bitmap_word(env, word_pos, updated);
+    }
+}
+
+/// Index of the bitmap word holding a spacing-aligned tick
+pub fn word_position(tick: i32, tick_spacing: i32) -> i32 {
+    position(tick.div_euclid(tick_spacing)).0
+}
+
+/// Lowest tick covered by bitmap word `word_pos`, clamped to the valid range
+pub fn word_start_tick(word_pos: i32, tick_spacing: i32) -> i32 {
+    decompress(word_pos as i64 * WORD_BITS as i64, tick_spacing)
+}
+
+/// Ticks whose bits are set in bitmap word `word_pos`, in ascending order
+pub fn ticks_in_word(env: &Env, word_pos: i32, word: u128, tick_spacing: i32) -> Vec<i32> {
+    let mut ticks = Vec::new(env);
+    let mut remaining = word;
+
+    while remaining != 0 {
+        let bit = remaining.trailing_zeros();
+        remaining &= remaining - 1;
+        ticks.push_back(decompress(word_pos as i64 * WORD_BITS as i64 + bit as i64, tick_spacing));
+    }
+
+    ticks
+}
+
+/// Find the next initialized tick within the bitmap word holding `tick`
+///
+/// Going down (`lte`) searches ticks <= `tick`; going up searches ticks
+/// strictly > `tick`. If no bit is set in the word, the word boundary is
+/// returned with `initialized = false` so the caller can step to it and
+/// search again. Results are clamped to [MIN_TICK, MAX_TICK].
+///
+/// # Returns
+/// (next_tick, initialized)
+pub fn next_initialized_tick_within_one_word(
+    env: &Env,
+    tick: i32,
+    tick_spacing: i32,
+    lte: bool,
+) -> (i32, bool) {
+    let compressed = tick.div_euclid(tick_spacing);
+
+    let (next, initialized) = if lte {
+        let (word_pos, bit_pos) = position(compressed);
+        // All bits at or below bit_pos
+        let mask = u128::MAX >> (127 - bit_pos);
+        let masked = read_tick_bitmap_word(env, word_pos) & mask;
+
+        if masked != 0 {
+            let msb = 127 - masked.leading_zeros();
+            (compressed as i64 - (bit_pos - msb) as i64, true)
+        } else {
+            (compressed as i64 - bit_pos as i64, false)
+        }
+    } else {
+        let (word_pos, bit_pos) = position(compressed + 1);
+        // All bits at or above bit_pos
+        let mask = u128::MAX << bit_pos;
+        let masked = read_tick_bitmap_word(env, word_pos) & mask;
+
+        if masked != 0 {
+            let lsb = masked.trailing_zeros();
+            (compressed as i64 + 1 + (lsb - bit_pos) as i64, true)
+        } else {
+            (compressed as i64 + 1 + (127 - bit_pos) as i64, false)
+        }
+    };
+
+    let next_tick = decompress(next, tick_spacing);
+
+    // A clamped result is a range boundary, not the tick the bit refers to
+    (next_tick, initialized && next_tick as i64 == next * tick_spacing as i64)
+}
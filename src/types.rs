@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, Symbol};
+use soroban_sdk::{contracttype, Address, Symbol, Vec};
 
 // ============================================================
 // POOL CONFIGURATION
@@ -10,14 +10,58 @@ use soroban_sdk::{contracttype, Address, Symbol};
 pub struct PoolConfig {
     /// Admin address with special privileges
     pub admin: Address,
-    /// First token address (as provided by user)
+    /// Lower token address; always equal to `PoolState::token0`
     pub token_a: Address,
-    /// Second token address (as provided by user)
+    /// Higher token address; always equal to `PoolState::token1`
     pub token_b: Address,
     /// Swap fee in basis points (e.g., 30 = 0.30%)
     pub fee_bps: u32,
     /// Protocol fee in basis points (percentage of LP fees)
     pub protocol_fee_bps: u32,
+    /// Fee on deposited principal in basis points (goes to protocol)
+    pub deposit_fee_bps: u32,
+    /// Fee on withdrawn principal in basis points (goes to protocol)
+    pub withdraw_fee_bps: u32,
+    /// Canonical fee tier, or `Custom` for any other fee/spacing pair
+    pub fee_tier: FeeTier,
+}
+
+/// Canonical swap fee and tick spacing pairs
+/// 
+/// Routers can rely on a tiered pool's fee and spacing without reading
+/// them. Pools are always initialized with a canonical tier; `Custom`
+/// marks a pool whose fee `set_fee_bps` moved off its tier.
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeTier {
+    /// 5 bps fee, tick spacing 10 (stable pairs)
+    Low,
+    /// 30 bps fee, tick spacing 60
+    Medium,
+    /// 100 bps fee, tick spacing 200 (volatile pairs)
+    High,
+    /// Fee changed away from the tier of the pool's spacing
+    Custom,
+}
+
+impl FeeTier {
+    /// (fee_bps, tick_spacing) of a canonical tier, None for `Custom`
+    pub fn params(&self) -> Option<(u32, i32)> {
+        match self {
+            FeeTier::Low => Some((5, 10)),
+            FeeTier::Medium => Some((30, 60)),
+            FeeTier::High => Some((100, 200)),
+            FeeTier::Custom => None,
+        }
+    }
+
+    /// Canonical tier with exactly this fee and spacing, else `Custom`
+    pub fn from_params(fee_bps: u32, tick_spacing: i32) -> Self {
+        [FeeTier::Low, FeeTier::Medium, FeeTier::High]
+            .into_iter()
+            .find(|tier| tier.params() == Some((fee_bps, tick_spacing)))
+            .unwrap_or(FeeTier::Custom)
+    }
 }
 
 // ============================================================
@@ -44,10 +88,22 @@ pub struct PoolState {
     pub fee_growth_global_0: u128,
     /// Global fee growth for token1 (Q64.64 format)
     pub fee_growth_global_1: u128,
+    /// Global liquidity mining reward growth per unit of liquidity (Q64.64 format)
+    pub reward_growth_global: u128,
     /// Accumulated protocol fees for token0
     pub protocol_fees_0: u128,
     /// Accumulated protocol fees for token1
     pub protocol_fees_1: u128,
+    /// LP fees in token0 that rounded to zero fee growth (not credited to LPs)
+    pub zero_growth_fees_0: u128,
+    /// LP fees in token1 that rounded to zero fee growth (not credited to LPs)
+    pub zero_growth_fees_1: u128,
+    /// Sum of current_tick * seconds elapsed, up to `last_update_ts`
+    pub tick_cumulative: i128,
+    /// Ledger timestamp `tick_cumulative` was last brought up to date
+    pub last_update_ts: u64,
+    /// Id the next `open_position` will assign (ids start at 1)
+    pub next_position_id: u64,
 }
 
 // ============================================================
@@ -67,10 +123,72 @@ pub struct TickInfo {
     pub fee_growth_outside_0: u128,
     /// Fee growth outside this tick for token1
     pub fee_growth_outside_1: u128,
+    /// Reward growth outside this tick
+    pub reward_growth_outside: u128,
     /// Whether this tick is initialized
     pub initialized: bool,
 }
 
+// ============================================================
+// EVENT TYPES
+// ============================================================
+
+/// What changed the pool's active liquidity (carried by the `liq` event)
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiquidityChange {
+    /// In-range liquidity added to a position
+    Mint,
+    /// In-range liquidity removed from a position
+    Burn,
+    /// Initialized tick crossed during a swap
+    Cross,
+}
+
+/// Data of the `swap` event
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwapEvent {
+    /// Input taken from the caller, fee included
+    pub amount_in: i128,
+    /// Output sent to the recipient
+    pub amount_out: i128,
+    /// LP and protocol fee charged on the input
+    pub fee: i128,
+    /// Initialized ticks crossed
+    pub ticks_crossed: u32,
+    /// Input (exact input) or output (exact output) left unfilled
+    pub amount_remaining: i128,
+    /// Sqrt price after the swap
+    pub sqrt_price_x64: u128,
+    /// Current tick after the swap
+    pub tick: i32,
+}
+
+/// Data of the `add_liq` and `remove_liq` events
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LiquidityEvent {
+    /// Liquidity minted or burned
+    pub liquidity: i128,
+    /// Token0 paid in (add) or released to tokens owed (remove)
+    pub amount0: i128,
+    /// Token1 paid in (add) or released to tokens owed (remove)
+    pub amount1: i128,
+}
+
+/// Data of the `collect` event
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollectEvent {
+    /// Address the tokens were sent to
+    pub recipient: Address,
+    /// Token0 collected
+    pub amount0: u128,
+    /// Token1 collected
+    pub amount1: u128,
+}
+
 // ============================================================
 // POSITION
 // ============================================================
@@ -89,6 +207,41 @@ pub struct Position {
     pub tokens_owed_0: u128,
     /// Uncollected fees for token1
     pub tokens_owed_1: u128,
+    /// All token0 fees ever credited (not reduced by collects)
+    pub lifetime_fees_0: u128,
+    /// All token1 fees ever credited (not reduced by collects)
+    pub lifetime_fees_1: u128,
+    /// Reward growth inside at last update
+    pub reward_growth_inside_last: u128,
+    /// Unclaimed liquidity mining rewards
+    pub rewards_owed: u128,
+}
+
+/// Position addressed by a numeric id rather than (owner, lower, upper)
+/// 
+/// Several records may share the same owner and range; each keeps its own
+/// fee checkpoints.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct PositionRecord {
+    /// Account allowed to manage the position
+    pub owner: Address,
+    /// Lower tick (spacing-aligned)
+    pub lower_tick: i32,
+    /// Upper tick (spacing-aligned)
+    pub upper_tick: i32,
+    /// Liquidity and fee state
+    pub position: Position,
+}
+
+/// Limit order resting on a one-spacing id-based position
+/// 
+/// Stored under the id of the position it occupies.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LimitOrder {
+    /// Direction (true = sells token0 for token1, resting above the price)
+    pub zero_for_one: bool,
 }
 
 // ============================================================
@@ -109,6 +262,53 @@ pub struct PositionInfo {
     pub fees_owed_0: u128,
     /// Uncollected fees for token1
     pub fees_owed_1: u128,
+    /// Token0 fees earned over the position's lifetime, including pending
+    pub lifetime_fees_0: u128,
+    /// Token1 fees earned over the position's lifetime, including pending
+    pub lifetime_fees_1: u128,
+}
+
+/// Snapshot of everything the swap engine reads, returned by get_swap_context
+/// 
+/// Enough to replay `engine_swap` off-chain for any swap that stays within
+/// the returned bitmap words.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SwapContext {
+    /// Current sqrt price (Q64.64)
+    pub sqrt_price_x64: u128,
+    /// Current tick
+    pub current_tick: i32,
+    /// Active liquidity
+    pub liquidity: i128,
+    /// Tick spacing
+    pub tick_spacing: i32,
+    /// Swap fee in basis points
+    pub fee_bps: u32,
+    /// Protocol share of the swap fee in basis points
+    pub protocol_fee_bps: u32,
+    /// (word_pos, word) for the tick bitmap words around the current tick
+    pub bitmap_words: Vec<(i32, u128)>,
+    /// (tick, liquidity_net) for every initialized tick in those words
+    pub ticks: Vec<(i32, i128)>,
+}
+
+/// Pool summary returned by get_info
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct PoolInfo {
+    /// Fixed marker identifying a BelugaSwap pool (see `belugaswap_pool_marker`)
+    pub marker: Symbol,
+    /// Pool interface version
+    pub interface_version: u32,
+    /// Token0 address (sorted: lower address)
+    pub token0: Address,
+    /// Token1 address (sorted: higher address)
+    pub token1: Address,
+    /// Swap fee in basis points
+    pub fee_bps: u32,
+    /// Tick spacing for this pool
+    pub tick_spacing: i32,
 }
 
 /// Swap result returned by swap functions
@@ -119,10 +319,37 @@ pub struct SwapResult {
     pub amount_in: i128,
     /// Actual amount of output token received
     pub amount_out: i128,
+    /// LP and protocol fee charged on the input (included in `amount_in`)
+    pub fee_amount: i128,
+    /// Initialized ticks crossed
+    pub ticks_crossed: u32,
+    /// Part of the requested amount left unfilled: unspent input for exact
+    /// input, undelivered output for exact output. Beyond rounding dust,
+    /// nonzero only at the price limit or with `allow_partial`
+    pub amount_remaining: i128,
     /// Current tick after swap
     pub current_tick: i32,
     /// Current sqrt price after swap
     pub sqrt_price_x64: u128,
+    /// False if the swap stopped short of the requested amount (price
+    /// limit reached, liquidity ran out or dust input left over)
+    pub fully_filled: bool,
+}
+
+/// Result of `swap_and_add`
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SwapAndAddResult {
+    /// Liquidity credited to the position
+    pub liquidity: i128,
+    /// Token0 principal deposited
+    pub amount0: i128,
+    /// Token1 principal deposited
+    pub amount1: i128,
+    /// Token0 left with the owner (not swapped and not deposited)
+    pub refund0: i128,
+    /// Token1 left with the owner (not swapped and not deposited)
+    pub refund1: i128,
 }
 
 /// Preview result returned by preview_swap functions
@@ -143,11 +370,242 @@ pub struct PreviewResult {
     pub error_message: Option<Symbol>,
 }
 
+// ============================================================
+// ENTRYPOINT PARAMETERS
+// ============================================================
+
+/// Parameters for `swap_with_params`
+/// 
+/// Build with `SwapParams::new` and the `with_*` methods; zero/None optional
+/// fields reproduce `swap_advanced` defaults.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct SwapParams {
+    /// > 0 exact input, < 0 exact output (negated amount wanted)
+    pub amount_specified: i128,
+    /// Direction (true = token0 -> token1)
+    pub zero_for_one: bool,
+    /// Minimum output accepted (0 = no check beyond the engine's own)
+    pub min_amount_out: i128,
+    /// Maximum input charged, fee included (0 = no limit); bounds what an
+    /// exact-output swap may cost
+    pub max_amount_in: i128,
+    /// Price limit (0 = no limit)
+    pub sqrt_price_limit_x64: u128,
+    /// Ledger timestamp after which the swap reverts (0 = none)
+    pub deadline: u64,
+    /// Receiver of the output tokens (None = caller)
+    pub recipient: Option<Address>,
+    /// Accept a partial fill when liquidity runs out before the amount is
+    /// filled (false = revert with `IncompleteSwap`; stopping at
+    /// `sqrt_price_limit_x64` is always accepted)
+    pub allow_partial: bool,
+}
+
+impl SwapParams {
+    pub fn new(amount_specified: i128, zero_for_one: bool) -> Self {
+        Self {
+            amount_specified,
+            zero_for_one,
+            min_amount_out: 0,
+            max_amount_in: 0,
+            sqrt_price_limit_x64: 0,
+            deadline: 0,
+            recipient: None,
+            allow_partial: false,
+        }
+    }
+
+    pub fn with_min_amount_out(mut self, min_amount_out: i128) -> Self {
+        self.min_amount_out = min_amount_out;
+        self
+    }
+
+    pub fn with_max_amount_in(mut self, max_amount_in: i128) -> Self {
+        self.max_amount_in = max_amount_in;
+        self
+    }
+
+    pub fn with_sqrt_price_limit(mut self, sqrt_price_limit_x64: u128) -> Self {
+        self.sqrt_price_limit_x64 = sqrt_price_limit_x64;
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: u64) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn with_recipient(mut self, recipient: Address) -> Self {
+        self.recipient = Some(recipient);
+        self
+    }
+
+    pub fn with_allow_partial(mut self, allow_partial: bool) -> Self {
+        self.allow_partial = allow_partial;
+        self
+    }
+}
+
+/// Parameters for `mint_with_params`
+/// 
+/// Build with `MintParams::new` and the `with_*` methods; zero/None optional
+/// fields reproduce `add_liquidity_advanced` defaults.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct MintParams {
+    /// Lower tick (spacing-aligned)
+    pub lower_tick: i32,
+    /// Upper tick (spacing-aligned)
+    pub upper_tick: i32,
+    /// Token0 amount offered
+    pub amount0_desired: i128,
+    /// Token1 amount offered
+    pub amount1_desired: i128,
+    /// Minimum token0 deposited (slippage guard)
+    pub amount0_min: i128,
+    /// Minimum token1 deposited (slippage guard)
+    pub amount1_min: i128,
+    /// Ledger timestamp after which the mint reverts (0 = none)
+    pub deadline: u64,
+    /// Owner of the resulting position (None = payer)
+    pub recipient: Option<Address>,
+}
+
+impl MintParams {
+    pub fn new(lower_tick: i32, upper_tick: i32, amount0_desired: i128, amount1_desired: i128) -> Self {
+        Self {
+            lower_tick,
+            upper_tick,
+            amount0_desired,
+            amount1_desired,
+            amount0_min: 0,
+            amount1_min: 0,
+            deadline: 0,
+            recipient: None,
+        }
+    }
+
+    pub fn with_min_amounts(mut self, amount0_min: i128, amount1_min: i128) -> Self {
+        self.amount0_min = amount0_min;
+        self.amount1_min = amount1_min;
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: u64) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn with_recipient(mut self, recipient: Address) -> Self {
+        self.recipient = Some(recipient);
+        self
+    }
+}
+
+// ============================================================
+// LIQUIDITY MINING
+// ============================================================
+
+/// Stream of an external reward token to in-range liquidity
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct RewardSchedule {
+    /// Token paid out as rewards (never one of the pool's tokens)
+    pub token: Address,
+    /// Reward tokens streamed per second (Q64.64 format)
+    pub rate_x64: u128,
+    /// Timestamp the stream runs out
+    pub end: u64,
+    /// Timestamp rewards were last accrued into `reward_growth_global`
+    pub last_update: u64,
+}
+
+// ============================================================
+// PROTOCOL FEE VESTING
+// ============================================================
+
+/// Linear vesting schedule for collected protocol fees
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct VestingSchedule {
+    /// Address receiving released fees
+    pub beneficiary: Address,
+    /// Timestamp the current vesting window started
+    pub start: u64,
+    /// Length of the vesting window in seconds
+    pub duration: u64,
+    /// Token0 amount vesting in the current window
+    pub amount_0: u128,
+    /// Token1 amount vesting in the current window
+    pub amount_1: u128,
+    /// Token0 already released from the current window
+    pub released_0: u128,
+    /// Token1 already released from the current window
+    pub released_1: u128,
+}
+
+// ============================================================
+// RESERVE TRACKING
+// ============================================================
+
+/// Token balances the pool expects to hold, kept for rebasing tokens
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct TrackedReserves {
+    /// Expected token0 balance
+    pub reserve0: i128,
+    /// Expected token1 balance
+    pub reserve1: i128,
+}
+
+// ============================================================
+// WITHDRAWAL QUEUE
+// ============================================================
+
+/// Withdrawal queue settings (threshold 0 = queue disabled)
+#[contracttype]
+#[derive(Clone, Debug, Default)]
+pub struct WithdrawalQueueConfig {
+    /// Largest liquidity amount removable at once; bigger exits are queued
+    pub threshold: i128,
+    /// Minimum seconds between two processed tranches
+    pub interval: u64,
+}
+
+/// Pending queued withdrawal for a position
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct WithdrawalRequest {
+    /// Liquidity still to be withdrawn
+    pub remaining: i128,
+    /// Earliest timestamp the next tranche can be processed
+    pub next_release: u64,
+}
+
+/// Liquidity removed from a position in the current queue interval
+/// 
+/// Direct removals and processed tranches share one `threshold` allowance
+/// per `interval`; the window opens with the first removal after the
+/// previous one has expired.
+#[contracttype]
+#[derive(Clone, Debug, Default)]
+pub struct WithdrawalWindow {
+    /// Timestamp the window opened
+    pub start: u64,
+    /// Liquidity removed since `start`
+    pub removed: i128,
+}
+
 // ============================================================
 // TWAP TYPES
 // ============================================================
 
 /// TWAP observation data
+/// 
+/// Captures the pool as it stood at `timestamp`, before any changes made
+/// in that ledger, so price and fee growth are constant between two
+/// consecutive observations.
 #[contracttype]
 #[derive(Clone, Debug, Default)]
 pub struct TWAPObservation {
@@ -155,6 +613,12 @@ pub struct TWAPObservation {
     pub timestamp: u64,
     /// Cumulative tick * time at this observation
     pub tick_cumulative: i128,
-    /// Cumulative 1/liquidity * time at this observation
+    /// Cumulative 1/liquidity * time at this observation (Q64.64)
     pub liquidity_cumulative: u128,
+    /// Sqrt price at this observation (Q64.64)
+    pub sqrt_price_x64: u128,
+    /// Global fee growth for token0 at this observation
+    pub fee_growth_global_0: u128,
+    /// Global fee growth for token1 at this observation
+    pub fee_growth_global_1: u128,
 }
@@ -0,0 +1,112 @@
+//! # Input Validation
+//! 
+//! Typed validators for entrypoint inputs. Every public function runs the
+//! checks that apply to its arguments before touching storage, so the
+//! validation surface can be audited here in one place. Failures raise a
+//! `PoolError` contract error rather than a panic string.
+//! 
+//! Entrypoints that only forward to another entrypoint (`swap`,
+//! `add_liquidity`, ...) rely on the callee's checks. Previews report
+//! problems through `PreviewResult` and are not validated here.
+
+use soroban_sdk::{panic_with_error, Address, Env};
+
+use crate::constants::{MAX_AMOUNT, MAX_TICK, MIN_TICK};
+use crate::error::PoolError;
+use crate::math::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::types::TickInfo;
+
+/// Raise the contract error of a failed validation
+pub fn ensure(env: &Env, result: Result<(), PoolError>) {
+    if let Err(error) = result {
+        panic_with_error!(env, error);
+    }
+}
+
+/// Amount must be > 0
+pub fn amount_positive(amount: i128) -> Result<(), PoolError> {
+    if amount <= 0 {
+        return Err(PoolError::AmountNotPositive);
+    }
+    Ok(())
+}
+
+/// Amount must be >= 0
+pub fn amount_non_negative(amount: i128) -> Result<(), PoolError> {
+    if amount < 0 {
+        return Err(PoolError::AmountNegative);
+    }
+    Ok(())
+}
+
+/// Amount magnitude must not exceed `MAX_AMOUNT`
+pub fn amount_below_cap(amount: i128) -> Result<(), PoolError> {
+    if amount.unsigned_abs() > MAX_AMOUNT as u128 {
+        return Err(PoolError::AmountAboveCap);
+    }
+    Ok(())
+}
+
+/// Both ticks in [MIN_TICK, MAX_TICK] and `lower < upper`
+/// 
+/// Spacing alignment is checked separately by `ticks_aligned`.
+pub fn tick_range(lower: i32, upper: i32) -> Result<(), PoolError> {
+    if lower < MIN_TICK || upper > MAX_TICK || lower >= upper {
+        return Err(PoolError::InvalidTickRange);
+    }
+    Ok(())
+}
+
+/// Both ticks must be multiples of the tick spacing
+pub fn ticks_aligned(lower: i32, upper: i32, tick_spacing: i32) -> Result<(), PoolError> {
+    if lower % tick_spacing != 0 || upper % tick_spacing != 0 {
+        return Err(PoolError::InvalidTick);
+    }
+    Ok(())
+}
+
+/// Sqrt price must map to a tick in range: [MIN_SQRT_RATIO, MAX_SQRT_RATIO)
+pub fn sqrt_price_in_bounds(sqrt_price_x64: u128) -> Result<(), PoolError> {
+    if !(MIN_SQRT_RATIO..MAX_SQRT_RATIO).contains(&sqrt_price_x64) {
+        return Err(PoolError::SqrtPriceOutOfBounds);
+    }
+    Ok(())
+}
+
+/// Deadline must be 0 (none) or at most `now + window` (0 window = no
+/// ceiling)
+pub fn deadline_within_window(now: u64, deadline: u64, window: u64) -> Result<(), PoolError> {
+    if window > 0 && deadline > now.saturating_add(window) {
+        return Err(PoolError::DeadlineTooFar);
+    }
+    Ok(())
+}
+
+/// A tick must hold at least the liquidity being removed from it
+pub fn tick_covers_removal(gross_before: i128, liquidity_removed: i128) -> Result<(), PoolError> {
+    if gross_before < liquidity_removed {
+        return Err(PoolError::TickAccountingMismatch);
+    }
+    Ok(())
+}
+
+/// A boundary tick of a fully burned position must have held at least the
+/// burned liquidity, and afterwards its net liquidity must fit within its
+/// gross (so a tick left with no gross liquidity carries no net either)
+pub fn tick_accounting(gross_before: i128, liquidity_burned: i128, after: &TickInfo) -> Result<(), PoolError> {
+    if gross_before < liquidity_burned
+        || after.liquidity_gross < 0
+        || after.liquidity_net.unsigned_abs() > after.liquidity_gross as u128
+    {
+        return Err(PoolError::TickAccountingMismatch);
+    }
+    Ok(())
+}
+
+/// Address must not be the pool contract
+pub fn address_not_self(env: &Env, address: &Address) -> Result<(), PoolError> {
+    if *address == env.current_contract_address() {
+        return Err(PoolError::SelfAddress);
+    }
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use soroban_sdk::{token, Address, Env};
+
+use crate::reserves::track_reserves;
+use crate::types::VestingSchedule;
+
+// ============================================================
+// LINEAR VESTING MATH
+// ============================================================
+
+/// Amount of `total` vested at `now` for a linear schedule
+/// 
+/// Nothing is vested before `start`; everything is vested once
+/// `start + duration` has passed.
+pub fn vested_amount(total: u128, start: u64, duration: u64, now: u64) -> u128 {
+    if now <= start {
+        return 0;
+    }
+
+    let elapsed = now - start;
+    if duration == 0 || elapsed >= duration {
+        return total;
+    }
+
+    // total * elapsed / duration without overflowing u128
+    let elapsed = elapsed as u128;
+    let duration = duration as u128;
+    (total / duration) * elapsed + (total % duration) * elapsed / duration
+}
+
+/// Amounts that can be released from a schedule right now
+/// 
+/// # Returns
+/// (releasable_0, releasable_1)
+pub fn releasable_amounts(env: &Env, schedule: &VestingSchedule) -> (u128, u128) {
+    let now = env.ledger().timestamp();
+
+    let vested_0 = vested_amount(schedule.amount_0, schedule.start, schedule.duration, now);
+    let vested_1 = vested_amount(schedule.amount_1, schedule.start, schedule.duration, now);
+
+    (
+        vested_0.saturating_sub(schedule.released_0),
+        vested_1.saturating_sub(schedule.released_1),
+    )
+}
+
+/// Restart a schedule at `now`, stacking `amount_0`/`amount_1` on top of
+/// whatever is still unreleased
+/// 
+/// Callers must release the currently vested portion first, otherwise it
+/// would be vested a second time under the new window.
+pub fn restack_schedule(
+    schedule: &mut VestingSchedule,
+    amount_0: u128,
+    amount_1: u128,
+    now: u64,
+    duration: u64,
+) {
+    let remaining_0 = schedule.amount_0.saturating_sub(schedule.released_0);
+    let remaining_1 = schedule.amount_1.saturating_sub(schedule.released_1);
+
+    schedule.amount_0 = remaining_0.saturating_add(amount_0);
+    schedule.amount_1 = remaining_1.saturating_add(amount_1);
+    schedule.released_0 = 0;
+    schedule.released_1 = 0;
+    schedule.start = now;
+    schedule.duration = duration;
+}
+
+// ============================================================
+// RELEASE
+// ============================================================
+
+/// Transfer the currently releasable portion of the schedule to its
+/// beneficiary and record it as released
+/// 
+/// The caller is responsible for persisting the updated schedule.
+/// 
+/// # Returns
+/// (released_0, released_1)
+pub fn release_vested_fees(
+    env: &Env,
+    token0: &Address,
+    token1: &Address,
+    schedule: &mut VestingSchedule,
+) -> (u128, u128) {
+    let (amount0, amount1) = releasable_amounts(env, schedule);
+    let pool_addr = env.current_contract_address();
+
+    schedule.released_0 = schedule.released_0.saturating_add(amount0);
+    schedule.released_1 = schedule.released_1.saturating_add(amount1);
+
+    if amount0 > 0 {
+        token::Client::new(env, token0).transfer(&pool_addr, &schedule.beneficiary, &(amount0 as i128));
+    }
+    if amount1 > 0 {
+        token::Client::new(env, token1).transfer(&pool_addr, &schedule.beneficiary, &(amount1 as i128));
+    }
+    track_reserves(env, -(amount0 as i128), -(amount1 as i128));
+
+    (amount0, amount1)
+}
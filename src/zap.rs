@@ -0,0 +1,110 @@
+//! # Single-Token Entry
+//! 
+//! Sizing for `swap_and_add`: how much of a single input token to swap so
+//! that what is left, together with the swap output, fills a position on
+//! the target range with as little residual as possible.
+
+use soroban_sdk::Env;
+
+use crate::constants::{MIN_SWAP_AMOUNT, ZAP_SEARCH_ITERATIONS};
+use crate::math::{get_liquidity_for_amount0, get_liquidity_for_amount1};
+use crate::swap::simulate_swap;
+use crate::types::PoolState;
+
+// ============================================================
+// SWAP SIZING
+// ============================================================
+
+/// Whether swapping `swap_amount` of the input still leaves the input side
+/// in excess for a position on `[sqrt_lower, sqrt_upper]`
+/// 
+/// Compares the liquidity each side could back at the post-swap price.
+/// Amounts below `MIN_SWAP_AMOUNT` are treated as no swap.
+fn input_in_excess(
+    env: &Env,
+    pool: &PoolState,
+    sqrt_lower: u128,
+    sqrt_upper: u128,
+    zero_for_one: bool,
+    amount_in: i128,
+    swap_amount: i128,
+    fee_bps: u32,
+    protocol_fee_bps: u32,
+) -> bool {
+    let (used, out, sqrt_price) = if swap_amount < MIN_SWAP_AMOUNT {
+        (0, 0, pool.sqrt_price_x64)
+    } else {
+        let (outcome, after) = simulate_swap(
+            env, pool, swap_amount, zero_for_one, 0, fee_bps, protocol_fee_bps,
+        );
+        (outcome.amount_in, outcome.amount_out, after.sqrt_price_x64)
+    };
+
+    let (amount0, amount1) = if zero_for_one {
+        (amount_in - used, out)
+    } else {
+        (out, amount_in - used)
+    };
+
+    // Outside the range only one token is needed
+    if sqrt_price <= sqrt_lower {
+        return !zero_for_one;
+    }
+    if sqrt_price >= sqrt_upper {
+        return zero_for_one;
+    }
+
+    let liquidity0 = get_liquidity_for_amount0(env, amount0, sqrt_price, sqrt_upper);
+    let liquidity1 = get_liquidity_for_amount1(env, amount1, sqrt_lower, sqrt_price);
+
+    if zero_for_one {
+        liquidity0 > liquidity1
+    } else {
+        liquidity1 > liquidity0
+    }
+}
+
+/// Portion of `amount_in` to swap before minting on `[sqrt_lower, sqrt_upper]`
+/// 
+/// Bisects on the swap amount against the simulated post-swap price, so
+/// price impact and tick crossings are accounted for. Returns 0 when no
+/// swap is needed (or the best swap is below `MIN_SWAP_AMOUNT`).
+pub fn optimal_swap_amount(
+    env: &Env,
+    pool: &PoolState,
+    sqrt_lower: u128,
+    sqrt_upper: u128,
+    zero_for_one: bool,
+    amount_in: i128,
+    fee_bps: u32,
+    protocol_fee_bps: u32,
+) -> i128 {
+    let excess = |swap_amount: i128| input_in_excess(
+        env, pool, sqrt_lower, sqrt_upper, zero_for_one,
+        amount_in, swap_amount, fee_bps, protocol_fee_bps,
+    );
+
+    if !excess(0) {
+        return 0;
+    }
+    if excess(amount_in) {
+        return amount_in;
+    }
+
+    // Invariant: excess(lo) && !excess(hi)
+    let mut lo = 0i128;
+    let mut hi = amount_in;
+    for _ in 0..ZAP_SEARCH_ITERATIONS {
+        if hi - lo <= 1 {
+            break;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if excess(mid) {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    if lo < MIN_SWAP_AMOUNT { 0 } else { lo }
+}